@@ -64,6 +64,17 @@ pub fn gen_test_strs(total: usize) -> Vec<ByteArray> {
   values
 }
 
+pub fn gen_test_int96s(total: usize) -> Vec<Int96> {
+  let mut rnd = rand::thread_rng();
+  let mut values = Vec::with_capacity(total);
+  for _ in 0..total {
+    let mut value = Int96::new();
+    value.set_data(rnd.gen::<u32>(), rnd.gen::<u32>(), rnd.gen_range::<u32>(0, 1000));
+    values.push(value);
+  }
+  values
+}
+
 pub fn col_desc(type_length: i32, primitive_ty: Type) -> ColumnDescriptor {
   let ty = SchemaType::primitive_type_builder("col", primitive_ty)
     .with_length(type_length)
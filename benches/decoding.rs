@@ -79,6 +79,48 @@ macro_rules! dict {
   }
 }
 
+macro_rules! plain_buffer {
+  ($fname:ident, $num_values:expr, $batch_size:expr, $gen_data_fn:expr) => {
+    #[bench]
+    fn $fname(bench: &mut Bencher) {
+      let mem_tracker = Rc::new(MemTracker::new());
+      let mut encoder = PlainEncoder::<ByteArrayType>::new(
+        Rc::new(col_desc(0, Type::BYTE_ARRAY)), mem_tracker, vec!());
+
+      let values = $gen_data_fn($num_values);
+      encoder.put(&values[..]).expect("put() should be OK");
+      let buffer = encoder.flush_buffer().expect("flush_buffer() should be OK");
+
+      let decoder = PlainDecoder::<ByteArrayType>::new(0);
+      bench_decoding_into(bench, $num_values, $batch_size, buffer, decoder);
+    }
+  }
+}
+
+macro_rules! dict_buffer {
+  ($fname:ident, $num_values:expr, $batch_size:expr, $gen_data_fn:expr) => {
+    #[bench]
+    fn $fname(bench: &mut Bencher) {
+      let mem_tracker = Rc::new(MemTracker::new());
+      let mut encoder = DictEncoder::<ByteArrayType>::new(
+        Rc::new(col_desc(0, Type::BYTE_ARRAY)), mem_tracker);
+
+      let values = $gen_data_fn($num_values);
+      encoder.put(&values[..]).expect("put() should be OK");
+      let mut dict_decoder = PlainDecoder::<ByteArrayType>::new(0);
+      dict_decoder.set_data(
+        encoder.write_dict().expect("write_dict() should be OK"),
+        encoder.num_entries()).expect("set_data() should be OK");
+
+      let buffer = encoder.flush_buffer().expect("flush_buffer() should be OK");
+      let mut decoder = DictDecoder::<ByteArrayType>::new();
+      decoder.set_dict(Box::new(dict_decoder)).expect("set_dict() should be OK");
+
+      bench_decoding_into(bench, $num_values, $batch_size, buffer, decoder);
+    }
+  }
+}
+
 macro_rules! delta_bit_pack {
   ($fname:ident, $num_values:expr, $batch_size:expr, $ty:ident, $gen_data_fn:expr) => {
     #[bench]
@@ -113,14 +155,38 @@ fn bench_decoding<T: DataType>(
   })
 }
 
+fn bench_decoding_into<D: Decoder<ByteArrayType> + ByteArrayDecoder>(
+  bench: &mut Bencher,
+  num_values: usize,
+  batch_size: usize,
+  buffer: ByteBufferPtr,
+  mut decoder: D
+) {
+  bench.iter(|| {
+    decoder.set_data(buffer.clone(), num_values).expect("set_data() should be OK");
+    let mut values = ByteArrayBuffer::new();
+    loop {
+      values.clear();
+      if decoder.get_into(&mut values, batch_size).expect("get_into() should be OK")
+          < batch_size {
+        break
+      }
+    }
+  })
+}
+
 
 plain!(plain_i32_1k_32, 1024, 32, Int32Type, Type::INT32, gen_1000);
 plain!(plain_i32_1k_64, 1024, 64, Int32Type, Type::INT32, gen_1000);
 plain!(plain_i32_1k_128, 1024, 128, Int32Type, Type::INT32, gen_1000);
-plain!(plain_i32_1m_32, 1024, 32, Int32Type, Type::INT32, gen_1000);
-plain!(plain_i32_1m_64, 1024, 64, Int32Type, Type::INT32, gen_1000);
-plain!(plain_i32_1m_128, 1024, 128, Int32Type, Type::INT32, gen_1000);
-plain!(plain_str_1m_128, 1024, 128, ByteArrayType, Type::BYTE_ARRAY, gen_test_strs);
+plain!(plain_i32_1m_32, 1024 * 1024, 32, Int32Type, Type::INT32, gen_1000);
+plain!(plain_i32_1m_64, 1024 * 1024, 64, Int32Type, Type::INT32, gen_1000);
+plain!(plain_i32_1m_128, 1024 * 1024, 128, Int32Type, Type::INT32, gen_1000);
+plain!(plain_str_1m_128, 1024 * 1024, 128, ByteArrayType, Type::BYTE_ARRAY,
+       gen_test_strs);
+plain!(plain_int96_1m_128, 1024 * 1024, 128, Int96Type, Type::INT96, gen_test_int96s);
+
+plain_buffer!(plain_str_buffer_1m_128, 1024 * 1024, 128, gen_test_strs);
 
 dict!(dict_i32_1k_32, 1024, 32, Int32Type, Type::INT32, gen_1000);
 dict!(dict_i32_1k_64, 1024, 64, Int32Type, Type::INT32, gen_1000);
@@ -130,6 +196,8 @@ dict!(dict_i32_1m_64, 1024 * 1024, 64, Int32Type, Type::INT32, gen_1000);
 dict!(dict_i32_1m_128, 1024 * 1024, 128, Int32Type, Type::INT32, gen_1000);
 dict!(dict_str_1m_128, 1024 * 1024, 128, ByteArrayType, Type::BYTE_ARRAY, gen_test_strs);
 
+dict_buffer!(dict_str_buffer_1m_128, 1024 * 1024, 128, gen_test_strs);
+
 delta_bit_pack!(delta_bit_pack_i32_1k_32, 1024, 32, Int32Type, gen_1000);
 delta_bit_pack!(delta_bit_pack_i32_1k_64, 1024, 64, Int32Type, gen_1000);
 delta_bit_pack!(delta_bit_pack_i32_1k_128, 1024, 128, Int32Type, gen_1000);
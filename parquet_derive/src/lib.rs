@@ -0,0 +1,165 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Derive macros for `parquet::record::typed::RecordWriter` and `RecordReader`.
+//!
+//! `#[derive(ParquetRecordWriter)]` and `#[derive(ParquetRecordReader)]` apply to structs
+//! with named fields, whose types implement `WriteField` and `ReadField` respectively.
+//! The struct becomes a group of the schema, with one field per struct field. The
+//! buffers and readers of the leaf columns of the struct are the tuple of those of its
+//! fields, nested as `(first, (second, ()))`, so that each field is shredded into and
+//! assembled from leaf columns of the data type of its values.
+
+extern crate proc_macro;
+extern crate syn;
+#[macro_use]
+extern crate quote;
+
+use proc_macro::TokenStream;
+use syn::{Data, DeriveInput, Fields, Ident, Type};
+
+#[proc_macro_derive(ParquetRecordWriter)]
+pub fn parquet_record_writer(input: TokenStream) -> TokenStream {
+  let input: DeriveInput = syn::parse(input).unwrap();
+  let name = input.ident;
+  let (idents, types) = struct_fields(&input);
+  let tr = quote!(::parquet::record::typed::WriteField);
+  let field_type = field_type(&tr, &types, &idents);
+  let leaves_type = leaves_type(&tr, &types);
+  let leaves = leaves(idents.len());
+
+  let tokens = quote! {
+    impl ::parquet::record::typed::WriteField for #name {
+      type Leaves = #leaves_type;
+
+      #field_type
+
+      fn shred(&self, def: i16, rep: i16, depth: i16, leaves: &mut Self::Leaves) {
+        #(
+          ::parquet::record::typed::WriteField::shred(
+            &self.#idents, def, rep, depth, #leaves);
+        )*
+      }
+    }
+
+    impl ::parquet::record::typed::RecordWriter for #name {}
+  };
+  tokens.into()
+}
+
+#[proc_macro_derive(ParquetRecordReader)]
+pub fn parquet_record_reader(input: TokenStream) -> TokenStream {
+  let input: DeriveInput = syn::parse(input).unwrap();
+  let name = input.ident;
+  let (idents, types) = struct_fields(&input);
+  let tr = quote!(::parquet::record::typed::ReadField<'a>);
+  let field_type = field_type(&tr, &types, &idents);
+  let leaves_type = leaves_type(&tr, &types);
+  let leaves = leaves(idents.len());
+  let trs = ::std::iter::repeat(&tr);
+
+  let tokens = quote! {
+    impl<'a> ::parquet::record::typed::ReadField<'a> for #name {
+      type Leaves = #leaves_type;
+
+      #field_type
+
+      fn assemble(
+        def: i16,
+        depth: i16,
+        leaves: &mut Self::Leaves
+      ) -> ::parquet::errors::Result<Self> {
+        Ok(#name {
+          #(
+            #idents: <#types as #trs>::assemble(def, depth, #leaves)?
+          ),*
+        })
+      }
+    }
+
+    impl ::parquet::record::typed::RecordReader for #name {}
+  };
+  tokens.into()
+}
+
+/// Returns the names and types of the fields of the struct `input`.
+fn struct_fields(input: &DeriveInput) -> (Vec<Ident>, Vec<Type>) {
+  if !input.generics.params.is_empty() {
+    panic!("Parquet records cannot have generic parameters: {}", input.ident);
+  }
+  let fields = match input.data {
+    Data::Struct(ref data) => match data.fields {
+      Fields::Named(ref fields) => &fields.named,
+      _ => panic!("Parquet records must have named fields: {}", input.ident)
+    },
+    _ => panic!("Parquet records must be structs: {}", input.ident)
+  };
+  if fields.is_empty() {
+    panic!("Parquet records must have at least one field: {}", input.ident);
+  }
+  let idents = fields.iter().map(|f| f.ident.unwrap()).collect();
+  let types = fields.iter().map(|f| f.ty.clone()).collect();
+  (idents, types)
+}
+
+/// Generates `field_type` of trait `tr`, which returns a group with a field for each
+/// struct field.
+fn field_type(tr: &quote::Tokens, types: &[Type], idents: &[Ident]) -> quote::Tokens {
+  let names = idents.iter().map(|ident| ident.as_ref().to_owned());
+  let trs = ::std::iter::repeat(tr);
+  quote! {
+    fn field_type(
+      name: &str,
+      repetition: ::parquet::basic::Repetition
+    ) -> ::parquet::errors::Result<::parquet::schema::types::TypePtr> {
+      let mut fields: Vec<::parquet::schema::types::TypePtr> = vec![
+        #(
+          <#types as #trs>::field_type(
+            #names, ::parquet::basic::Repetition::REQUIRED)?
+        ),*
+      ];
+      let tp = ::parquet::schema::types::Type::group_type_builder(name)
+        .with_repetition(repetition)
+        .with_fields(&mut fields)
+        .build()?;
+      Ok(::std::rc::Rc::new(tp))
+    }
+  }
+}
+
+/// Generates the `Leaves` type of trait `tr`, the leaves of the struct fields nested as
+/// `(first, (second, ()))`.
+fn leaves_type(tr: &quote::Tokens, types: &[Type]) -> quote::Tokens {
+  let mut tokens = quote!(());
+  for ty in types.iter().rev() {
+    tokens = quote!((<#ty as #tr>::Leaves, #tokens));
+  }
+  tokens
+}
+
+/// Generates `&mut (leaves).0`, `&mut ((leaves).1).0`, ..., the leaves of each of the
+/// `num_fields` struct fields. The parentheses keep `leaves.1.0` from being parsed as a
+/// float.
+fn leaves(num_fields: usize) -> Vec<quote::Tokens> {
+  (0..num_fields).map(|i| {
+    let mut tokens = quote!(leaves);
+    for _ in 0..i {
+      tokens = quote!((#tokens).1);
+    }
+    quote!(&mut (#tokens).0)
+  }).collect()
+}
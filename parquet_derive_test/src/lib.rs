@@ -0,0 +1,197 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Tests of the `parquet_derive` macros, which cannot be used from the crate that
+//! defines them.
+
+#[macro_use]
+extern crate parquet_derive;
+extern crate parquet;
+
+#[cfg(test)]
+mod tests {
+  use std::env;
+  use std::fs::{self, File};
+  use std::path::PathBuf;
+
+  use parquet::data_type::ByteArray;
+  use parquet::file::reader::{FileReader, SerializedFileReader};
+  use parquet::file::writer::{SerializedFileWriter, WriterOptions};
+  use parquet::record::typed::{RecordReader, RecordWriter};
+  use parquet::schema::parser::parse_message_type;
+
+  #[derive(Debug, PartialEq, ParquetRecordWriter, ParquetRecordReader)]
+  struct Flat {
+    a_bool: bool,
+    a_i8: i8,
+    a_u16: u16,
+    a_i32: i32,
+    a_u64: u64,
+    a_f32: f32,
+    a_f64: f64,
+    a_string: String,
+    a_bytes: ByteArray
+  }
+
+  #[derive(Debug, PartialEq, ParquetRecordWriter, ParquetRecordReader)]
+  struct Location {
+    lat: f64,
+    lon: f64
+  }
+
+  #[derive(Debug, PartialEq, ParquetRecordWriter, ParquetRecordReader)]
+  struct Nested {
+    id: i64,
+    name: Option<String>,
+    scores: Vec<Option<f64>>,
+    location: Option<Location>,
+    visits: Vec<Location>,
+    tags: Option<Vec<Vec<String>>>
+  }
+
+  #[derive(Debug, PartialEq, ParquetRecordReader)]
+  struct Projection {
+    tags: Option<Vec<Vec<String>>>,
+    id: i64
+  }
+
+  fn temp_file_path(file_name: &str) -> PathBuf {
+    let mut path = env::temp_dir();
+    path.push("parquet-derive-test");
+    fs::create_dir_all(&path).unwrap();
+    path.push(file_name);
+    path
+  }
+
+  fn write_read<W: RecordWriter, R: RecordReader>(
+    file_name: &str, records: &[W]
+  ) -> Vec<R> {
+    let path = temp_file_path(file_name);
+    let file = File::create(&path).unwrap();
+    let mut writer = SerializedFileWriter::new(
+      file, W::schema().unwrap(), WriterOptions::new()).unwrap();
+    let mut row_group = writer.next_row_group();
+    W::write_to_row_group(records, &mut row_group).unwrap();
+    writer.write_row_group(row_group).unwrap();
+    writer.close().unwrap();
+
+    let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+    assert_eq!(reader.num_row_groups(), 1);
+    let row_group = reader.get_row_group(0).unwrap();
+    R::read_from_row_group(&*row_group).unwrap()
+  }
+
+  fn nested_records() -> Vec<Nested> {
+    vec![
+      Nested {
+        id: 1,
+        name: Some("a".to_owned()),
+        scores: vec![Some(1.5), None, Some(2.5)],
+        location: Some(Location { lat: 1.0, lon: 2.0 }),
+        visits: vec![Location { lat: 3.0, lon: 4.0 }, Location { lat: 5.0, lon: 6.0 }],
+        tags: Some(vec![vec!["x".to_owned(), "y".to_owned()], vec![]])
+      },
+      Nested {
+        id: 2,
+        name: None,
+        scores: vec![],
+        location: None,
+        visits: vec![],
+        tags: None
+      },
+      Nested {
+        id: 3,
+        name: Some("c".to_owned()),
+        scores: vec![None],
+        location: Some(Location { lat: 7.0, lon: 8.0 }),
+        visits: vec![],
+        tags: Some(vec![])
+      }
+    ]
+  }
+
+  #[test]
+  fn test_schema() {
+    let expected = parse_message_type("
+      message schema {
+        REQUIRED INT64 id;
+        OPTIONAL BYTE_ARRAY name (UTF8);
+        REQUIRED GROUP scores (LIST) {
+          REPEATED GROUP list {
+            OPTIONAL DOUBLE element;
+          }
+        }
+        OPTIONAL GROUP location {
+          REQUIRED DOUBLE lat;
+          REQUIRED DOUBLE lon;
+        }
+        REQUIRED GROUP visits (LIST) {
+          REPEATED GROUP list {
+            REQUIRED GROUP element {
+              REQUIRED DOUBLE lat;
+              REQUIRED DOUBLE lon;
+            }
+          }
+        }
+        OPTIONAL GROUP tags (LIST) {
+          REPEATED GROUP list {
+            REQUIRED GROUP element (LIST) {
+              REPEATED GROUP list {
+                REQUIRED BYTE_ARRAY element (UTF8);
+              }
+            }
+          }
+        }
+      }
+    ").unwrap();
+    assert_eq!(*Nested::schema().unwrap(), expected);
+  }
+
+  #[test]
+  fn test_write_read_flat() {
+    let records: Vec<Flat> = (0..2000).map(|i| Flat {
+      a_bool: i % 3 == 0,
+      a_i8: (i % 128) as i8,
+      a_u16: i as u16,
+      a_i32: -i,
+      a_u64: i as u64 * 1_000_000_000_000,
+      a_f32: i as f32 / 2.0,
+      a_f64: i as f64 / 4.0,
+      a_string: format!("string {}", i),
+      a_bytes: ByteArray::from(vec![1, 2, (i % 256) as u8])
+    }).collect();
+    let read: Vec<Flat> = write_read("flat.parquet", &records);
+    assert_eq!(read, records);
+  }
+
+  #[test]
+  fn test_write_read_nested() {
+    let records = nested_records();
+    let read: Vec<Nested> = write_read("nested.parquet", &records);
+    assert_eq!(read, records);
+  }
+
+  #[test]
+  fn test_read_projection() {
+    let records = nested_records();
+    let read: Vec<Projection> = write_read("projection.parquet", &records);
+    let expected: Vec<Projection> = records.into_iter()
+      .map(|r| Projection { tags: r.tags, id: r.id })
+      .collect();
+    assert_eq!(read, expected);
+  }
+}
@@ -68,6 +68,127 @@ pub enum LogicalType {
   INTERVAL
 }
 
+/// Mirrors `parquet::TimeUnit`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeUnit {
+  MILLIS,
+  MICROS,
+  NANOS
+}
+
+/// Mirrors `parquet::LogicalType`, the logical type annotation that supersedes
+/// `LogicalType` (i.e., `parquet::ConvertedType`).
+/// Unlike converted types, annotations can be parameterized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogicalAnnotation {
+  STRING,
+  MAP,
+  LIST,
+  ENUM,
+  DECIMAL { scale: i32, precision: i32 },
+  DATE,
+  TIME { is_adjusted_to_utc: bool, unit: TimeUnit },
+  TIMESTAMP { is_adjusted_to_utc: bool, unit: TimeUnit },
+  INTEGER { bit_width: i8, is_signed: bool },
+  UNKNOWN,
+  JSON,
+  BSON,
+  UUID
+}
+
+impl LogicalAnnotation {
+  /// Returns the converted type that has to be written along with this annotation for
+  /// backward compatibility, or `LogicalType::NONE` if there is none. Legacy time and
+  /// timestamp types are adjusted to UTC, so local times and timestamps have none.
+  pub fn to_converted_type(&self) -> LogicalType {
+    match *self {
+      LogicalAnnotation::STRING => LogicalType::UTF8,
+      LogicalAnnotation::MAP => LogicalType::MAP,
+      LogicalAnnotation::LIST => LogicalType::LIST,
+      LogicalAnnotation::ENUM => LogicalType::ENUM,
+      LogicalAnnotation::DECIMAL { .. } => LogicalType::DECIMAL,
+      LogicalAnnotation::DATE => LogicalType::DATE,
+      LogicalAnnotation::TIME { is_adjusted_to_utc: false, .. } |
+        LogicalAnnotation::TIMESTAMP { is_adjusted_to_utc: false, .. } => {
+        LogicalType::NONE
+      },
+      LogicalAnnotation::TIME { unit, .. } => match unit {
+        TimeUnit::MILLIS => LogicalType::TIME_MILLIS,
+        TimeUnit::MICROS => LogicalType::TIME_MICROS,
+        TimeUnit::NANOS => LogicalType::NONE
+      },
+      LogicalAnnotation::TIMESTAMP { unit, .. } => match unit {
+        TimeUnit::MILLIS => LogicalType::TIMESTAMP_MILLIS,
+        TimeUnit::MICROS => LogicalType::TIMESTAMP_MICROS,
+        TimeUnit::NANOS => LogicalType::NONE
+      },
+      LogicalAnnotation::INTEGER { bit_width, is_signed } => {
+        match (bit_width, is_signed) {
+          (8, true) => LogicalType::INT_8,
+          (16, true) => LogicalType::INT_16,
+          (32, true) => LogicalType::INT_32,
+          (64, true) => LogicalType::INT_64,
+          (8, false) => LogicalType::UINT_8,
+          (16, false) => LogicalType::UINT_16,
+          (32, false) => LogicalType::UINT_32,
+          (64, false) => LogicalType::UINT_64,
+          _ => LogicalType::NONE
+        }
+      },
+      LogicalAnnotation::JSON => LogicalType::JSON,
+      LogicalAnnotation::BSON => LogicalType::BSON,
+      LogicalAnnotation::UNKNOWN | LogicalAnnotation::UUID => LogicalType::NONE
+    }
+  }
+
+  /// Returns the annotation equivalent to the converted type `tp`, if any. `precision`
+  /// and `scale` are only used for `LogicalType::DECIMAL`.
+  /// Legacy time and timestamp types are adjusted to UTC.
+  pub fn from_converted_type(
+    tp: LogicalType, precision: i32, scale: i32
+  ) -> Option<Self> {
+    let integer = |bit_width, is_signed| {
+      LogicalAnnotation::INTEGER { bit_width: bit_width, is_signed: is_signed }
+    };
+    let annotation = match tp {
+      LogicalType::UTF8 => LogicalAnnotation::STRING,
+      LogicalType::MAP => LogicalAnnotation::MAP,
+      LogicalType::LIST => LogicalAnnotation::LIST,
+      LogicalType::ENUM => LogicalAnnotation::ENUM,
+      LogicalType::DECIMAL => {
+        LogicalAnnotation::DECIMAL { scale: scale, precision: precision }
+      },
+      LogicalType::DATE => LogicalAnnotation::DATE,
+      LogicalType::TIME_MILLIS => {
+        LogicalAnnotation::TIME { is_adjusted_to_utc: true, unit: TimeUnit::MILLIS }
+      },
+      LogicalType::TIME_MICROS => {
+        LogicalAnnotation::TIME { is_adjusted_to_utc: true, unit: TimeUnit::MICROS }
+      },
+      LogicalType::TIMESTAMP_MILLIS => {
+        LogicalAnnotation::TIMESTAMP { is_adjusted_to_utc: true, unit: TimeUnit::MILLIS }
+      },
+      LogicalType::TIMESTAMP_MICROS => {
+        LogicalAnnotation::TIMESTAMP { is_adjusted_to_utc: true, unit: TimeUnit::MICROS }
+      },
+      LogicalType::UINT_8 => integer(8, false),
+      LogicalType::UINT_16 => integer(16, false),
+      LogicalType::UINT_32 => integer(32, false),
+      LogicalType::UINT_64 => integer(64, false),
+      LogicalType::INT_8 => integer(8, true),
+      LogicalType::INT_16 => integer(16, true),
+      LogicalType::INT_32 => integer(32, true),
+      LogicalType::INT_64 => integer(64, true),
+      LogicalType::JSON => LogicalAnnotation::JSON,
+      LogicalType::BSON => LogicalAnnotation::BSON,
+      LogicalType::NONE | LogicalType::MAP_KEY_VALUE | LogicalType::INTERVAL => {
+        return None
+      }
+    };
+    Some(annotation)
+  }
+}
+
 /// Mirrors `parquet::FieldRepetitionType`
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Repetition {
@@ -120,6 +241,34 @@ impl fmt::Display for LogicalType {
   }
 }
 
+impl fmt::Display for TimeUnit {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{:?}", self)
+  }
+}
+
+/// Formats the annotation in the syntax used by `schema::parser`, e.g.
+/// `TIMESTAMP(MILLIS,true)` or `INTEGER(8,false)`.
+impl fmt::Display for LogicalAnnotation {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      LogicalAnnotation::DECIMAL { scale, precision } => {
+        write!(f, "DECIMAL({},{})", precision, scale)
+      },
+      LogicalAnnotation::TIME { is_adjusted_to_utc, unit } => {
+        write!(f, "TIME({},{})", unit, is_adjusted_to_utc)
+      },
+      LogicalAnnotation::TIMESTAMP { is_adjusted_to_utc, unit } => {
+        write!(f, "TIMESTAMP({},{})", unit, is_adjusted_to_utc)
+      },
+      LogicalAnnotation::INTEGER { bit_width, is_signed } => {
+        write!(f, "INTEGER({},{})", bit_width, is_signed)
+      },
+      other => write!(f, "{:?}", other)
+    }
+  }
+}
+
 impl fmt::Display for Repetition {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     write!(f, "{:?}", self)
@@ -193,6 +342,46 @@ impl convert::From<Option<parquet::ConvertedType>> for LogicalType {
   }
 }
 
+impl convert::From<parquet::TimeUnit> for TimeUnit {
+  fn from(tp: parquet::TimeUnit) -> Self {
+    match tp {
+      parquet::TimeUnit::MILLIS(_) => TimeUnit::MILLIS,
+      parquet::TimeUnit::MICROS(_) => TimeUnit::MICROS,
+      parquet::TimeUnit::NANOS(_) => TimeUnit::NANOS
+    }
+  }
+}
+
+impl convert::From<parquet::LogicalType> for LogicalAnnotation {
+  fn from(tp: parquet::LogicalType) -> Self {
+    match tp {
+      parquet::LogicalType::STRING(_) => LogicalAnnotation::STRING,
+      parquet::LogicalType::MAP(_) => LogicalAnnotation::MAP,
+      parquet::LogicalType::LIST(_) => LogicalAnnotation::LIST,
+      parquet::LogicalType::ENUM(_) => LogicalAnnotation::ENUM,
+      parquet::LogicalType::DECIMAL(t) => {
+        LogicalAnnotation::DECIMAL { scale: t.scale, precision: t.precision }
+      },
+      parquet::LogicalType::DATE(_) => LogicalAnnotation::DATE,
+      parquet::LogicalType::TIME(t) => LogicalAnnotation::TIME {
+        is_adjusted_to_utc: t.is_adjusted_to_u_t_c,
+        unit: TimeUnit::from(t.unit)
+      },
+      parquet::LogicalType::TIMESTAMP(t) => LogicalAnnotation::TIMESTAMP {
+        is_adjusted_to_utc: t.is_adjusted_to_u_t_c,
+        unit: TimeUnit::from(t.unit)
+      },
+      parquet::LogicalType::INTEGER(t) => {
+        LogicalAnnotation::INTEGER { bit_width: t.bit_width, is_signed: t.is_signed }
+      },
+      parquet::LogicalType::UNKNOWN(_) => LogicalAnnotation::UNKNOWN,
+      parquet::LogicalType::JSON(_) => LogicalAnnotation::JSON,
+      parquet::LogicalType::BSON(_) => LogicalAnnotation::BSON,
+      parquet::LogicalType::UUID(_) => LogicalAnnotation::UUID
+    }
+  }
+}
+
 impl convert::From<parquet::FieldRepetitionType> for Repetition {
   fn from(tp: parquet::FieldRepetitionType) -> Self {
     match tp {
@@ -241,6 +430,145 @@ impl convert::From<parquet::PageType> for PageType {
   }
 }
 
+impl convert::From<Type> for parquet::Type {
+  fn from(tp: Type) -> Self {
+    match tp {
+      Type::BOOLEAN => parquet::Type::BOOLEAN,
+      Type::INT32 => parquet::Type::INT32,
+      Type::INT64 => parquet::Type::INT64,
+      Type::INT96 => parquet::Type::INT96,
+      Type::FLOAT => parquet::Type::FLOAT,
+      Type::DOUBLE => parquet::Type::DOUBLE,
+      Type::BYTE_ARRAY => parquet::Type::BYTE_ARRAY,
+      Type::FIXED_LEN_BYTE_ARRAY => parquet::Type::FIXED_LEN_BYTE_ARRAY
+    }
+  }
+}
+
+impl convert::From<LogicalType> for Option<parquet::ConvertedType> {
+  fn from(tp: LogicalType) -> Self {
+    match tp {
+      LogicalType::NONE => None,
+      LogicalType::UTF8 => Some(parquet::ConvertedType::UTF8),
+      LogicalType::MAP => Some(parquet::ConvertedType::MAP),
+      LogicalType::MAP_KEY_VALUE => Some(parquet::ConvertedType::MAP_KEY_VALUE),
+      LogicalType::LIST => Some(parquet::ConvertedType::LIST),
+      LogicalType::ENUM => Some(parquet::ConvertedType::ENUM),
+      LogicalType::DECIMAL => Some(parquet::ConvertedType::DECIMAL),
+      LogicalType::DATE => Some(parquet::ConvertedType::DATE),
+      LogicalType::TIME_MILLIS => Some(parquet::ConvertedType::TIME_MILLIS),
+      LogicalType::TIME_MICROS => Some(parquet::ConvertedType::TIME_MICROS),
+      LogicalType::TIMESTAMP_MILLIS => Some(parquet::ConvertedType::TIMESTAMP_MILLIS),
+      LogicalType::TIMESTAMP_MICROS => Some(parquet::ConvertedType::TIMESTAMP_MICROS),
+      LogicalType::UINT_8 => Some(parquet::ConvertedType::UINT_8),
+      LogicalType::UINT_16 => Some(parquet::ConvertedType::UINT_16),
+      LogicalType::UINT_32 => Some(parquet::ConvertedType::UINT_32),
+      LogicalType::UINT_64 => Some(parquet::ConvertedType::UINT_64),
+      LogicalType::INT_8 => Some(parquet::ConvertedType::INT_8),
+      LogicalType::INT_16 => Some(parquet::ConvertedType::INT_16),
+      LogicalType::INT_32 => Some(parquet::ConvertedType::INT_32),
+      LogicalType::INT_64 => Some(parquet::ConvertedType::INT_64),
+      LogicalType::JSON => Some(parquet::ConvertedType::JSON),
+      LogicalType::BSON => Some(parquet::ConvertedType::BSON),
+      LogicalType::INTERVAL => Some(parquet::ConvertedType::INTERVAL)
+    }
+  }
+}
+
+impl convert::From<TimeUnit> for parquet::TimeUnit {
+  fn from(tp: TimeUnit) -> Self {
+    match tp {
+      TimeUnit::MILLIS => parquet::TimeUnit::MILLIS(parquet::MilliSeconds {}),
+      TimeUnit::MICROS => parquet::TimeUnit::MICROS(parquet::MicroSeconds {}),
+      TimeUnit::NANOS => parquet::TimeUnit::NANOS(parquet::NanoSeconds {})
+    }
+  }
+}
+
+impl convert::From<LogicalAnnotation> for parquet::LogicalType {
+  fn from(tp: LogicalAnnotation) -> Self {
+    match tp {
+      LogicalAnnotation::STRING => parquet::LogicalType::STRING(parquet::StringType {}),
+      LogicalAnnotation::MAP => parquet::LogicalType::MAP(parquet::MapType {}),
+      LogicalAnnotation::LIST => parquet::LogicalType::LIST(parquet::ListType {}),
+      LogicalAnnotation::ENUM => parquet::LogicalType::ENUM(parquet::EnumType {}),
+      LogicalAnnotation::DECIMAL { scale, precision } => {
+        parquet::LogicalType::DECIMAL(
+          parquet::DecimalType { scale: scale, precision: precision })
+      },
+      LogicalAnnotation::DATE => parquet::LogicalType::DATE(parquet::DateType {}),
+      LogicalAnnotation::TIME { is_adjusted_to_utc, unit } => {
+        parquet::LogicalType::TIME(parquet::TimeType {
+          is_adjusted_to_u_t_c: is_adjusted_to_utc,
+          unit: unit.into()
+        })
+      },
+      LogicalAnnotation::TIMESTAMP { is_adjusted_to_utc, unit } => {
+        parquet::LogicalType::TIMESTAMP(parquet::TimestampType {
+          is_adjusted_to_u_t_c: is_adjusted_to_utc,
+          unit: unit.into()
+        })
+      },
+      LogicalAnnotation::INTEGER { bit_width, is_signed } => {
+        parquet::LogicalType::INTEGER(
+          parquet::IntType { bit_width: bit_width, is_signed: is_signed })
+      },
+      LogicalAnnotation::UNKNOWN => parquet::LogicalType::UNKNOWN(parquet::NullType {}),
+      LogicalAnnotation::JSON => parquet::LogicalType::JSON(parquet::JsonType {}),
+      LogicalAnnotation::BSON => parquet::LogicalType::BSON(parquet::BsonType {}),
+      LogicalAnnotation::UUID => parquet::LogicalType::UUID(parquet::UUIDType {})
+    }
+  }
+}
+
+impl convert::From<Repetition> for parquet::FieldRepetitionType {
+  fn from(tp: Repetition) -> Self {
+    match tp {
+      Repetition::REQUIRED => parquet::FieldRepetitionType::REQUIRED,
+      Repetition::OPTIONAL => parquet::FieldRepetitionType::OPTIONAL,
+      Repetition::REPEATED => parquet::FieldRepetitionType::REPEATED
+    }
+  }
+}
+
+impl convert::From<Encoding> for parquet::Encoding {
+  fn from(tp: Encoding) -> Self {
+    match tp {
+      Encoding::PLAIN => parquet::Encoding::PLAIN,
+      Encoding::PLAIN_DICTIONARY => parquet::Encoding::PLAIN_DICTIONARY,
+      Encoding::RLE => parquet::Encoding::RLE,
+      Encoding::BIT_PACKED => parquet::Encoding::BIT_PACKED,
+      Encoding::DELTA_BINARY_PACKED => parquet::Encoding::DELTA_BINARY_PACKED,
+      Encoding::DELTA_LENGTH_BYTE_ARRAY => parquet::Encoding::DELTA_LENGTH_BYTE_ARRAY,
+      Encoding::DELTA_BYTE_ARRAY => parquet::Encoding::DELTA_BYTE_ARRAY,
+      Encoding::RLE_DICTIONARY => parquet::Encoding::RLE_DICTIONARY
+    }
+  }
+}
+
+impl convert::From<Compression> for parquet::CompressionCodec {
+  fn from(tp: Compression) -> Self {
+    match tp {
+      Compression::UNCOMPRESSED => parquet::CompressionCodec::UNCOMPRESSED,
+      Compression::SNAPPY => parquet::CompressionCodec::SNAPPY,
+      Compression::GZIP => parquet::CompressionCodec::GZIP,
+      Compression::LZO => parquet::CompressionCodec::LZO,
+      Compression::BROTLI => parquet::CompressionCodec::BROTLI
+    }
+  }
+}
+
+impl convert::From<PageType> for parquet::PageType {
+  fn from(tp: PageType) -> Self {
+    match tp {
+      PageType::DATA_PAGE => parquet::PageType::DATA_PAGE,
+      PageType::INDEX_PAGE => parquet::PageType::INDEX_PAGE,
+      PageType::DICTIONARY_PAGE => parquet::PageType::DICTIONARY_PAGE,
+      PageType::DATA_PAGE_V2 => parquet::PageType::DATA_PAGE_V2
+    }
+  }
+}
+
 impl str::FromStr for Repetition {
   type Err = ParquetError;
   fn from_str(s: &str) -> result::Result<Self, Self::Err> {
@@ -270,6 +598,18 @@ impl str::FromStr for Type {
   }
 }
 
+impl str::FromStr for TimeUnit {
+  type Err = ParquetError;
+  fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+    match s {
+      "MILLIS" => Ok(TimeUnit::MILLIS),
+      "MICROS" => Ok(TimeUnit::MICROS),
+      "NANOS" => Ok(TimeUnit::NANOS),
+      other => Err(general_err!("Invalid time unit {}", other)),
+    }
+  }
+}
+
 impl str::FromStr for LogicalType {
   type Err = ParquetError;
   fn from_str(s: &str) -> result::Result<Self, Self::Err> {
@@ -566,4 +906,135 @@ mod tests {
                PageType::DICTIONARY_PAGE);
     assert_eq!(PageType::from(parquet::PageType::DATA_PAGE_V2), PageType::DATA_PAGE_V2);
   }
+
+  #[test]
+  fn test_into_thrift() {
+    let types = vec![Type::BOOLEAN, Type::INT32, Type::INT64, Type::INT96, Type::FLOAT,
+      Type::DOUBLE, Type::BYTE_ARRAY, Type::FIXED_LEN_BYTE_ARRAY];
+    for tp in types {
+      assert_eq!(Type::from(parquet::Type::from(tp)), tp);
+    }
+
+    assert_eq!(Option::<parquet::ConvertedType>::from(LogicalType::NONE), None);
+    assert_eq!(Option::<parquet::ConvertedType>::from(LogicalType::INTERVAL),
+               Some(parquet::ConvertedType::INTERVAL));
+    let logical_types = vec![LogicalType::NONE, LogicalType::UTF8, LogicalType::MAP,
+      LogicalType::MAP_KEY_VALUE, LogicalType::LIST, LogicalType::ENUM,
+      LogicalType::DECIMAL, LogicalType::DATE, LogicalType::TIME_MILLIS,
+      LogicalType::TIME_MICROS, LogicalType::TIMESTAMP_MILLIS,
+      LogicalType::TIMESTAMP_MICROS, LogicalType::UINT_8, LogicalType::UINT_16,
+      LogicalType::UINT_32, LogicalType::UINT_64, LogicalType::INT_8,
+      LogicalType::INT_16, LogicalType::INT_32, LogicalType::INT_64, LogicalType::JSON,
+      LogicalType::BSON, LogicalType::INTERVAL];
+    for tp in logical_types {
+      assert_eq!(LogicalType::from(Option::<parquet::ConvertedType>::from(tp)), tp);
+    }
+
+    for tp in vec![Repetition::REQUIRED, Repetition::OPTIONAL, Repetition::REPEATED] {
+      assert_eq!(Repetition::from(parquet::FieldRepetitionType::from(tp)), tp);
+    }
+
+    let encodings = vec![Encoding::PLAIN, Encoding::PLAIN_DICTIONARY, Encoding::RLE,
+      Encoding::BIT_PACKED, Encoding::DELTA_BINARY_PACKED,
+      Encoding::DELTA_LENGTH_BYTE_ARRAY, Encoding::DELTA_BYTE_ARRAY,
+      Encoding::RLE_DICTIONARY];
+    for encoding in encodings {
+      assert_eq!(Encoding::from(parquet::Encoding::from(encoding)), encoding);
+    }
+
+    let codecs = vec![Compression::UNCOMPRESSED, Compression::SNAPPY, Compression::GZIP,
+      Compression::LZO, Compression::BROTLI];
+    for codec in codecs {
+      assert_eq!(Compression::from(parquet::CompressionCodec::from(codec)), codec);
+    }
+
+    let page_types = vec![PageType::DATA_PAGE, PageType::INDEX_PAGE,
+      PageType::DICTIONARY_PAGE, PageType::DATA_PAGE_V2];
+    for tp in page_types {
+      assert_eq!(PageType::from(parquet::PageType::from(tp)), tp);
+    }
+  }
+
+  #[test]
+  fn test_logical_annotation() {
+    let annotations = vec![
+      LogicalAnnotation::STRING, LogicalAnnotation::MAP, LogicalAnnotation::LIST,
+      LogicalAnnotation::ENUM, LogicalAnnotation::DECIMAL { scale: 2, precision: 9 },
+      LogicalAnnotation::DATE,
+      LogicalAnnotation::TIME { is_adjusted_to_utc: true, unit: TimeUnit::MILLIS },
+      LogicalAnnotation::TIME { is_adjusted_to_utc: false, unit: TimeUnit::NANOS },
+      LogicalAnnotation::TIMESTAMP { is_adjusted_to_utc: true, unit: TimeUnit::MICROS },
+      LogicalAnnotation::INTEGER { bit_width: 64, is_signed: false },
+      LogicalAnnotation::UNKNOWN, LogicalAnnotation::JSON, LogicalAnnotation::BSON,
+      LogicalAnnotation::UUID];
+    for annotation in annotations {
+      assert_eq!(LogicalAnnotation::from(parquet::LogicalType::from(annotation)),
+                 annotation);
+      // Converted types are derived for backward compatibility
+      let converted_type = annotation.to_converted_type();
+      if converted_type != LogicalType::NONE {
+        assert_eq!(LogicalAnnotation::from_converted_type(converted_type, 9, 2)
+                     .unwrap().to_converted_type(), converted_type);
+      }
+    }
+
+    assert_eq!(LogicalAnnotation::STRING.to_converted_type(), LogicalType::UTF8);
+    assert_eq!(
+      LogicalAnnotation::TIMESTAMP { is_adjusted_to_utc: true, unit: TimeUnit::MILLIS }
+        .to_converted_type(),
+      LogicalType::TIMESTAMP_MILLIS);
+    // Legacy timestamps and times are adjusted to UTC
+    assert_eq!(
+      LogicalAnnotation::TIMESTAMP { is_adjusted_to_utc: false, unit: TimeUnit::MILLIS }
+        .to_converted_type(),
+      LogicalType::NONE);
+    assert_eq!(
+      LogicalAnnotation::TIME { is_adjusted_to_utc: false, unit: TimeUnit::MICROS }
+        .to_converted_type(),
+      LogicalType::NONE);
+    assert_eq!(
+      LogicalAnnotation::TIMESTAMP { is_adjusted_to_utc: false, unit: TimeUnit::NANOS }
+        .to_converted_type(),
+      LogicalType::NONE);
+    assert_eq!(
+      LogicalAnnotation::INTEGER { bit_width: 16, is_signed: true }.to_converted_type(),
+      LogicalType::INT_16);
+    assert_eq!(
+      LogicalAnnotation::INTEGER { bit_width: 12, is_signed: true }.to_converted_type(),
+      LogicalType::NONE);
+    assert_eq!(
+      LogicalAnnotation::from_converted_type(LogicalType::TIMESTAMP_MILLIS, 0, 0),
+      Some(LogicalAnnotation::TIMESTAMP {
+        is_adjusted_to_utc: true, unit: TimeUnit::MILLIS }));
+    assert_eq!(LogicalAnnotation::from_converted_type(LogicalType::DECIMAL, 9, 2),
+               Some(LogicalAnnotation::DECIMAL { scale: 2, precision: 9 }));
+    assert_eq!(LogicalAnnotation::from_converted_type(LogicalType::INTERVAL, 0, 0), None);
+    assert_eq!(LogicalAnnotation::from_converted_type(LogicalType::NONE, 0, 0), None);
+  }
+
+  #[test]
+  fn test_display_logical_annotation() {
+    assert_eq!(LogicalAnnotation::STRING.to_string(), "STRING");
+    assert_eq!(LogicalAnnotation::UUID.to_string(), "UUID");
+    assert_eq!(LogicalAnnotation::DECIMAL { scale: 2, precision: 9 }.to_string(),
+               "DECIMAL(9,2)");
+    assert_eq!(
+      LogicalAnnotation::TIME { is_adjusted_to_utc: false, unit: TimeUnit::MICROS }
+        .to_string(),
+      "TIME(MICROS,false)");
+    assert_eq!(
+      LogicalAnnotation::TIMESTAMP { is_adjusted_to_utc: true, unit: TimeUnit::NANOS }
+        .to_string(),
+      "TIMESTAMP(NANOS,true)");
+    assert_eq!(LogicalAnnotation::INTEGER { bit_width: 8, is_signed: false }.to_string(),
+               "INTEGER(8,false)");
+  }
+
+  #[test]
+  fn test_from_string_into_time_unit() {
+    assert_eq!("MILLIS".parse::<TimeUnit>().unwrap(), TimeUnit::MILLIS);
+    assert_eq!("MICROS".parse::<TimeUnit>().unwrap(), TimeUnit::MICROS);
+    assert_eq!("NANOS".parse::<TimeUnit>().unwrap(), TimeUnit::NANOS);
+    assert!("SECONDS".parse::<TimeUnit>().is_err());
+  }
 }
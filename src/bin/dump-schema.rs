@@ -23,12 +23,16 @@ use std::fs::File;
 use std::path::Path;
 
 use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::schema::json::{print_file_metadata_json, print_parquet_metadata_json};
 use parquet::schema::printer::{print_parquet_metadata, print_file_metadata};
 
 fn main() {
-  let args: Vec<_> = env::args().collect();
+  // `--json` may be given anywhere, the remaining arguments are positional
+  let all_args: Vec<_> = env::args().collect();
+  let json = all_args.iter().any(|a| a == "--json");
+  let args: Vec<_> = all_args.into_iter().filter(|a| a != "--json").collect();
   if args.len() != 2 && args.len() != 3 {
-    println!("Usage: dump-schema <file-path> <verbose>");
+    println!("Usage: dump-schema [--json] <file-path> <verbose>");
     process::exit(1);
   }
   let path = Path::new(&args[1]);
@@ -52,6 +56,15 @@ fn main() {
     },
     Ok(parquet_reader) => {
       let metadata = parquet_reader.metadata();
+      if json {
+        // Only the JSON document is printed, so that the output can be parsed
+        if verbose {
+          print_parquet_metadata_json(&mut std::io::stdout(), metadata);
+        } else {
+          print_file_metadata_json(&mut std::io::stdout(), metadata.file_metadata());
+        }
+        return;
+      }
       println!("Metadata for file: {}", &args[1]);
       println!("");
       if verbose {
@@ -0,0 +1,953 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Columnar batches of values, in the memory layout of Arrow arrays.
+//!
+//! `ColumnReaderImpl::read_batch` returns the non-null values of a column compacted,
+//! along with raw definition and repetition levels. A `ColumnBatchImpl` instead has a
+//! slot for every value, null or not, a validity bitmap, and the offsets of each level
+//! of lists of the column. `ColumnBatchIter` reads the rows of a column chunk in
+//! batches of a given number of rows.
+//!
+//! - Every repeated field of the path of the column is a level of lists. List `i` of a
+//! level contains the slots `offsets[i]..offsets[i + 1]` of the next level of lists, or
+//! of the values for the innermost level. Lists have a validity bitmap if an optional
+//! field between them and the previous level of lists can make them null.
+//! - Values have a validity bitmap if the column is optional within its innermost list.
+//! Their buffers depend on the physical type, see `BatchValues`, and null values have a
+//! slot with zeroed or no bytes.
+//! - Optional groups are not represented: a null group makes the innermost level below it
+//! null.
+//!
+//! For example, column `a.list.element` of
+//!
+//! ```text
+//! message schema {
+//!   OPTIONAL GROUP a (LIST) {
+//!     REPEATED GROUP list {
+//!       OPTIONAL INT32 element;
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! with rows `[1, null]`, `null`, `[]` and `[2]` is read as a batch of 4 rows, with a
+//! level of lists with offsets `[0, 2, 2, 2, 3]` and validity `[1, 0, 1, 1]`, and values
+//! `[1, 0, 2]` with validity `[1, 0, 1]`.
+
+use std::cmp;
+
+use basic::Repetition;
+use column::reader::{ColumnReader, ColumnReaderImpl};
+use data_type::*;
+use errors::{ErrorContext, ParquetError, Result};
+use schema::types::ColumnDescriptor;
+
+/// Number of levels read at once from a column chunk.
+const READ_BATCH_SIZE: usize = 1024;
+
+/// A bitmap of the validity of slots, where bit `i` is set if slot `i` is not null.
+/// Bits are numbered from the least significant bit of each byte, as in Arrow.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Bitmap {
+  data: Vec<u8>,
+  len: usize
+}
+
+impl Bitmap {
+  pub fn new() -> Self {
+    Self { data: vec!(), len: 0 }
+  }
+
+  /// Returns the bytes of this bitmap.
+  pub fn data(&self) -> &[u8] {
+    &self.data
+  }
+
+  /// Returns the number of bits of this bitmap.
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Returns true if bit `i` is set.
+  pub fn is_set(&self, i: usize) -> bool {
+    assert!(i < self.len, "Index {} out of bounds of a bitmap of length {}", i, self.len);
+    self.data[i / 8] & (1 << (i % 8)) != 0
+  }
+
+  /// Returns the number of bits that are not set.
+  pub fn null_count(&self) -> usize {
+    self.len - self.data.iter().map(|b| b.count_ones() as usize).sum::<usize>()
+  }
+
+  /// Appends a bit.
+  pub fn push(&mut self, is_set: bool) {
+    if self.len % 8 == 0 {
+      self.data.push(0);
+    }
+    if is_set {
+      self.data[self.len / 8] |= 1 << (self.len % 8);
+    }
+    self.len += 1;
+  }
+}
+
+/// The buffers of the values of a column batch, with a slot for every value, in the
+/// memory layout of an Arrow array of the physical type of the column:
+///
+/// - BOOLEAN values are the bits of a `Bitmap`.
+/// - INT32, INT64, FLOAT and DOUBLE values are a `Vec` of the values.
+/// - INT96 values are timestamps, a `Vec` of the number of nanoseconds since the Unix
+/// epoch, see `Int96::to_nanos`.
+/// - BYTE_ARRAY values are `BinaryValues`, offsets into a buffer of their bytes.
+/// - FIXED_LEN_BYTE_ARRAY values are `FixedSizeBinaryValues`, a buffer of their bytes.
+pub trait BatchValues<T> {
+  /// Returns empty buffers for the values of column `descr`.
+  fn new(descr: &ColumnDescriptor) -> Self;
+
+  /// Appends `value`, or an empty slot if it is null.
+  fn push(&mut self, value: Option<&T>) -> Result<()>;
+}
+
+/// A physical type with the buffers of the values of its column batches.
+pub trait BatchType: DataType {
+  type Values: BatchValues<Self::T>;
+}
+
+impl BatchValues<bool> for Bitmap {
+  fn new(_descr: &ColumnDescriptor) -> Self {
+    Bitmap::new()
+  }
+
+  fn push(&mut self, value: Option<&bool>) -> Result<()> {
+    Bitmap::push(self, value == Some(&true));
+    Ok(())
+  }
+}
+
+macro_rules! primitive_values {
+  ($ty:ty) => {
+    impl BatchValues<$ty> for Vec<$ty> {
+      fn new(_descr: &ColumnDescriptor) -> Self {
+        vec!()
+      }
+
+      fn push(&mut self, value: Option<&$ty>) -> Result<()> {
+        Vec::push(self, value.cloned().unwrap_or_default());
+        Ok(())
+      }
+    }
+  }
+}
+
+primitive_values!(i32);
+primitive_values!(i64);
+primitive_values!(f32);
+primitive_values!(f64);
+
+impl BatchValues<Int96> for Vec<i64> {
+  fn new(_descr: &ColumnDescriptor) -> Self {
+    vec!()
+  }
+
+  fn push(&mut self, value: Option<&Int96>) -> Result<()> {
+    let nanos = match value {
+      Some(value) => value.to_nanos()?,
+      None => 0
+    };
+    Vec::push(self, nanos);
+    Ok(())
+  }
+}
+
+/// The values of a BYTE_ARRAY column batch: value `i` is
+/// `data[offsets[i]..offsets[i + 1]]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BinaryValues {
+  offsets: Vec<i32>,
+  data: Vec<u8>
+}
+
+impl BinaryValues {
+  /// Returns the number of values.
+  pub fn len(&self) -> usize {
+    self.offsets.len() - 1
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Returns the offsets of the values, one more than the number of values.
+  pub fn offsets(&self) -> &[i32] {
+    &self.offsets
+  }
+
+  /// Returns the bytes of all values.
+  pub fn data(&self) -> &[u8] {
+    &self.data
+  }
+
+  /// Returns the bytes of value `i`.
+  pub fn value(&self, i: usize) -> &[u8] {
+    &self.data[self.offsets[i] as usize..self.offsets[i + 1] as usize]
+  }
+}
+
+impl BatchValues<ByteArray> for BinaryValues {
+  fn new(_descr: &ColumnDescriptor) -> Self {
+    Self { offsets: vec![0], data: vec!() }
+  }
+
+  fn push(&mut self, value: Option<&ByteArray>) -> Result<()> {
+    if let Some(value) = value {
+      self.data.extend_from_slice(value.data());
+    }
+    if self.data.len() > i32::max_value() as usize {
+      return Err(general_err!(
+        "Values of a batch take more than {} bytes", i32::max_value()));
+    }
+    self.offsets.push(self.data.len() as i32);
+    Ok(())
+  }
+}
+
+/// The values of a FIXED_LEN_BYTE_ARRAY column batch: value `i` is
+/// `data[i * byte_width..(i + 1) * byte_width]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixedSizeBinaryValues {
+  byte_width: usize,
+  data: Vec<u8>
+}
+
+impl FixedSizeBinaryValues {
+  /// Returns the number of values.
+  pub fn len(&self) -> usize {
+    if self.byte_width == 0 { 0 } else { self.data.len() / self.byte_width }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Returns the number of bytes of every value.
+  pub fn byte_width(&self) -> usize {
+    self.byte_width
+  }
+
+  /// Returns the bytes of all values.
+  pub fn data(&self) -> &[u8] {
+    &self.data
+  }
+
+  /// Returns the bytes of value `i`.
+  pub fn value(&self, i: usize) -> &[u8] {
+    &self.data[i * self.byte_width..(i + 1) * self.byte_width]
+  }
+}
+
+impl BatchValues<ByteArray> for FixedSizeBinaryValues {
+  fn new(descr: &ColumnDescriptor) -> Self {
+    Self { byte_width: descr.type_length() as usize, data: vec!() }
+  }
+
+  fn push(&mut self, value: Option<&ByteArray>) -> Result<()> {
+    match value {
+      Some(value) => {
+        if value.len() != self.byte_width {
+          return Err(general_err!("Value of {} bytes in a column of {} bytes",
+            value.len(), self.byte_width));
+        }
+        self.data.extend_from_slice(value.data());
+      },
+      None => {
+        let len = self.data.len();
+        self.data.resize(len + self.byte_width, 0);
+      }
+    }
+    Ok(())
+  }
+}
+
+impl BatchType for BoolType {
+  type Values = Bitmap;
+}
+
+impl BatchType for Int32Type {
+  type Values = Vec<i32>;
+}
+
+impl BatchType for Int64Type {
+  type Values = Vec<i64>;
+}
+
+impl BatchType for Int96Type {
+  type Values = Vec<i64>;
+}
+
+impl BatchType for FloatType {
+  type Values = Vec<f32>;
+}
+
+impl BatchType for DoubleType {
+  type Values = Vec<f64>;
+}
+
+impl BatchType for ByteArrayType {
+  type Values = BinaryValues;
+}
+
+impl BatchType for FixedLenByteArrayType {
+  type Values = FixedSizeBinaryValues;
+}
+
+/// A level of lists of a column batch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ListLevel {
+  // Definition level of the elements of lists of this level
+  def_level: i16,
+  offsets: Vec<i32>,
+  validity: Option<Bitmap>
+}
+
+impl ListLevel {
+  fn new(def_level: i16, is_nullable: bool) -> Self {
+    Self {
+      def_level: def_level,
+      offsets: vec![0],
+      validity: if is_nullable { Some(Bitmap::new()) } else { None }
+    }
+  }
+
+  /// Returns the number of lists of this level.
+  pub fn len(&self) -> usize {
+    self.offsets.len() - 1
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Returns the offsets of the lists of this level, one more than the number of lists.
+  pub fn offsets(&self) -> &[i32] {
+    &self.offsets
+  }
+
+  /// Returns the validity of the lists of this level, or `None` if they cannot be null.
+  pub fn validity(&self) -> Option<&Bitmap> {
+    self.validity.as_ref()
+  }
+}
+
+/// A batch of rows of a column, with values of type `T`.
+pub struct ColumnBatchImpl<T: BatchType> {
+  num_rows: usize,
+  lists: Vec<ListLevel>,
+  values: T::Values,
+  validity: Option<Bitmap>
+}
+
+impl<T: BatchType> ColumnBatchImpl<T> {
+  fn new(descr: &ColumnDescriptor, list_def_levels: &[i16]) -> Self {
+    let mut lists = Vec::with_capacity(list_def_levels.len());
+    let mut def_level = 0;
+    for &list_def_level in list_def_levels {
+      lists.push(ListLevel::new(list_def_level, list_def_level - 1 > def_level));
+      def_level = list_def_level;
+    }
+    Self {
+      num_rows: 0,
+      lists: lists,
+      values: <T::Values as BatchValues<T::T>>::new(descr),
+      validity: if descr.max_def_level() > def_level { Some(Bitmap::new()) } else { None }
+    }
+  }
+
+  /// Returns the number of rows of this batch.
+  pub fn num_rows(&self) -> usize {
+    self.num_rows
+  }
+
+  /// Returns the levels of lists of this batch, from the outermost to the innermost.
+  /// Lists of the outermost level are the rows of the batch.
+  pub fn lists(&self) -> &[ListLevel] {
+    &self.lists
+  }
+
+  /// Returns the values of this batch, with a slot for every null value.
+  pub fn values(&self) -> &T::Values {
+    &self.values
+  }
+
+  /// Returns the validity of the values, or `None` if they cannot be null.
+  pub fn validity(&self) -> Option<&Bitmap> {
+    self.validity.as_ref()
+  }
+
+  /// Adds a slot to lists of level `level`, or a row if `level` is 0.
+  fn push_slot(&mut self, level: usize) {
+    if level == 0 {
+      self.num_rows += 1;
+    } else {
+      *self.lists[level - 1].offsets.last_mut().unwrap() += 1;
+    }
+  }
+
+  /// Adds the triplet with levels `def` and `rep`, and `value` if it is not null.
+  fn push(&mut self, def: i16, rep: i16, value: Option<&T::T>) -> Result<()> {
+    let mut level = rep as usize;
+    self.push_slot(level);
+    while level < self.lists.len() {
+      // Start a list of the next level, in the slot just added
+      let is_empty = {
+        let list = &mut self.lists[level];
+        let end = *list.offsets.last().unwrap();
+        list.offsets.push(end);
+        if let Some(ref mut validity) = list.validity {
+          validity.push(def >= list.def_level - 1);
+        }
+        def < list.def_level
+      };
+      if is_empty {
+        return Ok(());
+      }
+      level += 1;
+      self.push_slot(level);
+    }
+    if let Some(ref mut validity) = self.validity {
+      validity.push(value.is_some());
+    }
+    self.values.push(value)
+  }
+}
+
+/// A batch of rows of a column of any physical type.
+pub enum ColumnBatch {
+  BoolColumnBatch(ColumnBatchImpl<BoolType>),
+  Int32ColumnBatch(ColumnBatchImpl<Int32Type>),
+  Int64ColumnBatch(ColumnBatchImpl<Int64Type>),
+  Int96ColumnBatch(ColumnBatchImpl<Int96Type>),
+  FloatColumnBatch(ColumnBatchImpl<FloatType>),
+  DoubleColumnBatch(ColumnBatchImpl<DoubleType>),
+  ByteArrayColumnBatch(ColumnBatchImpl<ByteArrayType>),
+  FixedLenByteArrayColumnBatch(ColumnBatchImpl<FixedLenByteArrayType>)
+}
+
+/// Iterator over the rows of a column chunk of any physical type, in batches of at most
+/// a given number of rows.
+pub struct ColumnBatchIter<'a> {
+  reader: TypedBatchReader<'a>,
+  batch_size: usize
+}
+
+enum TypedBatchReader<'a> {
+  BoolBatchReader(ColumnBatchReader<'a, BoolType>),
+  Int32BatchReader(ColumnBatchReader<'a, Int32Type>),
+  Int64BatchReader(ColumnBatchReader<'a, Int64Type>),
+  Int96BatchReader(ColumnBatchReader<'a, Int96Type>),
+  FloatBatchReader(ColumnBatchReader<'a, FloatType>),
+  DoubleBatchReader(ColumnBatchReader<'a, DoubleType>),
+  ByteArrayBatchReader(ColumnBatchReader<'a, ByteArrayType>),
+  FixedLenByteArrayBatchReader(ColumnBatchReader<'a, FixedLenByteArrayType>)
+}
+
+impl<'a> ColumnBatchIter<'a> {
+  /// Creates an iterator over the rows of the column chunk read by `reader`, in batches
+  /// of at most `batch_size` rows.
+  pub fn new(reader: ColumnReader<'a>, batch_size: usize) -> Result<Self> {
+    let reader = match reader {
+      ColumnReader::BoolColumnReader(r) => {
+        TypedBatchReader::BoolBatchReader(ColumnBatchReader::new(r)?)
+      },
+      ColumnReader::Int32ColumnReader(r) => {
+        TypedBatchReader::Int32BatchReader(ColumnBatchReader::new(r)?)
+      },
+      ColumnReader::Int64ColumnReader(r) => {
+        TypedBatchReader::Int64BatchReader(ColumnBatchReader::new(r)?)
+      },
+      ColumnReader::Int96ColumnReader(r) => {
+        TypedBatchReader::Int96BatchReader(ColumnBatchReader::new(r)?)
+      },
+      ColumnReader::FloatColumnReader(r) => {
+        TypedBatchReader::FloatBatchReader(ColumnBatchReader::new(r)?)
+      },
+      ColumnReader::DoubleColumnReader(r) => {
+        TypedBatchReader::DoubleBatchReader(ColumnBatchReader::new(r)?)
+      },
+      ColumnReader::ByteArrayColumnReader(r) => {
+        TypedBatchReader::ByteArrayBatchReader(ColumnBatchReader::new(r)?)
+      },
+      ColumnReader::FixedLenByteArrayColumnReader(r) => {
+        TypedBatchReader::FixedLenByteArrayBatchReader(ColumnBatchReader::new(r)?)
+      }
+    };
+    Ok(Self { reader: reader, batch_size: batch_size })
+  }
+}
+
+impl<'a> Iterator for ColumnBatchIter<'a> {
+  type Item = Result<ColumnBatch>;
+
+  fn next(&mut self) -> Option<Result<ColumnBatch>> {
+    let batch_size = self.batch_size;
+    let batch = match self.reader {
+      TypedBatchReader::BoolBatchReader(ref mut r) => {
+        r.next_batch(batch_size).map(|b| b.map(ColumnBatch::BoolColumnBatch))
+      },
+      TypedBatchReader::Int32BatchReader(ref mut r) => {
+        r.next_batch(batch_size).map(|b| b.map(ColumnBatch::Int32ColumnBatch))
+      },
+      TypedBatchReader::Int64BatchReader(ref mut r) => {
+        r.next_batch(batch_size).map(|b| b.map(ColumnBatch::Int64ColumnBatch))
+      },
+      TypedBatchReader::Int96BatchReader(ref mut r) => {
+        r.next_batch(batch_size).map(|b| b.map(ColumnBatch::Int96ColumnBatch))
+      },
+      TypedBatchReader::FloatBatchReader(ref mut r) => {
+        r.next_batch(batch_size).map(|b| b.map(ColumnBatch::FloatColumnBatch))
+      },
+      TypedBatchReader::DoubleBatchReader(ref mut r) => {
+        r.next_batch(batch_size).map(|b| b.map(ColumnBatch::DoubleColumnBatch))
+      },
+      TypedBatchReader::ByteArrayBatchReader(ref mut r) => {
+        r.next_batch(batch_size).map(|b| b.map(ColumnBatch::ByteArrayColumnBatch))
+      },
+      TypedBatchReader::FixedLenByteArrayBatchReader(ref mut r) => {
+        r.next_batch(batch_size)
+          .map(|b| b.map(ColumnBatch::FixedLenByteArrayColumnBatch))
+      }
+    };
+    match batch {
+      Ok(Some(batch)) => Some(Ok(batch)),
+      Ok(None) => None,
+      Err(e) => Some(Err(e))
+    }
+  }
+}
+
+/// Reader of the rows of a column chunk in batches.
+pub struct ColumnBatchReader<'a, T: BatchType> {
+  reader: ColumnReaderImpl<'a, T>,
+  path: String,
+  max_def_level: i16,
+  max_rep_level: i16,
+  // Definition levels of the elements of the repeated fields of the column path
+  list_def_levels: Vec<i16>,
+  // Levels and values read from `reader`, and the position of the next triplet
+  def_levels: Vec<i16>,
+  rep_levels: Vec<i16>,
+  values: Vec<T::T>,
+  num_levels: usize,
+  num_values: usize,
+  level_index: usize,
+  value_index: usize
+}
+
+impl<'a, T: BatchType> ColumnBatchReader<'a, T> where T: 'static {
+  pub fn new(reader: ColumnReaderImpl<'a, T>) -> Result<Self> {
+    let (path, max_def_level, max_rep_level, list_def_levels) = {
+      let descr = reader.descr();
+      (descr.path().string(), descr.max_def_level(), descr.max_rep_level(),
+       list_def_levels(descr)?)
+    };
+    Ok(Self {
+      reader: reader,
+      path: path,
+      max_def_level: max_def_level,
+      max_rep_level: max_rep_level,
+      list_def_levels: list_def_levels,
+      def_levels: vec![0; READ_BATCH_SIZE],
+      rep_levels: vec![0; READ_BATCH_SIZE],
+      values: vec![<T::T>::default(); READ_BATCH_SIZE],
+      num_levels: 0,
+      num_values: 0,
+      level_index: 0,
+      value_index: 0
+    })
+  }
+
+  /// Reads a batch of at most `max_rows` rows, or returns `None` if all rows have been
+  /// read.
+  pub fn next_batch(&mut self, max_rows: usize) -> Result<Option<ColumnBatchImpl<T>>> {
+    let mut batch = ColumnBatchImpl::new(self.reader.descr(), &self.list_def_levels);
+    while self.has_next()? {
+      let i = self.level_index;
+      let def = if self.max_def_level > 0 { self.def_levels[i] } else { 0 };
+      let rep = if self.max_rep_level > 0 { self.rep_levels[i] } else { 0 };
+      if rep == 0 && batch.num_rows == cmp::max(max_rows, 1) {
+        break;
+      }
+      if rep > 0 && batch.num_rows == 0 {
+        return Err(general_err!(
+          "Column {} has a row starting at repetition level {}", self.path, rep));
+      }
+      let value = if def == self.max_def_level {
+        if self.value_index == self.num_values {
+          return Err(decode_err!(ErrorContext::new().with_column(self.path.clone()),
+            "Expected more than {} values for the definition levels of the batch",
+            self.num_values));
+        }
+        self.value_index += 1;
+        Some(&self.values[self.value_index - 1])
+      } else {
+        None
+      };
+      batch.push(def, rep, value)?;
+      self.level_index += 1;
+    }
+    if batch.num_rows == 0 {
+      Ok(None)
+    } else {
+      Ok(Some(batch))
+    }
+  }
+
+  /// Returns true if there is a triplet left to read, reading more levels and values
+  /// if needed.
+  fn has_next(&mut self) -> Result<bool> {
+    if self.level_index < self.num_levels {
+      return Ok(true);
+    }
+    let (values_read, levels_read) = {
+      let def_levels =
+        if self.max_def_level > 0 { Some(&mut self.def_levels[..]) } else { None };
+      let rep_levels =
+        if self.max_rep_level > 0 { Some(&mut self.rep_levels[..]) } else { None };
+      self.reader.read_batch(READ_BATCH_SIZE, def_levels, rep_levels, &mut self.values)?
+    };
+    self.num_levels = if self.max_def_level > 0 { levels_read } else { values_read };
+    self.num_values = values_read;
+    self.level_index = 0;
+    self.value_index = 0;
+    Ok(self.num_levels > 0)
+  }
+}
+
+/// Returns the definition levels of the elements of the repeated fields of the path of
+/// column `descr`, from the outermost.
+fn list_def_levels(descr: &ColumnDescriptor) -> Result<Vec<i16>> {
+  let mut result = vec!();
+  let mut def_level = 0;
+  let mut tp = descr.root_type();
+  for (i, name) in descr.path().parts().iter().enumerate() {
+    if i > 0 {
+      tp = match tp.get_fields().iter().find(|f| f.name() == name) {
+        Some(field) => field,
+        None => return Err(schema_err!(
+          ErrorContext::new().with_column(descr.path().string()),
+          "Column path is not part of the root type {}", descr.root_type().name()))
+      };
+    }
+    if !tp.get_basic_info().has_repetition() {
+      continue;
+    }
+    match tp.get_basic_info().repetition() {
+      Repetition::REQUIRED => {},
+      Repetition::OPTIONAL => def_level += 1,
+      Repetition::REPEATED => {
+        def_level += 1;
+        result.push(def_level);
+      }
+    }
+  }
+  Ok(result)
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::fs::File;
+  use std::rc::Rc;
+  use std::vec::IntoIter;
+
+  use basic::{Encoding, TimeUnit, Type as PhysicalType};
+  use column::page::{Page, PageReader};
+  use encodings::levels::LevelEncoder;
+  use file::reader::{FileReader, SerializedFileReader};
+  use file::writer::{SerializedFileWriter, WriterOptions};
+  use record::api::{Field, Row};
+  use record::writer::RowWriter;
+  use schema::parser::parse_message_type;
+  use schema::types::{ColumnPath, Type as SchemaType};
+  use util::memory::ByteBufferPtr;
+  use util::test_common::get_temp_file_path;
+
+  struct TestPageReader {
+    pages: IntoIter<Page>
+  }
+
+  impl PageReader for TestPageReader {
+    fn get_next_page(&mut self) -> Result<Option<Page>> {
+      Ok(self.pages.next())
+    }
+  }
+
+  fn bitmap(bits: &[u8]) -> Bitmap {
+    let mut bitmap = Bitmap::new();
+    for &bit in bits {
+      bitmap.push(bit == 1);
+    }
+    bitmap
+  }
+
+  fn write_file(file_name: &str, schema: &str, rows: Vec<Vec<(&str, Field)>>) -> File {
+    let path = get_temp_file_path(file_name);
+    let schema = Rc::new(parse_message_type(schema).unwrap());
+    let mut file_writer = SerializedFileWriter::new(
+      File::create(&path).unwrap(), schema, WriterOptions::new()).unwrap();
+    let mut row_writer = RowWriter::new(file_writer.schema_descr().clone());
+    for fields in rows {
+      let row = Row::new(fields.into_iter().map(|(k, v)| (k.to_owned(), v)).collect());
+      row_writer.write(&row).unwrap();
+    }
+    let mut row_group = file_writer.next_row_group();
+    row_writer.write_to_row_group(&mut row_group).unwrap();
+    file_writer.write_row_group(row_group).unwrap();
+    file_writer.close().unwrap();
+    File::open(&path).unwrap()
+  }
+
+  fn list(values: Vec<Field>) -> Field {
+    Field::List(values)
+  }
+
+  #[test]
+  fn test_bitmap() {
+    let bits = bitmap(&[1, 0, 1, 1, 0, 0, 0, 0, 1, 1]);
+    assert_eq!(bits.len(), 10);
+    assert_eq!(bits.data(), &[0b0000_1101, 0b0000_0011]);
+    assert_eq!(bits.null_count(), 5);
+    assert!(bits.is_set(0));
+    assert!(!bits.is_set(4));
+    assert!(bits.is_set(9));
+  }
+
+  #[test]
+  fn test_read_column_batch_nested() {
+    let file = write_file("test_read_column_batch_nested.parquet", "
+      message schema {
+        REQUIRED INT32 id;
+        OPTIONAL GROUP a (LIST) {
+          REPEATED GROUP list {
+            OPTIONAL GROUP element (LIST) {
+              REPEATED GROUP list {
+                OPTIONAL INT32 element;
+              }
+            }
+          }
+        }
+      }
+    ", vec![
+      vec![("id", Field::Int(1)), ("a", list(vec![
+        list(vec![Field::Int(1), Field::Null, Field::Int(2)]),
+        Field::Null,
+        list(vec![])
+      ]))],
+      vec![("id", Field::Int(2))],
+      vec![("id", Field::Int(3)), ("a", list(vec![]))],
+      vec![("id", Field::Int(4)), ("a", list(vec![list(vec![Field::Int(3)])]))]
+    ]);
+    let reader = SerializedFileReader::new(file).unwrap();
+    let row_group = reader.get_row_group(0).unwrap();
+
+    let mut batches = row_group.get_column_batch_iter(0, 10).unwrap();
+    match batches.next().unwrap().unwrap() {
+      ColumnBatch::Int32ColumnBatch(batch) => {
+        assert_eq!(batch.num_rows(), 4);
+        assert!(batch.lists().is_empty());
+        assert_eq!(batch.values(), &[1, 2, 3, 4]);
+        assert_eq!(batch.validity(), None);
+      },
+      _ => panic!("Expected an INT32 batch")
+    }
+    assert!(batches.next().is_none());
+
+    let mut batches = row_group.get_column_batch_iter(1, 10).unwrap();
+    match batches.next().unwrap().unwrap() {
+      ColumnBatch::Int32ColumnBatch(batch) => {
+        assert_eq!(batch.num_rows(), 4);
+        let lists = batch.lists();
+        assert_eq!(lists.len(), 2);
+        assert_eq!(lists[0].offsets(), &[0, 3, 3, 3, 4]);
+        assert_eq!(lists[0].validity(), Some(&bitmap(&[1, 0, 1, 1])));
+        assert_eq!(lists[1].offsets(), &[0, 3, 3, 3, 4]);
+        assert_eq!(lists[1].validity(), Some(&bitmap(&[1, 0, 1, 1])));
+        assert_eq!(batch.values(), &[1, 0, 2, 3]);
+        assert_eq!(batch.validity(), Some(&bitmap(&[1, 0, 1, 1])));
+      },
+      _ => panic!("Expected an INT32 batch")
+    }
+    assert!(batches.next().is_none());
+  }
+
+  #[test]
+  fn test_read_column_batch_values() {
+    let timestamp = Timestamp::new(1235865600000, TimeUnit::MILLIS, true);
+    let bytes = |data: Vec<u8>| Field::Bytes(ByteArray::from(data));
+    let file = write_file("test_read_column_batch_values.parquet", "
+      message schema {
+        OPTIONAL BOOLEAN a;
+        OPTIONAL BYTE_ARRAY b (UTF8);
+        OPTIONAL INT96 c;
+        OPTIONAL FIXED_LEN_BYTE_ARRAY (2) d;
+      }
+    ", vec![
+      vec![("a", Field::Bool(true)), ("b", Field::Str("ab".to_owned())),
+           ("c", Field::Timestamp(timestamp)), ("d", bytes(vec![1, 2]))],
+      vec![],
+      vec![("a", Field::Bool(false)), ("b", Field::Str("".to_owned())),
+           ("d", bytes(vec![3, 4]))],
+      vec![("a", Field::Bool(true)), ("b", Field::Str("cde".to_owned()))]
+    ]);
+    let reader = SerializedFileReader::new(file).unwrap();
+    let row_group = reader.get_row_group(0).unwrap();
+    let first_batch = |i: usize| {
+      row_group.get_column_batch_iter(i, 10).unwrap().next().unwrap().unwrap()
+    };
+
+    match first_batch(0) {
+      ColumnBatch::BoolColumnBatch(batch) => {
+        assert_eq!(batch.values(), &bitmap(&[1, 0, 0, 1]));
+        assert_eq!(batch.validity(), Some(&bitmap(&[1, 0, 1, 1])));
+      },
+      _ => panic!("Expected a BOOLEAN batch")
+    }
+
+    match first_batch(1) {
+      ColumnBatch::ByteArrayColumnBatch(batch) => {
+        let values = batch.values();
+        assert_eq!(values.len(), 4);
+        assert_eq!(values.offsets(), &[0, 2, 2, 2, 5]);
+        assert_eq!(values.data(), b"abcde");
+        assert_eq!(values.value(3), b"cde");
+        assert_eq!(batch.validity(), Some(&bitmap(&[1, 0, 1, 1])));
+      },
+      _ => panic!("Expected a BYTE_ARRAY batch")
+    }
+
+    match first_batch(2) {
+      ColumnBatch::Int96ColumnBatch(batch) => {
+        assert_eq!(batch.values(), &[1235865600000000000, 0, 0, 0]);
+        assert_eq!(batch.validity(), Some(&bitmap(&[1, 0, 0, 0])));
+      },
+      _ => panic!("Expected an INT96 batch")
+    }
+
+    match first_batch(3) {
+      ColumnBatch::FixedLenByteArrayColumnBatch(batch) => {
+        let values = batch.values();
+        assert_eq!(values.len(), 4);
+        assert_eq!(values.byte_width(), 2);
+        assert_eq!(values.data(), &[1, 2, 0, 0, 3, 4, 0, 0]);
+        assert_eq!(batch.validity(), Some(&bitmap(&[1, 0, 1, 0])));
+      },
+      _ => panic!("Expected a FIXED_LEN_BYTE_ARRAY batch")
+    }
+  }
+
+  #[test]
+  fn test_read_batches_of_rows() {
+    let rows = (0..3000).map(|i| {
+      let values = (0..i % 4).map(|j| Field::Long(i * 10 + j)).collect();
+      vec![("a", list(values))]
+    }).collect();
+    let file = write_file("test_read_batches_of_rows.parquet", "
+      message schema {
+        REPEATED INT64 a;
+      }
+    ", rows);
+    let reader = SerializedFileReader::new(file).unwrap();
+    let row_group = reader.get_row_group(0).unwrap();
+    let column_reader = match row_group.get_column_reader(0).unwrap() {
+      ColumnReader::Int64ColumnReader(r) => r,
+      _ => panic!("Expected an INT64 column")
+    };
+    let mut batch_reader = ColumnBatchReader::new(column_reader).unwrap();
+
+    let mut num_rows = 0;
+    while let Some(batch) = batch_reader.next_batch(1000).unwrap() {
+      assert_eq!(batch.num_rows(), 1000);
+      let lists = batch.lists();
+      assert_eq!(lists.len(), 1);
+      assert_eq!(lists[0].len(), 1000);
+      assert_eq!(lists[0].validity(), None);
+      assert_eq!(batch.validity(), None);
+      for i in 0..1000 {
+        let row = (num_rows + i) as i64;
+        let start = lists[0].offsets()[i] as usize;
+        let end = lists[0].offsets()[i + 1] as usize;
+        let expected: Vec<i64> = (0..row % 4).map(|j| row * 10 + j).collect();
+        assert_eq!(&batch.values()[start..end], &expected[..]);
+      }
+      num_rows += batch.num_rows();
+    }
+    assert_eq!(num_rows, 3000);
+  }
+
+  #[test]
+  fn test_next_batch_truncated_page() {
+    let tp = Rc::new(SchemaType::primitive_type_builder("a", PhysicalType::INT32)
+      .with_repetition(Repetition::OPTIONAL)
+      .build()
+      .unwrap());
+    let descr = Rc::new(ColumnDescriptor::new(
+      tp.clone(), Some(tp), 1, 0, ColumnPath::new(vec!["a".to_owned()])));
+
+    // The definition levels declare 3 values, but the page only contains 1
+    let def_levels = [1, 0, 1, 1];
+    let size = LevelEncoder::max_buffer_size(Encoding::RLE, 1, def_levels.len());
+    let mut encoder = LevelEncoder::new(Encoding::RLE, 1, vec![0; size]);
+    encoder.put(&def_levels).unwrap();
+    let mut buf = encoder.consume().unwrap();
+    buf.extend_from_slice(7i32.as_bytes());
+    let page = Page::DataPage {
+      buf: ByteBufferPtr::new(buf),
+      num_values: def_levels.len() as u32,
+      encoding: Encoding::PLAIN,
+      def_level_encoding: Encoding::RLE,
+      rep_level_encoding: Encoding::RLE
+    };
+    let page_reader = TestPageReader { pages: vec![page].into_iter() };
+    let reader = ColumnReaderImpl::<Int32Type>::new(descr, Box::new(page_reader));
+
+    let mut batch_reader = ColumnBatchReader::new(reader).unwrap();
+    match batch_reader.next_batch(10) {
+      Err(ParquetError::Decode(_, _)) => (),
+      Err(e) => panic!("Expected a decode error, got {:?}", e),
+      Ok(_) => panic!("Expected a decode error")
+    }
+  }
+
+  #[test]
+  fn test_list_def_levels_invalid_path() {
+    let tp = Rc::new(SchemaType::primitive_type_builder("a", PhysicalType::INT32)
+      .build()
+      .unwrap());
+    let descr = ColumnDescriptor::new(
+      tp.clone(), Some(tp), 0, 0, ColumnPath::new(vec!["a".to_owned(), "b".to_owned()]));
+    match list_def_levels(&descr) {
+      Err(ParquetError::SchemaMismatch(_, _)) => (),
+      other => panic!("Expected a schema error, got {:?}", other)
+    }
+  }
+}
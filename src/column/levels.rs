@@ -0,0 +1,602 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Computation of the definition and repetition levels of a leaf column from nested
+//! values, and of nested values from levels.
+//!
+//! The nesting of a column is given by the shape of its Rust values: `Option<V>` is an
+//! optional field or group, `Vec<V>` a repeated field or list, and the physical values
+//! of the column (`bool`, `i32`, `i64`, `Int96`, `f32`, `f64` and `ByteArray`) are the
+//! leaves. For example, the values of column `a.list.element` of
+//!
+//! ```text
+//! message schema {
+//!   OPTIONAL GROUP a (LIST) {
+//!     REPEATED GROUP list {
+//!       OPTIONAL INT32 element;
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! are `Option<Vec<Option<i32>>>`, and are converted with:
+//!
+//! ```ignore
+//! let levels = to_levels(&descr, &values)?;
+//! writer.write_batch(
+//!   &levels.values, Some(&levels.def_levels), Some(&levels.rep_levels))?;
+//!
+//! let values: Vec<Option<Vec<Option<i32>>>> = from_levels(&descr, levels)?;
+//! ```
+//!
+//! Every OPTIONAL field on the path of the column, from its root to the leaf, must be
+//! matched by an `Option` of the shape, and every REPEATED field by a `Vec`.
+//!
+//! The level computation of `Option` and `Vec` is done by `shred_option`, `shred_list`,
+//! `assemble_option` and `assemble_list`, which also shred and assemble the fields of
+//! `record::typed` into several leaf columns at once through `LevelSink` and
+//! `LevelSource`.
+
+use std::mem;
+
+use basic::Repetition;
+use data_type::{ByteArray, Int96};
+use errors::{ParquetError, Result};
+use schema::types::ColumnDescriptor;
+
+/// The definition and repetition levels of a leaf column, with its non-null values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Levels<T> {
+  pub def_levels: Vec<i16>,
+  pub rep_levels: Vec<i16>,
+  pub values: Vec<T>
+}
+
+impl<T> Levels<T> {
+  pub fn new() -> Self {
+    Self { def_levels: vec!(), rep_levels: vec!(), values: vec!() }
+  }
+
+  /// Appends a value, or a null if `value` is `None`, with its levels.
+  pub fn push(&mut self, def_level: i16, rep_level: i16, value: Option<T>) {
+    self.def_levels.push(def_level);
+    self.rep_levels.push(rep_level);
+    if let Some(value) = value {
+      self.values.push(value);
+    }
+  }
+}
+
+impl<T> LevelSink for Levels<T> {
+  fn push_null(&mut self, def_level: i16, rep_level: i16) {
+    self.push(def_level, rep_level, None);
+  }
+}
+
+/// Computes the levels and values of column `descr` from `values`, one per row.
+/// Returns an error if the shape of the values does not match the path of the column.
+pub fn to_levels<V: NestedValue>(
+  descr: &ColumnDescriptor, values: &[V]
+) -> Result<Levels<V::Leaf>> {
+  check_shape::<V>(descr)?;
+  let mut levels = Levels::new();
+  for value in values {
+    value.shred(0, 0, 0, &mut levels);
+  }
+  Ok(levels)
+}
+
+/// Assembles the rows of column `descr` from `levels`, the reverse of `to_levels`.
+///
+/// As with `ColumnReaderImpl::read_batch`, definition levels can be empty if the
+/// maximum definition level is 0, and repetition levels can be empty if the maximum
+/// repetition level is 0.
+pub fn from_levels<V: NestedValue>(
+  descr: &ColumnDescriptor, levels: Levels<V::Leaf>
+) -> Result<Vec<V>> {
+  check_shape::<V>(descr)?;
+  let mut reader = LevelReader::new(descr, levels)?;
+  let mut result = vec!();
+  while reader.has_next() {
+    if reader.peek_rep_level().unwrap_or(0) != 0 {
+      return Err(general_err!("Row of column {} starts at repetition level {}",
+        descr.path().string(), reader.peek_rep_level().unwrap()));
+    }
+    result.push(V::assemble(0, 0, &mut reader)?);
+  }
+  if reader.has_next_value() {
+    return Err(general_err!("Column {} has more values than non-null levels",
+      descr.path().string()));
+  }
+  Ok(result)
+}
+
+fn check_shape<V: NestedValue>(descr: &ColumnDescriptor) -> Result<()> {
+  let shape = column_shape(descr)?;
+  if V::shape() != shape {
+    return Err(general_err!("Values of shape {} do not match column {} of shape {}",
+      shape_string(&V::shape()), descr.path().string(), shape_string(&shape)));
+  }
+  Ok(())
+}
+
+/// Returns the repetitions of the OPTIONAL and REPEATED fields on the path of column
+/// `descr`, from its root to the leaf, i.e. the `NestedValue::shape` of its values.
+pub fn column_shape(descr: &ColumnDescriptor) -> Result<Vec<Repetition>> {
+  let mut shape = vec!();
+  let mut tp = descr.root_type();
+  for (i, name) in descr.path().parts().iter().enumerate() {
+    if i > 0 {
+      let parent = tp;
+      tp = parent.get_fields().iter()
+        .find(|field| field.name() == name.as_str())
+        .map(|field| field.as_ref())
+        .ok_or_else(|| general_err!(
+          "Column {} is not part of field {}", descr.path().string(), parent.name()))?;
+    }
+    let repetition = tp.get_basic_info().repetition();
+    if repetition != Repetition::REQUIRED {
+      shape.push(repetition);
+    }
+  }
+  Ok(shape)
+}
+
+// Formats `shape` as the type of the values, e.g. `Option<Vec<_>>`
+fn shape_string(shape: &[Repetition]) -> String {
+  let mut result = String::new();
+  for repetition in shape {
+    result.push_str(if *repetition == Repetition::REPEATED { "Vec<" } else { "Option<" });
+  }
+  result.push('_');
+  for _ in shape {
+    result.push('>');
+  }
+  result
+}
+
+/// The shape of the values of a leaf column.
+///
+/// `def` is the definition level of the parent of a value, `rep` the repetition level
+/// of its first leaf value, and `depth` the number of `Vec`s around it.
+pub trait NestedValue: Sized {
+  /// Type of the non-null values of the column.
+  type Leaf;
+
+  /// Returns the repetitions of the fields of a column with values of this shape, from
+  /// the outside in: OPTIONAL for an `Option` and REPEATED for a `Vec`.
+  fn shape() -> Vec<Repetition>;
+
+  /// Appends the levels and leaf values of this value to `levels`.
+  fn shred(&self, def: i16, rep: i16, depth: i16, levels: &mut Levels<Self::Leaf>);
+
+  /// Reads the next value from `reader`.
+  fn assemble(def: i16, depth: i16, reader: &mut LevelReader<Self::Leaf>) -> Result<Self>;
+}
+
+/// Destination of the levels of one or more leaf columns, see `shred_option` and
+/// `shred_list`.
+pub trait LevelSink {
+  /// Appends a null, or an empty list, with its levels to every leaf column.
+  fn push_null(&mut self, def_level: i16, rep_level: i16);
+}
+
+/// Source of the levels of one or more leaf columns, which are positioned at the same
+/// value, see `assemble_option` and `assemble_list`. Levels are those of the first leaf
+/// column.
+pub trait LevelSource {
+  /// Returns the definition level of the next value.
+  fn peek_def_level(&mut self) -> Result<i16>;
+
+  /// Returns the repetition level of the next value, or `None` at the end of the
+  /// columns.
+  fn peek_rep_level(&mut self) -> Result<Option<i16>>;
+
+  /// Skips a null, or an empty list, in every leaf column.
+  fn skip_null(&mut self) -> Result<()>;
+}
+
+/// Shreds `value`, an optional value whose parent is at definition level `def`: a
+/// null is appended to `sink` if `value` is `None`, and otherwise the value is shredded
+/// by `shred` with its levels.
+pub fn shred_option<V, S: LevelSink, F>(
+  value: Option<&V>, def: i16, rep: i16, depth: i16, sink: &mut S, shred: F
+) where F: FnOnce(&V, i16, i16, i16, &mut S) {
+  match value {
+    Some(value) => shred(value, def + 1, rep, depth, sink),
+    None => sink.push_null(def, rep)
+  }
+}
+
+/// Shreds `values`, a list whose parent is at definition level `def`: a null is
+/// appended to `sink` if the list is empty, and otherwise each element is shredded by
+/// `shred` with its levels.
+pub fn shred_list<V, S: LevelSink, F>(
+  values: &[V], def: i16, rep: i16, depth: i16, sink: &mut S, mut shred: F
+) where F: FnMut(&V, i16, i16, i16, &mut S) {
+  if values.is_empty() {
+    sink.push_null(def, rep);
+  }
+  for (i, element) in values.iter().enumerate() {
+    let rep = if i == 0 { rep } else { depth + 1 };
+    shred(element, def + 1, rep, depth + 1, sink);
+  }
+}
+
+/// Reads an optional value whose parent is at definition level `def` from `source`,
+/// the reverse of `shred_option`. The value is assembled by `assemble`.
+pub fn assemble_option<V, R: LevelSource, F>(
+  def: i16, depth: i16, source: &mut R, assemble: F
+) -> Result<Option<V>> where F: FnOnce(i16, i16, &mut R) -> Result<V> {
+  if source.peek_def_level()? <= def {
+    source.skip_null()?;
+    return Ok(None);
+  }
+  assemble(def + 1, depth, source).map(Some)
+}
+
+/// Reads a list whose parent is at definition level `def` from `source`, the reverse
+/// of `shred_list`. The elements are assembled by `assemble`.
+pub fn assemble_list<V, R: LevelSource, F>(
+  def: i16, depth: i16, source: &mut R, mut assemble: F
+) -> Result<Vec<V>> where F: FnMut(i16, i16, &mut R) -> Result<V> {
+  let mut result = vec!();
+  if source.peek_def_level()? <= def {
+    source.skip_null()?;
+    return Ok(result);
+  }
+  loop {
+    result.push(assemble(def + 1, depth + 1, source)?);
+    if source.peek_rep_level()? != Some(depth + 1) {
+      return Ok(result);
+    }
+  }
+}
+
+/// Reader of the levels and values of a leaf column, used by `NestedValue::assemble`.
+pub struct LevelReader<T> {
+  path: String,
+  max_def_level: i16,
+  max_rep_level: i16,
+  // Buffers of the levels and values, of which only the first `num_levels` levels and
+  // `num_values` values are read
+  levels: Levels<T>,
+  num_levels: usize,
+  num_values: usize,
+  // Index of the next level and of the next value to read
+  index: usize,
+  value_index: usize
+}
+
+impl<T> LevelReader<T> {
+  /// Creates a reader of `levels`, the levels and values of column `descr`, see
+  /// `from_levels`.
+  pub fn new(descr: &ColumnDescriptor, levels: Levels<T>) -> Result<Self> {
+    let num_levels = if descr.max_def_level() > 0 {
+      levels.def_levels.len()
+    } else {
+      levels.rep_levels.len()
+    };
+    if descr.max_def_level() > 0 && levels.def_levels.len() != num_levels ||
+      descr.max_rep_level() > 0 && levels.rep_levels.len() != num_levels {
+      return Err(general_err!(
+        "Column {} has {} definition levels and {} repetition levels",
+        descr.path().string(), levels.def_levels.len(), levels.rep_levels.len()));
+    }
+    let num_values = levels.values.len();
+    let mut result = Self {
+      path: descr.path().string(),
+      max_def_level: descr.max_def_level(),
+      max_rep_level: descr.max_rep_level(),
+      levels: levels,
+      num_levels: 0,
+      num_values: 0,
+      index: 0,
+      value_index: 0
+    };
+    result.reset(num_values, num_levels)?;
+    Ok(result)
+  }
+
+  /// Reads the levels and values written by `fill` to the buffers of this reader,
+  /// instead of the remaining ones. `fill` returns the number of values and levels it
+  /// wrote at the start of the buffers, as `ColumnReaderImpl::read_batch`, and can
+  /// resize them; buffers are kept between calls, so that they are only allocated once.
+  pub fn refill<F>(&mut self, fill: F) -> Result<()>
+    where F: FnOnce(&mut Levels<T>) -> Result<(usize, usize)> {
+    let (num_values, num_levels) = fill(&mut self.levels)?;
+    self.reset(num_values, num_levels)
+  }
+
+  // Starts reading the first `num_values` values and `num_levels` levels of the buffers.
+  // Levels are only read from buffers of levels whose max level is not 0, and there is
+  // one level per value if both max levels are 0.
+  fn reset(&mut self, num_values: usize, num_levels: usize) -> Result<()> {
+    let num_levels = if self.max_def_level > 0 || self.max_rep_level > 0 {
+      num_levels
+    } else {
+      num_values
+    };
+    if num_values > self.levels.values.len() ||
+      self.max_def_level > 0 && num_levels > self.levels.def_levels.len() ||
+      self.max_rep_level > 0 && num_levels > self.levels.rep_levels.len() {
+      return Err(general_err!(
+        "Column {} has {} levels and {} values, more than its buffers hold",
+        self.path, num_levels, num_values));
+    }
+    self.num_levels = num_levels;
+    self.num_values = num_values;
+    self.index = 0;
+    self.value_index = 0;
+    Ok(())
+  }
+
+  /// Returns true if there are levels left to read.
+  pub fn has_next(&self) -> bool {
+    self.index < self.num_levels
+  }
+
+  /// Returns true if there are values left to read.
+  pub fn has_next_value(&self) -> bool {
+    self.value_index < self.num_values
+  }
+
+  /// Returns the definition level of the next value.
+  pub fn peek_def_level(&self) -> Result<i16> {
+    if !self.has_next() {
+      return Err(general_err!("Unexpected end of column {}", self.path));
+    }
+    if self.max_def_level == 0 {
+      Ok(0)
+    } else {
+      Ok(self.levels.def_levels[self.index])
+    }
+  }
+
+  /// Returns the repetition level of the next value, if any.
+  pub fn peek_rep_level(&self) -> Option<i16> {
+    if !self.has_next() {
+      None
+    } else if self.max_rep_level == 0 {
+      Some(0)
+    } else {
+      Some(self.levels.rep_levels[self.index])
+    }
+  }
+
+  /// Skips the next value, which must be a null or an empty list.
+  pub fn skip_null(&mut self) -> Result<()> {
+    if self.peek_def_level()? == self.max_def_level {
+      return Err(general_err!("Unexpected non-null value in column {}", self.path));
+    }
+    self.index += 1;
+    Ok(())
+  }
+}
+
+impl<T: Default> LevelReader<T> {
+  /// Reads the next value, which must not be null. Its slot in the buffer is left
+  /// with the default value.
+  pub fn next_value(&mut self) -> Result<T> {
+    if self.peek_def_level()? != self.max_def_level {
+      return Err(general_err!("Unexpected null value in column {}", self.path));
+    }
+    if !self.has_next_value() {
+      return Err(general_err!(
+        "Column {} has fewer values than non-null levels", self.path));
+    }
+    let value = mem::replace(&mut self.levels.values[self.value_index], T::default());
+    self.value_index += 1;
+    self.index += 1;
+    Ok(value)
+  }
+}
+
+impl<T> LevelSource for LevelReader<T> {
+  fn peek_def_level(&mut self) -> Result<i16> {
+    LevelReader::peek_def_level(self)
+  }
+
+  fn peek_rep_level(&mut self) -> Result<Option<i16>> {
+    Ok(LevelReader::peek_rep_level(self))
+  }
+
+  fn skip_null(&mut self) -> Result<()> {
+    LevelReader::skip_null(self)
+  }
+}
+
+macro_rules! leaf_value {
+  ($ty:ty) => {
+    impl NestedValue for $ty {
+      type Leaf = $ty;
+
+      fn shape() -> Vec<Repetition> {
+        vec!()
+      }
+
+      fn shred(&self, def: i16, rep: i16, _depth: i16, levels: &mut Levels<$ty>) {
+        levels.push(def, rep, Some(self.clone()));
+      }
+
+      fn assemble(_def: i16, _depth: i16, reader: &mut LevelReader<$ty>) -> Result<Self> {
+        reader.next_value()
+      }
+    }
+  }
+}
+
+leaf_value!(bool);
+leaf_value!(i32);
+leaf_value!(i64);
+leaf_value!(Int96);
+leaf_value!(f32);
+leaf_value!(f64);
+leaf_value!(ByteArray);
+
+impl<V: NestedValue> NestedValue for Option<V> {
+  type Leaf = V::Leaf;
+
+  fn shape() -> Vec<Repetition> {
+    let mut shape = vec![Repetition::OPTIONAL];
+    shape.extend(V::shape());
+    shape
+  }
+
+  fn shred(&self, def: i16, rep: i16, depth: i16, levels: &mut Levels<V::Leaf>) {
+    shred_option(self.as_ref(), def, rep, depth, levels, V::shred)
+  }
+
+  fn assemble(def: i16, depth: i16, reader: &mut LevelReader<V::Leaf>) -> Result<Self> {
+    assemble_option(def, depth, reader, V::assemble)
+  }
+}
+
+impl<V: NestedValue> NestedValue for Vec<V> {
+  type Leaf = V::Leaf;
+
+  fn shape() -> Vec<Repetition> {
+    let mut shape = vec![Repetition::REPEATED];
+    shape.extend(V::shape());
+    shape
+  }
+
+  fn shred(&self, def: i16, rep: i16, depth: i16, levels: &mut Levels<V::Leaf>) {
+    shred_list(self, def, rep, depth, levels, V::shred)
+  }
+
+  fn assemble(def: i16, depth: i16, reader: &mut LevelReader<V::Leaf>) -> Result<Self> {
+    assemble_list(def, depth, reader, V::assemble)
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::error::Error;
+  use std::rc::Rc;
+
+  use schema::parser::parse_message_type;
+  use schema::types::{ColumnDescPtr, SchemaDescriptor};
+
+  fn column(schema: &str) -> ColumnDescPtr {
+    let schema = parse_message_type(schema).unwrap();
+    SchemaDescriptor::new(Rc::new(schema)).column(0)
+  }
+
+  #[test]
+  fn test_nested_lists() {
+    let descr = column("
+      message schema {
+        REQUIRED GROUP a (LIST) {
+          REPEATED GROUP list {
+            OPTIONAL GROUP element (LIST) {
+              REPEATED GROUP list {
+                OPTIONAL INT32 element;
+              }
+            }
+          }
+        }
+      }
+    ");
+    let values: Vec<Vec<Option<Vec<Option<i32>>>>> = vec![
+      vec![Some(vec![Some(1), None, Some(2)]), None, Some(vec![]), Some(vec![None])],
+      vec![Some(vec![Some(3)])],
+      vec![]
+    ];
+    let levels = to_levels(&descr, &values).unwrap();
+    assert_eq!(levels, Levels {
+      def_levels: vec![4, 3, 4, 1, 2, 3, 4, 0],
+      rep_levels: vec![0, 2, 2, 1, 1, 1, 0, 0],
+      values: vec![1, 2, 3]
+    });
+    assert_eq!(from_levels(&descr, levels).unwrap(), values);
+  }
+
+  #[test]
+  fn test_two_level_list() {
+    let descr = column("
+      message schema {
+        OPTIONAL GROUP a {
+          REPEATED INT64 b;
+        }
+      }
+    ");
+    let values = vec![Some(vec![1i64, 2]), None, Some(vec![]), Some(vec![3])];
+    let levels = to_levels(&descr, &values).unwrap();
+    assert_eq!(levels.def_levels, vec![2, 2, 0, 1, 2]);
+    assert_eq!(levels.rep_levels, vec![0, 1, 0, 0, 0]);
+    assert_eq!(from_levels(&descr, levels).unwrap(), values);
+  }
+
+  #[test]
+  fn test_required_values_without_levels() {
+    let descr = column("message schema { REQUIRED BOOLEAN a; }");
+    let levels = Levels {
+      def_levels: vec!(), rep_levels: vec!(), values: vec![true, false]
+    };
+    assert_eq!(from_levels::<bool>(&descr, levels).unwrap(), vec![true, false]);
+  }
+
+  #[test]
+  fn test_shape_mismatch() {
+    let descr = column("message schema { OPTIONAL INT32 a; }");
+    let result = to_levels(&descr, &[1i32, 2]);
+    assert_eq!(result.unwrap_err().description(),
+      "Values of shape _ do not match column a of shape Option<_>");
+
+    // Same maximum levels, but the list is inside the optional group
+    let descr = column("
+      message schema {
+        OPTIONAL GROUP a {
+          REQUIRED GROUP b (LIST) {
+            REPEATED GROUP list {
+              REQUIRED INT32 element;
+            }
+          }
+        }
+      }
+    ");
+    assert_eq!(column_shape(&descr).unwrap(),
+      vec![Repetition::OPTIONAL, Repetition::REPEATED]);
+    let values: Vec<Vec<Option<i32>>> = vec![vec![Some(1)]];
+    let result = to_levels(&descr, &values);
+    assert_eq!(result.unwrap_err().description(),
+      "Values of shape Vec<Option<_>> do not match column a.b.list.element of shape \
+       Option<Vec<_>>");
+  }
+
+  #[test]
+  fn test_invalid_levels() {
+    let descr = column("message schema { OPTIONAL INT32 a; }");
+    let levels = Levels {
+      def_levels: vec![1, 0, 1], rep_levels: vec!(), values: vec![1]
+    };
+    let result = from_levels::<Option<i32>>(&descr, levels);
+    assert_eq!(result.unwrap_err().description(),
+      "Column a has fewer values than non-null levels");
+
+    let levels = Levels {
+      def_levels: vec![1, 0], rep_levels: vec!(), values: vec![1, 2]
+    };
+    let result = from_levels::<Option<i32>>(&descr, levels);
+    assert_eq!(result.unwrap_err().description(),
+      "Column a has more values than non-null levels");
+  }
+}
@@ -15,5 +15,9 @@
 // specific language governing permissions and limitations
 // under the License.
 
+pub mod batch;
+pub mod levels;
 pub mod page;
+pub mod projection;
 pub mod reader;
+pub mod writer;
@@ -0,0 +1,782 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Reading columns with a requested schema that differs from the schema of the file,
+//! e.g. because the schema of a table evolved over time.
+//!
+//! The columns of the requested schema are first resolved against the columns of the
+//! file with `resolve_columns`. Columns that are missing from the file are read as
+//! nulls. Columns that exist in the file can be read as long as their type can be
+//! promoted safely:
+//! - INT32 to INT64, for integers and decimals
+//! - FLOAT to DOUBLE
+//! - DECIMAL precision widening with the same scale, including decimals stored as
+//!   FIXED_LEN_BYTE_ARRAY with a greater length
+//!
+//! A column can also be OPTIONAL in the requested schema and REQUIRED in the file.
+//!
+//! A missing column is null at its nearest ancestor that exists in the file, so it can
+//! only be read if it is not REQUIRED below that ancestor. Its levels are derived from
+//! the levels of a column of the file under the same ancestor.
+
+use std::cmp;
+use std::slice;
+
+use basic::{LogicalType, Repetition, Type};
+use column::reader::{get_typed_column_reader, ColumnReader, ColumnReaderImpl};
+use data_type::*;
+use errors::{ErrorContext, ParquetError, Result};
+use file::reader::RowGroupReader;
+use schema::merge::{is_promotable, type_str};
+use schema::types::{ColumnDescPtr, ColumnDescriptor, ColumnPath, SchemaDescriptor};
+use schema::types::Type as SchemaType;
+
+/// Where the values of a column of the requested schema come from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColumnSource {
+  /// The leaf column with this index in the schema of the file.
+  Column(usize),
+  /// The column does not exist in the file, and all its values are null.
+  Missing
+}
+
+/// Resolves the leaf columns of the `requested` schema against the leaf columns of the
+/// `file` schema, and returns the source of each requested column.
+///
+/// Columns are matched by path, or by the field id of the leaf column if `match_by_id`
+/// is set and the requested column has an id. Returns an error if a requested column
+/// cannot be read from the file, i.e. if it is missing but REQUIRED below its nearest
+/// ancestor in the file, or if its type or repetition differ in a way that cannot be
+/// promoted.
+pub fn resolve_columns(
+  requested: &SchemaDescriptor, file: &SchemaDescriptor, match_by_id: bool
+) -> Result<Vec<ColumnSource>> {
+  let mut result = Vec::with_capacity(requested.num_columns());
+  for column in requested.columns() {
+    let id = if match_by_id { field_id(column) } else { None };
+    let index = match id {
+      Some(id) => {
+        file.column_index_by_id(id).map_err(|e| e.with_context(&context(column)))?
+      },
+      None => file.column_index(column.path())
+    };
+    match index {
+      Some(i) => {
+        check_promotion(column, &file.column(i))?;
+        result.push(ColumnSource::Column(i));
+      },
+      None => {
+        present_ancestor_depth(column, file)?;
+        result.push(ColumnSource::Missing);
+      }
+    }
+  }
+  Ok(result)
+}
+
+/// Returns a reader for leaf column `i` of the `requested` schema in `row_group`, where
+/// `sources` are the sources of the requested columns returned by `resolve_columns`.
+/// `T` must be the data type of the requested column.
+pub fn get_projected_column_reader<'a, 'b, T: DataType>(
+  row_group: &'b RowGroupReader<'a>,
+  requested: &SchemaDescriptor,
+  sources: &[ColumnSource],
+  i: usize
+) -> Result<ProjectedColumnReader<'b, T>> where T: 'static {
+  let descr = requested.column(i);
+  match sources[i] {
+    ColumnSource::Column(j) => {
+      let file_descr = row_group.metadata().schema_descr().column(j);
+      ProjectedColumnReader::new(descr, file_descr, row_group.get_column_reader(j)?)
+    },
+    ColumnSource::Missing => ProjectedColumnReader::missing(descr, row_group)
+  }
+}
+
+/// A value reader for a column of a requested schema. Reads the values of the file
+/// column, promoted to the requested type `T`, or nulls if the column is missing.
+pub struct ProjectedColumnReader<'a, T: DataType> {
+  descr: ColumnDescPtr,
+  source: Source<'a, T>,
+  // Maximum definition level of the file column
+  file_max_def_level: i16
+}
+
+enum Source<'a, T: DataType> {
+  // The file column has the same physical type as `T`
+  Column(ColumnReaderImpl<'a, T>),
+  // The values of the file column are promoted to `T`
+  Promoted(ColumnReader<'a>, Promotion),
+  // The column is missing at the top level, with the number of rows left to read
+  Missing(usize),
+  // The column is missing below a group that exists in the file
+  MissingNested(MissingLevels<'a>)
+}
+
+// Levels of a missing column below its nearest ancestor in the file, derived from the
+// levels of a file column under that ancestor.
+struct MissingLevels<'a> {
+  reader: ColumnReader<'a>,
+  // Maximum levels of the file column
+  file_max_def_level: i16,
+  file_max_rep_level: i16,
+  // Repetition level of the ancestor. Levels of the file column with a greater
+  // repetition level repeat within a value of the ancestor, and are skipped.
+  max_rep_level: i16,
+  // Definition level of the requested column for each definition level of the file
+  // column up to the one of the ancestor. Greater levels map to the last one, as the
+  // requested column is null right below the ancestor.
+  def_levels: Vec<i16>,
+  // Levels of the file column of the current batch
+  file_def_levels: Vec<i16>,
+  file_rep_levels: Vec<i16>
+}
+
+#[derive(Clone, Copy)]
+enum Promotion {
+  Int32ToInt64 { unsigned: bool },
+  FloatToDouble,
+  // Sign-extends FIXED_LEN_BYTE_ARRAY decimals to this length
+  FixedLength(usize)
+}
+
+impl<'a, T: DataType> ProjectedColumnReader<'a, T> where T: 'static {
+  /// Creates a reader for the requested column `descr`, reading from the file column
+  /// `file_descr` with `reader`.
+  pub fn new(
+    descr: ColumnDescPtr, file_descr: ColumnDescPtr, reader: ColumnReader<'a>
+  ) -> Result<Self> {
+    if T::get_physical_type() != descr.physical_type() {
+      return Err(general_err!("Cannot read {} column {} as {}",
+        descr.physical_type(), descr.path().string(), T::get_physical_type()));
+    }
+    check_promotion(&descr, &file_descr)?;
+
+    let source = match (file_descr.physical_type(), descr.physical_type()) {
+      (Type::INT32, Type::INT64) => {
+        let unsigned = match file_descr.logical_type() {
+          LogicalType::UINT_8 | LogicalType::UINT_16 | LogicalType::UINT_32 => true,
+          _ => false
+        };
+        Source::Promoted(reader, Promotion::Int32ToInt64 { unsigned: unsigned })
+      },
+      (Type::FLOAT, Type::DOUBLE) => Source::Promoted(reader, Promotion::FloatToDouble),
+      (Type::FIXED_LEN_BYTE_ARRAY, Type::FIXED_LEN_BYTE_ARRAY)
+          if file_descr.type_length() != descr.type_length() => {
+        Source::Promoted(reader, Promotion::FixedLength(descr.type_length() as usize))
+      },
+      _ => Source::Column(get_typed_column_reader::<T>(reader))
+    };
+    Ok(Self {
+      descr: descr, source: source, file_max_def_level: file_descr.max_def_level()
+    })
+  }
+
+  /// Creates a reader for the requested column `descr` that is missing from
+  /// `row_group`.
+  pub fn missing(descr: ColumnDescPtr, row_group: &RowGroupReader<'a>) -> Result<Self> {
+    let schema_descr = row_group.metadata().schema_descr();
+    let depth = present_ancestor_depth(&descr, schema_descr)?;
+    let file_max_def_level = descr.max_def_level();
+    if depth == 0 {
+      let num_rows = row_group.metadata().num_rows() as usize;
+      return Ok(Self {
+        descr: descr,
+        source: Source::Missing(num_rows),
+        file_max_def_level: file_max_def_level
+      });
+    }
+
+    // Read the levels of the file column under the ancestor with the fewest repeated
+    // fields, which has the fewest levels to skip
+    let ancestor = ColumnPath::new(descr.path().parts()[..depth].to_vec());
+    let column = schema_descr.columns().iter().enumerate()
+      .filter(|&(_, c)| c.path().parts().starts_with(ancestor.parts()))
+      .min_by_key(|&(_, c)| c.max_rep_level())
+      .map(|(i, _)| i)
+      .ok_or_else(|| schema_err!(context(&descr),
+        "Group {} has no columns in the file", ancestor.string()))?;
+    let file_descr = schema_descr.column(column);
+
+    let names = &ancestor.parts()[1..];
+    let repetitions = path_repetitions(descr.root_type(), names);
+    let file_repetitions = path_repetitions(schema_descr.get_column_root(column), names);
+    let mut def_levels = vec![0];
+    let mut level = 0;
+    let mut max_rep_level = 0;
+    for (&repetition, &file_repetition) in repetitions.iter().zip(&file_repetitions) {
+      let repeated = repetition == Repetition::REPEATED;
+      if repeated != (file_repetition == Repetition::REPEATED) {
+        return Err(schema_err!(context(&descr), "Cannot read group {} with repetition \
+          {} as {}", ancestor.string(), file_repetition, repetition));
+      }
+      if repetition != Repetition::REQUIRED {
+        level += 1;
+      }
+      if repeated {
+        max_rep_level += 1;
+      }
+      if file_repetition == Repetition::REQUIRED {
+        *def_levels.last_mut().unwrap() = level;
+      } else {
+        def_levels.push(level);
+      }
+    }
+
+    let levels = MissingLevels {
+      reader: row_group.get_column_reader(column)?,
+      file_max_def_level: file_descr.max_def_level(),
+      file_max_rep_level: file_descr.max_rep_level(),
+      max_rep_level: max_rep_level,
+      def_levels: def_levels,
+      file_def_levels: vec!(),
+      file_rep_levels: vec!()
+    };
+    Ok(Self {
+      descr: descr,
+      source: Source::MissingNested(levels),
+      file_max_def_level: file_max_def_level
+    })
+  }
+
+  /// Reads a batch of at most `batch_size` values and levels, see
+  /// `ColumnReaderImpl::read_batch`.
+  ///
+  /// For a missing column no values are read, and every level is a null at the nearest
+  /// ancestor of the column in the file. At the top level, these are definition and
+  /// repetition levels of 0 for every row.
+  pub fn read_batch(
+    &mut self,
+    batch_size: usize,
+    mut def_levels: Option<&mut [i16]>,
+    rep_levels: Option<&mut [i16]>,
+    values: &mut [T::T]
+  ) -> Result<(usize, usize)> {
+    let (values_read, mut levels_read) = match self.source {
+      Source::Column(ref mut reader) => {
+        reader.read_batch(
+          batch_size, def_levels.as_mut().map(|l| &mut **l), rep_levels, values)?
+      },
+      Source::Promoted(ref mut reader, promotion) => {
+        read_promoted::<T>(
+          reader, promotion, batch_size, def_levels.as_mut().map(|l| &mut **l),
+          rep_levels, values)?
+      },
+      Source::Missing(ref mut num_rows_left) => {
+        let num_rows = cmp::min(batch_size, *num_rows_left);
+        *num_rows_left -= num_rows;
+        if let Some(levels) = def_levels {
+          for level in levels[..num_rows].iter_mut() {
+            *level = 0;
+          }
+        }
+        if self.descr.max_rep_level() > 0 {
+          if let Some(levels) = rep_levels {
+            for level in levels[..num_rows].iter_mut() {
+              *level = 0;
+            }
+          }
+        }
+        return Ok((0, num_rows));
+      },
+      Source::MissingNested(ref mut levels) => {
+        let max_rep_level = self.descr.max_rep_level();
+        let rep_levels = if max_rep_level > 0 { rep_levels } else { None };
+        return Ok((0, levels.read(batch_size, def_levels, rep_levels)?));
+      }
+    };
+
+    // The column is REQUIRED in the file but OPTIONAL in the requested schema
+    let max_def_level = self.descr.max_def_level();
+    if self.file_max_def_level < max_def_level {
+      if let Some(levels) = def_levels {
+        if self.file_max_def_level == 0 {
+          // The file has no definition levels, all values are defined
+          levels_read = values_read;
+          for level in levels[..values_read].iter_mut() {
+            *level = max_def_level;
+          }
+        } else {
+          for level in levels[..levels_read].iter_mut() {
+            if *level == self.file_max_def_level {
+              *level = max_def_level;
+            }
+          }
+        }
+      }
+    }
+    Ok((values_read, levels_read))
+  }
+}
+
+impl<'a> MissingLevels<'a> {
+  // Reads at most `batch_size` levels of the missing column. Returns 0 only once the
+  // file column is depleted.
+  fn read(
+    &mut self,
+    batch_size: usize,
+    mut def_levels: Option<&mut [i16]>,
+    mut rep_levels: Option<&mut [i16]>
+  ) -> Result<usize> {
+    if self.file_def_levels.len() < batch_size {
+      self.file_def_levels.resize(batch_size, 0);
+      self.file_rep_levels.resize(batch_size, 0);
+    }
+    let mut num_levels = 0;
+    while num_levels == 0 {
+      let num_file_levels = read_levels(
+        &mut self.reader, batch_size, &mut self.file_def_levels[..batch_size],
+        &mut self.file_rep_levels[..batch_size])?;
+      if num_file_levels == 0 {
+        break;
+      }
+      for i in 0..num_file_levels {
+        let rep_level =
+          if self.file_max_rep_level > 0 { self.file_rep_levels[i] } else { 0 };
+        if rep_level > self.max_rep_level {
+          continue;
+        }
+        let file_def_level =
+          if self.file_max_def_level > 0 { self.file_def_levels[i] as usize } else { 0 };
+        let def_level = if file_def_level < self.def_levels.len() {
+          self.def_levels[file_def_level]
+        } else {
+          self.def_levels[self.def_levels.len() - 1]
+        };
+        if let Some(ref mut levels) = def_levels {
+          levels[num_levels] = def_level;
+        }
+        if let Some(ref mut levels) = rep_levels {
+          levels[num_levels] = rep_level;
+        }
+        num_levels += 1;
+      }
+    }
+    Ok(num_levels)
+  }
+}
+
+// Reads a batch of at most `batch_size` levels of the column of `reader`, and discards
+// its values. Returns the number of levels read.
+fn read_levels(
+  reader: &mut ColumnReader,
+  batch_size: usize,
+  def_levels: &mut [i16],
+  rep_levels: &mut [i16]
+) -> Result<usize> {
+  match *reader {
+    ColumnReader::BoolColumnReader(ref mut r) => {
+      read_typed_levels(r, batch_size, def_levels, rep_levels)
+    },
+    ColumnReader::Int32ColumnReader(ref mut r) => {
+      read_typed_levels(r, batch_size, def_levels, rep_levels)
+    },
+    ColumnReader::Int64ColumnReader(ref mut r) => {
+      read_typed_levels(r, batch_size, def_levels, rep_levels)
+    },
+    ColumnReader::Int96ColumnReader(ref mut r) => {
+      read_typed_levels(r, batch_size, def_levels, rep_levels)
+    },
+    ColumnReader::FloatColumnReader(ref mut r) => {
+      read_typed_levels(r, batch_size, def_levels, rep_levels)
+    },
+    ColumnReader::DoubleColumnReader(ref mut r) => {
+      read_typed_levels(r, batch_size, def_levels, rep_levels)
+    },
+    ColumnReader::ByteArrayColumnReader(ref mut r) => {
+      read_typed_levels(r, batch_size, def_levels, rep_levels)
+    },
+    ColumnReader::FixedLenByteArrayColumnReader(ref mut r) => {
+      read_typed_levels(r, batch_size, def_levels, rep_levels)
+    }
+  }
+}
+
+fn read_typed_levels<T: DataType>(
+  reader: &mut ColumnReaderImpl<T>,
+  batch_size: usize,
+  def_levels: &mut [i16],
+  rep_levels: &mut [i16]
+) -> Result<usize> where T: 'static {
+  let mut values = vec![T::T::default(); batch_size];
+  let (values_read, levels_read) =
+    reader.read_batch(batch_size, Some(def_levels), Some(rep_levels), &mut values)?;
+  // A REQUIRED column that is not repeated has no levels, but one value per row
+  Ok(if reader.descr().max_def_level() > 0 { levels_read } else { values_read })
+}
+
+// Reads values of the file column with `reader`, and promotes them to `T`.
+fn read_promoted<T: DataType>(
+  reader: &mut ColumnReader,
+  promotion: Promotion,
+  batch_size: usize,
+  def_levels: Option<&mut [i16]>,
+  rep_levels: Option<&mut [i16]>,
+  values: &mut [T::T]
+) -> Result<(usize, usize)> {
+  match (reader, promotion) {
+    (&mut ColumnReader::Int32ColumnReader(ref mut r),
+     Promotion::Int32ToInt64 { unsigned }) => {
+      let mut buffer = vec![0; values.len()];
+      let result = r.read_batch(batch_size, def_levels, rep_levels, &mut buffer)?;
+      let values = unsafe { cast_values::<T, i64>(values) };
+      for i in 0..result.0 {
+        values[i] = if unsigned { buffer[i] as u32 as i64 } else { buffer[i] as i64 };
+      }
+      Ok(result)
+    },
+    (&mut ColumnReader::FloatColumnReader(ref mut r), Promotion::FloatToDouble) => {
+      let mut buffer = vec![0.0; values.len()];
+      let result = r.read_batch(batch_size, def_levels, rep_levels, &mut buffer)?;
+      let values = unsafe { cast_values::<T, f64>(values) };
+      for i in 0..result.0 {
+        values[i] = buffer[i] as f64;
+      }
+      Ok(result)
+    },
+    (&mut ColumnReader::FixedLenByteArrayColumnReader(ref mut r),
+     Promotion::FixedLength(len)) => {
+      let mut buffer = vec![ByteArray::new(); values.len()];
+      let result = r.read_batch(batch_size, def_levels, rep_levels, &mut buffer)?;
+      let values = unsafe { cast_values::<T, ByteArray>(values) };
+      for i in 0..result.0 {
+        let bytes = buffer[i].data();
+        let fill = if bytes.first().map(|b| b & 0x80 != 0).unwrap_or(false) {
+          0xFF
+        } else {
+          0
+        };
+        let mut value = vec![fill; len - bytes.len()];
+        value.extend_from_slice(bytes);
+        values[i] = ByteArray::from(value);
+      }
+      Ok(result)
+    },
+    _ => Err(general_err!("Invalid promotion of column values"))
+  }
+}
+
+// Reinterprets `values` as a slice of `U`.
+// NOTE: the caller MUST guarantee that `T::T` is `U`, i.e. check the physical type of
+// `T`.
+unsafe fn cast_values<T: DataType, U>(values: &mut [T::T]) -> &mut [U] {
+  slice::from_raw_parts_mut(values.as_mut_ptr() as *mut U, values.len())
+}
+
+// Checks that values of the file column `file` can be read as the requested column
+// `requested`.
+fn check_promotion(requested: &ColumnDescriptor, file: &ColumnDescriptor) -> Result<()> {
+  let relaxed = requested.max_def_level() == file.max_def_level() + 1 &&
+    repetition(requested) == Repetition::OPTIONAL &&
+    repetition(file) == Repetition::REQUIRED;
+  if requested.max_rep_level() != file.max_rep_level() ||
+      requested.max_def_level() != file.max_def_level() && !relaxed {
+    return Err(schema_err!(context(requested),
+      "Cannot read column with repetition {} (max levels {}/{}) as {} (max levels {}/{})",
+      repetition(file), file.max_def_level(), file.max_rep_level(),
+      repetition(requested), requested.max_def_level(), requested.max_rep_level()));
+  }
+  if !is_promotable(file.self_type(), requested.self_type()) {
+    return Err(schema_err!(context(requested), "Cannot read {} column as {}",
+      type_str(file.self_type()), type_str(requested.self_type())));
+  }
+  Ok(())
+}
+
+// Returns the number of fields of the path of the nearest ancestor of the missing column
+// `descr` that is a group in the `file` schema, or 0 if only the root is. Returns an
+// error if the column is REQUIRED right below that ancestor, i.e. cannot be null.
+fn present_ancestor_depth(
+  descr: &ColumnDescriptor, file: &SchemaDescriptor
+) -> Result<usize> {
+  let parts = descr.path().parts();
+  let mut depth = 0;
+  {
+    let mut file_type = file.root_schema();
+    while depth + 1 < parts.len() {
+      match file_type.get_fields().iter().find(|f| f.name() == parts[depth]) {
+        Some(field) if field.is_group() => file_type = field.as_ref(),
+        _ => break
+      }
+      depth += 1;
+    }
+  }
+  let repetitions = path_repetitions(descr.root_type(), &parts[1..]);
+  if repetitions[depth] == Repetition::REQUIRED {
+    return Err(schema_err!(context(descr), "Required column is missing"));
+  }
+  Ok(depth)
+}
+
+// Returns the repetitions of the fields of a path, given the type `first` of its first
+// field and the names of the remaining fields.
+fn path_repetitions(first: &SchemaType, names: &[String]) -> Vec<Repetition> {
+  let mut result = vec![first.get_basic_info().repetition()];
+  let mut tp = first;
+  for name in names {
+    tp = tp.get_fields().iter().find(|f| f.name() == name.as_str())
+      .map(|f| f.as_ref())
+      .expect("Column path must be part of the root type");
+    result.push(tp.get_basic_info().repetition());
+  }
+  result
+}
+
+fn field_id(descr: &ColumnDescriptor) -> Option<i32> {
+  let info = descr.self_type().get_basic_info();
+  if info.has_id() { Some(info.id()) } else { None }
+}
+
+fn repetition(descr: &ColumnDescriptor) -> Repetition {
+  descr.self_type().get_basic_info().repetition()
+}
+
+fn context(descr: &ColumnDescriptor) -> ErrorContext {
+  ErrorContext::new().with_column(descr.path().string())
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::fs::File;
+  use std::rc::Rc;
+
+  use column::writer::get_typed_column_writer_mut;
+  use file::reader::{FileReader, SerializedFileReader};
+  use file::writer::{SerializedFileWriter, WriterOptions};
+  use schema::parser::parse_message_type;
+  use util::test_common::{get_temp_file_path, get_test_file};
+
+  fn schema_descr(message_type: &str) -> SchemaDescriptor {
+    SchemaDescriptor::new(Rc::new(parse_message_type(message_type).unwrap()))
+  }
+
+  #[test]
+  fn test_resolve_columns() {
+    let file = schema_descr("
+      message schema {
+        REQUIRED INT32 a = 1;
+        OPTIONAL FLOAT b = 2;
+        OPTIONAL GROUP c {
+          REQUIRED INT32 d (DECIMAL(5,2)) = 3;
+          OPTIONAL FIXED_LEN_BYTE_ARRAY (4) e (DECIMAL(9,2)) = 4;
+        }
+      }
+    ");
+    let requested = schema_descr("
+      message schema {
+        OPTIONAL GROUP c {
+          OPTIONAL INT64 d (DECIMAL(12,2)) = 3;
+          OPTIONAL FIXED_LEN_BYTE_ARRAY (8) e (DECIMAL(18,2)) = 4;
+          OPTIONAL BYTE_ARRAY f (UTF8) = 5;
+        }
+        OPTIONAL INT64 a = 1;
+        OPTIONAL DOUBLE renamed_b = 2;
+      }
+    ");
+
+    assert_eq!(resolve_columns(&requested, &file, true).unwrap(), vec![
+      ColumnSource::Column(2), ColumnSource::Column(3), ColumnSource::Missing,
+      ColumnSource::Column(0), ColumnSource::Column(1)
+    ]);
+    assert_eq!(resolve_columns(&requested, &file, false).unwrap(), vec![
+      ColumnSource::Column(2), ColumnSource::Column(3), ColumnSource::Missing,
+      ColumnSource::Column(0), ColumnSource::Missing
+    ]);
+  }
+
+  #[test]
+  fn test_resolve_columns_incompatible() {
+    let file = schema_descr("
+      message schema {
+        OPTIONAL INT64 a;
+        OPTIONAL INT32 b (DECIMAL(5,2));
+        OPTIONAL INT32 c (UINT_32);
+        REPEATED INT32 d;
+        OPTIONAL BYTE_ARRAY e (UTF8);
+      }
+    ");
+    let check = |requested: &str, message: &str| {
+      match resolve_columns(&schema_descr(requested), &file, false) {
+        Err(ParquetError::SchemaMismatch(m, _)) => assert_eq!(m, message),
+        _ => panic!("Expected schema mismatch for {}", requested)
+      }
+    };
+
+    check("message schema { OPTIONAL INT32 a; }", "Cannot read INT64 column as INT32");
+    check("message schema { OPTIONAL INT64 b (DECIMAL(5,1)); }",
+          "Cannot read INT32 DECIMAL(5,2) column as INT64 DECIMAL(5,1)");
+    check("message schema { OPTIONAL INT64 b (DECIMAL(4,2)); }",
+          "Cannot read INT32 DECIMAL(5,2) column as INT64 DECIMAL(4,2)");
+    check("message schema { OPTIONAL INT64 c (INT_64); }",
+          "Cannot read INT32 INTEGER(32,false) column as INT64 INTEGER(64,true)");
+    check("message schema { OPTIONAL INT32 d; }",
+          "Cannot read column with repetition REPEATED (max levels 1/1) as OPTIONAL \
+           (max levels 1/0)");
+    check("message schema { REQUIRED BYTE_ARRAY e (UTF8); }",
+          "Cannot read column with repetition OPTIONAL (max levels 1/0) as REQUIRED \
+           (max levels 0/0)");
+    check("message schema { OPTIONAL BYTE_ARRAY e; }",
+          "Cannot read BYTE_ARRAY STRING column as BYTE_ARRAY");
+    check("message schema { REQUIRED INT32 f; }", "Required column is missing");
+  }
+
+  #[test]
+  fn test_projected_column_reader() {
+    let reader = SerializedFileReader::new(get_test_file("alltypes_plain.parquet"))
+      .unwrap();
+    let requested = schema_descr("
+      message schema {
+        OPTIONAL INT64 int_col;
+        OPTIONAL DOUBLE float_col;
+        OPTIONAL INT64 bigint_col;
+        OPTIONAL INT32 added;
+      }
+    ");
+    let sources = resolve_columns(
+      &requested, reader.metadata().file_metadata().schema_descr(), false).unwrap();
+    assert_eq!(sources, vec![
+      ColumnSource::Column(4), ColumnSource::Column(6), ColumnSource::Column(5),
+      ColumnSource::Missing
+    ]);
+
+    let row_group = reader.get_row_group(0).unwrap();
+    let mut def_levels = vec![0; 16];
+
+    let mut ints = vec![0; 16];
+    let mut column_reader = get_projected_column_reader::<Int64Type>(
+      &*row_group, &requested, &sources, 0).unwrap();
+    assert_eq!(
+      column_reader.read_batch(16, Some(&mut def_levels[..]), None, &mut ints).unwrap(),
+      (8, 8));
+    assert_eq!(&ints[..8], &[0, 1, 0, 1, 0, 1, 0, 1]);
+    assert_eq!(&def_levels[..8], &[1; 8]);
+
+    let mut doubles = vec![0.0; 16];
+    let mut column_reader = get_projected_column_reader::<DoubleType>(
+      &*row_group, &requested, &sources, 1).unwrap();
+    assert_eq!(
+      column_reader.read_batch(16, Some(&mut def_levels[..]), None, &mut doubles)
+        .unwrap(),
+      (8, 8));
+    assert_eq!(doubles[0], 0.0);
+    assert_eq!(doubles[1], 1.1f32 as f64);
+
+    let mut column_reader = get_projected_column_reader::<Int64Type>(
+      &*row_group, &requested, &sources, 2).unwrap();
+    assert_eq!(
+      column_reader.read_batch(16, Some(&mut def_levels[..]), None, &mut ints).unwrap(),
+      (8, 8));
+    assert_eq!(&ints[..8], &[0, 10, 0, 10, 0, 10, 0, 10]);
+
+    // The missing column is all nulls
+    let mut def_levels = vec![1; 16];
+    let mut column_reader = get_projected_column_reader::<Int32Type>(
+      &*row_group, &requested, &sources, 3).unwrap();
+    let mut values = vec![0; 16];
+    assert_eq!(
+      column_reader.read_batch(5, Some(&mut def_levels[..]), None, &mut values).unwrap(),
+      (0, 5));
+    assert_eq!(
+      column_reader.read_batch(5, Some(&mut def_levels[5..]), None, &mut values).unwrap(),
+      (0, 3));
+    assert_eq!(&def_levels[..8], &[0; 8]);
+    assert_eq!(
+      column_reader.read_batch(5, Some(&mut def_levels[..]), None, &mut values).unwrap(),
+      (0, 0));
+
+    // The requested type must match the reader type
+    assert!(get_projected_column_reader::<Int32Type>(
+      &*row_group, &requested, &sources, 0).is_err());
+  }
+
+  #[test]
+  fn test_projected_column_reader_missing_nested() {
+    // Rows: {c: null}, {c: {e: []}}, {c: {e: [1, 2]}}, {c: {e: [3]}}
+    let schema = parse_message_type("
+      message schema {
+        OPTIONAL GROUP c {
+          REPEATED INT32 e;
+        }
+      }
+    ").unwrap();
+    let path = get_temp_file_path("test_projection_missing_nested.parquet");
+    let mut writer = SerializedFileWriter::new(
+      File::create(&path).unwrap(), Rc::new(schema), WriterOptions::new()).unwrap();
+    let mut row_group = writer.next_row_group();
+    get_typed_column_writer_mut::<Int32Type>(row_group.column(0)).unwrap()
+      .write_batch(&[1, 2, 3], Some(&[0, 1, 2, 2, 2]), Some(&[0, 0, 0, 1, 0]))
+      .unwrap();
+    writer.write_row_group(row_group).unwrap();
+    writer.close().unwrap();
+
+    let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+    let file = reader.metadata().file_metadata().schema_descr();
+    let requested = schema_descr("
+      message schema {
+        OPTIONAL GROUP c {
+          REPEATED INT32 e;
+          OPTIONAL GROUP f {
+            REQUIRED INT32 g;
+          }
+        }
+      }
+    ");
+    let sources = resolve_columns(&requested, file, false).unwrap();
+    assert_eq!(sources, vec![ColumnSource::Column(0), ColumnSource::Missing]);
+    let row_group = reader.get_row_group(0).unwrap();
+
+    let mut def_levels = vec![0; 8];
+    let mut rep_levels = vec![0; 8];
+    let mut values = vec![0; 8];
+    let mut column_reader = get_projected_column_reader::<Int32Type>(
+      &*row_group, &requested, &sources, 0).unwrap();
+    assert_eq!(
+      column_reader.read_batch(
+        8, Some(&mut def_levels[..]), Some(&mut rep_levels[..]), &mut values).unwrap(),
+      (3, 5));
+    assert_eq!(&def_levels[..5], &[0, 1, 2, 2, 2]);
+
+    // c.f.g is null at f if c exists, and at c otherwise; it has one level per row
+    let mut def_levels = vec![-1; 8];
+    let mut column_reader = get_projected_column_reader::<Int32Type>(
+      &*row_group, &requested, &sources, 1).unwrap();
+    assert_eq!(
+      column_reader.read_batch(8, Some(&mut def_levels[..]), None, &mut values).unwrap(),
+      (0, 4));
+    assert_eq!(&def_levels[..4], &[0, 1, 1, 1]);
+    assert_eq!(
+      column_reader.read_batch(8, Some(&mut def_levels[..]), None, &mut values).unwrap(),
+      (0, 0));
+
+    // A missing column cannot be REQUIRED below an existing group
+    let requested = schema_descr("
+      message schema {
+        OPTIONAL GROUP c {
+          REQUIRED INT32 f;
+        }
+      }
+    ");
+    match resolve_columns(&requested, file, false) {
+      Err(ParquetError::SchemaMismatch(message, _)) => {
+        assert_eq!(message, "Required column is missing");
+      },
+      _ => panic!("Expected schema mismatch error")
+    }
+  }
+}
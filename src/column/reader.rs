@@ -20,11 +20,12 @@ use std::collections::HashMap;
 
 use basic::*;
 use data_type::*;
-use schema::types::ColumnDescPtr;
+use schema::types::{ColumnDescPtr, ColumnDescriptor};
+use util::bit_util::{set_array_bit, unset_array_bit};
 use util::memory::ByteBufferPtr;
 use encodings::decoding::{get_decoder, Decoder, PlainDecoder, DictDecoder};
 use encodings::levels::LevelDecoder;
-use errors::{Result, ParquetError};
+use errors::{Result, ParquetError, ErrorContext};
 use super::page::{Page, PageReader};
 
 pub enum ColumnReader<'a> {
@@ -83,6 +84,52 @@ pub fn get_typed_column_reader<'a, T: DataType>(
   }
 }
 
+/// Decodes the values of `page`, a dictionary page of column `descr`. Dictionary values
+/// are PLAIN encoded, whether the encoding of the page is PLAIN, PLAIN_DICTIONARY or
+/// RLE_DICTIONARY.
+pub fn decode_dictionary_page<T: DataType>(
+  descr: &ColumnDescriptor,
+  page: &Page
+) -> Result<Vec<T::T>> {
+  let context = ErrorContext::new().with_column(descr.path().string());
+  match page.encoding() {
+    Encoding::PLAIN | Encoding::PLAIN_DICTIONARY | Encoding::RLE_DICTIONARY => {},
+    encoding => return Err(unsupported_err!(context,
+      "Invalid/Unsupported encoding type for dictionary: {}", encoding))
+  }
+  let num_values = page.num_values() as usize;
+  let buf = page.buffer();
+  // Every value takes at least one bit, so anything above this is a corrupt page
+  if num_values > buf.len() * 8 {
+    return Err(corrupt_err!(context,
+      "Dictionary page claims {} values in {} bytes", num_values, buf.len()));
+  }
+
+  let mut decoder = PlainDecoder::<T>::new(descr.type_length());
+  let mut values = vec![T::T::default(); num_values];
+  decoder.set_data(buf.clone(), num_values).map_err(|e| e.with_context(&context))?;
+  let values_read = decoder.get(&mut values[..]).map_err(|e| e.with_context(&context))?;
+  if values_read != num_values {
+    return Err(corrupt_err!(context,
+      "Dictionary page claims {} values, but only {} could be decoded",
+      num_values, values_read));
+  }
+  Ok(values)
+}
+
+/// Outcome of [`ColumnReaderImpl::read_indices`](struct.ColumnReaderImpl.html).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IndicesRead {
+  /// The number of indices and levels read, as the number of values and levels returned
+  /// by `read_batch`. Both are 0 once the column chunk is exhausted.
+  Indices(usize, usize),
+
+  /// Nothing was read because the current page is not dictionary encoded, usually
+  /// because the writer fell back to plain encoding once the dictionary grew too large.
+  /// The values of this page must be read with `read_batch`.
+  PlainFallback
+}
+
 /// A value reader for a particular primitive column.
 /// The lifetime parameter `'a` denotes the lifetime of the page reader
 pub struct ColumnReaderImpl<'a, T: DataType> {
@@ -99,8 +146,11 @@ pub struct ColumnReaderImpl<'a, T: DataType> {
   // so far.
   num_decoded_values: u32,
 
-  // Cache of decoders for existing encodings
-  decoders: HashMap<Encoding, Box<Decoder<T>>>
+  // Cache of decoders for existing encodings, except for dictionary encoding
+  decoders: HashMap<Encoding, Box<Decoder<T>>>,
+
+  // Decoder for dictionary encoded pages, set once the dictionary page is read
+  dict_decoder: Option<DictDecoder<T>>
 }
 
 impl<'a, T: DataType> ColumnReaderImpl<'a, T> where T: 'static {
@@ -113,15 +163,21 @@ impl<'a, T: DataType> ColumnReaderImpl<'a, T> where T: 'static {
       current_encoding: None,
       num_buffered_values: 0,
       num_decoded_values: 0,
-      decoders: HashMap::new()
+      decoders: HashMap::new(),
+      dict_decoder: None
     }
   }
 
+  /// Returns the descriptor of the column read by this reader.
+  pub fn descr(&self) -> &ColumnDescPtr {
+    &self.descr
+  }
+
   /// Reads a batch of values of at most `batch_size`.
   ///
   /// This will try to read from the row group, and fills up at most `batch_size` values
   /// for `def_levels`, `rep_levels` and `values`. It will stop either when the row group
-  /// is depleted or `batch_size` values has been read.
+  /// is depleted or `batch_size` values or levels have been read.
   ///
   /// Note that in case the field being read is not required, `values` could contain less
   /// values than `def_levels`. Also note that this will skip reading def/rep levels if
@@ -136,26 +192,79 @@ impl<'a, T: DataType> ColumnReaderImpl<'a, T> where T: 'static {
   /// and the second element is the actual number of levels read.
   #[inline]
   pub fn read_batch(
+    &mut self,
+    batch_size: usize,
+    def_levels: Option<&mut [i16]>,
+    rep_levels: Option<&mut [i16]>,
+    values: &mut [T::T]
+  ) -> Result<(usize, usize)> {
+    self.read_batch_with(
+      batch_size, def_levels, rep_levels, values, false, Self::read_values)
+  }
+
+  /// Returns the dictionary of the column chunk, or `None` if it has no dictionary
+  /// page. As the dictionary page precedes the data pages, this reads pages up to the
+  /// first data page if none was read yet.
+  pub fn dictionary(&mut self) -> Result<Option<&[T::T]>> {
+    self.has_next()?;
+    Ok(self.dict_decoder.as_ref().map(|decoder| decoder.dictionary()))
+  }
+
+  /// Reads a batch of at most `batch_size` indices into the dictionary, rather than the
+  /// values they refer to, along with their levels.
+  ///
+  /// This behaves as `read_batch`, except that it stops before the first page that is
+  /// not dictionary encoded. If the current page is such a page, nothing is read and
+  /// `IndicesRead::PlainFallback` is returned, so that the caller can switch to
+  /// `read_batch` for it.
+  pub fn read_indices(
+    &mut self,
+    batch_size: usize,
+    def_levels: Option<&mut [i16]>,
+    rep_levels: Option<&mut [i16]>,
+    indices: &mut [i32]
+  ) -> Result<IndicesRead> {
+    if self.has_next()? && self.current_encoding != Some(Encoding::RLE_DICTIONARY) {
+      return Ok(IndicesRead::PlainFallback);
+    }
+    let (values_read, levels_read) = self.read_batch_with(
+      batch_size, def_levels, rep_levels, indices, true, Self::read_indices_values)?;
+    Ok(IndicesRead::Indices(values_read, levels_read))
+  }
+
+  // Reads levels as described in `read_batch`, and the values of the levels that are
+  // not null with `read_values`. If `dictionary_only` is set, this stops before the
+  // first page that is not dictionary encoded.
+  #[inline]
+  fn read_batch_with<V>(
     &mut self,
     batch_size: usize,
     mut def_levels: Option<&mut [i16]>,
     mut rep_levels: Option<&mut [i16]>,
-    values: &mut [T::T]
+    values: &mut [V],
+    dictionary_only: bool,
+    read_values: fn(&mut Self, &mut [V]) -> Result<usize>
   ) -> Result<(usize, usize)> {
     let mut values_read = 0;
     let mut levels_read = 0;
 
-    while values_read < batch_size {
+    while values_read < batch_size && levels_read < batch_size {
       if !self.has_next()? {
         break;
       }
+      if dictionary_only && self.current_encoding != Some(Encoding::RLE_DICTIONARY) {
+        break;
+      }
 
       let mut values_to_read = 0;
       let mut num_def_levels = 0;
-      let num_rep_levels;
+      let mut num_rep_levels = 0;
+      // Whether the page must have exactly `values_to_read` values left
+      let mut values_known = true;
 
-      let next_levels_read = levels_read + ::std::cmp::min(
-        batch_size, (self.num_buffered_values - self.num_decoded_values) as usize);
+      let num_values_left = (self.num_buffered_values - self.num_decoded_values) as usize;
+      let next_levels_read =
+        levels_read + ::std::cmp::min(batch_size - levels_read, num_values_left);
 
       // If the field is required and non-repeated, there are no definition levels
       if self.descr.max_def_level() > 0 && def_levels.as_ref().is_some() {
@@ -171,9 +280,13 @@ impl<'a, T: DataType> ColumnReaderImpl<'a, T> where T: 'static {
             }
           }
         }
+      } else if self.descr.max_def_level() == 0 {
+        // Required field, every level of the page has a value
+        values_to_read = ::std::cmp::min(batch_size - values_read, num_values_left);
       } else {
-        // Required field, read all values
-        values_to_read = batch_size;
+        // Definition levels are skipped, so the number of values of the page is unknown
+        values_to_read = batch_size - values_read;
+        values_known = false;
       }
 
       if self.descr.max_rep_level() > 0 && rep_levels.is_some() {
@@ -184,17 +297,28 @@ impl<'a, T: DataType> ColumnReaderImpl<'a, T> where T: 'static {
           num_rep_levels = self.read_rep_levels(
             &mut levels[levels_read..next_levels_read]
           )?;
-          assert_eq!(
-            num_def_levels, num_rep_levels,
-            "Number of decoded rep / def levels did not match");
-          levels_read += num_rep_levels;
+          if num_def_levels != num_rep_levels {
+            return Err(decode_err!(self.context(),
+              "Number of decoded rep / def levels did not match ({} vs {})",
+              num_rep_levels, num_def_levels));
+          }
         }
       }
+      levels_read += ::std::cmp::max(num_def_levels, num_rep_levels);
 
       assert!(values.len() >= values_read + values_to_read,
           "values.len() must be at least {}", values_read + values_to_read);
-      let curr_values_read = self.read_values(
-        &mut values[values_read..values_read + values_to_read])?;
+      let curr_values_read = read_values(
+        self, &mut values[values_read..values_read + values_to_read])?;
+      if num_def_levels == 0 && curr_values_read == 0 {
+        // The page claims more values than it actually contains
+        return Err(decode_err!(self.context(), "Unexpected end of page data"));
+      }
+      if values_known && curr_values_read != values_to_read {
+        return Err(decode_err!(self.context(),
+          "Expected {} values in page, but only {} could be decoded",
+          values_to_read, curr_values_read));
+      }
       self.num_decoded_values += ::std::cmp::max(num_def_levels, curr_values_read) as u32;
       values_read += curr_values_read;
     }
@@ -202,6 +326,107 @@ impl<'a, T: DataType> ColumnReaderImpl<'a, T> where T: 'static {
     Ok((values_read, levels_read))
   }
 
+  /// Reads a batch of at most `batch_size` levels, writing each value to the slot of its
+  /// level in `values`, so that `values[i]` corresponds to `def_levels[i]`.
+  ///
+  /// Bit `i` of `valid_bits` is set if slot `i` holds a value, i.e. if its definition
+  /// level is the maximum one, and unset otherwise; the content of such slots is
+  /// unspecified. Unlike `read_batch`, this avoids a compact read followed by a pass
+  /// to scatter the values to their slots.
+  ///
+  /// `def_levels` must be given if the field is not required. `rep_levels` is read as
+  /// in `read_batch`.
+  ///
+  /// Returns a tuple where the first element is the actual number of levels (and slots)
+  /// read, and the second element is the number of null slots among them.
+  pub fn read_batch_spaced(
+    &mut self,
+    batch_size: usize,
+    mut def_levels: Option<&mut [i16]>,
+    mut rep_levels: Option<&mut [i16]>,
+    values: &mut [T::T],
+    valid_bits: &mut [u8]
+  ) -> Result<(usize, usize)> {
+    let max_def_level = self.descr.max_def_level();
+    assert!(max_def_level == 0 || def_levels.is_some(),
+      "def_levels must be set for a column with max definition level {}", max_def_level);
+
+    let mut levels_read = 0;
+    let mut null_count = 0;
+
+    while levels_read < batch_size {
+      if !self.has_next()? {
+        break;
+      }
+
+      let num_levels = ::std::cmp::min(
+        batch_size - levels_read,
+        (self.num_buffered_values - self.num_decoded_values) as usize);
+      let next_levels_read = levels_read + num_levels;
+      assert!(values.len() >= next_levels_read,
+        "values.len() must be at least {}", next_levels_read);
+      assert!(valid_bits.len() * 8 >= next_levels_read,
+        "valid_bits must have at least {} bits", next_levels_read);
+
+      let mut page_null_count = 0;
+
+      let num_def_levels = if max_def_level > 0 {
+        let levels = def_levels.as_mut().unwrap();
+        assert!(
+          levels.len() >= next_levels_read,
+          "def_levels.len() ({}) must be at least {}", levels.len(), next_levels_read);
+        let num_def_levels = self.read_def_levels(
+          &mut levels[levels_read..next_levels_read])?;
+        let levels = &levels[levels_read..levels_read + num_def_levels];
+        for (i, &level) in levels.iter().enumerate() {
+          if level == max_def_level {
+            set_array_bit(valid_bits, levels_read + i);
+          } else {
+            unset_array_bit(valid_bits, levels_read + i);
+            page_null_count += 1;
+          }
+        }
+        num_def_levels
+      } else {
+        for i in levels_read..next_levels_read {
+          set_array_bit(valid_bits, i);
+        }
+        num_levels
+      };
+      if num_def_levels == 0 {
+        // The page claims more levels than it actually contains
+        return Err(decode_err!(self.context(), "Unexpected end of page data"));
+      }
+
+      if self.descr.max_rep_level() > 0 && rep_levels.is_some() {
+        if let Some(ref mut levels) = rep_levels {
+          assert!(
+            levels.len() >= next_levels_read,
+            "rep_levels.len() must be at least {}", next_levels_read);
+          let num_rep_levels = self.read_rep_levels(
+            &mut levels[levels_read..next_levels_read])?;
+          if num_def_levels != num_rep_levels {
+            return Err(decode_err!(self.context(),
+              "Number of decoded rep / def levels did not match ({} vs {})",
+              num_rep_levels, num_def_levels));
+          }
+        }
+      }
+
+      self.read_values_spaced(
+        &mut values[levels_read..levels_read + num_def_levels],
+        page_null_count,
+        valid_bits,
+        levels_read)?;
+
+      self.num_decoded_values += num_def_levels as u32;
+      levels_read += num_def_levels;
+      null_count += page_null_count;
+    }
+
+    Ok((levels_read, null_count))
+  }
+
   /// Reads a new page and set up the decoders for levels, values or dictionary.
   /// Returns false if there's no page left.
   fn read_new_page(&mut self) -> Result<bool> {
@@ -230,18 +455,18 @@ impl<'a, T: DataType> ColumnReaderImpl<'a, T> where T: 'static {
 
               if self.descr.max_rep_level() > 0 {
                 let mut rep_decoder = LevelDecoder::new(
-                  rep_level_encoding, self.descr.max_rep_level());
+                  rep_level_encoding, self.descr.max_rep_level())?;
                 let total_bytes = rep_decoder.set_data(
-                  self.num_buffered_values as usize, buffer_ptr.all());
+                  self.num_buffered_values as usize, buffer_ptr.all())?;
                 buffer_ptr = buffer_ptr.start_from(total_bytes);
                 self.rep_level_decoder = Some(rep_decoder);
               }
 
               if self.descr.max_def_level() > 0 {
                 let mut def_decoder = LevelDecoder::new(
-                  def_level_encoding, self.descr.max_def_level());
+                  def_level_encoding, self.descr.max_def_level())?;
                 let total_bytes = def_decoder.set_data(
-                  self.num_buffered_values as usize, buffer_ptr.all());
+                  self.num_buffered_values as usize, buffer_ptr.all())?;
                 buffer_ptr = buffer_ptr.start_from(total_bytes);
                 self.def_level_decoder = Some(def_decoder);
               }
@@ -262,10 +487,10 @@ impl<'a, T: DataType> ColumnReaderImpl<'a, T> where T: 'static {
               // DataPage v2 only supports RLE encoding for repetition levels
               if self.descr.max_rep_level() > 0 {
                 let mut rep_decoder = LevelDecoder::new(
-                  Encoding::RLE, self.descr.max_rep_level());
+                  Encoding::RLE, self.descr.max_rep_level())?;
                 let bytes_read = rep_decoder.set_data_range(
                   self.num_buffered_values as usize, &buf, offset,
-                  rep_levels_byte_len as usize);
+                  rep_levels_byte_len as usize)?;
                 offset += bytes_read;
                 self.rep_level_decoder = Some(rep_decoder);
               }
@@ -273,10 +498,10 @@ impl<'a, T: DataType> ColumnReaderImpl<'a, T> where T: 'static {
               // DataPage v2 only supports RLE encoding for definition levels
               if self.descr.max_def_level() > 0 {
                 let mut def_decoder = LevelDecoder::new(
-                  Encoding::RLE, self.descr.max_def_level());
+                  Encoding::RLE, self.descr.max_def_level())?;
                 let bytes_read = def_decoder.set_data_range(
                   self.num_buffered_values as usize, &buf, offset,
-                  def_levels_byte_len as usize);
+                  def_levels_byte_len as usize)?;
                 offset += bytes_read;
                 self.def_level_decoder = Some(def_decoder);
               }
@@ -305,28 +530,26 @@ impl<'a, T: DataType> ColumnReaderImpl<'a, T> where T: 'static {
       encoding = Encoding::RLE_DICTIONARY;
     }
 
-    let decoder =
-      if encoding == Encoding::RLE_DICTIONARY {
-        self.decoders.get_mut(&encoding).expect("Decoder for dict should have been set")
-      } else {
-        // Search cache for data page decoder
-        if !self.decoders.contains_key(&encoding) {
-          // Initialize decoder for this page
-          // TODO: support other types of encodings
-          let data_decoder = match encoding {
-            Encoding::PLAIN => get_decoder::<T>(self.descr.clone(), encoding)?,
-            Encoding::DELTA_BINARY_PACKED =>
-              get_decoder::<T>(self.descr.clone(), encoding)?,
-            en => return Err(nyi_err!("Unsupported encoding {}", en))
-          };
-          self.decoders.insert(encoding, data_decoder);
-        }
-        self.decoders.get_mut(&encoding).unwrap()
+    if encoding == Encoding::RLE_DICTIONARY {
+      if self.dict_decoder.is_none() {
+        return Err(corrupt_err!(self.context(),
+          "Found dictionary encoded data page without a dictionary page"))
+      }
+    } else if !self.decoders.contains_key(&encoding) {
+      // Initialize decoder for this page
+      // TODO: support other types of encodings
+      let data_decoder = match encoding {
+        Encoding::PLAIN => get_decoder::<T>(self.descr.clone(), encoding)?,
+        Encoding::DELTA_BINARY_PACKED =>
+          get_decoder::<T>(self.descr.clone(), encoding)?,
+        en => return Err(
+          unsupported_err!(self.context(), "Unsupported encoding {}", en))
       };
+      self.decoders.insert(encoding, data_decoder);
+    }
 
-    decoder.set_data(buffer_ptr.start_from(offset), len as usize)?;
     self.current_encoding = Some(encoding);
-    Ok(())
+    self.current_decoder().set_data(buffer_ptr.start_from(offset), len as usize)
   }
 
   #[inline]
@@ -335,7 +558,8 @@ impl<'a, T: DataType> ColumnReaderImpl<'a, T> where T: 'static {
         self.num_buffered_values == self.num_decoded_values {
         // TODO: should we return false if read_new_page() = true and
         // num_buffered_values = 0?
-      if !self.read_new_page()? {
+      let has_new_page = self.read_new_page().map_err(|e| self.decode_error(e))?;
+      if !has_new_page {
         Ok(false)
       } else {
         Ok(self.num_buffered_values != 0)
@@ -345,50 +569,90 @@ impl<'a, T: DataType> ColumnReaderImpl<'a, T> where T: 'static {
 
   #[inline]
   fn read_rep_levels(&mut self, buffer: &mut [i16]) -> Result<usize> {
-    let level_decoder = self.rep_level_decoder.as_mut()
-      .expect("rep_level_decoder be set");
-    level_decoder.get(buffer)
+    let result = {
+      let level_decoder = self.rep_level_decoder.as_mut()
+        .expect("rep_level_decoder be set");
+      level_decoder.get(buffer)
+    };
+    result.map_err(|e| self.decode_error(e))
   }
 
   #[inline]
   fn read_def_levels(&mut self, buffer: &mut [i16]) -> Result<usize> {
-    let level_decoder = self.def_level_decoder.as_mut()
-      .expect("def_level_decoder be set");
-    level_decoder.get(buffer)
+    let result = {
+      let level_decoder = self.def_level_decoder.as_mut()
+        .expect("def_level_decoder be set");
+      level_decoder.get(buffer)
+    };
+    result.map_err(|e| self.decode_error(e))
+  }
+
+  // Returns the decoder of the values of the current page
+  fn current_decoder(&mut self) -> &mut Decoder<T> {
+    let encoding = self.current_encoding.expect("current_encoding should be set");
+    if encoding == Encoding::RLE_DICTIONARY {
+      self.dict_decoder.as_mut().expect("dict_decoder should be set") as &mut Decoder<T>
+    } else {
+      &mut **self.decoders
+        .get_mut(&encoding)
+        .expect(format!("decoder for encoding {} should be set", encoding).as_str())
+    }
   }
 
   #[inline]
   fn read_values(&mut self, buffer: &mut [T::T]) -> Result<usize> {
-    let encoding = self.current_encoding.expect("current_encoding should be set");
-    let current_decoder = self.decoders
-      .get_mut(&encoding)
-      .expect(format!("decoder for encoding {} should be set", encoding).as_str());
-    current_decoder.get(buffer)
+    let result = self.current_decoder().get(buffer);
+    result.map_err(|e| self.decode_error(e))
   }
 
   #[inline]
-  fn configure_dictionary(&mut self, page: Page) -> Result<bool> {
-    let mut encoding = page.encoding();
-    if encoding == Encoding::PLAIN || encoding == Encoding::PLAIN_DICTIONARY {
-      encoding = Encoding::RLE_DICTIONARY
-    }
+  fn read_indices_values(&mut self, buffer: &mut [i32]) -> Result<usize> {
+    let result = self.dict_decoder.as_mut()
+      .expect("dict_decoder should be set")
+      .get_indices(buffer);
+    result.map_err(|e| self.decode_error(e))
+  }
+
+  #[inline]
+  fn read_values_spaced(
+    &mut self,
+    buffer: &mut [T::T],
+    null_count: usize,
+    valid_bits: &[u8],
+    valid_bits_offset: usize
+  ) -> Result<usize> {
+    let result = self.current_decoder()
+      .get_spaced(buffer, null_count, valid_bits, valid_bits_offset);
+    result.map_err(|e| self.decode_error(e))
+  }
+
+  // Returns the location of errors raised by this reader
+  fn context(&self) -> ErrorContext {
+    ErrorContext::new().with_column(self.descr.path().string())
+  }
 
-    if self.decoders.contains_key(&encoding) {
-      return Err(general_err!("Column cannot have more than one dictionary"))
+  // Decoders know nothing about the column they decode, so unstructured errors raised by
+  // them are turned into `ParquetError::Decode` errors for this column.
+  fn decode_error(&self, e: ParquetError) -> ParquetError {
+    match e {
+      ParquetError::General(message) | ParquetError::EOF(message) =>
+        ParquetError::Decode(message, self.context()),
+      e => e.with_context(&self.context())
     }
+  }
 
-    if encoding == Encoding::RLE_DICTIONARY {
-      let mut dictionary = PlainDecoder::<T>::new(self.descr.type_length());
-      let num_values = page.num_values();
-      dictionary.set_data(page.buffer().clone(), num_values as usize)?;
-
-      let mut decoder = DictDecoder::new();
-      decoder.set_dict(Box::new(dictionary))?;
-      self.decoders.insert(encoding, Box::new(decoder));
-      Ok(true)
-    } else {
-      Err(nyi_err!("Invalid/Unsupported encoding type for dictionary: {}", encoding))
+  #[inline]
+  fn configure_dictionary(&mut self, page: Page) -> Result<bool> {
+    if self.dict_decoder.is_some() {
+      return Err(corrupt_err!(
+        self.context(), "Column cannot have more than one dictionary"))
     }
+
+    let values = decode_dictionary_page::<T>(&self.descr, &page)?;
+    let mut decoder = DictDecoder::new();
+    decoder.set_dict_values(values);
+    self.dict_decoder = Some(decoder);
+    Ok(true)
   }
 }
 
@@ -406,6 +670,7 @@ mod tests {
   use encodings::encoding::{get_encoder, Encoder, DictEncoder};
   use encodings::levels::LevelEncoder;
   use schema::types::{Type as SchemaType, ColumnDescriptor, ColumnPath};
+  use util::bit_util::get_array_bit;
   use util::memory::{ByteBufferPtr, MemTracker, MemTrackerPtr};
   use util::test_common::random_numbers_range;
 
@@ -512,6 +777,229 @@ mod tests {
     NUM_PAGES, NUM_LEVELS, 16, 0, 3);
 
 
+  #[test]
+  fn test_read_batch_spaced_plain() {
+    test_read_batch_spaced(Encoding::PLAIN, 1, 0, 17);
+    test_read_batch_spaced(Encoding::PLAIN, MAX_DEF_LEVEL, MAX_REP_LEVEL, 512);
+  }
+
+  #[test]
+  fn test_read_batch_spaced_dict() {
+    test_read_batch_spaced(Encoding::RLE_DICTIONARY, 1, 0, 17);
+    test_read_batch_spaced(Encoding::RLE_DICTIONARY, MAX_DEF_LEVEL, MAX_REP_LEVEL, 512);
+  }
+
+  #[test]
+  fn test_read_batch_spaced_required() {
+    test_read_batch_spaced(Encoding::PLAIN, 0, 0, 16);
+  }
+
+  fn test_read_batch_spaced(
+    encoding: Encoding, max_def_level: i16, max_rep_level: i16, batch_size: usize
+  ) {
+    let desc = Rc::new(ColumnDescriptor::new(
+      Rc::new(get_test_int32_type()), None, max_def_level, max_rep_level,
+      ColumnPath::new(Vec::new())));
+    let mut def_levels = Vec::new();
+    let mut rep_levels = Vec::new();
+    let mut values = Vec::new();
+    let mut pages = VecDeque::new();
+    make_pages::<Int32Type>(
+      desc.clone(), encoding, NUM_PAGES, NUM_LEVELS, 0, 100,
+      &mut def_levels, &mut rep_levels, &mut values, &mut pages, false);
+
+    let page_reader = TestPageReader::new(Vec::from(pages));
+    let column_reader = get_column_reader(desc, Box::new(page_reader));
+    let mut typed_column_reader = get_typed_column_reader::<Int32Type>(column_reader);
+    let num_levels = NUM_LEVELS * NUM_PAGES;
+    let mut actual_def_levels = vec![0; num_levels];
+    let mut actual_rep_levels = vec![0; num_levels];
+    // Values of the slots read so far, `None` for null slots
+    let mut actual_values = Vec::new();
+    let mut batch_values = vec![0; batch_size];
+    let mut batch_valid_bits = vec![0; (batch_size + 7) / 8];
+
+    let mut curr_levels_read = 0;
+    let mut curr_null_count = 0;
+    loop {
+      let (levels_read, null_count) = typed_column_reader.read_batch_spaced(
+        batch_size,
+        Some(&mut actual_def_levels[curr_levels_read..]),
+        Some(&mut actual_rep_levels[curr_levels_read..]),
+        &mut batch_values[..],
+        &mut batch_valid_bits[..])
+      .expect("read_batch_spaced() should be OK");
+      if levels_read == 0 {
+        break;
+      }
+      for i in 0..levels_read {
+        if get_array_bit(&batch_valid_bits[..], i) {
+          actual_values.push(Some(batch_values[i]));
+        } else {
+          actual_values.push(None);
+        }
+      }
+      curr_levels_read += levels_read;
+      curr_null_count += null_count;
+    }
+
+    let mut expected_values = Vec::new();
+    let mut remaining_values = values.into_iter();
+    for i in 0..num_levels {
+      if max_def_level == 0 || def_levels[i] == max_def_level {
+        expected_values.push(remaining_values.next());
+      } else {
+        expected_values.push(None);
+      }
+    }
+    assert_eq!(remaining_values.next(), None);
+    assert_eq!(curr_levels_read, num_levels);
+    assert_eq!(actual_values, expected_values);
+    assert_eq!(
+      curr_null_count,
+      expected_values.iter().filter(|v| v.is_none()).count());
+    if max_def_level > 0 {
+      assert_eq!(actual_def_levels, def_levels);
+    }
+    if max_rep_level > 0 {
+      assert_eq!(actual_rep_levels, rep_levels);
+    }
+  }
+
+
+  #[test]
+  fn test_read_indices() {
+    let desc = Rc::new(ColumnDescriptor::new(
+      Rc::new(get_test_int32_type()), None, MAX_DEF_LEVEL, MAX_REP_LEVEL,
+      ColumnPath::new(Vec::new())));
+    let mut def_levels = Vec::new();
+    let mut rep_levels = Vec::new();
+    let mut values = Vec::new();
+    let mut pages = VecDeque::new();
+    make_pages::<Int32Type>(
+      desc.clone(), Encoding::RLE_DICTIONARY, NUM_PAGES, NUM_LEVELS, 0, 10,
+      &mut def_levels, &mut rep_levels, &mut values, &mut pages, false);
+    // The last pages fall back to plain encoding
+    let mut plain_def_levels = Vec::new();
+    let mut plain_rep_levels = Vec::new();
+    let mut plain_values = Vec::new();
+    let mut plain_pages = VecDeque::new();
+    make_pages::<Int32Type>(
+      desc.clone(), Encoding::PLAIN, NUM_PAGES, NUM_LEVELS, 0, 10,
+      &mut plain_def_levels, &mut plain_rep_levels, &mut plain_values, &mut plain_pages,
+      true);
+    pages.extend(plain_pages);
+
+    let page_reader = TestPageReader::new(Vec::from(pages));
+    let column_reader = get_column_reader(desc, Box::new(page_reader));
+    let mut typed_column_reader = get_typed_column_reader::<Int32Type>(column_reader);
+    let dictionary = typed_column_reader.dictionary()
+      .expect("dictionary() should be OK")
+      .expect("column should have a dictionary")
+      .to_vec();
+
+    let num_levels = NUM_LEVELS * NUM_PAGES;
+    let mut actual_def_levels = vec![0; num_levels];
+    let mut actual_rep_levels = vec![0; num_levels];
+    let mut actual_indices = vec![0; num_levels];
+    let mut curr_values_read = 0;
+    let mut curr_levels_read = 0;
+    loop {
+      let indices_read = typed_column_reader.read_indices(
+        17,
+        Some(&mut actual_def_levels[curr_levels_read..]),
+        Some(&mut actual_rep_levels[curr_levels_read..]),
+        &mut actual_indices[curr_values_read..])
+      .expect("read_indices() should be OK");
+      match indices_read {
+        IndicesRead::Indices(values_read, levels_read) => {
+          assert!(levels_read > 0, "pages should fall back before the end");
+          curr_values_read += values_read;
+          curr_levels_read += levels_read;
+        },
+        IndicesRead::PlainFallback => break
+      }
+    }
+    let actual_values: Vec<i32> = actual_indices[..curr_values_read].iter()
+      .map(|i| dictionary[*i as usize])
+      .collect();
+    assert_eq!(actual_values, values);
+    assert_eq!(actual_def_levels, def_levels);
+    assert_eq!(actual_rep_levels, rep_levels);
+
+    // The plain encoded pages are read as values
+    let mut actual_values = vec![0; num_levels];
+    let (values_read, levels_read) = typed_column_reader.read_batch(
+      num_levels,
+      Some(&mut actual_def_levels[..]),
+      Some(&mut actual_rep_levels[..]),
+      &mut actual_values[..])
+    .expect("read_batch() should be OK");
+    assert_eq!(levels_read, num_levels);
+    assert_eq!(&actual_values[..values_read], &plain_values[..]);
+    assert_eq!(actual_def_levels, plain_def_levels);
+    assert_eq!(actual_rep_levels, plain_rep_levels);
+
+    let indices_read = typed_column_reader.read_indices(
+      17, Some(&mut actual_def_levels[..]), Some(&mut actual_rep_levels[..]),
+      &mut actual_indices[..])
+    .expect("read_indices() should be OK");
+    assert_eq!(indices_read, IndicesRead::Indices(0, 0));
+  }
+
+  #[test]
+  fn test_read_indices_without_dictionary() {
+    let desc = Rc::new(ColumnDescriptor::new(
+      Rc::new(get_test_int32_type()), None, 0, 0, ColumnPath::new(Vec::new())));
+    let mut def_levels = Vec::new();
+    let mut rep_levels = Vec::new();
+    let mut values = Vec::new();
+    let mut pages = VecDeque::new();
+    make_pages::<Int32Type>(
+      desc.clone(), Encoding::PLAIN, 1, NUM_LEVELS, 0, 10,
+      &mut def_levels, &mut rep_levels, &mut values, &mut pages, false);
+
+    let page_reader = TestPageReader::new(Vec::from(pages));
+    let column_reader = get_column_reader(desc, Box::new(page_reader));
+    let mut typed_column_reader = get_typed_column_reader::<Int32Type>(column_reader);
+    assert_eq!(
+      typed_column_reader.dictionary().expect("dictionary() should be OK"), None);
+    let mut indices = vec![0; NUM_LEVELS];
+    let indices_read = typed_column_reader
+      .read_indices(NUM_LEVELS, None, None, &mut indices)
+      .expect("read_indices() should be OK");
+    assert_eq!(indices_read, IndicesRead::PlainFallback);
+  }
+
+  #[test]
+  fn test_read_batch_optional_non_repeated_multi_page() {
+    let desc = Rc::new(ColumnDescriptor::new(
+      Rc::new(get_test_int32_type()), None, 1, 0, ColumnPath::new(Vec::new())));
+    let mut def_levels = Vec::new();
+    let mut rep_levels = Vec::new();
+    let mut values = Vec::new();
+    let mut pages = VecDeque::new();
+    make_pages::<Int32Type>(
+      desc.clone(), Encoding::PLAIN, NUM_PAGES, NUM_LEVELS, 0, 10,
+      &mut def_levels, &mut rep_levels, &mut values, &mut pages, false);
+
+    let page_reader = TestPageReader::new(Vec::from(pages));
+    let column_reader = get_column_reader(desc, Box::new(page_reader));
+    let mut typed_column_reader = get_typed_column_reader::<Int32Type>(column_reader);
+
+    // A single batch spans all pages, and keeps the definition levels of each page
+    let num_levels = NUM_LEVELS * NUM_PAGES;
+    let mut actual_def_levels = vec![-1; num_levels];
+    let mut actual_values = vec![0; num_levels];
+    let (values_read, levels_read) = typed_column_reader.read_batch(
+      num_levels, Some(&mut actual_def_levels[..]), None, &mut actual_values[..])
+    .expect("read_batch() should be OK");
+    assert_eq!(levels_read, num_levels);
+    assert_eq!(actual_def_levels, def_levels);
+    assert_eq!(&actual_values[..values_read], &values[..]);
+  }
+
+
   fn get_test_int32_type() -> SchemaType {
     SchemaType::primitive_type_builder("a", PhysicalType::INT32)
       .with_repetition(Repetition::REQUIRED)
@@ -602,7 +1090,7 @@ mod tests {
           &mut actual_values[curr_values_read..])
         .expect("read_batch() should be OK");
 
-        if values_read == 0 {
+        if values_read == 0 && levels_read == 0 {
           done = true;
         }
 
@@ -672,7 +1160,7 @@ mod tests {
           &mut actual_values[curr_values_read..])
         .expect("read_batch() should be OK");
 
-        if values_read == 0 {
+        if values_read == 0 && levels_read == 0 {
           done = true;
         }
 
@@ -0,0 +1,542 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Column writers: encode the values and levels of a column chunk into data pages.
+//!
+//! Values are PLAIN encoded, or dictionary encoded if enabled by `WriterOptions`, and
+//! levels RLE encoded, in version 1 data pages. Pages are kept in memory until the
+//! column chunk is written to a file by its row group writer, so that the dictionary
+//! page, which is only complete once all values are written, can come first.
+
+use std::mem;
+use std::rc::Rc;
+
+use basic::*;
+use data_type::*;
+use encodings::encoding::{get_encoder, DictEncoder, Encoder};
+use encodings::levels::LevelEncoder;
+use errors::{ErrorContext, ParquetError, Result};
+use file::filter::is_sorted_dictionary;
+use file::writer::WriterOptions;
+use schema::types::ColumnDescPtr;
+use util::memory::{ByteBufferPtr, MemTracker};
+use super::page::Page;
+
+/// Number of levels that are encoded at once by `write_batch`. The size of the current
+/// page is checked after each of these.
+const WRITE_BATCH_SIZE: usize = 1024;
+
+pub enum ColumnWriter {
+  BoolColumnWriter(ColumnWriterImpl<BoolType>),
+  Int32ColumnWriter(ColumnWriterImpl<Int32Type>),
+  Int64ColumnWriter(ColumnWriterImpl<Int64Type>),
+  Int96ColumnWriter(ColumnWriterImpl<Int96Type>),
+  FloatColumnWriter(ColumnWriterImpl<FloatType>),
+  DoubleColumnWriter(ColumnWriterImpl<DoubleType>),
+  ByteArrayColumnWriter(ColumnWriterImpl<ByteArrayType>),
+  FixedLenByteArrayColumnWriter(ColumnWriterImpl<FixedLenByteArrayType>)
+}
+
+impl ColumnWriter {
+  /// Returns the descriptor of the column written by this writer.
+  pub fn descr(&self) -> &ColumnDescPtr {
+    match *self {
+      ColumnWriter::BoolColumnWriter(ref w) => w.descr(),
+      ColumnWriter::Int32ColumnWriter(ref w) => w.descr(),
+      ColumnWriter::Int64ColumnWriter(ref w) => w.descr(),
+      ColumnWriter::Int96ColumnWriter(ref w) => w.descr(),
+      ColumnWriter::FloatColumnWriter(ref w) => w.descr(),
+      ColumnWriter::DoubleColumnWriter(ref w) => w.descr(),
+      ColumnWriter::ByteArrayColumnWriter(ref w) => w.descr(),
+      ColumnWriter::FixedLenByteArrayColumnWriter(ref w) => w.descr()
+    }
+  }
+
+  /// Closes this writer, and returns the pages of the column chunk.
+  pub fn close(self) -> Result<ColumnChunkPages> {
+    match self {
+      ColumnWriter::BoolColumnWriter(w) => w.close(),
+      ColumnWriter::Int32ColumnWriter(w) => w.close(),
+      ColumnWriter::Int64ColumnWriter(w) => w.close(),
+      ColumnWriter::Int96ColumnWriter(w) => w.close(),
+      ColumnWriter::FloatColumnWriter(w) => w.close(),
+      ColumnWriter::DoubleColumnWriter(w) => w.close(),
+      ColumnWriter::ByteArrayColumnWriter(w) => w.close(),
+      ColumnWriter::FixedLenByteArrayColumnWriter(w) => w.close()
+    }
+  }
+}
+
+/// Gets a specific column writer corresponding to column descriptor `col_descr`.
+pub fn get_column_writer(
+  col_descr: ColumnDescPtr, options: &WriterOptions
+) -> ColumnWriter {
+  match col_descr.physical_type() {
+    Type::BOOLEAN => ColumnWriter::BoolColumnWriter(
+      ColumnWriterImpl::new(col_descr, options)),
+    Type::INT32 => ColumnWriter::Int32ColumnWriter(
+      ColumnWriterImpl::new(col_descr, options)),
+    Type::INT64 => ColumnWriter::Int64ColumnWriter(
+      ColumnWriterImpl::new(col_descr, options)),
+    Type::INT96 => ColumnWriter::Int96ColumnWriter(
+      ColumnWriterImpl::new(col_descr, options)),
+    Type::FLOAT => ColumnWriter::FloatColumnWriter(
+      ColumnWriterImpl::new(col_descr, options)),
+    Type::DOUBLE => ColumnWriter::DoubleColumnWriter(
+      ColumnWriterImpl::new(col_descr, options)),
+    Type::BYTE_ARRAY => ColumnWriter::ByteArrayColumnWriter(
+      ColumnWriterImpl::new(col_descr, options)),
+    Type::FIXED_LEN_BYTE_ARRAY => ColumnWriter::FixedLenByteArrayColumnWriter(
+      ColumnWriterImpl::new(col_descr, options))
+  }
+}
+
+/// Gets a typed column writer for the specific type `T` from `col_writer`. Returns an
+/// error if the physical type of the column is not the one of `T`.
+pub fn get_typed_column_writer_mut<T: DataType>(
+  col_writer: &mut ColumnWriter
+) -> Result<&mut ColumnWriterImpl<T>> {
+  let physical_type = col_writer.descr().physical_type();
+  if physical_type != T::get_physical_type() {
+    return Err(schema_err!(
+      ErrorContext::new().with_column(col_writer.descr().path().string()),
+      "Cannot write {} values to a column of type {}",
+      T::get_physical_type(), physical_type));
+  }
+  let result = match *col_writer {
+    ColumnWriter::BoolColumnWriter(ref mut w) => unsafe { mem::transmute(w) },
+    ColumnWriter::Int32ColumnWriter(ref mut w) => unsafe { mem::transmute(w) },
+    ColumnWriter::Int64ColumnWriter(ref mut w) => unsafe { mem::transmute(w) },
+    ColumnWriter::Int96ColumnWriter(ref mut w) => unsafe { mem::transmute(w) },
+    ColumnWriter::FloatColumnWriter(ref mut w) => unsafe { mem::transmute(w) },
+    ColumnWriter::DoubleColumnWriter(ref mut w) => unsafe { mem::transmute(w) },
+    ColumnWriter::ByteArrayColumnWriter(ref mut w) => unsafe { mem::transmute(w) },
+    ColumnWriter::FixedLenByteArrayColumnWriter(ref mut w) => unsafe { mem::transmute(w) }
+  };
+  Ok(result)
+}
+
+/// The pages of a column chunk written by a column writer, with their uncompressed
+/// data.
+pub struct ColumnChunkPages {
+  pub pages: Vec<Page>,
+  /// The number of values, including nulls, i.e. the number of levels.
+  pub num_values: i64,
+  /// The number of rows, i.e. the number of values with a repetition level of 0.
+  pub num_rows: i64,
+  /// The encodings used for values and levels.
+  pub encodings: Vec<Encoding>
+}
+
+/// A value writer for a particular primitive column.
+pub struct ColumnWriterImpl<T: DataType> {
+  descr: ColumnDescPtr,
+  encoder: Box<Encoder<T>>,
+  // Encoder of the values instead of `encoder` if they are dictionary encoded
+  dict_encoder: Option<DictEncoder<T>>,
+  data_page_size: usize,
+
+  // Levels of the current page
+  def_levels: Vec<i16>,
+  rep_levels: Vec<i16>,
+
+  // The number of levels of the current page
+  num_buffered_values: usize,
+
+  // Estimated size of the encoded values of the current page
+  num_buffered_bytes: usize,
+
+  pages: Vec<Page>,
+  num_values: i64,
+  num_rows: i64
+}
+
+impl<T: DataType> ColumnWriterImpl<T> where T: 'static {
+  pub fn new(descr: ColumnDescPtr, options: &WriterOptions) -> Self {
+    let encoder = get_encoder::<T>(
+      descr.clone(), Encoding::PLAIN, Rc::new(MemTracker::new()))
+      .expect("PLAIN encoding is supported for all types");
+    let dict_encoder =
+      if options.dictionary_enabled() && descr.physical_type() != Type::BOOLEAN {
+        Some(DictEncoder::new(descr.clone(), Rc::new(MemTracker::new())))
+      } else {
+        None
+      };
+    Self {
+      descr: descr,
+      encoder: encoder,
+      dict_encoder: dict_encoder,
+      data_page_size: options.data_page_size(),
+      def_levels: vec!(),
+      rep_levels: vec!(),
+      num_buffered_values: 0,
+      num_buffered_bytes: 0,
+      pages: vec!(),
+      num_values: 0,
+      num_rows: 0
+    }
+  }
+
+  pub fn descr(&self) -> &ColumnDescPtr {
+    &self.descr
+  }
+
+  /// Writes a batch of values with their levels.
+  ///
+  /// This is the counterpart of `ColumnReaderImpl::read_batch`: `values` only contains
+  /// the non-null values, i.e. one value per definition level that equals the maximum
+  /// definition level of the column. Definition levels must be given if the column is
+  /// not required, and repetition levels if it is repeated; both must have the same
+  /// length. Levels are ignored otherwise.
+  ///
+  /// Returns the number of values written.
+  pub fn write_batch(
+    &mut self,
+    values: &[T::T],
+    def_levels: Option<&[i16]>,
+    rep_levels: Option<&[i16]>
+  ) -> Result<usize> {
+    let max_def_level = self.descr.max_def_level();
+    let max_rep_level = self.descr.max_rep_level();
+    let ctx = ErrorContext::new().with_column(self.descr.path().string());
+
+    let def_levels = if max_def_level > 0 {
+      match def_levels {
+        Some(levels) => Some(levels),
+        None => return Err(general_err!(
+          "Definition levels are required for column {}", self.descr.path().string()))
+      }
+    } else {
+      None
+    };
+    let rep_levels = if max_rep_level > 0 {
+      match rep_levels {
+        Some(levels) => Some(levels),
+        None => return Err(general_err!(
+          "Repetition levels are required for column {}", self.descr.path().string()))
+      }
+    } else {
+      None
+    };
+
+    let num_levels = def_levels.map(|levels| levels.len()).unwrap_or(values.len());
+    if let Some(levels) = rep_levels {
+      if levels.len() != num_levels {
+        return Err(schema_err!(ctx,
+          "Expected {} repetition levels, found {}", num_levels, levels.len()));
+      }
+    }
+    if let Some(levels) = def_levels {
+      if levels.iter().any(|&level| level < 0 || level > max_def_level) {
+        return Err(schema_err!(ctx,
+          "Definition levels must be between 0 and {}", max_def_level));
+      }
+      let num_values = levels.iter().filter(|&&level| level == max_def_level).count();
+      if num_values != values.len() {
+        return Err(schema_err!(ctx,
+          "Expected {} values for {} definition levels, found {}",
+          num_values, num_levels, values.len()));
+      }
+    }
+    if let Some(levels) = rep_levels {
+      if levels.iter().any(|&level| level < 0 || level > max_rep_level) {
+        return Err(schema_err!(ctx,
+          "Repetition levels must be between 0 and {}", max_rep_level));
+      }
+    }
+
+    let mut levels_offset = 0;
+    let mut values_offset = 0;
+    while levels_offset < num_levels {
+      let end = ::std::cmp::min(levels_offset + WRITE_BATCH_SIZE, num_levels);
+      let def_batch = def_levels.map(|levels| &levels[levels_offset..end]);
+      let rep_batch = rep_levels.map(|levels| &levels[levels_offset..end]);
+      let num_values = match def_batch {
+        Some(levels) => levels.iter().filter(|&&level| level == max_def_level).count(),
+        None => end - levels_offset
+      };
+      self.write_mini_batch(
+        &values[values_offset..values_offset + num_values], def_batch, rep_batch)?;
+      levels_offset = end;
+      values_offset += num_values;
+    }
+    Ok(values.len())
+  }
+
+  fn write_mini_batch(
+    &mut self,
+    values: &[T::T],
+    def_levels: Option<&[i16]>,
+    rep_levels: Option<&[i16]>
+  ) -> Result<()> {
+    let num_levels = def_levels.map(|levels| levels.len()).unwrap_or(values.len());
+    if let Some(levels) = def_levels {
+      self.def_levels.extend_from_slice(levels);
+    }
+    match rep_levels {
+      Some(levels) => {
+        self.rep_levels.extend_from_slice(levels);
+        self.num_rows += levels.iter().filter(|&&level| level == 0).count() as i64;
+      },
+      None => self.num_rows += num_levels as i64
+    }
+
+    self.values_encoder().put(values)?;
+    self.num_buffered_values += num_levels;
+    self.num_buffered_bytes += values.iter().map(|v| v.as_bytes().len()).sum::<usize>();
+
+    if self.num_buffered_bytes >= self.data_page_size {
+      self.add_data_page()?;
+    }
+    Ok(())
+  }
+
+  /// Encodes the buffered levels and values into a data page.
+  fn add_data_page(&mut self) -> Result<()> {
+    let mut buffer = vec!();
+    if self.descr.max_rep_level() > 0 {
+      let levels = encode_levels(self.descr.max_rep_level(), &self.rep_levels)?;
+      buffer.extend_from_slice(&levels);
+    }
+    if self.descr.max_def_level() > 0 {
+      let levels = encode_levels(self.descr.max_def_level(), &self.def_levels)?;
+      buffer.extend_from_slice(&levels);
+    }
+    let values = self.values_encoder().flush_buffer()?;
+    buffer.extend_from_slice(values.data());
+
+    let encoding = self.values_encoder().encoding();
+    self.pages.push(Page::DataPage {
+      buf: ByteBufferPtr::new(buffer),
+      num_values: self.num_buffered_values as u32,
+      encoding: encoding,
+      def_level_encoding: Encoding::RLE,
+      rep_level_encoding: Encoding::RLE
+    });
+
+    self.num_values += self.num_buffered_values as i64;
+    self.def_levels.clear();
+    self.rep_levels.clear();
+    self.num_buffered_values = 0;
+    self.num_buffered_bytes = 0;
+    Ok(())
+  }
+
+  /// Closes this writer, and returns the pages of the column chunk.
+  pub fn close(mut self) -> Result<ColumnChunkPages> {
+    if self.num_buffered_values > 0 {
+      self.add_data_page()?;
+    }
+    let encodings = vec![self.values_encoder().encoding(), Encoding::RLE];
+    if let Some(ref encoder) = self.dict_encoder {
+      let is_sorted = is_sorted_dictionary::<T>(&self.descr, encoder.dictionary());
+      self.pages.insert(0, Page::DictionaryPage {
+        buf: encoder.write_dict()?,
+        num_values: encoder.num_entries() as u32,
+        encoding: Encoding::PLAIN,
+        is_sorted: is_sorted
+      });
+    }
+    Ok(ColumnChunkPages {
+      pages: self.pages,
+      num_values: self.num_values,
+      num_rows: self.num_rows,
+      encodings: encodings
+    })
+  }
+
+  // Returns the encoder of the values of data pages
+  fn values_encoder(&mut self) -> &mut Encoder<T> {
+    match self.dict_encoder {
+      Some(ref mut encoder) => encoder,
+      None => &mut *self.encoder
+    }
+  }
+}
+
+/// RLE encodes `levels`, prefixed with their encoded length as in data pages v1.
+fn encode_levels(max_level: i16, levels: &[i16]) -> Result<Vec<u8>> {
+  let size = LevelEncoder::max_buffer_size(Encoding::RLE, max_level, levels.len());
+  let mut encoder = LevelEncoder::new(Encoding::RLE, max_level, vec![0; size]);
+  let num_encoded = encoder.put(levels)?;
+  if num_encoded != levels.len() {
+    return Err(general_err!(
+      "Encoded {} levels, expected {}", num_encoded, levels.len()));
+  }
+  encoder.consume()
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::rc::Rc;
+
+  use column::page::{Page, PageReader};
+  use column::reader::{get_column_reader, get_typed_column_reader};
+  use schema::types::{ColumnDescriptor, ColumnPath, Type as SchemaType};
+
+  struct VecPageReader {
+    pages: ::std::vec::IntoIter<Page>
+  }
+
+  impl PageReader for VecPageReader {
+    fn get_next_page(&mut self) -> Result<Option<Page>> {
+      Ok(self.pages.next())
+    }
+  }
+
+  fn column(
+    physical_type: Type, repetition: Repetition, max_def: i16, max_rep: i16
+  ) -> ColumnDescPtr {
+    let tp = SchemaType::primitive_type_builder("col", physical_type)
+      .with_repetition(repetition)
+      .build()
+      .unwrap();
+    let path = ColumnPath::new(vec!["col".to_owned()]);
+    Rc::new(ColumnDescriptor::new(Rc::new(tp), None, max_def, max_rep, path))
+  }
+
+  #[test]
+  fn test_write_read_batch() {
+    let descr = column(Type::INT32, Repetition::REPEATED, 2, 1);
+    let options = WriterOptions::new().with_data_page_size(64);
+    let mut writer = ColumnWriterImpl::<Int32Type>::new(descr.clone(), &options);
+
+    // Rows [1, 2], [], null, [3, null, 4], repeated 100 times
+    let mut values = vec!();
+    let mut def_levels = vec!();
+    let mut rep_levels = vec!();
+    for _ in 0..100 {
+      values.extend_from_slice(&[1, 2, 3, 4]);
+      def_levels.extend_from_slice(&[2, 2, 1, 0, 2, 1, 2]);
+      rep_levels.extend_from_slice(&[0, 1, 0, 0, 0, 1, 1]);
+    }
+    // The page size is checked after each batch, so rows are written in batches of 4
+    for i in 0..100 {
+      let levels = i * 7..(i + 1) * 7;
+      let num_values = writer.write_batch(
+        &values[i * 4..(i + 1) * 4],
+        Some(&def_levels[levels.clone()]),
+        Some(&rep_levels[levels])).unwrap();
+      assert_eq!(num_values, 4);
+    }
+    let chunk = writer.close().unwrap();
+    assert!(chunk.pages.len() > 1);
+    assert_eq!(chunk.num_values, 700);
+    assert_eq!(chunk.num_rows, 400);
+
+    let page_reader = VecPageReader { pages: chunk.pages.into_iter() };
+    let reader = get_column_reader(descr, Box::new(page_reader));
+    let mut reader = get_typed_column_reader::<Int32Type>(reader);
+    let mut read_values = vec![0; 1000];
+    let mut read_def_levels = vec![0; 1000];
+    let mut read_rep_levels = vec![0; 1000];
+    let (values_read, levels_read) = reader.read_batch(
+      1000, Some(&mut read_def_levels[..]), Some(&mut read_rep_levels[..]),
+      &mut read_values).unwrap();
+    assert_eq!(values_read, 400);
+    assert_eq!(levels_read, 700);
+    assert_eq!(&read_values[..400], &values[..]);
+    assert_eq!(&read_def_levels[..700], &def_levels[..]);
+    assert_eq!(&read_rep_levels[..700], &rep_levels[..]);
+  }
+
+  #[test]
+  fn test_write_read_dictionary() {
+    let descr = column(Type::INT32, Repetition::OPTIONAL, 1, 0);
+    let options = WriterOptions::new().with_data_page_size(64).with_dictionary(true);
+    let mut writer = ColumnWriterImpl::<Int32Type>::new(descr.clone(), &options);
+    let values: Vec<i32> = (0..100).map(|i| [5, 1, 3][i % 3]).collect();
+    let def_levels: Vec<i16> = (0..150).map(|i| (i % 3 != 2) as i16).collect();
+    for (batch, levels) in values.chunks(2).zip(def_levels.chunks(3)) {
+      writer.write_batch(batch, Some(levels), None).unwrap();
+    }
+    let chunk = writer.close().unwrap();
+    assert!(chunk.pages.len() > 2);
+    assert_eq!(chunk.encodings, vec![Encoding::PLAIN_DICTIONARY, Encoding::RLE]);
+    match chunk.pages[0] {
+      Page::DictionaryPage { num_values, encoding, is_sorted, .. } => {
+        assert_eq!(num_values, 3);
+        assert_eq!(encoding, Encoding::PLAIN);
+        assert!(!is_sorted);
+      },
+      _ => panic!("Expected a dictionary page")
+    }
+    assert!(chunk.pages[1..].iter().all(|page| {
+      page.encoding() == Encoding::PLAIN_DICTIONARY
+    }));
+
+    let page_reader = VecPageReader { pages: chunk.pages.into_iter() };
+    let reader = get_column_reader(descr.clone(), Box::new(page_reader));
+    let mut reader = get_typed_column_reader::<Int32Type>(reader);
+    let mut read_values = vec![0; 200];
+    let mut read_def_levels = vec![0; 200];
+    let (values_read, levels_read) = reader.read_batch(
+      200, Some(&mut read_def_levels[..]), None, &mut read_values).unwrap();
+    assert_eq!(values_read, 100);
+    assert_eq!(levels_read, 150);
+    assert_eq!(&read_values[..100], &values[..]);
+    assert_eq!(&read_def_levels[..150], &def_levels[..]);
+
+    // Values written in ascending order make a sorted dictionary
+    let mut writer = ColumnWriterImpl::<Int32Type>::new(descr, &options);
+    writer.write_batch(&[-1, 2, 2, 7], Some(&[1, 1, 1, 1]), None).unwrap();
+    match writer.close().unwrap().pages[0] {
+      Page::DictionaryPage { num_values, is_sorted, .. } => {
+        assert_eq!(num_values, 3);
+        assert!(is_sorted);
+      },
+      _ => panic!("Expected a dictionary page")
+    }
+  }
+
+  #[test]
+  fn test_write_many_booleans() {
+    let descr = column(Type::BOOLEAN, Repetition::REQUIRED, 0, 0);
+    let options = WriterOptions::new();
+    let mut writer = ColumnWriterImpl::<BoolType>::new(descr.clone(), &options);
+    let values: Vec<bool> = (0..5000).map(|i| i % 3 == 0).collect();
+    writer.write_batch(&values, None, None).unwrap();
+    let chunk = writer.close().unwrap();
+    assert_eq!(chunk.pages.len(), 1);
+    assert_eq!(chunk.num_rows, 5000);
+
+    let page_reader = VecPageReader { pages: chunk.pages.into_iter() };
+    let reader = get_column_reader(descr, Box::new(page_reader));
+    let mut reader = get_typed_column_reader::<BoolType>(reader);
+    let mut read_values = vec![false; 5000];
+    let (values_read, _) = reader.read_batch(5000, None, None, &mut read_values).unwrap();
+    assert_eq!(values_read, 5000);
+    assert_eq!(read_values, values);
+  }
+
+  #[test]
+  fn test_write_batch_invalid_levels() {
+    let descr = column(Type::INT32, Repetition::OPTIONAL, 1, 0);
+    let mut writer = ColumnWriterImpl::<Int32Type>::new(descr, &WriterOptions::new());
+    assert!(writer.write_batch(&[1, 2], None, None).is_err());
+    assert!(writer.write_batch(&[1, 2], Some(&[1, 0]), None).is_err());
+    assert!(writer.write_batch(&[1], Some(&[1, 2]), None).is_err());
+    assert_eq!(writer.write_batch(&[1], Some(&[1, 0]), None).unwrap(), 1);
+
+    let mut writer = ColumnWriter::Int32ColumnWriter(writer);
+    assert!(get_typed_column_writer_mut::<Int64Type>(&mut writer).is_err());
+    assert!(get_typed_column_writer_mut::<Int32Type>(&mut writer).is_ok());
+  }
+}
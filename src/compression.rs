@@ -15,10 +15,10 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use std::io::{Read, Write};
+use std::io::{self, Read, Write};
 
 use basic::Compression as CodecType;
-use errors::{Result, ParquetError};
+use errors::{Result, ParquetError, ErrorContext};
 use snap::{Decoder, Encoder, decompress_len};
 use brotli;
 use flate2::Compression;
@@ -47,7 +47,8 @@ pub fn create_codec(codec: CodecType) -> Result<Option<Box<Codec>>> {
     CodecType::GZIP => Ok(Some(Box::new(GZipCodec::new()))),
     CodecType::SNAPPY => Ok(Some(Box::new(SnappyCodec::new()))),
     CodecType::UNCOMPRESSED => Ok(None),
-    _ => Err(nyi_err!("The codec type {} is not supported yet", codec))
+    _ => Err(unsupported_err!(
+      ErrorContext::new(), "The codec type {} is not supported yet", codec))
   }
 }
 
@@ -66,13 +67,13 @@ impl Codec for SnappyCodec {
   fn decompress(&mut self, input_buf: &[u8], output_buf: &mut Vec<u8>) -> Result<usize> {
     let len = decompress_len(input_buf)?;
     output_buf.resize(len, 0);
-    self.decoder.decompress(input_buf, output_buf)
-      .map_err(|e| general_err!("Error when decompressing using Snappy: {}", e))
+    let len = self.decoder.decompress(input_buf, output_buf)?;
+    Ok(len)
   }
 
   fn compress(&mut self, input_buf: &[u8]) -> Result<Vec<u8>> {
-    self.encoder.compress_vec(input_buf)
-      .map_err(|e| general_err!("Error when compressing using Snappy: {}", e))
+    let output = self.encoder.compress_vec(input_buf)?;
+    Ok(output)
   }
 }
 
@@ -87,16 +88,15 @@ impl GZipCodec {
 
 impl Codec for GZipCodec {
   fn decompress(&mut self, input_buf: &[u8], output_buf: &mut Vec<u8>) -> Result<usize> {
-    let mut decoder = GzDecoder::new(input_buf)?;
-    decoder.read_to_end(output_buf).map_err(
-      |e| general_err!("Error when decompressing using GZip: {}", e))
+    let mut decoder = GzDecoder::new(input_buf)
+      .map_err(|e| codec_err(CodecType::GZIP, e))?;
+    decoder.read_to_end(output_buf).map_err(|e| codec_err(CodecType::GZIP, e))
   }
 
   fn compress(&mut self, input_buf: &[u8]) -> Result<Vec<u8>> {
     let mut encoder = GzEncoder::new(Vec::new(), Compression::Default);
-    encoder.write_all(input_buf)?;
-    encoder.finish().map_err(
-      |e| general_err!("Error when compressing using GZip: {}", e))
+    encoder.write_all(input_buf).map_err(|e| codec_err(CodecType::GZIP, e))?;
+    encoder.finish().map_err(|e| codec_err(CodecType::GZIP, e))
   }
 }
 
@@ -117,7 +117,7 @@ impl Codec for BrotliCodec {
   fn decompress(&mut self, input_buf: &[u8], output_buf: &mut Vec<u8>) -> Result<usize> {
     brotli::Decompressor::new(input_buf, BROTLI_DEFAULT_BUFFER_SIZE)
       .read_to_end(output_buf)
-      .map_err(|e| general_err!("Error when decompressing using Brotli: {}", e))
+      .map_err(|e| codec_err(CodecType::BROTLI, e))
   }
 
   fn compress(&mut self, input_buf: &[u8]) -> Result<Vec<u8>> {
@@ -128,11 +128,17 @@ impl Codec for BrotliCodec {
       BROTLI_DEFAULT_COMPRESSION_QUALITY,
       BROTLI_DEFAULT_LG_WINDOW_SIZE
     );
-    reader.read_to_end(&mut buffer)?;
+    reader.read_to_end(&mut buffer).map_err(|e| codec_err(CodecType::BROTLI, e))?;
     Ok(buffer)
   }
 }
 
+/// Wraps an I/O error raised by the library implementing `codec` into a
+/// `ParquetError::Codec`.
+fn codec_err(codec: CodecType, e: io::Error) -> ParquetError {
+  ParquetError::Codec(codec, ErrorContext::new(), Box::new(e))
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
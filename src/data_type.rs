@@ -15,21 +15,24 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::cmp;
+use std::fmt;
 use std::mem;
 
-use basic::Type;
+use basic::{TimeUnit, Type};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use errors::{Result, ParquetError};
 use rand::{Rng, Rand};
 use util::memory::{ByteBuffer, ByteBufferPtr};
 
 // ----------------------------------------------------------------------
 // Types connect Parquet physical types with Rust-specific types
 
-// TODO: alignment?
-// TODO: we could also use [u32; 3], however it seems there is no easy way
-//   to convert [u32] to [u32; 3] in decoding.
-#[derive(Clone, Debug)]
+/// Rust representation for an INT96 value. The value is stored inline, so that
+/// decoding does not allocate per value.
+#[derive(Clone, Copy, Debug)]
 pub struct Int96 {
-  value: Option<Vec<u32>>,
+  value: Option<[u32; 3]>,
 }
 
 impl Int96 {
@@ -39,12 +42,36 @@ impl Int96 {
 
   pub fn data(&self) -> &[u32] {
     assert!(self.value.is_some());
-    &self.value.as_ref().unwrap()
+    self.value.as_ref().unwrap()
+  }
+
+  pub fn set_data(&mut self, elem0: u32, elem1: u32, elem2: u32) {
+    self.value = Some([elem0, elem1, elem2]);
   }
 
-  pub fn set_data(&mut self, v: Vec<u32>) {
-    assert_eq!(v.len(), 3);
-    self.value = Some(v);
+  /// Interprets this value as a timestamp, as written by Impala and Hive: nanoseconds
+  /// of the day in the first 8 bytes, followed by the Julian day in the last 4 bytes.
+  /// Returns the number of nanoseconds since the Unix epoch.
+  pub fn to_nanos(&self) -> Result<i64> {
+    let data = self.data();
+    let nanos_of_day = (data[1] as u64) << 32 | data[0] as u64;
+    if nanos_of_day >= NANOS_PER_DAY as u64 {
+      return Err(general_err!(
+        "Invalid INT96 timestamp: {} nanoseconds of day", nanos_of_day));
+    }
+    let days = data[2] as i64 - JULIAN_DAY_OF_EPOCH;
+    days.checked_mul(NANOS_PER_DAY)
+      .and_then(|nanos| nanos.checked_add(nanos_of_day as i64))
+      .ok_or_else(|| general_err!("INT96 timestamp out of range: Julian day {}", data[2]))
+  }
+
+  /// Creates an INT96 timestamp from the number of nanoseconds since the Unix epoch.
+  pub fn from_nanos(nanos: i64) -> Self {
+    let (days, nanos_of_day) = floor_div_rem(nanos, NANOS_PER_DAY);
+    let julian_day = (days + JULIAN_DAY_OF_EPOCH) as u32;
+    let mut result = Int96::new();
+    result.set_data(nanos_of_day as u32, (nanos_of_day >> 32) as u32, julian_day);
+    result
   }
 }
 
@@ -61,23 +88,24 @@ impl PartialEq for Int96 {
 impl From<Vec<u32>> for Int96 {
   fn from(buf: Vec<u32>) -> Int96 {
     assert_eq!(buf.len(), 3);
-    Self { value: Some(buf) }
+    Self { value: Some([buf[0], buf[1], buf[2]]) }
   }
 }
 
 impl Rand for Int96 {
   fn rand<R: Rng>(rng: &mut R) -> Self {
     let mut result = Int96::new();
-    let mut value = vec!();
-    for _ in 0..3 {
-      value.push(rng.gen::<u32>());
-    }
-    result.set_data(value);
+    result.set_data(rng.gen::<u32>(), rng.gen::<u32>(), rng.gen::<u32>());
     result
   }
 }
 
 
+/// Rust representation for BYTE_ARRAY and FIXED_LEN_BYTE_ARRAY values. Decoded values
+/// point into the buffer they are decoded from rather than copying their bytes.
+///
+/// To decode a batch of byte arrays without a `ByteBufferPtr` per value, use
+/// `ByteArrayBuffer` with `encodings::decoding::ByteArrayDecoder` instead.
 #[derive(Clone, Debug)]
 pub struct ByteArray {
   data: Option<ByteBufferPtr>,
@@ -153,6 +181,507 @@ impl Rand for ByteArray {
   }
 }
 
+/// A batch of BYTE_ARRAY or FIXED_LEN_BYTE_ARRAY values, stored as one values buffer
+/// plus offsets: the `i`th value is `data[offsets[i]..offsets[i + 1]]`.
+///
+/// Unlike a `Vec<ByteArray>`, a batch holds no reference count or memory tracker per
+/// value, and can be cleared and refilled without allocating.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ByteArrayBuffer {
+  data: Vec<u8>,
+  offsets: Vec<usize>,
+}
+
+impl ByteArrayBuffer {
+  pub fn new() -> Self {
+    ByteArrayBuffer { data: vec!(), offsets: vec![0] }
+  }
+
+  /// Removes all values, keeping the allocated capacity.
+  pub fn clear(&mut self) {
+    self.data.clear();
+    self.offsets.truncate(1);
+  }
+
+  /// Returns the number of values in this buffer.
+  pub fn len(&self) -> usize {
+    self.offsets.len() - 1
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Returns the bytes of the `i`th value.
+  pub fn value(&self, i: usize) -> &[u8] {
+    assert!(i < self.len(), "Index {} out of bounds ({} values)", i, self.len());
+    &self.data[self.offsets[i]..self.offsets[i + 1]]
+  }
+
+  /// Returns the concatenated bytes of all values.
+  pub fn data(&self) -> &[u8] {
+    &self.data[..]
+  }
+
+  /// Returns the `len() + 1` offsets of the values into `data()`.
+  pub fn offsets(&self) -> &[usize] {
+    &self.offsets[..]
+  }
+
+  /// Appends `value` as a new value.
+  pub fn push(&mut self, value: &[u8]) {
+    self.data.extend_from_slice(value);
+    self.offsets.push(self.data.len());
+  }
+
+  /// Appends the concatenation of `prefix` and `suffix` as a new value.
+  pub fn push_concat(&mut self, prefix: &[u8], suffix: &[u8]) {
+    self.data.extend_from_slice(prefix);
+    self.push(suffix);
+  }
+
+  /// Appends a new value, made of the first `prefix_len` bytes of the last value
+  /// followed by `suffix`. Used to decode DELTA_BYTE_ARRAY values.
+  pub fn push_with_prefix(&mut self, prefix_len: usize, suffix: &[u8]) {
+    assert!(!self.is_empty(), "There is no previous value to take a prefix of");
+    let start = self.offsets[self.len() - 1];
+    assert!(prefix_len <= self.data.len() - start);
+    for i in start..start + prefix_len {
+      let b = self.data[i];
+      self.data.push(b);
+    }
+    self.data.extend_from_slice(suffix);
+    self.offsets.push(self.data.len());
+  }
+}
+
+impl Default for ByteArrayBuffer {
+  fn default() -> Self { ByteArrayBuffer::new() }
+}
+
+/// Rust representation for a DECIMAL value: the unscaled value as big-endian two's
+/// complement bytes, together with the precision and scale of the column. The value of
+/// the decimal is the unscaled value * 10^(-scale).
+///
+/// Decimals can be stored in INT32, INT64, BYTE_ARRAY and FIXED_LEN_BYTE_ARRAY
+/// columns; the bytes are kept in their shortest form regardless of the column type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decimal {
+  value: Vec<u8>,
+  precision: i32,
+  scale: i32
+}
+
+impl Decimal {
+  /// Creates a decimal from the unscaled value of an INT32 column.
+  pub fn from_i32(value: i32, precision: i32, scale: i32) -> Self {
+    let mut bytes = [0; 4];
+    BigEndian::write_i32(&mut bytes, value);
+    Self::from_bytes(&bytes, precision, scale)
+  }
+
+  /// Creates a decimal from the unscaled value of an INT64 column.
+  pub fn from_i64(value: i64, precision: i32, scale: i32) -> Self {
+    let mut bytes = [0; 8];
+    BigEndian::write_i64(&mut bytes, value);
+    Self::from_bytes(&bytes, precision, scale)
+  }
+
+  /// Creates a decimal from an unscaled value.
+  pub fn from_i128(value: i128, precision: i32, scale: i32) -> Self {
+    let mut bytes = [0; 16];
+    for i in 0..16 {
+      bytes[15 - i] = (value >> (8 * i)) as u8;
+    }
+    Self::from_bytes(&bytes, precision, scale)
+  }
+
+  /// Creates a decimal from the unscaled big-endian two's complement bytes of a
+  /// BYTE_ARRAY or FIXED_LEN_BYTE_ARRAY column. Empty bytes are read as zero.
+  pub fn from_bytes(value: &[u8], precision: i32, scale: i32) -> Self {
+    // Strip redundant sign extension bytes
+    let mut start = 0;
+    while start + 1 < value.len() &&
+      (value[start] == 0 && value[start + 1] & 0x80 == 0 ||
+       value[start] == 0xFF && value[start + 1] & 0x80 != 0) {
+      start += 1;
+    }
+    let value = if value.is_empty() { vec![0] } else { value[start..].to_vec() };
+    Decimal { value: value, precision: precision, scale: scale }
+  }
+
+  /// Returns the unscaled value as big-endian two's complement bytes, in the shortest
+  /// possible form.
+  pub fn data(&self) -> &[u8] {
+    &self.value
+  }
+
+  pub fn precision(&self) -> i32 {
+    self.precision
+  }
+
+  pub fn scale(&self) -> i32 {
+    self.scale
+  }
+
+  pub fn is_negative(&self) -> bool {
+    self.value[0] & 0x80 != 0
+  }
+
+  /// Returns the unscaled value, or an error if it does not fit into an `i128`.
+  pub fn to_i128(&self) -> Result<i128> {
+    if self.value.len() > 16 {
+      return Err(general_err!("Decimal {} does not fit into i128", self));
+    }
+    let mut result: i128 = if self.is_negative() { -1 } else { 0 };
+    for &b in &self.value {
+      result = (result << 8) | b as i128;
+    }
+    Ok(result)
+  }
+
+  /// Returns the closest `f64` to the value of this decimal.
+  pub fn to_f64(&self) -> f64 {
+    self.to_string().parse::<f64>().unwrap()
+  }
+
+  /// Returns the unscaled value sign-extended to `len` bytes, as stored in a
+  /// FIXED_LEN_BYTE_ARRAY column of that length.
+  pub fn to_fixed_len_bytes(&self, len: usize) -> Result<Vec<u8>> {
+    if self.value.len() > len {
+      return Err(general_err!("Decimal {} does not fit into {} bytes", self, len));
+    }
+    let fill = if self.is_negative() { 0xFF } else { 0 };
+    let mut result = vec![fill; len - self.value.len()];
+    result.extend_from_slice(&self.value);
+    Ok(result)
+  }
+}
+
+impl fmt::Display for Decimal {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    // Absolute unscaled value as unsigned big-endian bytes
+    let negative = self.is_negative();
+    let mut magnitude = self.value.clone();
+    if negative {
+      let mut carry = true;
+      for b in magnitude.iter_mut().rev() {
+        *b = !*b;
+        if carry {
+          let (v, overflow) = b.overflowing_add(1);
+          *b = v;
+          carry = overflow;
+        }
+      }
+    }
+
+    // Digits in reverse order, by repeated division by 10
+    let mut digits = vec!();
+    while magnitude.iter().any(|&b| b != 0) {
+      let mut remainder = 0u32;
+      for b in magnitude.iter_mut() {
+        let current = (remainder << 8) | *b as u32;
+        *b = (current / 10) as u8;
+        remainder = current % 10;
+      }
+      digits.push(b'0' + remainder as u8);
+    }
+    let scale = cmp::max(self.scale, 0) as usize;
+    while digits.len() <= scale {
+      digits.push(b'0');
+    }
+    digits.reverse();
+
+    let (integer, fraction) = digits.split_at(digits.len() - scale);
+    if negative {
+      write!(f, "-")?;
+    }
+    write!(f, "{}", String::from_utf8_lossy(integer))?;
+    if !fraction.is_empty() {
+      write!(f, ".{}", String::from_utf8_lossy(fraction))?;
+    }
+    Ok(())
+  }
+}
+
+impl AsBytes for Decimal {
+  fn as_bytes(&self) -> &[u8] {
+    self.data()
+  }
+}
+
+const JULIAN_DAY_OF_EPOCH: i64 = 2_440_588;
+const SECONDS_PER_DAY: i64 = 86_400;
+const NANOS_PER_DAY: i64 = SECONDS_PER_DAY * 1_000_000_000;
+
+/// Rust representation for a DATE value: the number of days since the Unix epoch,
+/// 1970-01-01. Formats as an ISO-8601 date, e.g., `2009-03-01`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+  days: i32
+}
+
+impl Date {
+  pub fn from_days(days: i32) -> Self {
+    Date { days: days }
+  }
+
+  pub fn days(&self) -> i32 {
+    self.days
+  }
+
+  /// Returns the year, month (1-12) and day of month (1-31) of this date in the
+  /// proleptic Gregorian calendar.
+  pub fn to_ymd(&self) -> (i32, u32, u32) {
+    let (year, month, day) = civil_from_days(self.days as i64);
+    (year as i32, month, day)
+  }
+}
+
+impl fmt::Display for Date {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let (year, month, day) = self.to_ymd();
+    write!(f, "{:04}-{:02}-{:02}", year, month, day)
+  }
+}
+
+/// Rust representation for a TIME value: the time of day in `unit`. TIME_MILLIS is
+/// stored as INT32, TIME_MICROS and nanosecond times as INT64. Formats as an ISO-8601
+/// time with the precision of the unit, e.g., `13:45:00.250`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Time {
+  value: i64,
+  unit: TimeUnit
+}
+
+impl Time {
+  pub fn new(value: i64, unit: TimeUnit) -> Self {
+    Time { value: value, unit: unit }
+  }
+
+  pub fn from_millis(millis: i32) -> Self {
+    Self::new(millis as i64, TimeUnit::MILLIS)
+  }
+
+  pub fn from_micros(micros: i64) -> Self {
+    Self::new(micros, TimeUnit::MICROS)
+  }
+
+  pub fn from_nanos(nanos: i64) -> Self {
+    Self::new(nanos, TimeUnit::NANOS)
+  }
+
+  pub fn value(&self) -> i64 {
+    self.value
+  }
+
+  pub fn unit(&self) -> TimeUnit {
+    self.unit
+  }
+}
+
+impl fmt::Display for Time {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let (seconds, fraction) = floor_div_rem(self.value, units_per_second(self.unit));
+    write!(f, "{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)?;
+    write_fraction(f, fraction, self.unit)
+  }
+}
+
+/// Rust representation for a TIMESTAMP value: the number of `unit`s since the Unix
+/// epoch. TIMESTAMP_MILLIS and TIMESTAMP_MICROS are stored as INT64, legacy Impala and
+/// Hive timestamps as INT96.
+///
+/// Formats as an ISO-8601 date and time with the precision of the unit, followed by `Z`
+/// if the timestamp is adjusted to UTC, e.g., `2009-03-01T00:01:00.000Z`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Timestamp {
+  value: i64,
+  unit: TimeUnit,
+  is_adjusted_to_utc: bool
+}
+
+impl Timestamp {
+  pub fn new(value: i64, unit: TimeUnit, is_adjusted_to_utc: bool) -> Self {
+    Timestamp { value: value, unit: unit, is_adjusted_to_utc: is_adjusted_to_utc }
+  }
+
+  pub fn from_millis(millis: i64) -> Self {
+    Self::new(millis, TimeUnit::MILLIS, true)
+  }
+
+  pub fn from_micros(micros: i64) -> Self {
+    Self::new(micros, TimeUnit::MICROS, true)
+  }
+
+  pub fn from_nanos(nanos: i64) -> Self {
+    Self::new(nanos, TimeUnit::NANOS, true)
+  }
+
+  /// Creates a timestamp from a legacy INT96 timestamp. Like Hive and Spark, the value
+  /// is interpreted as UTC.
+  pub fn from_int96(value: &Int96) -> Result<Self> {
+    value.to_nanos().map(|nanos| Self::from_nanos(nanos))
+  }
+
+  pub fn value(&self) -> i64 {
+    self.value
+  }
+
+  pub fn unit(&self) -> TimeUnit {
+    self.unit
+  }
+
+  pub fn is_adjusted_to_utc(&self) -> bool {
+    self.is_adjusted_to_utc
+  }
+
+  /// Returns the number of milliseconds since the Unix epoch, rounded down.
+  pub fn to_millis(&self) -> Result<i64> {
+    self.to_unit(TimeUnit::MILLIS)
+  }
+
+  /// Returns the number of microseconds since the Unix epoch, rounded down.
+  pub fn to_micros(&self) -> Result<i64> {
+    self.to_unit(TimeUnit::MICROS)
+  }
+
+  /// Returns the number of nanoseconds since the Unix epoch.
+  pub fn to_nanos(&self) -> Result<i64> {
+    self.to_unit(TimeUnit::NANOS)
+  }
+
+  /// Converts this timestamp to a legacy INT96 timestamp.
+  pub fn to_int96(&self) -> Result<Int96> {
+    self.to_nanos().map(|nanos| Int96::from_nanos(nanos))
+  }
+
+  fn to_unit(&self, unit: TimeUnit) -> Result<i64> {
+    let from = units_per_second(self.unit);
+    let to = units_per_second(unit);
+    if from >= to {
+      Ok(floor_div_rem(self.value, from / to).0)
+    } else {
+      self.value.checked_mul(to / from)
+        .ok_or_else(|| general_err!("Timestamp {} out of range for {}", self, unit))
+    }
+  }
+}
+
+impl fmt::Display for Timestamp {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let (seconds, fraction) = floor_div_rem(self.value, units_per_second(self.unit));
+    let (days, seconds) = floor_div_rem(seconds, SECONDS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+    write!(f, "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+           year, month, day, seconds / 3600, seconds / 60 % 60, seconds % 60)?;
+    write_fraction(f, fraction, self.unit)?;
+    if self.is_adjusted_to_utc {
+      write!(f, "Z")?;
+    }
+    Ok(())
+  }
+}
+
+/// Rust representation for an INTERVAL value: a number of months, days and
+/// milliseconds, stored as little-endian unsigned integers in a FIXED_LEN_BYTE_ARRAY of
+/// length 12. Formats as an ISO-8601 duration, e.g., `P1M15DT3600.000S`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Interval {
+  months: u32,
+  days: u32,
+  millis: u32
+}
+
+impl Interval {
+  pub fn new(months: u32, days: u32, millis: u32) -> Self {
+    Interval { months: months, days: days, millis: millis }
+  }
+
+  /// Creates an interval from the 12 bytes of a FIXED_LEN_BYTE_ARRAY value.
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+    if bytes.len() != 12 {
+      return Err(general_err!("Invalid INTERVAL length: {} (expected 12)", bytes.len()));
+    }
+    Ok(Self::new(
+      LittleEndian::read_u32(&bytes[0..4]),
+      LittleEndian::read_u32(&bytes[4..8]),
+      LittleEndian::read_u32(&bytes[8..12])))
+  }
+
+  pub fn to_bytes(&self) -> [u8; 12] {
+    let mut bytes = [0; 12];
+    LittleEndian::write_u32(&mut bytes[0..4], self.months);
+    LittleEndian::write_u32(&mut bytes[4..8], self.days);
+    LittleEndian::write_u32(&mut bytes[8..12], self.millis);
+    bytes
+  }
+
+  pub fn months(&self) -> u32 {
+    self.months
+  }
+
+  pub fn days(&self) -> u32 {
+    self.days
+  }
+
+  pub fn millis(&self) -> u32 {
+    self.millis
+  }
+}
+
+impl fmt::Display for Interval {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "P{}M{}DT{}.{:03}S",
+           self.months, self.days, self.millis / 1000, self.millis % 1000)
+  }
+}
+
+impl From<Interval> for ByteArray {
+  fn from(interval: Interval) -> ByteArray {
+    ByteArray::from(interval.to_bytes().to_vec())
+  }
+}
+
+fn units_per_second(unit: TimeUnit) -> i64 {
+  match unit {
+    TimeUnit::MILLIS => 1_000,
+    TimeUnit::MICROS => 1_000_000,
+    TimeUnit::NANOS => 1_000_000_000
+  }
+}
+
+fn write_fraction(f: &mut fmt::Formatter, fraction: i64, unit: TimeUnit) -> fmt::Result {
+  match unit {
+    TimeUnit::MILLIS => write!(f, ".{:03}", fraction),
+    TimeUnit::MICROS => write!(f, ".{:06}", fraction),
+    TimeUnit::NANOS => write!(f, ".{:09}", fraction)
+  }
+}
+
+/// Division rounding towards negative infinity, with a non-negative remainder.
+fn floor_div_rem(a: i64, b: i64) -> (i64, i64) {
+  let (quotient, remainder) = (a / b, a % b);
+  if remainder < 0 { (quotient - 1, remainder + b) } else { (quotient, remainder) }
+}
+
+/// Converts a number of days since the Unix epoch to year, month and day in the
+/// proleptic Gregorian calendar, see
+/// http://howardhinnant.github.io/date_algorithms.html#civil_from_days.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+  let (era, day_of_era) = floor_div_rem(days + 719_468, 146_097);
+  let year_of_era =
+    (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+  let day_of_year =
+    day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  // Months are counted from March, so that the leap day is the last day of the year
+  let shifted_month = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+  let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+  let year = year_of_era + era * 400;
+  let year = if month <= 2 { year + 1 } else { year };
+  (year, month as u32, day as u32)
+}
 
 // ----------------------------------------------------------------------
 // AsBytes converts an instance of data type to a slice of u8
@@ -288,4 +817,144 @@ mod tests {
     let ba = ByteArray::from(vec![1, 2, 3]);
     assert_eq!(ba.as_bytes(), &[1, 2, 3]);
   }
+
+  #[test]
+  fn test_byte_array_buffer() {
+    let mut buffer = ByteArrayBuffer::new();
+    assert!(buffer.is_empty());
+    buffer.push(b"axis");
+    buffer.push_with_prefix(2, b"le");
+    buffer.push_concat(b"", b"");
+    buffer.push_concat(b"ba", b"bble");
+    assert_eq!(buffer.len(), 4);
+    assert_eq!(buffer.data(), b"axisaxlebabble");
+    assert_eq!(buffer.offsets(), &[0, 4, 8, 8, 14]);
+    assert_eq!(buffer.value(1), b"axle");
+    assert_eq!(buffer.value(2), b"");
+    assert_eq!(buffer.value(3), b"babble");
+
+    buffer.clear();
+    assert!(buffer.is_empty());
+    assert_eq!(buffer.offsets(), &[0]);
+  }
+
+  #[test]
+  fn test_decimal() {
+    assert_eq!(Decimal::from_i32(12345, 5, 2).to_string(), "123.45");
+    assert_eq!(Decimal::from_i32(-12345, 5, 2).to_string(), "-123.45");
+    assert_eq!(Decimal::from_i32(5, 3, 3).to_string(), "0.005");
+    assert_eq!(Decimal::from_i32(-5, 3, 3).to_string(), "-0.005");
+    assert_eq!(Decimal::from_i32(0, 3, 1).to_string(), "0.0");
+    assert_eq!(Decimal::from_i64(i64::min_value(), 19, 0).to_string(),
+               "-9223372036854775808");
+    assert_eq!(Decimal::from_bytes(&[], 5, 0).to_string(), "0");
+    assert_eq!(Decimal::from_bytes(&[255, 127], 5, 0).to_string(), "-129");
+
+    // Values larger than i128
+    let large = Decimal::from_bytes(
+      &[29, 99, 41, 241, 195, 92, 164, 191, 171, 185, 245, 97, 0, 0, 0, 0, 0], 41, 38);
+    assert_eq!(large.to_string(), "100.00000000000000000000000000000000000000");
+    assert!(large.to_i128().is_err());
+    assert_eq!(large.to_f64(), 100.0);
+    let large = Decimal::from_bytes(
+      &[226, 156, 214, 14, 60, 163, 91, 64, 84, 70, 10, 158, 255, 255, 255, 255, 255],
+      41, 0);
+    assert_eq!(large.to_string(), "-10000000000000000000000000000000000000001");
+
+    // Bytes are normalized
+    assert_eq!(Decimal::from_i32(-129, 5, 0), Decimal::from_bytes(&[255, 127], 5, 0));
+    assert_eq!(Decimal::from_i64(1, 5, 0).data(), &[1]);
+    assert_eq!(Decimal::from_i64(-1, 5, 0).data(), &[255]);
+    assert_eq!(Decimal::from_i64(128, 5, 0).data(), &[0, 128]);
+    assert_eq!(Decimal::from_i128(-129, 5, 0).as_bytes(), &[255, 127]);
+
+    // Conversions
+    assert_eq!(Decimal::from_i32(-12345, 5, 2).to_i128().unwrap(), -12345);
+    assert_eq!(Decimal::from_i128(i128::max_value(), 38, 0).to_i128().unwrap(),
+               i128::max_value());
+    assert_eq!(Decimal::from_i128(i128::min_value(), 38, 0).to_i128().unwrap(),
+               i128::min_value());
+    assert_eq!(Decimal::from_i64(-12345, 5, 2).to_f64(), -123.45);
+    assert_eq!(Decimal::from_i32(-129, 5, 0).to_fixed_len_bytes(4).unwrap(),
+               vec![255, 255, 255, 127]);
+    assert_eq!(Decimal::from_i32(129, 5, 0).to_fixed_len_bytes(3).unwrap(),
+               vec![0, 0, 129]);
+    assert!(Decimal::from_i32(129, 5, 0).to_fixed_len_bytes(1).is_err());
+  }
+
+  #[test]
+  fn test_int96_timestamp() {
+    let value = Int96::from(vec![0, 0, 2_440_588]);
+    assert_eq!(value.to_nanos().unwrap(), 0);
+    let value = Int96::from(vec![3_562_326_670, 866, 2_454_892]);
+    assert_eq!(value.to_nanos().unwrap(), 14_304 * NANOS_PER_DAY + 3_723_004_005_006);
+    assert_eq!(Timestamp::from_int96(&value).unwrap().to_string(),
+               "2009-03-01T01:02:03.004005006Z");
+    assert_eq!(Int96::from_nanos(value.to_nanos().unwrap()), value);
+    assert_eq!(Int96::from_nanos(-1),
+               Int96::from(vec![2_437_873_663, 20_116, 2_440_587]));
+    assert_eq!(Int96::from_nanos(-1).to_nanos().unwrap(), -1);
+
+    // Invalid nanoseconds of day
+    assert!(Int96::from(vec![0, 20_117, 2_440_588]).to_nanos().is_err());
+    // Out of range for nanoseconds since the epoch
+    assert!(Int96::from(vec![0, 0, u32::max_value()]).to_nanos().is_err());
+    assert!(Int96::from(vec![0, 0, 0]).to_nanos().is_err());
+  }
+
+  #[test]
+  fn test_date() {
+    assert_eq!(Date::from_days(0).to_string(), "1970-01-01");
+    assert_eq!(Date::from_days(-1).to_string(), "1969-12-31");
+    assert_eq!(Date::from_days(11_016).to_string(), "2000-02-29");
+    assert_eq!(Date::from_days(-25_508).to_string(), "1900-03-01");
+    assert_eq!(Date::from_days(-719_162).to_string(), "0001-01-01");
+    assert_eq!(Date::from_days(2_932_896).to_string(), "9999-12-31");
+    assert_eq!(Date::from_days(11_016).to_ymd(), (2000, 2, 29));
+  }
+
+  #[test]
+  fn test_time() {
+    assert_eq!(Time::from_millis(0).to_string(), "00:00:00.000");
+    assert_eq!(Time::from_millis(49_500_250).to_string(), "13:45:00.250");
+    assert_eq!(Time::from_micros(86_399_999_999).to_string(), "23:59:59.999999");
+    assert_eq!(Time::from_nanos(3_723_004_005_006).to_string(), "01:02:03.004005006");
+  }
+
+  #[test]
+  fn test_timestamp() {
+    assert_eq!(Timestamp::from_millis(0).to_string(), "1970-01-01T00:00:00.000Z");
+    assert_eq!(Timestamp::from_micros(-1).to_string(), "1969-12-31T23:59:59.999999Z");
+    assert_eq!(Timestamp::from_millis(1_235_865_660_000).to_string(),
+               "2009-03-01T00:01:00.000Z");
+    assert_eq!(Timestamp::new(1_235_865_660_000, TimeUnit::MILLIS, false).to_string(),
+               "2009-03-01T00:01:00.000");
+
+    let ts = Timestamp::from_micros(-1_500);
+    assert_eq!(ts.to_millis().unwrap(), -2);
+    assert_eq!(ts.to_micros().unwrap(), -1_500);
+    assert_eq!(ts.to_nanos().unwrap(), -1_500_000);
+    assert_eq!(ts.to_int96().unwrap().to_nanos().unwrap(), -1_500_000);
+    assert!(Timestamp::from_millis(i64::max_value()).to_nanos().is_err());
+  }
+
+  #[test]
+  fn test_interval() {
+    let bytes = [1, 0, 0, 0, 15, 0, 0, 0, 128, 238, 54, 0];
+    let interval = Interval::from_bytes(&bytes).unwrap();
+    assert_eq!(interval, Interval::new(1, 15, 3_600_000));
+    assert_eq!(interval.months(), 1);
+    assert_eq!(interval.days(), 15);
+    assert_eq!(interval.millis(), 3_600_000);
+    assert_eq!(interval.to_bytes(), bytes);
+    assert_eq!(interval.to_string(), "P1M15DT3600.000S");
+    assert_eq!(Interval::new(0, 0, 1_250).to_string(), "P0M0DT1.250S");
+    assert_eq!(ByteArray::from(interval).data(), &bytes);
+
+    let max = Interval::new(u32::max_value(), u32::max_value(), u32::max_value());
+    assert_eq!(Interval::from_bytes(&max.to_bytes()).unwrap(), max);
+
+    assert!(Interval::from_bytes(&bytes[..11]).is_err());
+    assert!(Interval::from_bytes(&[]).is_err());
+  }
 }
@@ -21,9 +21,10 @@ use std::marker::PhantomData;
 use std::slice::from_raw_parts_mut;
 use basic::*;
 use data_type::*;
+use byteorder::{ByteOrder, LittleEndian};
 use errors::{Result, ParquetError};
 use schema::types::ColumnDescPtr;
-use util::bit_util::BitReader;
+use util::bit_util::{get_array_bit, BitReader};
 use util::memory::{ByteBufferPtr, ByteBuffer};
 use super::rle_encoding::RleDecoder;
 
@@ -43,6 +44,23 @@ pub trait Decoder<T: DataType> {
   /// unless the remaining number of values is less than `buffer.len()`.
   fn get(&mut self, buffer: &mut [T::T]) -> Result<usize>;
 
+  /// Consumes values from this decoder and writes them to `buffer`, leaving a slot for
+  /// each null: bit `valid_bits_offset + i` of `valid_bits` is set if slot `i` of
+  /// `buffer` is not null, and `null_count` is the number of null slots in `buffer`. The
+  /// content of null slots is unspecified.
+  ///
+  /// Returns `buffer.len()`, or an error if fewer than `buffer.len() - null_count`
+  /// values are left, or if the number of slots set in `valid_bits` differs from it.
+  fn get_spaced(
+    &mut self,
+    buffer: &mut [T::T],
+    null_count: usize,
+    valid_bits: &[u8],
+    valid_bits_offset: usize
+  ) -> Result<usize> {
+    get_spaced_by_moving(self, buffer, null_count, valid_bits, valid_bits_offset)
+  }
+
   /// Returns the number of values left in this decoder stream
   fn values_left(&self) -> usize;
 
@@ -50,6 +68,20 @@ pub trait Decoder<T: DataType> {
   fn encoding(&self) -> Encoding;
 }
 
+/// Decoding of BYTE_ARRAY and FIXED_LEN_BYTE_ARRAY values into a `ByteArrayBuffer`,
+/// which copies their bytes into one buffer per batch instead of creating a
+/// `ByteBufferPtr` per value as `Decoder::get` does.
+pub trait ByteArrayDecoder {
+  /// Consumes at most `max_values` values from this decoder and appends them to
+  /// `buffer`.
+  ///
+  /// Returns the actual number of values decoded, which is `max_values` unless fewer
+  /// values are left.
+  fn get_into(
+    &mut self, buffer: &mut ByteArrayBuffer, max_values: usize
+  ) -> Result<usize>;
+}
+
 
 /// Gets a decoder for the column descriptor `descr` and encoding type `encoding`.
 /// NOTE: the primitive type in `descr` MUST match the data type `T`, otherwise
@@ -75,6 +107,55 @@ pub fn get_decoder<T: DataType>(
 }
 
 
+/// Returns the number of values to decode to fill `num_values` slots, of which
+/// `null_count` are null, checking that enough values are left.
+fn num_values_spaced(
+  num_values: usize, null_count: usize, values_left: usize
+) -> Result<usize> {
+  if null_count > num_values {
+    return Err(general_err!(
+      "Number of nulls ({}) exceeds the number of values ({})", null_count, num_values));
+  }
+  let values_to_read = num_values - null_count;
+  if values_to_read > values_left {
+    return Err(eof_err!(
+      "Not enough values to decode: {} needed, {} left", values_to_read, values_left));
+  }
+  Ok(values_to_read)
+}
+
+/// Implements `Decoder::get_spaced` by decoding values at the start of `buffer`, and
+/// moving them to their slots, starting from the last one.
+fn get_spaced_by_moving<T: DataType, D: Decoder<T> + ?Sized>(
+  decoder: &mut D,
+  buffer: &mut [T::T],
+  null_count: usize,
+  valid_bits: &[u8],
+  valid_bits_offset: usize
+) -> Result<usize> {
+  let num_values = buffer.len();
+  let values_to_read = num_values_spaced(num_values, null_count, decoder.values_left())?;
+  let values_read = decoder.get(&mut buffer[..values_to_read])?;
+  if values_read != values_to_read {
+    return Err(eof_err!("Not enough values to decode"));
+  }
+  let mut values_to_move = values_read;
+  for i in (0..num_values).rev() {
+    if values_to_move == 0 {
+      break;
+    }
+    if get_array_bit(valid_bits, valid_bits_offset + i) {
+      values_to_move -= 1;
+      buffer.swap(i, values_to_move);
+    }
+  }
+  if values_to_move != 0 {
+    return Err(general_err!(
+      "Fewer than {} slots are set in the validity bitmap", values_to_read));
+  }
+  Ok(num_values)
+}
+
 // ----------------------------------------------------------------------
 // PLAIN Decoding
 
@@ -146,6 +227,59 @@ default impl<T: DataType> Decoder<T> for PlainDecoder<T> {
 
     Ok(num_values)
   }
+
+  #[inline]
+  fn get_spaced(
+    &mut self,
+    buffer: &mut [T::T],
+    null_count: usize,
+    valid_bits: &[u8],
+    valid_bits_offset: usize
+  ) -> Result<usize> {
+    assert!(self.data.is_some());
+
+    let data = self.data.as_ref().unwrap();
+    let num_values = buffer.len();
+    let values_to_read = num_values_spaced(num_values, null_count, self.num_values)?;
+    let type_size = mem::size_of::<T::T>();
+    let bytes_to_decode = type_size * values_to_read;
+    if data.len() - self.start < bytes_to_decode {
+      return Err(eof_err!("Not enough bytes to decode"));
+    }
+    let bytes = &data.as_ref()[self.start..self.start + bytes_to_decode];
+    let raw_buffer: &mut [u8] = unsafe {
+      from_raw_parts_mut(buffer.as_ptr() as *mut u8, type_size * num_values)
+    };
+    // Copy each run of non-null values at once
+    let mut offset = 0;
+    let mut i = 0;
+    while i < num_values {
+      if !get_array_bit(valid_bits, valid_bits_offset + i) {
+        i += 1;
+        continue;
+      }
+      let run_start = i;
+      while i < num_values && get_array_bit(valid_bits, valid_bits_offset + i) {
+        i += 1;
+      }
+      let run_bytes = (i - run_start) * type_size;
+      if offset + run_bytes > bytes_to_decode {
+        return Err(general_err!(
+          "More than {} slots are set in the validity bitmap", values_to_read));
+      }
+      raw_buffer[run_start * type_size..i * type_size]
+        .copy_from_slice(&bytes[offset..offset + run_bytes]);
+      offset += run_bytes;
+    }
+    if offset != bytes_to_decode {
+      return Err(general_err!(
+        "Fewer than {} slots are set in the validity bitmap", values_to_read));
+    }
+    self.start += bytes_to_decode;
+    self.num_values -= values_to_read;
+
+    Ok(num_values)
+  }
 }
 
 impl Decoder<Int96Type> for PlainDecoder<Int96Type> {
@@ -159,21 +293,28 @@ impl Decoder<Int96Type> for PlainDecoder<Int96Type> {
     if bytes_left < bytes_to_decode {
       return Err(eof_err!("Not enough bytes to decode"));
     }
-    for i in 0..num_values {
+    let bytes = &data.as_ref()[self.start..self.start + bytes_to_decode];
+    for (i, value) in bytes.chunks(12).enumerate() {
       buffer[i].set_data(
-        unsafe {
-          // TODO: avoid this copying
-          let slice = ::std::slice::from_raw_parts(
-            data.range(self.start, 12).as_ref().as_ptr() as *mut u32, 3);
-          Vec::from(slice)
-        }
-      );
-      self.start += 12;
+        LittleEndian::read_u32(&value[0..4]),
+        LittleEndian::read_u32(&value[4..8]),
+        LittleEndian::read_u32(&value[8..12]));
     }
+    self.start += bytes_to_decode;
     self.num_values -= num_values;
 
     Ok(num_values)
   }
+
+  fn get_spaced(
+    &mut self,
+    buffer: &mut [Int96],
+    null_count: usize,
+    valid_bits: &[u8],
+    valid_bits_offset: usize
+  ) -> Result<usize> {
+    get_spaced_by_moving(self, buffer, null_count, valid_bits, valid_bits_offset)
+  }
 }
 
 impl Decoder<BoolType> for PlainDecoder<BoolType> {
@@ -196,6 +337,16 @@ impl Decoder<BoolType> for PlainDecoder<BoolType> {
 
     Ok(num_values)
   }
+
+  fn get_spaced(
+    &mut self,
+    buffer: &mut [bool],
+    null_count: usize,
+    valid_bits: &[u8],
+    valid_bits_offset: usize
+  ) -> Result<usize> {
+    get_spaced_by_moving(self, buffer, null_count, valid_bits, valid_bits_offset)
+  }
 }
 
 impl Decoder<ByteArrayType> for PlainDecoder<ByteArrayType> {
@@ -205,6 +356,9 @@ impl Decoder<ByteArrayType> for PlainDecoder<ByteArrayType> {
     let data = self.data.as_mut().unwrap();
     let num_values = cmp::min(buffer.len(), self.num_values);
     for i in 0..num_values {
+      if data.len() < self.start + mem::size_of::<u32>() {
+        return Err(eof_err!("Not enough bytes to decode"));
+      }
       let len: usize = read_num_bytes!(
         u32, 4, data.start_from(self.start).as_ref()) as usize;
       self.start += mem::size_of::<u32>();
@@ -218,12 +372,25 @@ impl Decoder<ByteArrayType> for PlainDecoder<ByteArrayType> {
 
     Ok(num_values)
   }
+
+  fn get_spaced(
+    &mut self,
+    buffer: &mut [ByteArray],
+    null_count: usize,
+    valid_bits: &[u8],
+    valid_bits_offset: usize
+  ) -> Result<usize> {
+    get_spaced_by_moving(self, buffer, null_count, valid_bits, valid_bits_offset)
+  }
 }
 
 impl Decoder<FixedLenByteArrayType> for PlainDecoder<FixedLenByteArrayType> {
   fn get(&mut self, buffer: &mut [ByteArray]) -> Result<usize> {
     assert!(self.data.is_some());
-    assert!(self.type_length > 0);
+    if self.type_length <= 0 {
+      return Err(general_err!(
+        "Invalid type length for FIXED_LEN_BYTE_ARRAY: {}", self.type_length));
+    }
 
     let data = self.data.as_mut().unwrap();
     let type_length = self.type_length as usize;
@@ -239,6 +406,69 @@ impl Decoder<FixedLenByteArrayType> for PlainDecoder<FixedLenByteArrayType> {
 
     Ok(num_values)
   }
+
+  fn get_spaced(
+    &mut self,
+    buffer: &mut [ByteArray],
+    null_count: usize,
+    valid_bits: &[u8],
+    valid_bits_offset: usize
+  ) -> Result<usize> {
+    get_spaced_by_moving(self, buffer, null_count, valid_bits, valid_bits_offset)
+  }
+}
+
+impl ByteArrayDecoder for PlainDecoder<ByteArrayType> {
+  fn get_into(
+    &mut self, buffer: &mut ByteArrayBuffer, max_values: usize
+  ) -> Result<usize> {
+    assert!(self.data.is_some());
+
+    let data = self.data.as_ref().unwrap();
+    let num_values = cmp::min(max_values, self.num_values);
+    for _ in 0..num_values {
+      if data.len() < self.start + mem::size_of::<u32>() {
+        return Err(eof_err!("Not enough bytes to decode"));
+      }
+      let len: usize = read_num_bytes!(
+        u32, 4, data.start_from(self.start).as_ref()) as usize;
+      self.start += mem::size_of::<u32>();
+      if data.len() < self.start + len {
+        return Err(eof_err!("Not enough bytes to decode"));
+      }
+      buffer.push(&data.as_ref()[self.start..self.start + len]);
+      self.start += len;
+    }
+    self.num_values -= num_values;
+
+    Ok(num_values)
+  }
+}
+
+impl ByteArrayDecoder for PlainDecoder<FixedLenByteArrayType> {
+  fn get_into(
+    &mut self, buffer: &mut ByteArrayBuffer, max_values: usize
+  ) -> Result<usize> {
+    assert!(self.data.is_some());
+    if self.type_length <= 0 {
+      return Err(general_err!(
+        "Invalid type length for FIXED_LEN_BYTE_ARRAY: {}", self.type_length));
+    }
+
+    let data = self.data.as_ref().unwrap();
+    let type_length = self.type_length as usize;
+    let num_values = cmp::min(max_values, self.num_values);
+    for _ in 0..num_values {
+      if data.len() < self.start + type_length {
+        return Err(eof_err!("Not enough bytes to decode"));
+      }
+      buffer.push(&data.as_ref()[self.start..self.start + type_length]);
+      self.start += type_length;
+    }
+    self.num_values -= num_values;
+
+    Ok(num_values)
+  }
 }
 
 
@@ -257,11 +487,20 @@ pub struct DictDecoder<T: DataType> {
 
   // Number of values left in the data stream
   num_values: usize,
+
+  // Buffer of the ids decoded by `get_spaced`, reused across calls
+  indices: Vec<i32>
 }
 
 impl<T: DataType> DictDecoder<T> {
   pub fn new() -> Self {
-    Self { dictionary: vec!(), has_dictionary: false, rle_decoder: None, num_values: 0 }
+    Self {
+      dictionary: vec!(),
+      has_dictionary: false,
+      rle_decoder: None,
+      num_values: 0,
+      indices: vec!()
+    }
   }
 
   pub fn set_dict(&mut self, mut decoder: Box<Decoder<T>>) -> Result<()> {
@@ -271,12 +510,53 @@ impl<T: DataType> DictDecoder<T> {
     self.has_dictionary = true;
     Ok(())
   }
+
+  /// Sets the dictionary to already decoded `values`, see
+  /// `column::reader::decode_dictionary_page`.
+  pub fn set_dict_values(&mut self, values: Vec<T::T>) {
+    self.dictionary = values;
+    self.has_dictionary = true;
+  }
+
+  /// Returns the dictionary, whose values are referred to by their index.
+  pub fn dictionary(&self) -> &[T::T] {
+    assert!(self.has_dictionary, "Must call set_dict() first!");
+    &self.dictionary[..]
+  }
+
+  /// Consumes values from this decoder as indices into the dictionary, rather than the
+  /// values they refer to, and writes them to `buffer`.
+  ///
+  /// Returns the actual number of indices decoded, as `Decoder::get`.
+  pub fn get_indices(&mut self, buffer: &mut [i32]) -> Result<usize> {
+    assert!(self.rle_decoder.is_some());
+    assert!(self.has_dictionary, "Must call set_dict() first!");
+
+    let num_values = cmp::min(buffer.len(), self.num_values);
+    let values_read = self.rle_decoder.as_mut().unwrap()
+      .get_batch(&mut buffer[..num_values])?;
+    for index in &buffer[..values_read] {
+      if *index < 0 || *index as usize >= self.dictionary.len() {
+        return Err(general_err!(
+          "Dictionary index {} out of bounds (dictionary size: {})",
+          index, self.dictionary.len()));
+      }
+    }
+    self.num_values -= values_read;
+    Ok(values_read)
+  }
 }
 
 impl<T: DataType> Decoder<T> for DictDecoder<T> {
   fn set_data(&mut self, data: ByteBufferPtr, num_values: usize) -> Result<()> {
     // First byte in `data` is bit width
+    if data.len() == 0 {
+      return Err(eof_err!("Not enough bytes to decode 'bit_width'"));
+    }
     let bit_width = data.as_ref()[0];
+    if bit_width > 32 {
+      return Err(general_err!("Invalid bit width for dictionary indices: {}", bit_width));
+    }
     let mut rle_decoder = RleDecoder::new(bit_width);
     rle_decoder.set_data(data.start_from(1));
     self.num_values = num_values;
@@ -290,7 +570,49 @@ impl<T: DataType> Decoder<T> for DictDecoder<T> {
 
     let rle = self.rle_decoder.as_mut().unwrap();
     let num_values = cmp::min(buffer.len(), self.num_values);
-    rle.get_batch_with_dict(&self.dictionary[..], buffer, num_values)
+    let values_read = rle.get_batch_with_dict(&self.dictionary[..], buffer, num_values)?;
+    self.num_values -= values_read;
+    Ok(values_read)
+  }
+
+  fn get_spaced(
+    &mut self,
+    buffer: &mut [T::T],
+    null_count: usize,
+    valid_bits: &[u8],
+    valid_bits_offset: usize
+  ) -> Result<usize> {
+    assert!(self.rle_decoder.is_some());
+    assert!(self.has_dictionary, "Must call set_dict() first!");
+
+    let num_values = buffer.len();
+    let values_to_read = num_values_spaced(num_values, null_count, self.num_values)?;
+    self.indices.resize(values_to_read, 0);
+    let indices_read =
+      self.rle_decoder.as_mut().unwrap().get_batch(&mut self.indices[..])?;
+    if indices_read != values_to_read {
+      return Err(eof_err!("Not enough data left"));
+    }
+    let mut indices = self.indices.iter();
+    for i in 0..num_values {
+      if !get_array_bit(valid_bits, valid_bits_offset + i) {
+        continue;
+      }
+      let index = indices.next().ok_or_else(|| general_err!(
+        "More than {} slots are set in the validity bitmap", values_to_read))?;
+      match self.dictionary.get(*index as usize) {
+        Some(value) => buffer[i] = value.clone(),
+        None => return Err(general_err!(
+          "Dictionary index {} out of bounds (dictionary size: {})",
+          index, self.dictionary.len()))
+      }
+    }
+    if indices.next().is_some() {
+      return Err(general_err!(
+        "Fewer than {} slots are set in the validity bitmap", values_to_read));
+    }
+    self.num_values -= values_to_read;
+    Ok(num_values)
   }
 
   /// Number of values left in this decoder stream
@@ -303,6 +625,39 @@ impl<T: DataType> Decoder<T> for DictDecoder<T> {
   }
 }
 
+impl<T: DataType<T = ByteArray>> ByteArrayDecoder for DictDecoder<T> {
+  fn get_into(
+    &mut self, buffer: &mut ByteArrayBuffer, max_values: usize
+  ) -> Result<usize> {
+    assert!(self.rle_decoder.is_some());
+    assert!(self.has_dictionary, "Must call set_dict() first!");
+
+    let rle = self.rle_decoder.as_mut().unwrap();
+    let num_values = cmp::min(max_values, self.num_values);
+    // Indices are decoded in chunks, so that no buffer is allocated for them
+    let mut indices = [0i32; 128];
+    let mut values_read = 0;
+    while values_read < num_values {
+      let batch_size = cmp::min(indices.len(), num_values - values_read);
+      let indices_read = rle.get_batch(&mut indices[..batch_size])?;
+      for &index in &indices[..indices_read] {
+        match self.dictionary.get(index as usize) {
+          Some(value) if index >= 0 => buffer.push(value.data()),
+          _ => return Err(general_err!(
+            "Dictionary index {} out of bounds (dictionary size: {})",
+            index, self.dictionary.len()))
+        }
+      }
+      values_read += indices_read;
+      if indices_read < batch_size {
+        break;
+      }
+    }
+    self.num_values -= values_read;
+    Ok(values_read)
+  }
+}
+
 
 // ----------------------------------------------------------------------
 // DELTA_BINARY_PACKED Decoding
@@ -369,12 +724,46 @@ impl<T: DataType> DeltaBitPackDecoder<T> {
       widths.push(w);
     }
 
+    if let Some(w) = widths.iter().find(|w| **w > 64) {
+      return Err(general_err!("Invalid delta bit width: {}", w));
+    }
     self.delta_bit_widths.set_data(widths);
     self.mini_block_idx = 0;
     self.delta_bit_width = self.delta_bit_widths.data()[0];
     self.values_current_mini_block = self.values_per_mini_block;
     Ok(())
   }
+
+  /// Decodes the next value.
+  #[inline]
+  fn next_value(&mut self) -> Result<i64> {
+    if !self.first_value_read {
+      self.current_value = self.first_value;
+      self.first_value_read = true;
+      return Ok(self.current_value);
+    }
+
+    if self.values_current_mini_block == 0 {
+      self.mini_block_idx += 1;
+      if self.mini_block_idx < self.delta_bit_widths.size() {
+        self.delta_bit_width = self.delta_bit_widths.data()[self.mini_block_idx];
+        self.values_current_mini_block = self.values_per_mini_block;
+      } else {
+        self.init_block()?;
+      }
+    }
+
+    // TODO: use SIMD to optimize this?
+    let delta = self.bit_reader.get_value::<u64>(self.delta_bit_width as usize)
+      .ok_or(eof_err!("Not enough data to decode 'delta'"))?;
+    // It is OK for deltas to contain "overflowed" values after encoding,
+    // e.g. i64::MAX - i64::MIN, so we use `wrapping_add` to "overflow" again and
+    // restore original value.
+    self.current_value = self.current_value.wrapping_add(self.min_delta);
+    self.current_value = self.current_value.wrapping_add(delta as i64);
+    self.values_current_mini_block -= 1;
+    Ok(self.current_value)
+  }
 }
 
 default impl<T: DataType> Decoder<T> for DeltaBitPackDecoder<T> {
@@ -399,8 +788,17 @@ default impl<T: DataType> Decoder<T> for DeltaBitPackDecoder<T> {
     self.delta_bit_widths.clear();
     self.values_current_mini_block = 0;
 
+    if block_size <= 0 || self.num_mini_blocks <= 0 {
+      return Err(general_err!(
+        "Invalid block size ({}) or number of mini blocks ({})",
+        block_size, self.num_mini_blocks));
+    }
     self.values_per_mini_block = (block_size / self.num_mini_blocks) as i64;
-    assert!(self.values_per_mini_block % 8 == 0);
+    if self.values_per_mini_block == 0 || self.values_per_mini_block % 8 != 0 {
+      return Err(general_err!(
+        "Number of values per mini block ({}) must be a positive multiple of 8",
+        self.values_per_mini_block));
+    }
 
     Ok(())
   }
@@ -410,36 +808,44 @@ default impl<T: DataType> Decoder<T> for DeltaBitPackDecoder<T> {
 
     let num_values = cmp::min(buffer.len(), self.num_values);
     for i in 0..num_values {
-      if !self.first_value_read {
-        self.set_decoded_value(buffer, i, self.first_value)?;
-        self.current_value = self.first_value;
-        self.first_value_read = true;
+      let value = self.next_value()?;
+      self.set_decoded_value(buffer, i, value)?;
+    }
+
+    self.num_values -= num_values;
+    Ok(num_values)
+  }
+
+  fn get_spaced(
+    &mut self,
+    buffer: &mut [T::T],
+    null_count: usize,
+    valid_bits: &[u8],
+    valid_bits_offset: usize
+  ) -> Result<usize> {
+    assert!(self.initialized, "bit reader is not initialized");
+
+    let num_values = buffer.len();
+    let values_to_read = num_values_spaced(num_values, null_count, self.num_values)?;
+    let mut values_read = 0;
+    for i in 0..num_values {
+      if !get_array_bit(valid_bits, valid_bits_offset + i) {
         continue;
       }
-
-      if self.values_current_mini_block == 0 {
-        self.mini_block_idx += 1;
-        if self.mini_block_idx < self.delta_bit_widths.size() {
-          self.delta_bit_width = self.delta_bit_widths.data()[self.mini_block_idx];
-          self.values_current_mini_block = self.values_per_mini_block;
-        } else {
-          self.init_block()?;
-        }
+      if values_read == values_to_read {
+        return Err(general_err!(
+          "More than {} slots are set in the validity bitmap", values_to_read));
       }
-
-      // TODO: use SIMD to optimize this?
-      let delta = self.bit_reader.get_value::<u64>(self.delta_bit_width as usize)
-        .ok_or(eof_err!("Not enough data to decode 'delta'"))?;
-      // It is OK for deltas to contain "overflowed" values after encoding,
-      // e.g. i64::MAX - i64::MIN, so we use `wrapping_add` to "overflow" again and
-      // restore original value.
-      self.current_value = self.current_value.wrapping_add(self.min_delta);
-      self.current_value = self.current_value.wrapping_add(delta as i64);
-      self.set_decoded_value(buffer, i, self.current_value)?;
-      self.values_current_mini_block -= 1;
+      let value = self.next_value()?;
+      self.set_decoded_value(buffer, i, value)?;
+      values_read += 1;
+    }
+    if values_read != values_to_read {
+      return Err(general_err!(
+        "Fewer than {} slots are set in the validity bitmap", values_to_read));
     }
 
-    self.num_values -= num_values;
+    self.num_values -= values_read;
     Ok(num_values)
   }
 
@@ -548,6 +954,11 @@ impl Decoder<ByteArrayType> for DeltaLengthByteArrayDecoder<ByteArrayType> {
     let mut len_decoder = DeltaBitPackDecoder::<Int32Type>::new();
     len_decoder.set_data(data.all(), num_values)?;
     let num_lengths = len_decoder.values_left();
+    if num_lengths > num_values {
+      return Err(general_err!(
+        "Number of encoded lengths ({}) exceeds number of values ({})",
+        num_lengths, num_values));
+    }
     self.lengths.resize(num_lengths, 0);
     len_decoder.get(&mut self.lengths[..])?;
 
@@ -564,7 +975,11 @@ impl Decoder<ByteArrayType> for DeltaLengthByteArrayDecoder<ByteArrayType> {
     let data = self.data.as_ref().unwrap();
     let num_values = cmp::min(buffer.len(), self.num_values);
     for i in 0..num_values {
-      let len = self.lengths[self.current_idx] as usize;
+      let len = self.lengths[self.current_idx];
+      if len < 0 || data.len() < self.offset + len as usize {
+        return Err(eof_err!("Not enough bytes to decode"));
+      }
+      let len = len as usize;
       buffer[i].set_data(data.range(self.offset, len));
       self.offset += len;
       self.current_idx += 1;
@@ -575,6 +990,30 @@ impl Decoder<ByteArrayType> for DeltaLengthByteArrayDecoder<ByteArrayType> {
   }
 }
 
+impl ByteArrayDecoder for DeltaLengthByteArrayDecoder<ByteArrayType> {
+  fn get_into(
+    &mut self, buffer: &mut ByteArrayBuffer, max_values: usize
+  ) -> Result<usize> {
+    assert!(self.data.is_some());
+
+    let data = self.data.as_ref().unwrap();
+    let num_values = cmp::min(max_values, self.num_values);
+    for _ in 0..num_values {
+      let len = self.lengths[self.current_idx];
+      if len < 0 || data.len() < self.offset + len as usize {
+        return Err(eof_err!("Not enough bytes to decode"));
+      }
+      let len = len as usize;
+      buffer.push(&data.as_ref()[self.offset..self.offset + len]);
+      self.offset += len;
+      self.current_idx += 1;
+    }
+
+    self.num_values -= num_values;
+    Ok(num_values)
+  }
+}
+
 // ----------------------------------------------------------------------
 // DELTA_BYTE_ARRAY Decoding
 
@@ -592,6 +1031,9 @@ pub struct DeltaByteArrayDecoder<T: DataType> {
   // The last byte array, used to derive the current prefix
   previous_value: Option<ByteBufferPtr>,
 
+  // Suffixes decoded by `get_into`, reused across batches
+  suffixes: ByteArrayBuffer,
+
   // Number of values left
   num_values: usize,
 
@@ -602,7 +1044,8 @@ pub struct DeltaByteArrayDecoder<T: DataType> {
 impl<T: DataType> DeltaByteArrayDecoder<T> {
   pub fn new() -> Self {
     Self { prefix_lengths: vec!(), current_idx: 0, suffix_decoder: None,
-           previous_value: None, num_values: 0, _phantom: PhantomData }
+           previous_value: None, suffixes: ByteArrayBuffer::new(), num_values: 0,
+           _phantom: PhantomData }
   }
 }
 
@@ -629,6 +1072,11 @@ impl<> Decoder<ByteArrayType> for DeltaByteArrayDecoder<ByteArrayType> {
     let mut prefix_len_decoder = DeltaBitPackDecoder::<Int32Type>::new();
     prefix_len_decoder.set_data(data.all(), num_values)?;
     let num_prefixes = prefix_len_decoder.values_left();
+    if num_prefixes > num_values {
+      return Err(general_err!(
+        "Number of encoded prefix lengths ({}) exceeds number of values ({})",
+        num_prefixes, num_values));
+    }
     self.prefix_lengths.resize(num_prefixes, 0);
     prefix_len_decoder.get(&mut self.prefix_lengths[..])?;
 
@@ -644,34 +1092,108 @@ impl<> Decoder<ByteArrayType> for DeltaByteArrayDecoder<ByteArrayType> {
     assert!(self.suffix_decoder.is_some());
 
     let num_values = cmp::min(buffer.len(), self.num_values);
+    let mut suffixes = vec![ByteArray::new(); num_values];
+    {
+      let suffix_decoder = self.suffix_decoder.as_mut().unwrap();
+      if suffix_decoder.get(&mut suffixes[..])? < num_values {
+        return Err(eof_err!("Not enough suffixes to decode"));
+      }
+    }
+
+    // All values of the batch are written into a single buffer, and each value in
+    // `buffer` points to its range of it
+    let mut values: Vec<u8> = vec!();
+    let mut ranges: Vec<(usize, usize)> = Vec::with_capacity(num_values);
     for i in 0..num_values {
-      // Process prefix
-      let mut prefix_slice: Option<Vec<u8>> = None;
       let prefix_len = self.prefix_lengths[self.current_idx];
-      if prefix_len != 0 {
-        assert!(self.previous_value.is_some());
-        let previous = self.previous_value.as_ref().unwrap();
-        prefix_slice = Some(Vec::from(previous.as_ref()));
+      let previous_len = if i == 0 {
+        self.previous_value.as_ref().map(|p| p.len()).unwrap_or(0)
+      } else {
+        ranges[i - 1].1
+      };
+      if prefix_len < 0 || prefix_len as usize > previous_len {
+        return Err(general_err!(
+          "Invalid prefix length {} (previous value length: {})",
+          prefix_len, previous_len));
       }
-      // Process suffix
-      // TODO: this is awkward - maybe we should add a non-vectorized API?
-      let mut suffix = vec![ByteArray::new(); 1];
-      let suffix_decoder = self.suffix_decoder.as_mut().unwrap();
-      suffix_decoder.get(&mut suffix[..])?;
+      let prefix_len = prefix_len as usize;
 
-      // Concatenate prefix with suffix
-      let result: Vec<u8> = match prefix_slice {
-        Some(mut prefix) => {
-          prefix.extend_from_slice(suffix[0].data());
-          prefix
+      let start = values.len();
+      if i == 0 {
+        if prefix_len > 0 {
+          let previous = self.previous_value.as_ref().unwrap();
+          values.extend_from_slice(&previous.as_ref()[..prefix_len]);
+        }
+      } else {
+        let previous_start = ranges[i - 1].0;
+        for j in previous_start..previous_start + prefix_len {
+          let b = values[j];
+          values.push(b);
         }
-        None => Vec::from(suffix[0].data())
+      }
+      values.extend_from_slice(suffixes[i].data());
+      ranges.push((start, values.len() - start));
+      self.current_idx += 1;
+    }
+
+    let data = ByteBufferPtr::new(values);
+    for (i, &(start, len)) in ranges.iter().enumerate() {
+      buffer[i].set_data(data.range(start, len));
+    }
+    if let Some(&(start, len)) = ranges.last() {
+      self.previous_value = Some(data.range(start, len));
+    }
+
+    self.num_values -= num_values;
+    Ok(num_values)
+  }
+}
+
+impl ByteArrayDecoder for DeltaByteArrayDecoder<ByteArrayType> {
+  fn get_into(
+    &mut self, buffer: &mut ByteArrayBuffer, max_values: usize
+  ) -> Result<usize> {
+    assert!(self.suffix_decoder.is_some());
+
+    let num_values = cmp::min(max_values, self.num_values);
+    self.suffixes.clear();
+    {
+      let suffix_decoder = self.suffix_decoder.as_mut().unwrap();
+      if suffix_decoder.get_into(&mut self.suffixes, num_values)? < num_values {
+        return Err(eof_err!("Not enough suffixes to decode"));
+      }
+    }
+
+    for i in 0..num_values {
+      let prefix_len = self.prefix_lengths[self.current_idx];
+      let previous_len = if i == 0 {
+        self.previous_value.as_ref().map(|p| p.len()).unwrap_or(0)
+      } else {
+        buffer.value(buffer.len() - 1).len()
       };
+      if prefix_len < 0 || prefix_len as usize > previous_len {
+        return Err(general_err!(
+          "Invalid prefix length {} (previous value length: {})",
+          prefix_len, previous_len));
+      }
+      let prefix_len = prefix_len as usize;
+
+      if i == 0 {
+        let prefix: &[u8] = match self.previous_value {
+          Some(ref previous) => &previous.as_ref()[..prefix_len],
+          None => &[]
+        };
+        buffer.push_concat(prefix, self.suffixes.value(i));
+      } else {
+        buffer.push_with_prefix(prefix_len, self.suffixes.value(i));
+      }
+      self.current_idx += 1;
+    }
 
-      // TODO: can reuse the original vec
-      let data = ByteBufferPtr::new(result);
-      buffer[i].set_data(data.all());
-      self.previous_value = Some(data);
+    // The last value is kept for the prefix of the first value of the next batch
+    if num_values > 0 {
+      let last = buffer.value(buffer.len() - 1).to_vec();
+      self.previous_value = Some(ByteBufferPtr::new(last));
     }
 
     self.num_values -= num_values;
@@ -730,15 +1252,11 @@ mod tests {
 
   #[test]
   fn test_plain_decode_int96() {
-    let v0 = vec![11, 22, 33];
-    let v1 = vec![44, 55, 66];
-    let v2 = vec![10, 20, 30];
-    let v3 = vec![40, 50, 60];
     let mut data = vec![Int96::new(); 4];
-    data[0].set_data(v0);
-    data[1].set_data(v1);
-    data[2].set_data(v2);
-    data[3].set_data(v3);
+    data[0].set_data(11, 22, 33);
+    data[1].set_data(44, 55, 66);
+    data[2].set_data(10, 20, 30);
+    data[3].set_data(40, 50, 60);
     let data_bytes = Int96Type::to_byte_array(&data[..]);
     let mut buffer = vec![Int96::new(); 4];
     test_plain_decode::<Int96Type>(
@@ -914,6 +1432,192 @@ mod tests {
     test_delta_bit_packed_decode::<Int64Type>(data);
   }
 
+  #[test]
+  fn test_plain_decode_byte_array_truncated() {
+    // Length prefix says 10 bytes, but only 3 are available
+    let mut data_bytes = usize_to_bytes(10).to_vec();
+    data_bytes.extend_from_slice(b"abc");
+    let mut decoder: PlainDecoder<ByteArrayType> = PlainDecoder::new(-1);
+    decoder.set_data(ByteBufferPtr::new(data_bytes), 1).expect("ok to set data");
+    let mut buffer = vec![ByteArray::new(); 1];
+    assert!(decoder.get(&mut buffer[..]).is_err());
+
+    // Not enough bytes for the length prefix itself
+    decoder.set_data(ByteBufferPtr::new(vec![1, 0]), 1).expect("ok to set data");
+    assert!(decoder.get(&mut buffer[..]).is_err());
+  }
+
+  #[test]
+  fn test_dict_decode_malformed() {
+    let mut decoder: DictDecoder<Int32Type> = DictDecoder::new();
+    assert!(decoder.set_data(ByteBufferPtr::new(vec![]), 1).is_err());
+    assert!(decoder.set_data(ByteBufferPtr::new(vec![33, 0]), 1).is_err());
+
+    // Dictionary has a single value, but the data refers to index 2
+    let mut dictionary: PlainDecoder<Int32Type> = PlainDecoder::new(-1);
+    dictionary.set_data(ByteBufferPtr::new(Int32Type::to_byte_array(&[7])), 1)
+      .expect("ok to set data");
+    decoder.set_dict(Box::new(dictionary)).expect("ok to set dictionary");
+    // bit width 2, RLE run of 1 value with value 2
+    decoder.set_data(ByteBufferPtr::new(vec![2, 2, 2]), 1).expect("ok to set data");
+    let mut buffer = vec![0; 1];
+    assert!(decoder.get(&mut buffer[..]).is_err());
+  }
+
+  #[test]
+  fn test_delta_bit_packed_malformed_header() {
+    let mut decoder: DeltaBitPackDecoder<Int32Type> = DeltaBitPackDecoder::new();
+    // block size 128, 0 mini blocks
+    let data = ByteBufferPtr::new(vec![128, 1, 0, 1, 0]);
+    assert!(decoder.set_data(data, 1).is_err());
+    // block size 12, 1 mini block: 12 values per mini block is not a multiple of 8
+    let data = ByteBufferPtr::new(vec![12, 1, 1, 0]);
+    assert!(decoder.set_data(data, 1).is_err());
+    // Truncated header
+    let data = ByteBufferPtr::new(vec![128, 1]);
+    assert!(decoder.set_data(data, 1).is_err());
+  }
+
+  #[test]
+  fn test_plain_decode_spaced_int32() {
+    let data_bytes = Int32Type::to_byte_array(&[1, 2, 3, 4]);
+    let mut decoder: PlainDecoder<Int32Type> = PlainDecoder::new(-1);
+    decoder.set_data(ByteBufferPtr::new(data_bytes), 4).expect("ok to set data");
+    test_get_spaced::<Int32Type>(
+      &mut decoder, &[None, Some(1), Some(2), None, None, Some(3)]);
+    test_get_spaced::<Int32Type>(&mut decoder, &[Some(4), None]);
+    assert_eq!(decoder.values_left(), 0);
+  }
+
+  #[test]
+  fn test_plain_decode_spaced_bool() {
+    let data_bytes = BoolType::to_byte_array(&[true, false, true]);
+    let mut decoder: PlainDecoder<BoolType> = PlainDecoder::new(-1);
+    decoder.set_data(ByteBufferPtr::new(data_bytes), 3).expect("ok to set data");
+    test_get_spaced::<BoolType>(
+      &mut decoder, &[Some(true), None, Some(false), None, Some(true)]);
+    assert_eq!(decoder.values_left(), 0);
+  }
+
+  #[test]
+  fn test_plain_decode_spaced_not_enough_values() {
+    let data_bytes = Int32Type::to_byte_array(&[1, 2]);
+    let mut decoder: PlainDecoder<Int32Type> = PlainDecoder::new(-1);
+    decoder.set_data(ByteBufferPtr::new(data_bytes), 2).expect("ok to set data");
+    let mut buffer = vec![0; 4];
+    assert!(decoder.get_spaced(&mut buffer[..], 1, &[0b1101], 0).is_err());
+  }
+
+  #[test]
+  fn test_dict_decode_spaced() {
+    let mut dictionary: PlainDecoder<Int32Type> = PlainDecoder::new(-1);
+    dictionary.set_data(ByteBufferPtr::new(Int32Type::to_byte_array(&[7, 9])), 2)
+      .expect("ok to set data");
+    let mut decoder: DictDecoder<Int32Type> = DictDecoder::new();
+    decoder.set_dict(Box::new(dictionary)).expect("ok to set dictionary");
+    // bit width 1, bit-packed run of 8 values with indices 1, 0, 1
+    decoder.set_data(ByteBufferPtr::new(vec![1, 3, 5]), 3).expect("ok to set data");
+    test_get_spaced::<Int32Type>(&mut decoder, &[None, Some(9), Some(7), None]);
+    test_get_spaced::<Int32Type>(&mut decoder, &[None, Some(9)]);
+    assert_eq!(decoder.values_left(), 0);
+  }
+
+  #[test]
+  fn test_decode_spaced_bitmap_mismatch() {
+    // 3 values to decode into 4 slots with 1 null, but 2 or 4 slots are set
+    let mut buffer = vec![0; 4];
+    let mut decoder: PlainDecoder<Int32Type> = PlainDecoder::new(-1);
+    decoder.set_data(ByteBufferPtr::new(Int32Type::to_byte_array(&[1, 2, 3])), 3)
+      .expect("ok to set data");
+    assert!(decoder.get_spaced(&mut buffer[..], 1, &[0b0011], 0).is_err());
+    assert!(decoder.get_spaced(&mut buffer[..], 1, &[0b1111], 0).is_err());
+
+    let mut dictionary: PlainDecoder<Int32Type> = PlainDecoder::new(-1);
+    dictionary.set_data(ByteBufferPtr::new(Int32Type::to_byte_array(&[7, 9])), 2)
+      .expect("ok to set data");
+    let mut decoder: DictDecoder<Int32Type> = DictDecoder::new();
+    decoder.set_dict(Box::new(dictionary)).expect("ok to set dictionary");
+    decoder.set_data(ByteBufferPtr::new(vec![1, 3, 5]), 3).expect("ok to set data");
+    assert!(decoder.get_spaced(&mut buffer[..], 1, &[0b0011], 0).is_err());
+    decoder.set_data(ByteBufferPtr::new(vec![1, 3, 5]), 3).expect("ok to set data");
+    assert!(decoder.get_spaced(&mut buffer[..], 1, &[0b1111], 0).is_err());
+  }
+
+  #[test]
+  fn test_dict_decode_indices() {
+    let mut dictionary: PlainDecoder<Int32Type> = PlainDecoder::new(-1);
+    dictionary.set_data(ByteBufferPtr::new(Int32Type::to_byte_array(&[7, 9])), 2)
+      .expect("ok to set data");
+    let mut decoder: DictDecoder<Int32Type> = DictDecoder::new();
+    decoder.set_dict(Box::new(dictionary)).expect("ok to set dictionary");
+    assert_eq!(decoder.dictionary(), &[7, 9]);
+    // bit width 1, bit-packed run of 8 values with indices 1, 0, 1
+    decoder.set_data(ByteBufferPtr::new(vec![1, 3, 5]), 3).expect("ok to set data");
+    let mut buffer = vec![0; 2];
+    assert_eq!(decoder.get_indices(&mut buffer[..]).unwrap(), 2);
+    assert_eq!(buffer, vec![1, 0]);
+    assert_eq!(decoder.get_indices(&mut buffer[..]).unwrap(), 1);
+    assert_eq!(buffer[0], 1);
+    assert_eq!(decoder.values_left(), 0);
+
+    // bit width 2, RLE run of 1 value with index 2, out of bounds
+    decoder.set_data(ByteBufferPtr::new(vec![2, 2, 2]), 1).expect("ok to set data");
+    assert!(decoder.get_indices(&mut buffer[..]).is_err());
+  }
+
+  #[test]
+  fn test_delta_bit_packed_decode_spaced() {
+    let data: Vec<i64> = (0..300).map(|i| i * i - 1000).collect();
+    let mut encoder: DeltaBitPackEncoder<Int64Type> = DeltaBitPackEncoder::new();
+    encoder.put(&data[..]).expect("ok to encode");
+    let bytes = encoder.flush_buffer().expect("ok to flush buffer");
+    let mut decoder: DeltaBitPackDecoder<Int64Type> = DeltaBitPackDecoder::new();
+    decoder.set_data(bytes, data.len()).expect("ok to set data");
+
+    // Every third slot is null
+    let mut values = data.iter();
+    let mut expected = Vec::new();
+    while values.len() > 0 {
+      if expected.len() % 3 == 0 {
+        expected.push(None);
+      } else {
+        expected.push(values.next().cloned());
+      }
+    }
+    test_get_spaced::<Int64Type>(&mut decoder, &expected[..200]);
+    test_get_spaced::<Int64Type>(&mut decoder, &expected[200..]);
+    assert_eq!(decoder.values_left(), 0);
+  }
+
+  // Decodes `expected.len()` slots with `get_spaced`, and checks the values of the slots
+  // that are not null. The validity of the slots starts at an offset of the bitmap
+  // that is not a multiple of 8, whose bits before it are set.
+  fn test_get_spaced<T: DataType>(decoder: &mut Decoder<T>, expected: &[Option<T::T>])
+    where T::T: Default + PartialEq + ::std::fmt::Debug {
+    let offset = 5;
+    let mut valid_bits = vec![0; (offset + expected.len() + 7) / 8];
+    let mut null_count = 0;
+    for i in 0..offset {
+      set_array_bit(&mut valid_bits[..], i);
+    }
+    for (i, value) in expected.iter().enumerate() {
+      match *value {
+        Some(_) => set_array_bit(&mut valid_bits[..], offset + i),
+        None => null_count += 1
+      }
+    }
+    let mut buffer = vec![T::T::default(); expected.len()];
+    let num_values = decoder
+      .get_spaced(&mut buffer[..], null_count, &valid_bits[..], offset)
+      .expect("ok to decode");
+    assert_eq!(num_values, expected.len());
+    for (i, value) in expected.iter().enumerate() {
+      if let Some(ref value) = *value {
+        assert_eq!(&buffer[i], value, "slot {}", i);
+      }
+    }
+  }
+
   fn test_plain_decode<T: DataType>(data: ByteBufferPtr,
                                     num_values: usize,
                                     type_length: i32,
@@ -928,6 +1632,136 @@ mod tests {
     assert_eq!(buffer, expected);
   }
 
+  #[test]
+  fn test_delta_byte_array_decode() {
+    let data = delta_byte_array_bytes(
+      &[0, 2, 0, 3], &["axis", "le", "babble", "yhood"]);
+    let mut decoder = DeltaByteArrayDecoder::<ByteArrayType>::new();
+    decoder.set_data(data, 4).unwrap();
+    assert_eq!(decoder.values_left(), 4);
+
+    // The previous value is carried over to the next batch
+    let mut buffer = vec![ByteArray::new(); 3];
+    assert_eq!(decoder.get(&mut buffer[..]).unwrap(), 3);
+    assert_eq!(buffer[0].data(), b"axis");
+    assert_eq!(buffer[1].data(), b"axle");
+    assert_eq!(buffer[2].data(), b"babble");
+    assert_eq!(decoder.get(&mut buffer[..]).unwrap(), 1);
+    assert_eq!(buffer[0].data(), b"babyhood");
+    assert_eq!(decoder.values_left(), 0);
+  }
+
+  #[test]
+  fn test_delta_byte_array_decode_into_buffer() {
+    let data = delta_byte_array_bytes(
+      &[0, 2, 0, 3], &["axis", "le", "babble", "yhood"]);
+    let mut decoder = DeltaByteArrayDecoder::<ByteArrayType>::new();
+    decoder.set_data(data, 4).unwrap();
+
+    // The previous value is carried over to the next batch
+    let mut buffer = ByteArrayBuffer::new();
+    assert_eq!(decoder.get_into(&mut buffer, 3).unwrap(), 3);
+    assert_eq!(buffer.data(), b"axisaxlebabble");
+    assert_eq!(buffer.offsets(), &[0, 4, 8, 14]);
+    buffer.clear();
+    assert_eq!(decoder.get_into(&mut buffer, 3).unwrap(), 1);
+    assert_eq!(buffer.len(), 1);
+    assert_eq!(buffer.value(0), b"babyhood");
+    assert_eq!(decoder.values_left(), 0);
+  }
+
+  #[test]
+  fn test_decode_into_buffer() {
+    let values = vec![ByteArray::from("hello"), ByteArray::from("parquet"),
+                      ByteArray::from(""), ByteArray::from("hello")];
+    let expected = vec![&b"hello"[..], &b"parquet"[..], &b""[..], &b"hello"[..]];
+
+    let mut decoder = PlainDecoder::<ByteArrayType>::new(-1);
+    decoder.set_data(
+      ByteBufferPtr::new(ByteArrayType::to_byte_array(&values[..])), 4).unwrap();
+    test_get_into(&mut decoder, &expected[..]);
+
+    let data = delta_length_byte_array_bytes(&["hello", "parquet", "", "hello"]);
+    let mut decoder = DeltaLengthByteArrayDecoder::<ByteArrayType>::new();
+    decoder.set_data(ByteBufferPtr::new(data), 4).unwrap();
+    test_get_into(&mut decoder, &expected[..]);
+
+    let mut dictionary = PlainDecoder::<ByteArrayType>::new(-1);
+    dictionary.set_data(
+      ByteBufferPtr::new(ByteArrayType::to_byte_array(&values[..3])), 3).unwrap();
+    let mut decoder = DictDecoder::<ByteArrayType>::new();
+    decoder.set_dict(Box::new(dictionary)).unwrap();
+    // bit width 2, bit-packed run of 8 values with indices 0, 1, 2, 0
+    decoder.set_data(ByteBufferPtr::new(vec![2, 3, 0b00100100, 0]), 4).unwrap();
+    test_get_into(&mut decoder, &expected[..]);
+
+    let values = vec![ByteArray::from("bird"), ByteArray::from("come")];
+    let mut decoder = PlainDecoder::<FixedLenByteArrayType>::new(4);
+    let data = FixedLenByteArrayType::to_byte_array(&values[..]);
+    decoder.set_data(ByteBufferPtr::new(data), 2).unwrap();
+    test_get_into(&mut decoder, &[&b"bird"[..], &b"come"[..]]);
+  }
+
+  #[test]
+  fn test_decode_into_buffer_truncated() {
+    let mut decoder = PlainDecoder::<ByteArrayType>::new(-1);
+    // The second value claims 5 bytes, only 2 are left
+    decoder.set_data(ByteBufferPtr::new(vec![1, 0, 0, 0, 1, 5, 0, 0, 0, 1, 2]), 2)
+      .unwrap();
+    let mut buffer = ByteArrayBuffer::new();
+    assert!(decoder.get_into(&mut buffer, 2).is_err());
+  }
+
+  // Decodes all values of `decoder` into a buffer, two at a time, and checks them
+  // against `expected`.
+  fn test_get_into<D: ByteArrayDecoder>(decoder: &mut D, expected: &[&[u8]]) {
+    let mut buffer = ByteArrayBuffer::new();
+    loop {
+      let values_read = decoder.get_into(&mut buffer, 2).expect("get_into() is ok");
+      if values_read < 2 {
+        break;
+      }
+    }
+    assert_eq!(buffer.len(), expected.len());
+    for (i, value) in expected.iter().enumerate() {
+      assert_eq!(buffer.value(i), *value);
+    }
+  }
+
+  #[test]
+  fn test_delta_byte_array_decode_invalid_prefix() {
+    let data = delta_byte_array_bytes(&[0, 5], &["axis", "le"]);
+    let mut decoder = DeltaByteArrayDecoder::<ByteArrayType>::new();
+    decoder.set_data(data, 2).unwrap();
+    let mut buffer = vec![ByteArray::new(); 2];
+    assert!(decoder.get(&mut buffer[..]).is_err());
+  }
+
+  // Encodes values with DELTA_BYTE_ARRAY encoding: the prefix lengths followed by the
+  // suffixes in DELTA_LENGTH_BYTE_ARRAY encoding.
+  fn delta_byte_array_bytes(prefix_lengths: &[i32], suffixes: &[&str]) -> ByteBufferPtr {
+    let mut result = vec!();
+    let mut encoder = DeltaBitPackEncoder::<Int32Type>::new();
+    encoder.put(prefix_lengths).unwrap();
+    result.extend_from_slice(encoder.flush_buffer().unwrap().data());
+    result.extend_from_slice(&delta_length_byte_array_bytes(suffixes)[..]);
+    ByteBufferPtr::new(result)
+  }
+
+  // Encodes values with DELTA_LENGTH_BYTE_ARRAY encoding: the lengths followed by the
+  // concatenated values.
+  fn delta_length_byte_array_bytes(values: &[&str]) -> Vec<u8> {
+    let mut result = vec!();
+    let lengths: Vec<i32> = values.iter().map(|s| s.len() as i32).collect();
+    let mut encoder = DeltaBitPackEncoder::<Int32Type>::new();
+    encoder.put(&lengths[..]).unwrap();
+    result.extend_from_slice(encoder.flush_buffer().unwrap().data());
+    for value in values {
+      result.extend_from_slice(value.as_bytes());
+    }
+    result
+  }
+
   // Data to encode/decode, each vector is mapped to block of values in encoder, e.g.
   // vec![vec![1, 2, 3]] will write single block with 1, 2, 3 values
   fn test_delta_bit_packed_decode<T: DataType>(data: Vec<Vec<T::T>>) {
@@ -125,7 +125,14 @@ default impl<T: DataType> Encoder<T> for PlainEncoder<T> {
 impl Encoder<BoolType> for PlainEncoder<BoolType> {
   fn put(&mut self, values: &[bool]) -> Result<()> {
     for v in values {
-      self.bit_writer.put_value(*v as u64, 1);
+      if !self.bit_writer.put_value(*v as u64, 1) {
+        // The bit writer is full, which happens on a byte boundary: move its content
+        // to the buffer and start over.
+        self.bit_writer.flush();
+        self.buffer.write(&self.bit_writer.buffer()[0..self.bit_writer.byte_offset()])?;
+        self.bit_writer.clear();
+        self.bit_writer.put_value(*v as u64, 1);
+      }
     }
     Ok(())
   }
@@ -219,6 +226,11 @@ default impl<T: DataType> DictEncoder<T> {
     self.uniques.size()
   }
 
+  /// Returns the dictionary values, in the order of their indices.
+  pub fn dictionary(&self) -> &[T::T] {
+    self.uniques.data()
+  }
+
   /// Writes out the dictionary values with PLAIN encoding in a byte buffer, and return
   /// the result.
   #[inline]
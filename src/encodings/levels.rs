@@ -20,7 +20,7 @@ use std::mem;
 
 use basic::Encoding;
 use data_type::AsBytes;
-use errors::{Result, ParquetError};
+use errors::{Result, ParquetError, ErrorContext};
 use util::bit_util::{BitReader, BitWriter, ceil, log2};
 use util::memory::ByteBufferPtr;
 use super::rle_encoding::{RleEncoder, RleDecoder};
@@ -154,15 +154,16 @@ impl LevelDecoder {
   /// This method only initializes level decoder, `set_data()` method must be called
   /// before reading any value.
   ///
-  /// Panics if encoding is not supported
-  pub fn new(encoding: Encoding, max_level: i16) -> Self {
+  /// Returns error if encoding is not supported
+  pub fn new(encoding: Encoding, max_level: i16) -> Result<Self> {
     let bit_width = log2(max_level as u64 + 1) as u8;
     let decoder = match encoding {
       Encoding::RLE => InternalDecoder::RLE(RleDecoder::new(bit_width)),
       Encoding::BIT_PACKED => InternalDecoder::BIT_PACKED(BitReader::from(Vec::new())),
-      _ => panic!("Unsupported encoding type {}", encoding),
+      _ => return Err(unsupported_err!(
+        ErrorContext::new(), "Unsupported level encoding type {}", encoding)),
     };
-    LevelDecoder { bit_width: bit_width, num_values: None, decoder: decoder }
+    Ok(LevelDecoder { bit_width: bit_width, num_values: None, decoder: decoder })
   }
 
   /// Sets data for this level decoder, and returns total number of bytes set.
@@ -172,40 +173,60 @@ impl LevelDecoder {
   ///
   /// Both RLE and BIT_PACKED level decoders set `num_buffered_values` as total number of
   /// values that they can return and track num values.
+  ///
+  /// Returns error if `data` is too short for the encoded levels.
   #[inline]
-  pub fn set_data(&mut self, num_buffered_values: usize, data: ByteBufferPtr) -> usize {
+  pub fn set_data(
+    &mut self, num_buffered_values: usize, data: ByteBufferPtr
+  ) -> Result<usize> {
     self.num_values = Some(num_buffered_values);
     match self.decoder {
       InternalDecoder::RLE(ref mut rle_decoder) => {
         let i32_size = mem::size_of::<i32>();
-        let data_size = read_num_bytes!(i32, i32_size, data.as_ref()) as usize;
+        if data.len() < i32_size {
+          return Err(eof_err!("Not enough bytes to decode the length of levels"));
+        }
+        let data_size = read_num_bytes!(i32, i32_size, data.as_ref());
+        if data_size < 0 || data.len() - i32_size < data_size as usize {
+          return Err(general_err!(
+            "Invalid length of levels: {} (available bytes: {})",
+            data_size, data.len() - i32_size));
+        }
+        let data_size = data_size as usize;
         rle_decoder.set_data(data.range(i32_size, data_size));
-        i32_size + data_size
+        Ok(i32_size + data_size)
       },
       InternalDecoder::BIT_PACKED(ref mut bit_packed_decoder) => {
         // Set appropriate number of bytes: if max size is larger than buffer - set full
         // buffer
         let num_bytes = ceil((num_buffered_values * self.bit_width as usize) as i64, 8);
         let data_size = cmp::min(num_bytes as usize, data.len());
-        bit_packed_decoder.reset(data.range(data.start(), data_size));
-        data_size
+        bit_packed_decoder.reset(data.range(0, data_size));
+        Ok(data_size)
       },
     }
   }
 
   /// Sets byte array explicitly when start position `start` and length `len` are known in
   /// advance. Only supported by RLE level decoder.
-  /// Returns number of total bytes set for this decoder (len)
+  /// Returns number of total bytes set for this decoder (len), or error if the range is
+  /// out of bounds of `data`.
   #[inline]
   pub fn set_data_range(&mut self, num_buffered_values: usize, data: &ByteBufferPtr,
-      start: usize, len: usize) -> usize {
+      start: usize, len: usize) -> Result<usize> {
     match self.decoder {
       InternalDecoder::RLE(ref mut rle_decoder) => {
+        if start > data.len() || data.len() - start < len {
+          return Err(general_err!(
+            "Invalid range of levels: [{}, {}) (available bytes: {})",
+            start, start + len, data.len()));
+        }
         rle_decoder.set_data(data.range(start, len));
         self.num_values = Some(num_buffered_values);
-        len
+        Ok(len)
       },
-      _ => panic!("set_data_range() method is only supported by RLE encoding type"),
+      _ => Err(unsupported_err!(ErrorContext::new(),
+        "set_data_range() method is only supported by RLE encoding type"))
     }
   }
 
@@ -257,8 +278,8 @@ mod tests {
     encoder.put(&levels).expect("put() should be OK");
     let encoded_levels = encoder.consume().expect("consume() should be OK");
 
-    let mut decoder = LevelDecoder::new(enc, max_level);
-    decoder.set_data(levels.len(), ByteBufferPtr::new(encoded_levels));
+    let mut decoder = LevelDecoder::new(enc, max_level).unwrap();
+    decoder.set_data(levels.len(), ByteBufferPtr::new(encoded_levels)).unwrap();
     let mut buffer = vec![0; levels.len()];
     let num_decoded = decoder.get(&mut buffer).expect("get() should be OK");
     assert_eq!(num_decoded, levels.len());
@@ -272,8 +293,8 @@ mod tests {
     encoder.put(&levels).expect("put() should be OK");
     let encoded_levels = encoder.consume().expect("consume() should be OK");
 
-    let mut decoder = LevelDecoder::new(enc, max_level);
-    decoder.set_data(levels.len(), ByteBufferPtr::new(encoded_levels));
+    let mut decoder = LevelDecoder::new(enc, max_level).unwrap();
+    decoder.set_data(levels.len(), ByteBufferPtr::new(encoded_levels)).unwrap();
 
     let mut buffer = vec![0; levels.len() * 2];
     let mut total_decoded = 0;
@@ -302,9 +323,9 @@ mod tests {
     let encoded_levels = encoder.consume().expect("consume() should be OK");
     assert_eq!(num_encoded, 1);
 
-    let mut decoder = LevelDecoder::new(enc, max_level);
+    let mut decoder = LevelDecoder::new(enc, max_level).unwrap();
     // Set one encoded value as `num_buffered_values`
-    decoder.set_data(1, ByteBufferPtr::new(encoded_levels));
+    decoder.set_data(1, ByteBufferPtr::new(encoded_levels)).unwrap();
     let mut buffer = vec![0; levels.len()];
     let num_decoded = decoder.get(&mut buffer).expect("get() should be OK");
     assert_eq!(num_decoded, num_encoded);
@@ -396,16 +417,16 @@ mod tests {
     let buffer = ByteBufferPtr::new(vec![5, 198, 2, 5, 42, 168, 10, 0, 2, 3, 36, 73]);
 
     let max_rep_level = 1;
-    let mut decoder = LevelDecoder::new(Encoding::RLE, max_rep_level);
-    assert_eq!(decoder.set_data_range(10, &buffer, 0, 3), 3);
+    let mut decoder = LevelDecoder::new(Encoding::RLE, max_rep_level).unwrap();
+    assert_eq!(decoder.set_data_range(10, &buffer, 0, 3).unwrap(), 3);
     let mut result = vec![0; 10];
     let num_decoded = decoder.get(&mut result).expect("get() should be OK");
     assert_eq!(num_decoded, 10);
     assert_eq!(result, vec![0, 1, 1, 0, 0, 0, 1, 1, 0, 1]);
 
     let max_def_level = 2;
-    let mut decoder = LevelDecoder::new(Encoding::RLE, max_def_level);
-    assert_eq!(decoder.set_data_range(10, &buffer, 3, 5), 5);
+    let mut decoder = LevelDecoder::new(Encoding::RLE, max_def_level).unwrap();
+    assert_eq!(decoder.set_data_range(10, &buffer, 3, 5).unwrap(), 5);
     let mut result = vec![0; 10];
     let num_decoded = decoder.get(&mut result).expect("get() should be OK");
     assert_eq!(num_decoded, 10);
@@ -413,15 +434,15 @@ mod tests {
   }
 
   #[test]
-  #[should_panic(
-    expected = "set_data_range() method is only supported by RLE encoding type"
-  )]
   fn test_bit_packed_decoder_set_data_range() {
     // Buffer containing both repetition and definition levels
     let buffer = ByteBufferPtr::new(vec![1, 2, 3, 4, 5]);
     let max_level = 1;
-    let mut decoder = LevelDecoder::new(Encoding::BIT_PACKED, max_level);
-    decoder.set_data_range(10, &buffer, 0, 3);
+    let mut decoder = LevelDecoder::new(Encoding::BIT_PACKED, max_level).unwrap();
+    match decoder.set_data_range(10, &buffer, 0, 3) {
+      Err(ParquetError::Unsupported(_, _)) => { },
+      _ => panic!("Expected unsupported encoding error")
+    }
   }
 
   #[test]
@@ -429,11 +450,35 @@ mod tests {
     // Test the maximum size that is assigned based on number of values and buffer length
     let buffer = ByteBufferPtr::new(vec![1, 2, 3, 4, 5]);
     let max_level = 1;
-    let mut decoder = LevelDecoder::new(Encoding::BIT_PACKED, max_level);
+    let mut decoder = LevelDecoder::new(Encoding::BIT_PACKED, max_level).unwrap();
     // This should reset to entire buffer
-    assert_eq!(decoder.set_data(1024, buffer.all()), buffer.len());
+    assert_eq!(decoder.set_data(1024, buffer.all()).unwrap(), buffer.len());
     // This should set smallest num bytes
-    assert_eq!(decoder.set_data(3, buffer.all()), 1);
+    assert_eq!(decoder.set_data(3, buffer.all()).unwrap(), 1);
+  }
+
+  #[test]
+  fn test_level_decoder_unsupported_encoding() {
+    match LevelDecoder::new(Encoding::PLAIN, 1) {
+      Err(ParquetError::Unsupported(_, _)) => { },
+      _ => panic!("Expected unsupported encoding error")
+    }
+    assert!(LevelDecoder::new(Encoding::DELTA_BINARY_PACKED, 1).is_err());
+  }
+
+  #[test]
+  fn test_rle_decoder_set_data_malformed() {
+    let mut decoder = LevelDecoder::new(Encoding::RLE, 1).unwrap();
+    // Not enough bytes for the length prefix
+    assert!(decoder.set_data(10, ByteBufferPtr::new(vec![1, 0])).is_err());
+    // Length prefix larger than the buffer
+    assert!(decoder.set_data(10, ByteBufferPtr::new(vec![10, 0, 0, 0, 1])).is_err());
+    // Negative length prefix
+    assert!(decoder.set_data(10, ByteBufferPtr::new(vec![255, 255, 255, 255])).is_err());
+    // Range out of bounds
+    let buffer = ByteBufferPtr::new(vec![1, 2, 3]);
+    assert!(decoder.set_data_range(10, &buffer, 2, 2).is_err());
+    assert!(decoder.set_data_range(10, &buffer, 4, 0).is_err());
   }
 
   #[test]
@@ -442,7 +487,7 @@ mod tests {
     // `get()` normally panics because bit_reader is not set for RLE decoding
     // we have explicit check now in set_data
     let max_rep_level = 2;
-    let mut decoder = LevelDecoder::new(Encoding::RLE, max_rep_level);
+    let mut decoder = LevelDecoder::new(Encoding::RLE, max_rep_level).unwrap();
     let mut buffer = vec![0; 16];
     decoder.get(&mut buffer).unwrap();
   }
@@ -451,7 +496,7 @@ mod tests {
   #[should_panic(expected = "No data set for decoding")]
   fn test_bit_packed_level_decoder_get_no_set_data() {
     let max_rep_level = 2;
-    let mut decoder = LevelDecoder::new(Encoding::BIT_PACKED, max_rep_level);
+    let mut decoder = LevelDecoder::new(Encoding::BIT_PACKED, max_rep_level).unwrap();
     let mut buffer = vec![0; 16];
     decoder.get(&mut buffer).unwrap();
   }
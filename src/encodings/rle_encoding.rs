@@ -411,6 +411,11 @@ impl RleDecoder {
         assert!(self.current_value.is_some());
         let num_values = cmp::min(max_values - values_read, self.rle_left as usize);
         let dict_idx = self.current_value.unwrap() as usize;
+        if dict_idx >= dict.len() {
+          return Err(general_err!(
+            "Dictionary index {} out of bounds (dictionary size: {})",
+            dict_idx, dict.len()));
+        }
         for i in 0..num_values {
           buffer[values_read + i] = dict[dict_idx].clone();
         }
@@ -422,9 +427,14 @@ impl RleDecoder {
           max_values - values_read, self.bit_packed_left as usize);
         if let Some(ref mut bit_reader) = self.bit_reader {
           for i in 0..num_values {
-            bit_reader.get_value::<i32>(self.bit_width as usize)
-              .map(|v| { buffer[values_read + i] = dict[v as usize].clone(); })
-              .ok_or(eof_err!("Not enough data left"))?;
+            let dict_idx = bit_reader.get_value::<i32>(self.bit_width as usize)
+              .ok_or(eof_err!("Not enough data left"))? as usize;
+            if dict_idx >= dict.len() {
+              return Err(general_err!(
+                "Dictionary index {} out of bounds (dictionary size: {})",
+                dict_idx, dict.len()));
+            }
+            buffer[values_read + i] = dict[dict_idx].clone();
           }
           self.bit_packed_left -= num_values as u32;
           values_read += num_values;
@@ -444,13 +454,19 @@ impl RleDecoder {
     assert!(self.bit_reader.is_some());
     if let Some(ref mut bit_reader) = self.bit_reader {
       if let Some(indicator_value) = bit_reader.get_vlq_int() {
+        if indicator_value < 0 || (indicator_value >> 1) > (u32::max_value() / 8) as i64 {
+          // Malformed input: the run length does not fit into a u32
+          return false;
+        }
         if indicator_value & 1 == 1 {
           self.bit_packed_left = ((indicator_value >> 1) * 8) as u32;
         } else {
           self.rle_left = (indicator_value >> 1) as u32;
           let value_width = bit_util::ceil(self.bit_width as i64, 8);
           self.current_value = bit_reader.get_aligned::<u64>(value_width as usize);
-          assert!(self.current_value.is_some());
+          if self.current_value.is_none() {
+            return false;
+          }
         }
         return true;
       } else {
@@ -18,20 +18,98 @@
 use std::io;
 use std::cell;
 use std::convert;
+use std::error::Error;
+use std::fmt;
 use std::result;
 
+use basic::Compression;
 use thrift;
 use snap;
 
+/// Location in a Parquet file an error refers to. Each part is optional, since not every
+/// component knows the full location: e.g., decoders know nothing about the file, while
+/// page readers know the column, row group and byte offset of the page.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ErrorContext {
+  column: Option<String>,
+  row_group: Option<usize>,
+  offset: Option<u64>
+}
+
+impl ErrorContext {
+  pub fn new() -> Self {
+    Self { column: None, row_group: None, offset: None }
+  }
+
+  /// Sets the (dot-separated) path of the column.
+  pub fn with_column(mut self, column: String) -> Self {
+    self.column = Some(column);
+    self
+  }
+
+  /// Sets the index of the row group.
+  pub fn with_row_group(mut self, row_group: usize) -> Self {
+    self.row_group = Some(row_group);
+    self
+  }
+
+  /// Sets the byte offset in the file.
+  pub fn with_offset(mut self, offset: u64) -> Self {
+    self.offset = Some(offset);
+    self
+  }
+
+  pub fn column(&self) -> Option<&str> {
+    self.column.as_ref().map(|c| c.as_str())
+  }
+
+  pub fn row_group(&self) -> Option<usize> {
+    self.row_group
+  }
+
+  pub fn offset(&self) -> Option<u64> {
+    self.offset
+  }
+
+  /// Fills in the parts of this context that are not set from `other`.
+  fn merge(&mut self, other: &ErrorContext) {
+    if self.column.is_none() {
+      self.column = other.column.clone();
+    }
+    if self.row_group.is_none() {
+      self.row_group = other.row_group;
+    }
+    if self.offset.is_none() {
+      self.offset = other.offset;
+    }
+  }
+}
+
+impl fmt::Display for ErrorContext {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let mut parts = vec!();
+    if let Some(ref column) = self.column {
+      parts.push(format!("column {}", column));
+    }
+    if let Some(row_group) = self.row_group {
+      parts.push(format!("row group {}", row_group));
+    }
+    if let Some(offset) = self.offset {
+      parts.push(format!("offset {}", offset));
+    }
+    if !parts.is_empty() {
+      write!(f, " ({})", parts.join(", "))?;
+    }
+    Ok(())
+  }
+}
+
 quick_error! {
   #[derive(Debug)]
   pub enum ParquetError {
     General(message: String) {
       display("Parquet error: {}", message)
       description(message)
-      from(e: io::Error) -> (format!("underlying IO error: {}", e))
-      from(e: snap::Error) -> (format!("underlying snap error: {}", e))
-      from(e: thrift::Error) -> (format!("underlying Thrift error: {}", e))
       from(e: cell::BorrowMutError) -> (format!("underlying borrow error: {}", e))
     }
     NYI(message: String) {
@@ -42,11 +120,112 @@ quick_error! {
       display("EOF: {}", message)
       description(message)
     }
+    /// The CRC of the page at `context` does not match the checksum stored in its page
+    /// header.
+    Checksum(expected: u32, actual: u32, context: ErrorContext) {
+      display("Checksum mismatch for page{}: expected {:#010x}, found {:#010x}",
+        context, expected, actual)
+      description("page checksum mismatch")
+    }
+    /// File metadata, a page header or the schema is corrupt or inconsistent.
+    CorruptMetadata(message: String, context: ErrorContext) {
+      display("Corrupt metadata{}: {}", context, message)
+      description(message)
+    }
+    /// Thrift-encoded file metadata or page header could not be decoded.
+    Thrift(err: thrift::Error, context: ErrorContext) {
+      display("Corrupt metadata{}: {}", context, err)
+      description("invalid Thrift data")
+      cause(err)
+    }
+    /// The file uses a feature (encoding, codec, type, ...) that is not supported.
+    Unsupported(message: String, context: ErrorContext) {
+      display("Unsupported feature{}: {}", context, message)
+      description(message)
+    }
+    /// Page data could not be decoded.
+    Decode(message: String, context: ErrorContext) {
+      display("Decode error{}: {}", context, message)
+      description(message)
+    }
+    /// Compression codec `codec` failed to compress or decompress data.
+    Codec(codec: Compression, context: ErrorContext, err: Box<Error + Send + Sync>) {
+      display("{} codec error{}: {}", codec, context, err)
+      description("codec error")
+      cause(&**err)
+      from(e: snap::Error) -> (Compression::SNAPPY, ErrorContext::new(), Box::new(e))
+    }
+    /// Data does not match the schema it is read with, e.g. the number of column chunks
+    /// in a row group differs from the number of leaf columns.
+    SchemaMismatch(message: String, context: ErrorContext) {
+      display("Schema mismatch{}: {}", context, message)
+      description(message)
+    }
+    /// An I/O error occurred while reading or writing.
+    Io(err: io::Error, context: ErrorContext) {
+      display("IO error{}: {}", context, err)
+      description(err.description())
+      cause(err)
+      from(e: io::Error) -> (e, ErrorContext::new())
+    }
+  }
+}
+
+impl ParquetError {
+  /// Returns the location this error refers to, or `None` if the error does not carry a
+  /// location.
+  pub fn context(&self) -> Option<&ErrorContext> {
+    match *self {
+      ParquetError::Checksum(_, _, ref context) |
+      ParquetError::CorruptMetadata(_, ref context) |
+      ParquetError::Thrift(_, ref context) |
+      ParquetError::Unsupported(_, ref context) |
+      ParquetError::Decode(_, ref context) |
+      ParquetError::Codec(_, ref context, _) |
+      ParquetError::SchemaMismatch(_, ref context) |
+      ParquetError::Io(_, ref context) => Some(context),
+      _ => None
+    }
+  }
+
+  /// Fills in the parts of the location of this error that are not known yet from
+  /// `context`. Errors that do not carry a location are returned unchanged.
+  pub fn with_context(mut self, context: &ErrorContext) -> Self {
+    match self {
+      ParquetError::Checksum(_, _, ref mut c) |
+      ParquetError::CorruptMetadata(_, ref mut c) |
+      ParquetError::Thrift(_, ref mut c) |
+      ParquetError::Unsupported(_, ref mut c) |
+      ParquetError::Decode(_, ref mut c) |
+      ParquetError::Codec(_, ref mut c, _) |
+      ParquetError::SchemaMismatch(_, ref mut c) |
+      ParquetError::Io(_, ref mut c) => c.merge(context),
+      _ => { }
+    }
+    self
   }
 }
 
 pub type Result<T> = result::Result<T, ParquetError>;
 
+/// Thrift transport errors come from the underlying reader or writer, and are reported
+/// as I/O errors. Other Thrift errors mean that the data could not be decoded.
+impl convert::From<thrift::Error> for ParquetError {
+  fn from(e: thrift::Error) -> Self {
+    match e {
+      thrift::Error::Transport(e) => {
+        let kind = match e.kind {
+          thrift::TransportErrorKind::EndOfFile => io::ErrorKind::UnexpectedEof,
+          thrift::TransportErrorKind::TimedOut => io::ErrorKind::TimedOut,
+          _ => io::ErrorKind::Other
+        };
+        ParquetError::Io(io::Error::new(kind, e), ErrorContext::new())
+      },
+      e => ParquetError::Thrift(e, ErrorContext::new())
+    }
+  }
+}
+
 
 /// Conversion from `ParquetError` TO other types of `Error`s
 
@@ -77,3 +256,94 @@ macro_rules! eof_err {
   ($fmt:expr, $($args:expr),*) => (ParquetError::EOF(format!($fmt, $($args),*)));
 }
 
+macro_rules! corrupt_err {
+  ($ctx:expr, $fmt:expr) => (ParquetError::CorruptMetadata($fmt.to_owned(), $ctx));
+  ($ctx:expr, $fmt:expr, $($args:expr),*) => (
+    ParquetError::CorruptMetadata(format!($fmt, $($args),*), $ctx));
+}
+
+macro_rules! unsupported_err {
+  ($ctx:expr, $fmt:expr) => (ParquetError::Unsupported($fmt.to_owned(), $ctx));
+  ($ctx:expr, $fmt:expr, $($args:expr),*) => (
+    ParquetError::Unsupported(format!($fmt, $($args),*), $ctx));
+}
+
+macro_rules! decode_err {
+  ($ctx:expr, $fmt:expr) => (ParquetError::Decode($fmt.to_owned(), $ctx));
+  ($ctx:expr, $fmt:expr, $($args:expr),*) => (
+    ParquetError::Decode(format!($fmt, $($args),*), $ctx));
+}
+
+macro_rules! schema_err {
+  ($ctx:expr, $fmt:expr) => (ParquetError::SchemaMismatch($fmt.to_owned(), $ctx));
+  ($ctx:expr, $fmt:expr, $($args:expr),*) => (
+    ParquetError::SchemaMismatch(format!($fmt, $($args),*), $ctx));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_error_context_display() {
+    assert_eq!(ErrorContext::new().to_string(), "");
+    let context = ErrorContext::new().with_column("a.b".to_owned()).with_offset(4);
+    assert_eq!(context.to_string(), " (column a.b, offset 4)");
+
+    let err = corrupt_err!(context.with_row_group(1), "bad page header");
+    assert_eq!(
+      err.to_string(),
+      "Corrupt metadata (column a.b, row group 1, offset 4): bad page header");
+  }
+
+  #[test]
+  fn test_error_with_context() {
+    let err = decode_err!(ErrorContext::new().with_offset(10), "not enough bytes")
+      .with_context(&ErrorContext::new().with_column("a".to_owned()).with_offset(0));
+    let context = err.context().unwrap();
+    assert_eq!(context.column(), Some("a"));
+    assert_eq!(context.row_group(), None);
+    // Parts that are already known are not overwritten
+    assert_eq!(context.offset(), Some(10));
+
+    // Errors without a location are left unchanged
+    let err = general_err!("error").with_context(&ErrorContext::new().with_offset(0));
+    assert!(err.context().is_none());
+  }
+
+  #[test]
+  fn test_error_cause() {
+    let io_err = io::Error::new(io::ErrorKind::UnexpectedEof, "unexpected eof");
+    let err = ParquetError::from(io_err);
+    match err {
+      ParquetError::Io(_, _) => { },
+      _ => panic!("Expected IO error")
+    }
+    assert_eq!(err.cause().unwrap().to_string(), "unexpected eof");
+
+    let err = ParquetError::Codec(
+      Compression::GZIP, ErrorContext::new(),
+      Box::new(io::Error::new(io::ErrorKind::InvalidData, "invalid header")));
+    assert_eq!(err.cause().unwrap().to_string(), "invalid header");
+    assert!(general_err!("error").cause().is_none());
+  }
+
+  #[test]
+  fn test_thrift_error_conversion() {
+    let err = ParquetError::from(thrift::Error::Transport(thrift::TransportError::new(
+      thrift::TransportErrorKind::EndOfFile, "end of file")));
+    match err {
+      ParquetError::Io(ref e, _) => assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof),
+      _ => panic!("Expected IO error")
+    }
+    assert!(err.cause().unwrap().to_string().contains("end of file"));
+
+    let err = ParquetError::from(thrift::Error::Protocol(thrift::ProtocolError::new(
+      thrift::ProtocolErrorKind::InvalidData, "invalid data")));
+    match err {
+      ParquetError::Thrift(_, _) => { },
+      _ => panic!("Expected Thrift error")
+    }
+    assert!(err.cause().unwrap().to_string().contains("invalid data"));
+  }
+}
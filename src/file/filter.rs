@@ -0,0 +1,396 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Filtering of column chunks with their dictionary.
+//!
+//! A column chunk whose data pages are all dictionary encoded only contains values of its
+//! dictionary. If no value of the dictionary is equal to the values of an equality or
+//! `IN` predicate, no value of the column chunk matches the predicate, and the row group
+//! can be skipped. Only the dictionary page is read to prove this, which catches cases
+//! where the min/max statistics of the column chunk cannot tell.
+
+use std::cmp::Ordering;
+
+use basic::{LogicalType, Type};
+use column::page::{Page, PageReader};
+use column::reader::decode_dictionary_page;
+use data_type::*;
+use errors::{Result, ParquetError};
+use file::reader::RowGroupReader;
+use schema::types::ColumnDescriptor;
+
+/// The values of the dictionary of a column chunk.
+pub struct Dictionary<T: DataType> {
+  values: Vec<T::T>,
+  is_sorted: bool
+}
+
+impl<T: DataType> Dictionary<T> {
+  /// Creates a dictionary with `values`. If `is_sorted` is set, `values` must be in
+  /// ascending order, comparing integers as signed, floating point numbers by value and
+  /// byte arrays as unsigned bytes.
+  pub fn new(values: Vec<T::T>, is_sorted: bool) -> Self {
+    Self { values: values, is_sorted: is_sorted }
+  }
+
+  /// Returns the values of this dictionary.
+  pub fn values(&self) -> &[T::T] {
+    &self.values[..]
+  }
+
+  /// Whether the values of this dictionary are sorted.
+  pub fn is_sorted(&self) -> bool {
+    self.is_sorted
+  }
+
+  /// Whether this dictionary contains `value`. Uses a binary search if the dictionary is
+  /// sorted and its values can be ordered, and a linear search otherwise.
+  pub fn contains(&self, value: &T::T) -> bool {
+    if self.is_sorted {
+      if let Some(found) = self.binary_search(value) {
+        return found;
+      }
+    }
+    self.values.iter().any(|v| v == value)
+  }
+
+  /// Whether this dictionary contains any of `values`.
+  pub fn contains_any(&self, values: &[T::T]) -> bool {
+    values.iter().any(|value| self.contains(value))
+  }
+
+  // Returns whether `value` is in the sorted values of this dictionary, or `None` if
+  // values could not be compared with it.
+  fn binary_search(&self, value: &T::T) -> Option<bool> {
+    let mut low = 0;
+    let mut high = self.values.len();
+    while low < high {
+      let mid = low + (high - low) / 2;
+      match Self::compare(&self.values[mid], value) {
+        Some(Ordering::Less) => low = mid + 1,
+        Some(Ordering::Greater) => high = mid,
+        Some(Ordering::Equal) => return Some(true),
+        None => return None
+      }
+    }
+    Some(false)
+  }
+}
+
+// Helper trait to compare values in the order of sorted dictionaries
+trait DictionaryOrder<T: DataType> {
+  fn compare(a: &T::T, b: &T::T) -> Option<Ordering>;
+}
+
+default impl<T: DataType> DictionaryOrder<T> for Dictionary<T> {
+  fn compare(_: &T::T, _: &T::T) -> Option<Ordering> {
+    None
+  }
+}
+
+macro_rules! partial_dictionary_order {
+  ($ty:ty, $native_ty:ty) => {
+    impl DictionaryOrder<$ty> for Dictionary<$ty> {
+      fn compare(a: &$native_ty, b: &$native_ty) -> Option<Ordering> {
+        a.partial_cmp(b)
+      }
+    }
+  }
+}
+
+partial_dictionary_order!(BoolType, bool);
+partial_dictionary_order!(Int32Type, i32);
+partial_dictionary_order!(Int64Type, i64);
+partial_dictionary_order!(FloatType, f32);
+partial_dictionary_order!(DoubleType, f64);
+
+impl DictionaryOrder<ByteArrayType> for Dictionary<ByteArrayType> {
+  fn compare(a: &ByteArray, b: &ByteArray) -> Option<Ordering> {
+    Some(a.data().cmp(b.data()))
+  }
+}
+
+impl DictionaryOrder<FixedLenByteArrayType> for Dictionary<FixedLenByteArrayType> {
+  fn compare(a: &ByteArray, b: &ByteArray) -> Option<Ordering> {
+    Some(a.data().cmp(b.data()))
+  }
+}
+
+/// Reads the dictionary of the `i`th column chunk of `row_group`, reading no other page.
+///
+/// Returns `None` if the column chunk has no dictionary, or if some of its data pages
+/// may not be dictionary encoded, see `ColumnChunkMetaData::is_fully_dictionary_encoded`.
+/// The physical type of the column must be that of `T`.
+pub fn read_dictionary<'a, T: DataType>(
+  row_group: &RowGroupReader<'a>,
+  i: usize
+) -> Result<Option<Dictionary<T>>> {
+  let column = row_group.metadata().column(i);
+  if column.column_type() != T::get_physical_type() {
+    return Err(general_err!(
+      "Cannot read dictionary of {} column {} as {}",
+      column.column_type(), column.column_path().string(), T::get_physical_type()));
+  }
+  if !column.is_fully_dictionary_encoded() {
+    return Ok(None);
+  }
+  let mut page_reader = row_group.get_column_page_reader(i)?;
+  read_dictionary_page(column.column_descr(), &mut *page_reader)
+}
+
+/// Returns true if no value of the `i`th column chunk of `row_group` can be equal to
+/// any of `values`, as proven by its dictionary. A single value is an equality
+/// predicate, and several values an `IN` predicate. Nulls never match such predicates.
+///
+/// Returns false if the column chunk has no dictionary to prove it, see
+/// `read_dictionary`.
+pub fn can_skip_column_chunk<'a, T: DataType>(
+  row_group: &RowGroupReader<'a>,
+  i: usize,
+  values: &[T::T]
+) -> Result<bool> {
+  match read_dictionary::<T>(row_group, i)? {
+    Some(dictionary) => Ok(!dictionary.contains_any(values)),
+    None => Ok(false)
+  }
+}
+
+/// Whether `values`, the dictionary of a column chunk of column `descr`, are in strictly
+/// ascending order, so that its dictionary page can be marked as sorted. Only values
+/// that `Dictionary` can search in sorted order are considered sorted.
+pub fn is_sorted_dictionary<T: DataType>(
+  descr: &ColumnDescriptor,
+  values: &[T::T]
+) -> bool {
+  has_natural_order(descr) && values.windows(2).all(|pair| {
+    Dictionary::<T>::compare(&pair[0], &pair[1]) == Some(Ordering::Less)
+  })
+}
+
+/// Reads the dictionary from the first page of `page_reader`, a page reader of a column
+/// chunk of column `descr`. Returns `None` if the first page is not a dictionary page.
+pub fn read_dictionary_page<T: DataType>(
+  descr: &ColumnDescriptor,
+  page_reader: &mut PageReader
+) -> Result<Option<Dictionary<T>>> {
+  let page = match page_reader.get_next_page()? {
+    Some(page) => page,
+    None => return Ok(None)
+  };
+  let is_sorted = match page {
+    Page::DictionaryPage { is_sorted, .. } => is_sorted,
+    _ => return Ok(None)
+  };
+  let values = decode_dictionary_page::<T>(descr, &page)?;
+  let is_sorted = is_sorted && has_natural_order(descr);
+  Ok(Some(Dictionary::new(values, is_sorted)))
+}
+
+// Whether the sort order of the values of column `descr` is that of `DictionaryOrder`.
+// Sorted dictionaries are sorted in the order of the logical type of the column, which
+// differs for unsigned integers, decimals stored as bytes and intervals.
+fn has_natural_order(descr: &ColumnDescriptor) -> bool {
+  match descr.logical_type() {
+    LogicalType::UINT_8 | LogicalType::UINT_16 | LogicalType::UINT_32 |
+      LogicalType::UINT_64 | LogicalType::INTERVAL => false,
+    LogicalType::DECIMAL => match descr.physical_type() {
+      Type::INT32 | Type::INT64 => true,
+      _ => false
+    },
+    _ => true
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::fs::File;
+  use std::rc::Rc;
+
+  use basic::Encoding;
+  use column::writer::get_typed_column_writer_mut;
+  use file::reader::{FileReader, SerializedFileReader};
+  use file::writer::{SerializedFileWriter, WriterOptions};
+  use schema::parser::parse_message_type;
+  use schema::types::{ColumnPath, Type as SchemaType};
+  use util::memory::ByteBufferPtr;
+  use util::test_common::{get_temp_file_path, get_test_file};
+
+  struct TestPageReader {
+    pages: ::std::vec::IntoIter<Page>
+  }
+
+  impl PageReader for TestPageReader {
+    fn get_next_page(&mut self) -> Result<Option<Page>> {
+      Ok(self.pages.next())
+    }
+  }
+
+  fn column_descr(
+    physical_type: Type, logical_type: LogicalType
+  ) -> ColumnDescriptor {
+    let tp = SchemaType::primitive_type_builder("a", physical_type)
+      .with_logical_type(logical_type)
+      .build()
+      .unwrap();
+    ColumnDescriptor::new(Rc::new(tp), None, 1, 0, ColumnPath::new(vec![]))
+  }
+
+  fn dictionary_page(data: Vec<u8>, num_values: u32, is_sorted: bool) -> TestPageReader {
+    let page = Page::DictionaryPage {
+      buf: ByteBufferPtr::new(data),
+      num_values: num_values,
+      encoding: Encoding::PLAIN_DICTIONARY,
+      is_sorted: is_sorted
+    };
+    TestPageReader { pages: vec![page].into_iter() }
+  }
+
+  #[test]
+  fn test_dictionary_contains() {
+    for &is_sorted in &[false, true] {
+      let dictionary: Dictionary<Int32Type> =
+        Dictionary::new(vec![-5, 1, 3, 8], is_sorted);
+      assert!(dictionary.contains(&-5));
+      assert!(dictionary.contains(&8));
+      assert!(!dictionary.contains(&2));
+      assert!(!dictionary.contains(&9));
+      assert!(dictionary.contains_any(&[2, 3]));
+      assert!(!dictionary.contains_any(&[0, 2, 4]));
+      assert!(!dictionary.contains_any(&[]));
+    }
+
+    let dictionary: Dictionary<ByteArrayType> = Dictionary::new(
+      vec![ByteArray::from("a"), ByteArray::from("ab"), ByteArray::from("b")], true);
+    assert!(dictionary.contains(&ByteArray::from("ab")));
+    assert!(!dictionary.contains(&ByteArray::from("aa")));
+
+    // NaN cannot be compared, so the dictionary is searched linearly
+    let dictionary: Dictionary<DoubleType> = Dictionary::new(vec![1.0, 2.0], true);
+    assert!(dictionary.contains(&2.0));
+    assert!(!dictionary.contains(&::std::f64::NAN));
+  }
+
+  #[test]
+  fn test_read_dictionary_page() {
+    let descr = column_descr(Type::INT32, LogicalType::NONE);
+    let data = int32_bytes(&[1, 4, 9]);
+    let mut page_reader = dictionary_page(data, 3, true);
+    let dictionary = read_dictionary_page::<Int32Type>(&descr, &mut page_reader)
+      .unwrap()
+      .unwrap();
+    assert_eq!(dictionary.values(), &[1, 4, 9]);
+    assert!(dictionary.is_sorted());
+
+    // Unsigned integers are not sorted as signed ones
+    let descr = column_descr(Type::INT32, LogicalType::UINT_32);
+    let data = int32_bytes(&[1, -1]);
+    let mut page_reader = dictionary_page(data, 2, true);
+    let dictionary = read_dictionary_page::<Int32Type>(&descr, &mut page_reader)
+      .unwrap()
+      .unwrap();
+    assert!(!dictionary.is_sorted());
+    assert!(dictionary.contains(&-1));
+
+    let mut page_reader = TestPageReader { pages: vec![].into_iter() };
+    let dictionary = read_dictionary_page::<Int32Type>(&descr, &mut page_reader);
+    assert!(dictionary.unwrap().is_none());
+  }
+
+  #[test]
+  fn test_read_dictionary_page_malformed() {
+    let descr = column_descr(Type::INT32, LogicalType::NONE);
+    let mut page_reader = dictionary_page(vec![1, 0, 0, 0], 2, false);
+    assert!(read_dictionary_page::<Int32Type>(&descr, &mut page_reader).is_err());
+    let mut page_reader = dictionary_page(vec![1], 100, false);
+    assert!(read_dictionary_page::<Int32Type>(&descr, &mut page_reader).is_err());
+  }
+
+  #[test]
+  fn test_can_skip_column_chunk() {
+    let reader = SerializedFileReader::new(get_test_file("alltypes_dictionary.parquet"))
+      .unwrap();
+    let row_group = reader.get_row_group(0).unwrap();
+    // The encodings of the column chunks do not tell whether data pages fell back to
+    // plain encoding, so they cannot be skipped
+    assert!(!can_skip_column_chunk::<Int32Type>(&*row_group, 0, &[100]).unwrap());
+    assert!(read_dictionary::<Int32Type>(&*row_group, 0).unwrap().is_none());
+    assert!(read_dictionary::<Int64Type>(&*row_group, 0).is_err());
+  }
+
+  #[test]
+  fn test_can_skip_dictionary_encoded_column_chunk() {
+    let schema = parse_message_type("
+      message schema {
+        REQUIRED INT32 a;
+        REQUIRED BYTE_ARRAY b (UTF8);
+      }
+    ").unwrap();
+    let path = get_temp_file_path("test_can_skip_dictionary_encoded.parquet");
+    let options = WriterOptions::new().with_data_page_size(64).with_dictionary(true);
+    let mut writer =
+      SerializedFileWriter::new(File::create(&path).unwrap(), Rc::new(schema), options)
+      .unwrap();
+    let mut row_group = writer.next_row_group();
+    // Values of `a` are written in ascending order, those of `b` in descending order
+    let a: Vec<i32> = (0..200).map(|i| i / 2 * 3).collect();
+    let b: Vec<ByteArray> =
+      (0..200).map(|i| ByteArray::from(format!("{:03}", 300 - i).as_str())).collect();
+    for batch in a.chunks(10) {
+      get_typed_column_writer_mut::<Int32Type>(row_group.column(0)).unwrap()
+        .write_batch(batch, None, None).unwrap();
+    }
+    for batch in b.chunks(10) {
+      get_typed_column_writer_mut::<ByteArrayType>(row_group.column(1)).unwrap()
+        .write_batch(batch, None, None).unwrap();
+    }
+    writer.write_row_group(row_group).unwrap();
+    writer.close().unwrap();
+
+    let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+    let row_group = reader.get_row_group(0).unwrap();
+    for i in 0..2 {
+      let column = row_group.metadata().column(i);
+      assert!(column.encoding_stats().is_some());
+      assert!(column.is_fully_dictionary_encoded());
+    }
+
+    let dictionary = read_dictionary::<Int32Type>(&*row_group, 0).unwrap().unwrap();
+    assert_eq!(dictionary.values().len(), 100);
+    assert!(dictionary.is_sorted());
+    assert!(!can_skip_column_chunk::<Int32Type>(&*row_group, 0, &[0]).unwrap());
+    assert!(!can_skip_column_chunk::<Int32Type>(&*row_group, 0, &[297]).unwrap());
+    assert!(!can_skip_column_chunk::<Int32Type>(&*row_group, 0, &[1, 150]).unwrap());
+    // Missing values within the range of the column chunk, which min/max statistics
+    // cannot tell apart from present ones
+    assert!(can_skip_column_chunk::<Int32Type>(&*row_group, 0, &[1]).unwrap());
+    assert!(can_skip_column_chunk::<Int32Type>(&*row_group, 0, &[-3, 151, 298]).unwrap());
+
+    let dictionary = read_dictionary::<ByteArrayType>(&*row_group, 1).unwrap().unwrap();
+    assert_eq!(dictionary.values().len(), 200);
+    assert!(!dictionary.is_sorted());
+    let present = [ByteArray::from("250")];
+    assert!(!can_skip_column_chunk::<ByteArrayType>(&*row_group, 1, &present).unwrap());
+    let missing = [ByteArray::from("050"), ByteArray::from("25")];
+    assert!(can_skip_column_chunk::<ByteArrayType>(&*row_group, 1, &missing).unwrap());
+  }
+
+  // Plain encodes `values`
+  fn int32_bytes(values: &[i32]) -> Vec<u8> {
+    values.iter().flat_map(|v| (0..4).map(move |i| (*v >> (8 * i)) as u8)).collect()
+  }
+}
@@ -0,0 +1,274 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Reading and parsing of the Parquet file footer, i.e., the file metadata at the end
+//! of a Parquet file.
+//!
+//! Layout of Parquet file
+//! ```text
+//! +---------------------------+---+-----+
+//! |      Rest of file         | B |  A  |
+//! +---------------------------+---+-----+
+//! ```
+//! where A: parquet footer (metadata length + magic number), B: parquet metadata.
+
+use std::cmp;
+use std::io::{Read, Seek, SeekFrom};
+use std::rc::Rc;
+
+use byteorder::{LittleEndian, ByteOrder};
+use errors::{Result, ParquetError, ErrorContext};
+use file::metadata::{FileMetaData, ParquetMetaData, RowGroupMetaData};
+use file::reader::TMemoryBuffer;
+use parquet_thrift::parquet::FileMetaData as TFileMetaData;
+use schema::types::{self, SchemaDescriptor};
+use thrift::protocol::TCompactInputProtocol;
+
+/// Size of the footer: 4 bytes of metadata length followed by the magic number.
+pub const FOOTER_SIZE: usize = 8;
+
+/// Magic number at the start and at the end of every Parquet file.
+pub const PARQUET_MAGIC: [u8; 4] = [b'P', b'A', b'R', b'1'];
+
+/// Default number of bytes read from the end of a file when reading the metadata. If the
+/// metadata fits, no further read is needed.
+pub const DEFAULT_FOOTER_READ_SIZE: usize = 64 * 1024;
+
+/// Decodes the `FOOTER_SIZE` bytes at the end of a Parquet file, and returns the length
+/// of the metadata that precedes them.
+pub fn decode_footer(footer: &[u8]) -> Result<usize> {
+  if footer.len() != FOOTER_SIZE {
+    return Err(corrupt_err!(ErrorContext::new(),
+      "Invalid footer length: {} (expected {})", footer.len(), FOOTER_SIZE));
+  }
+  if footer[4..] != PARQUET_MAGIC {
+    return Err(corrupt_err!(
+      ErrorContext::new(), "Invalid parquet file. Corrupt footer."));
+  }
+  let metadata_len = LittleEndian::read_i32(&footer[0..4]);
+  if metadata_len < 0 {
+    return Err(corrupt_err!(ErrorContext::new(),
+      "Invalid parquet file. Metadata length is less than zero ({})", metadata_len));
+  }
+  Ok(metadata_len as usize)
+}
+
+/// Parses Thrift-encoded file metadata, i.e., the bytes between the column chunks and
+/// the footer of a Parquet file.
+pub fn parse_metadata(metadata: &[u8]) -> Result<ParquetMetaData> {
+  let mut data = metadata;
+  let transport = TMemoryBuffer::new(&mut data);
+
+  // TODO: row group filtering
+  let mut prot = TCompactInputProtocol::new(transport);
+  let mut t_file_metadata: TFileMetaData =
+    TFileMetaData::read_from_in_protocol(&mut prot)
+    // The metadata is in memory, so even transport errors mean it is truncated
+    .map_err(|e| ParquetError::Thrift(e, ErrorContext::new()))?;
+  let schema = types::from_thrift(&mut t_file_metadata.schema)?;
+  let schema_descr = Rc::new(SchemaDescriptor::new(schema.clone()));
+  let mut row_groups = Vec::new();
+  for (i, rg) in t_file_metadata.row_groups.into_iter().enumerate() {
+    let row_group = RowGroupMetaData::from_thrift(schema_descr.clone(), rg)
+      .map_err(|e| e.with_context(&ErrorContext::new().with_row_group(i)))?;
+    row_groups.push(row_group);
+  }
+
+  let file_metadata = FileMetaData::new(
+    t_file_metadata.version,
+    t_file_metadata.num_rows,
+    t_file_metadata.created_by,
+    t_file_metadata.key_value_metadata,
+    schema,
+    schema_descr);
+  Ok(ParquetMetaData::new(file_metadata, row_groups))
+}
+
+/// Parses the file metadata from `tail`, the last bytes of a Parquet file. `tail` must
+/// end with the footer and contain at least the whole metadata.
+pub fn parse_footer(tail: &[u8]) -> Result<ParquetMetaData> {
+  if tail.len() < FOOTER_SIZE {
+    return Err(corrupt_err!(ErrorContext::new(),
+      "Footer buffer too small ({} bytes)", tail.len()));
+  }
+  let footer_start = tail.len() - FOOTER_SIZE;
+  let metadata_len = decode_footer(&tail[footer_start..])?;
+  if metadata_len > footer_start {
+    return Err(corrupt_err!(ErrorContext::new(),
+      "Footer buffer ({} bytes) does not contain the whole metadata ({} bytes)",
+      tail.len(), metadata_len));
+  }
+  parse_metadata(&tail[footer_start - metadata_len..footer_start])
+}
+
+/// Reads the file metadata of the Parquet file of `file_size` bytes in `reader`.
+///
+/// The last `read_size` bytes of the file are fetched with a single read. Only if the
+/// metadata does not fit into them, the remaining bytes are fetched with a second read.
+pub fn read_metadata<R: Read + Seek>(
+  reader: &mut R, file_size: u64, read_size: usize
+) -> Result<ParquetMetaData> {
+  if file_size < (FOOTER_SIZE as u64) {
+    return Err(corrupt_err!(
+      ErrorContext::new(), "Corrputed file, smaller than file footer"));
+  }
+  let footer_ctx = ErrorContext::new().with_offset(file_size - FOOTER_SIZE as u64);
+
+  // Read the tail of the file, including the footer
+  let read_size = cmp::min(cmp::max(read_size, FOOTER_SIZE) as u64, file_size) as usize;
+  let mut tail = vec![0; read_size];
+  reader.seek(SeekFrom::Start(file_size - read_size as u64))
+    .and_then(|_| reader.read_exact(&mut tail))
+    .map_err(|e| ParquetError::from(e).with_context(&footer_ctx))?;
+
+  let footer_start = read_size - FOOTER_SIZE;
+  let metadata_len = decode_footer(&tail[footer_start..])
+    .map_err(|e| e.with_context(&footer_ctx))?;
+  let metadata_start: i64 = file_size as i64 - FOOTER_SIZE as i64 - metadata_len as i64;
+  if metadata_start < 0 {
+    return Err(corrupt_err!(footer_ctx,
+      "Invalid parquet file. Metadata start is less than zero ({})", metadata_start));
+  }
+  let metadata_ctx = ErrorContext::new().with_offset(metadata_start as u64);
+
+  let result = if metadata_len <= footer_start {
+    // The whole metadata has been read already
+    parse_metadata(&tail[footer_start - metadata_len..footer_start])
+  } else {
+    // Read the part of the metadata preceding the tail
+    let mut metadata = vec![0; metadata_len - footer_start];
+    reader.seek(SeekFrom::Start(metadata_start as u64))
+      .and_then(|_| reader.read_exact(&mut metadata))
+      .map_err(|e| ParquetError::from(e).with_context(&metadata_ctx))?;
+    metadata.extend_from_slice(&tail[..footer_start]);
+    parse_metadata(&metadata)
+  };
+  result.map_err(|e| e.with_context(&metadata_ctx))
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::{self, Cursor};
+  use util::test_common::get_test_file;
+
+  #[test]
+  fn test_decode_footer() {
+    assert_eq!(decode_footer(&[10, 0, 0, 0, b'P', b'A', b'R', b'1']).unwrap(), 10);
+    assert!(decode_footer(&[10, 0, 0, 0, b'P', b'A', b'R', b'2']).is_err());
+    assert!(decode_footer(&[255, 255, 255, 255, b'P', b'A', b'R', b'1']).is_err());
+    assert!(decode_footer(&[b'P', b'A', b'R', b'1']).is_err());
+  }
+
+  #[test]
+  fn test_parse_footer() {
+    let bytes = get_test_file_bytes("alltypes_plain.parquet");
+    let metadata = parse_footer(&bytes).unwrap();
+    assert_eq!(metadata.file_metadata().num_rows(), 8);
+    assert_eq!(metadata.num_row_groups(), 1);
+
+    // Only the tail of the file is needed
+    let metadata_len = decode_footer(&bytes[bytes.len() - FOOTER_SIZE..]).unwrap();
+    let tail = &bytes[bytes.len() - FOOTER_SIZE - metadata_len..];
+    assert_eq!(parse_footer(tail).unwrap().file_metadata().num_rows(), 8);
+
+    // Truncated metadata
+    assert!(parse_footer(&tail[1..]).is_err());
+    assert!(parse_footer(&tail[tail.len() - 4..]).is_err());
+  }
+
+  #[test]
+  fn test_parse_metadata_corrupt() {
+    match parse_metadata(&[1, 2, 3]) {
+      Err(ParquetError::Thrift(_, _)) => { },
+      _ => panic!("Expected Thrift error")
+    }
+  }
+
+  #[test]
+  fn test_read_metadata_single_read() {
+    let bytes = get_test_file_bytes("alltypes_plain.parquet");
+    let file_size = bytes.len() as u64;
+    let mut reader = SeekCounter::new(bytes);
+    let metadata =
+      read_metadata(&mut reader, file_size, DEFAULT_FOOTER_READ_SIZE).unwrap();
+    assert_eq!(metadata.file_metadata().num_rows(), 8);
+    assert_eq!(reader.num_seeks, 1);
+  }
+
+  #[test]
+  fn test_read_metadata_two_reads() {
+    let bytes = get_test_file_bytes("alltypes_plain.parquet");
+    let file_size = bytes.len() as u64;
+    for read_size in vec![0, FOOTER_SIZE, FOOTER_SIZE + 10] {
+      let mut reader = SeekCounter::new(bytes.clone());
+      let metadata = read_metadata(&mut reader, file_size, read_size).unwrap();
+      assert_eq!(metadata.file_metadata().num_rows(), 8);
+      assert_eq!(reader.num_seeks, 2);
+    }
+  }
+
+  #[test]
+  fn test_read_metadata_corrupt_footer() {
+    let mut reader = SeekCounter::new(vec![1, 2, 3]);
+    assert!(read_metadata(&mut reader, 3, DEFAULT_FOOTER_READ_SIZE).is_err());
+
+    let mut bytes = get_test_file_bytes("alltypes_plain.parquet");
+    let len = bytes.len();
+    bytes[len - 1] = b'2';
+    let mut reader = SeekCounter::new(bytes);
+    match read_metadata(&mut reader, len as u64, DEFAULT_FOOTER_READ_SIZE) {
+      Err(ParquetError::CorruptMetadata(_, context)) => {
+        assert_eq!(context.offset(), Some((len - FOOTER_SIZE) as u64));
+      },
+      _ => panic!("Expected corrupt metadata error")
+    }
+  }
+
+  fn get_test_file_bytes(file_name: &str) -> Vec<u8> {
+    let mut bytes = vec!();
+    get_test_file(file_name).read_to_end(&mut bytes).unwrap();
+    bytes
+  }
+
+  // In-memory reader that counts the number of seeks, each of which is followed by a
+  // single read in `read_metadata`.
+  struct SeekCounter {
+    inner: Cursor<Vec<u8>>,
+    num_seeks: usize
+  }
+
+  impl SeekCounter {
+    fn new(data: Vec<u8>) -> Self {
+      Self { inner: Cursor::new(data), num_seeks: 0 }
+    }
+  }
+
+  impl Read for SeekCounter {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+      self.inner.read(buf)
+    }
+  }
+
+  impl Seek for SeekCounter {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+      self.num_seeks += 1;
+      self.inner.seek(pos)
+    }
+  }
+}
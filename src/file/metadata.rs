@@ -16,11 +16,26 @@
 // under the License.
 
 use basic::{Encoding, Type, Compression};
-use errors::{Result, ParquetError};
-use schema::types::{TypePtr, ColumnDescriptor, SchemaDescriptor};
+use errors::{Result, ParquetError, ErrorContext};
+use schema::types::{self, TypePtr, ColumnDescriptor, SchemaDescriptor};
 use schema::types::Type as SchemaType;
 use schema::types::{SchemaDescPtr, ColumnDescPtr, ColumnPath};
-use parquet_thrift::parquet::{ColumnChunk, ColumnMetaData, RowGroup};
+use parquet_thrift::parquet::{ColumnChunk, ColumnMetaData, PageType, RowGroup};
+use parquet_thrift::parquet::FileMetaData as TFileMetaData;
+
+/// Key/value pair of the file metadata, mirrors `parquet::KeyValue`.
+pub use parquet_thrift::parquet::KeyValue;
+
+/// Statistics of a column chunk, mirrors `parquet::Statistics`. Min and max values are
+/// PLAIN encoded, without a length prefix for BYTE_ARRAY columns.
+pub use parquet_thrift::parquet::Statistics;
+
+/// Sort order of a column in a row group, mirrors `parquet::SortingColumn`.
+pub use parquet_thrift::parquet::SortingColumn;
+
+/// Number of pages of a column chunk with a given page type and encoding, mirrors
+/// `parquet::PageEncodingStats`.
+pub use parquet_thrift::parquet::PageEncodingStats;
 
 pub struct ParquetMetaData {
   file_metadata: FileMetaData,
@@ -47,6 +62,20 @@ impl ParquetMetaData {
   pub fn row_groups(&self) -> &[RowGroupMetaData] {
     &self.row_groups.as_slice()
   }
+
+  /// Conversion to Thrift.
+  pub fn to_thrift(&self) -> Result<TFileMetaData> {
+    let file_metadata = &self.file_metadata;
+    let result = TFileMetaData {
+      version: file_metadata.version,
+      schema: types::to_thrift(file_metadata.schema())?,
+      num_rows: file_metadata.num_rows,
+      row_groups: self.row_groups.iter().map(|rg| rg.to_thrift()).collect(),
+      key_value_metadata: file_metadata.key_value_metadata.clone(),
+      created_by: file_metadata.created_by.clone()
+    };
+    Ok(result)
+  }
 }
 
 /// Metadata for a Parquet file
@@ -54,14 +83,18 @@ pub struct FileMetaData {
   version: i32,
   num_rows: i64,
   created_by: Option<String>,
+  key_value_metadata: Option<Vec<KeyValue>>,
   schema: TypePtr,
   schema_descr: SchemaDescPtr
 }
 
 impl FileMetaData {
   pub fn new(version: i32, num_rows: i64, created_by: Option<String>,
+             key_value_metadata: Option<Vec<KeyValue>>,
              schema: TypePtr, schema_descr: SchemaDescPtr) -> Self {
-    FileMetaData { version, num_rows, created_by, schema, schema_descr }
+    FileMetaData {
+      version, num_rows, created_by, key_value_metadata, schema, schema_descr
+    }
   }
 
   pub fn version(&self) -> i32 {
@@ -76,6 +109,11 @@ impl FileMetaData {
     &self.created_by
   }
 
+  /// Application defined key/value metadata, if any
+  pub fn key_value_metadata(&self) -> &Option<Vec<KeyValue>> {
+    &self.key_value_metadata
+  }
+
   pub fn schema(&self) -> &SchemaType {
     self.schema.as_ref()
   }
@@ -90,6 +128,7 @@ pub struct RowGroupMetaData {
   columns: Vec<ColumnChunkMetaData>,
   num_rows: i64,
   total_byte_size: i64,
+  sorting_columns: Option<Vec<SortingColumn>>,
   schema_descr: SchemaDescPtr
 }
 
@@ -114,6 +153,11 @@ impl RowGroupMetaData {
     self.total_byte_size
   }
 
+  /// Columns the rows of this row group are sorted by, if any
+  pub fn sorting_columns(&self) -> Option<&Vec<SortingColumn>> {
+    self.sorting_columns.as_ref()
+  }
+
   pub fn schema_descr(&self) -> &SchemaDescriptor {
     self.schema_descr.as_ref()
   }
@@ -121,14 +165,31 @@ impl RowGroupMetaData {
   pub fn from_thrift(
     schema_descr: SchemaDescPtr, mut rg: RowGroup
   ) -> Result<RowGroupMetaData> {
-    assert_eq!(schema_descr.num_columns(), rg.columns.len());
+    if schema_descr.num_columns() != rg.columns.len() {
+      return Err(schema_err!(ErrorContext::new(),
+        "Number of column chunks in row group ({}) does not match number of \
+        columns in schema ({})", rg.columns.len(), schema_descr.num_columns()));
+    }
     let total_byte_size = rg.total_byte_size;
     let num_rows = rg.num_rows;
+    let sorting_columns = rg.sorting_columns.take();
     let mut columns = vec!();
     for (c, d) in rg.columns.drain(0..).zip(schema_descr.columns()) {
       columns.push(ColumnChunkMetaData::from_thrift(d.clone(), c)?);
     }
-    Ok(RowGroupMetaData{columns, num_rows, total_byte_size, schema_descr})
+    Ok(RowGroupMetaData {
+      columns, num_rows, total_byte_size, sorting_columns, schema_descr
+    })
+  }
+
+  /// Conversion to Thrift
+  pub fn to_thrift(&self) -> RowGroup {
+    RowGroup {
+      columns: self.columns.iter().map(|c| c.to_thrift()).collect(),
+      total_byte_size: self.total_byte_size,
+      num_rows: self.num_rows,
+      sorting_columns: self.sorting_columns.clone()
+    }
   }
 }
 
@@ -146,7 +207,10 @@ pub struct ColumnChunkMetaData {
   total_uncompressed_size: i64,
   data_page_offset: i64,
   index_page_offset: Option<i64>,
-  dictionary_page_offset: Option<i64>
+  dictionary_page_offset: Option<i64>,
+  statistics: Option<Statistics>,
+  encoding_stats: Option<Vec<PageEncodingStats>>,
+  key_value_metadata: Option<Vec<KeyValue>>
 }
 
 /// Represents common operations for a column chunk
@@ -222,17 +286,62 @@ impl ColumnChunkMetaData {
     self.dictionary_page_offset.is_some()
   }
 
-  /// TODO: add statistics
-
   /// Get the offset for the dictionary page, if any
   pub fn dictionary_page_offset(&self) -> Option<i64> {
     self.dictionary_page_offset
   }
 
+  /// Statistics of this column chunk, if written by the writer of the file
+  pub fn statistics(&self) -> Option<&Statistics> {
+    self.statistics.as_ref()
+  }
+
+  /// Application defined key/value metadata of this column chunk, if any
+  pub fn key_value_metadata(&self) -> Option<&Vec<KeyValue>> {
+    self.key_value_metadata.as_ref()
+  }
+
+  /// Number of pages of this column chunk per page type and encoding, if written by the
+  /// writer of the file
+  pub fn encoding_stats(&self) -> Option<&Vec<PageEncodingStats>> {
+    self.encoding_stats.as_ref()
+  }
+
+  /// Whether all data pages of this column chunk are dictionary encoded.
+  ///
+  /// This relies on the page encoding stats if any, and on the encodings otherwise.
+  /// In the latter case, `PLAIN` may be the encoding of the dictionary page as well as
+  /// that of data pages that fell back to plain encoding, so this returns false.
+  pub fn is_fully_dictionary_encoded(&self) -> bool {
+    if !self.has_dictionary_page() {
+      return false;
+    }
+    match self.encoding_stats {
+      Some(ref encoding_stats) => encoding_stats.iter().all(|stats| {
+        match stats.page_type {
+          PageType::DATA_PAGE | PageType::DATA_PAGE_V2 => {
+            let encoding = Encoding::from(stats.encoding);
+            stats.count == 0 || encoding == Encoding::PLAIN_DICTIONARY ||
+              encoding == Encoding::RLE_DICTIONARY
+          },
+          _ => true
+        }
+      }),
+      None => self.encodings.iter().all(|encoding| match *encoding {
+        // RLE and BIT_PACKED are only used for levels
+        Encoding::PLAIN_DICTIONARY | Encoding::RLE_DICTIONARY |
+          Encoding::RLE | Encoding::BIT_PACKED => true,
+        _ => false
+      })
+    }
+  }
+
   /// Conversion from Thrift
   pub fn from_thrift(column_descr: ColumnDescPtr, cc: ColumnChunk) -> Result<Self> {
     if cc.meta_data.is_none() {
-      return Err(general_err!("Expected to have column metadata"));
+      return Err(corrupt_err!(
+        ErrorContext::new().with_column(column_descr.path().string()),
+        "Expected to have column metadata"));
     }
     let mut col_metadata: ColumnMetaData = cc.meta_data.unwrap();
     let column_type = Type::from(col_metadata.type_);
@@ -247,12 +356,187 @@ impl ColumnChunkMetaData {
     let data_page_offset = col_metadata.data_page_offset;
     let index_page_offset = col_metadata.index_page_offset;
     let dictionary_page_offset = col_metadata.dictionary_page_offset;
+    let statistics = col_metadata.statistics;
+    let encoding_stats = col_metadata.encoding_stats;
+    let key_value_metadata = col_metadata.key_value_metadata;
     let result = ColumnChunkMetaData {
       column_type, column_path, column_descr, encodings, file_path,
       file_offset, num_values, compression, total_compressed_size,
       total_uncompressed_size, data_page_offset, index_page_offset,
-      dictionary_page_offset
+      dictionary_page_offset, statistics, encoding_stats, key_value_metadata
     };
     Ok(result)
   }
+
+  /// Conversion to Thrift
+  pub fn to_thrift(&self) -> ColumnChunk {
+    let column_metadata = ColumnMetaData {
+      type_: self.column_type.into(),
+      encodings: self.encodings.iter().map(|&v| v.into()).collect(),
+      path_in_schema: Vec::from(self.column_path.parts()),
+      codec: self.compression.into(),
+      num_values: self.num_values,
+      total_uncompressed_size: self.total_uncompressed_size,
+      total_compressed_size: self.total_compressed_size,
+      key_value_metadata: self.key_value_metadata.clone(),
+      data_page_offset: self.data_page_offset,
+      index_page_offset: self.index_page_offset,
+      dictionary_page_offset: self.dictionary_page_offset,
+      statistics: self.statistics.clone(),
+      encoding_stats: self.encoding_stats.clone()
+    };
+
+    ColumnChunk {
+      file_path: self.file_path.clone(),
+      file_offset: self.file_offset,
+      meta_data: Some(column_metadata)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::Read;
+  use file::footer::{self, FOOTER_SIZE};
+  use file::reader::TMemoryBuffer;
+  use parquet_thrift::parquet::Encoding as TEncoding;
+  use thrift::protocol::{TCompactInputProtocol, TCompactOutputProtocol, TOutputProtocol};
+  use util::test_common::get_test_file;
+
+  #[test]
+  fn test_metadata_thrift_conversion() {
+    let files = vec![
+      "alltypes_dictionary.parquet",
+      "alltypes_plain.parquet",
+      "alltypes_plain.snappy.parquet",
+      "nation.dict-malformed.parquet",
+      "test_datapage_v2.snappy.parquet"
+    ];
+    for file_name in files {
+      let metadata_bytes = read_metadata_bytes(file_name);
+      let expected = {
+        let mut data = &metadata_bytes[..];
+        let mut prot = TCompactInputProtocol::new(TMemoryBuffer::new(&mut data));
+        TFileMetaData::read_from_in_protocol(&mut prot).unwrap()
+      };
+
+      let metadata = footer::parse_metadata(&metadata_bytes).unwrap();
+      let result = metadata.to_thrift().unwrap();
+      // Logical type annotations are derived from converted types
+      let mut converted = result.clone();
+      for (element, original) in converted.schema.iter_mut().zip(&expected.schema) {
+        if original.logical_type.is_none() {
+          element.logical_type = None;
+        }
+      }
+      assert_eq!(converted, expected, "Thrift conversion of {} differs", file_name);
+
+      // Serialized metadata can be parsed again
+      let mut bytes = vec!();
+      {
+        let mut prot = TCompactOutputProtocol::new(&mut bytes);
+        result.write_to_out_protocol(&mut prot).unwrap();
+        prot.flush().unwrap();
+      }
+      let reparsed = footer::parse_metadata(&bytes).unwrap();
+      assert_eq!(reparsed.to_thrift().unwrap(), result);
+    }
+  }
+
+  #[test]
+  fn test_row_group_metadata_thrift_conversion() {
+    let metadata = footer::parse_metadata(
+      &read_metadata_bytes("alltypes_plain.parquet")).unwrap();
+    let row_group = metadata.row_group(0);
+    let schema_descr = metadata.file_metadata().schema_descr.clone();
+
+    let result = RowGroupMetaData::from_thrift(schema_descr, row_group.to_thrift())
+      .unwrap();
+    assert_eq!(result.num_rows(), row_group.num_rows());
+    assert_eq!(result.total_byte_size(), row_group.total_byte_size());
+    assert_eq!(result.num_columns(), row_group.num_columns());
+    for (a, b) in result.columns().iter().zip(row_group.columns()) {
+      assert_eq!(a.column_path(), b.column_path());
+      assert_eq!(a.column_type(), b.column_type());
+      assert_eq!(a.encodings(), b.encodings());
+      assert_eq!(a.compression(), b.compression());
+      assert_eq!(a.num_values(), b.num_values());
+      assert_eq!(a.data_page_offset(), b.data_page_offset());
+      assert_eq!(a.dictionary_page_offset(), b.dictionary_page_offset());
+      assert_eq!(a.statistics(), b.statistics());
+      assert_eq!(a.to_thrift(), b.to_thrift());
+    }
+  }
+
+  #[test]
+  fn test_column_chunk_fully_dictionary_encoded() {
+    let metadata = footer::parse_metadata(
+      &read_metadata_bytes("alltypes_dictionary.parquet")).unwrap();
+    let row_group = metadata.row_group(0);
+    // PLAIN may be the encoding of data pages, and `bool_col` has no dictionary
+    assert!(!row_group.column(0).is_fully_dictionary_encoded());
+    assert!(!row_group.column(1).is_fully_dictionary_encoded());
+
+    let column = row_group.column(0);
+    let column_descr = metadata.file_metadata().schema_descr.column(0);
+    let with_thrift = |update: &Fn(&mut ColumnMetaData)| {
+      let mut cc = column.to_thrift();
+      update(cc.meta_data.as_mut().unwrap());
+      ColumnChunkMetaData::from_thrift(column_descr.clone(), cc).unwrap()
+    };
+    let encodings = with_thrift(&|m| {
+      m.encodings = vec![TEncoding::RLE, TEncoding::PLAIN_DICTIONARY];
+    });
+    assert!(encodings.is_fully_dictionary_encoded());
+
+    let stats = |page_type, encoding, count| {
+      PageEncodingStats { page_type: page_type, encoding: encoding, count: count }
+    };
+    let dictionary_stats = with_thrift(&|m| {
+      m.encoding_stats = Some(vec![
+        stats(PageType::DICTIONARY_PAGE, TEncoding::PLAIN, 1),
+        stats(PageType::DATA_PAGE, TEncoding::PLAIN_DICTIONARY, 2),
+        stats(PageType::DATA_PAGE, TEncoding::PLAIN, 0)]);
+    });
+    assert!(dictionary_stats.is_fully_dictionary_encoded());
+    let fallback_stats = with_thrift(&|m| {
+      m.encodings = vec![TEncoding::RLE, TEncoding::PLAIN_DICTIONARY];
+      m.encoding_stats = Some(vec![
+        stats(PageType::DICTIONARY_PAGE, TEncoding::PLAIN, 1),
+        stats(PageType::DATA_PAGE, TEncoding::PLAIN_DICTIONARY, 2),
+        stats(PageType::DATA_PAGE_V2, TEncoding::PLAIN, 1)]);
+    });
+    assert!(!fallback_stats.is_fully_dictionary_encoded());
+  }
+
+  #[test]
+  fn test_file_metadata_key_value_metadata() {
+    let metadata = footer::parse_metadata(
+      &read_metadata_bytes("alltypes_plain.parquet")).unwrap();
+    let mut t_metadata = metadata.to_thrift().unwrap();
+    t_metadata.key_value_metadata = Some(vec![
+      KeyValue { key: "origin".to_owned(), value: Some("test".to_owned()) }]);
+    t_metadata.row_groups.clear();
+
+    let mut bytes = vec!();
+    {
+      let mut prot = TCompactOutputProtocol::new(&mut bytes);
+      t_metadata.write_to_out_protocol(&mut prot).unwrap();
+      prot.flush().unwrap();
+    }
+    let result = footer::parse_metadata(&bytes).unwrap();
+    assert_eq!(result.num_row_groups(), 0);
+    let key_value_metadata = result.file_metadata().key_value_metadata();
+    assert_eq!(key_value_metadata.as_ref().unwrap()[0].key, "origin");
+    assert_eq!(result.to_thrift().unwrap(), t_metadata);
+  }
+
+  fn read_metadata_bytes(file_name: &str) -> Vec<u8> {
+    let mut bytes = vec!();
+    get_test_file(file_name).read_to_end(&mut bytes).unwrap();
+    let footer_start = bytes.len() - FOOTER_SIZE;
+    let metadata_len = footer::decode_footer(&bytes[footer_start..]).unwrap();
+    bytes[footer_start - metadata_len..footer_start].to_vec()
+  }
 }
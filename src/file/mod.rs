@@ -15,5 +15,8 @@
 // specific language governing permissions and limitations
 // under the License.
 
+pub mod filter;
+pub mod footer;
 pub mod metadata;
 pub mod reader;
+pub mod writer;
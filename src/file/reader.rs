@@ -17,20 +17,21 @@
 
 use std::fs::File;
 use std::io::{self, Read, BufReader, Seek, SeekFrom};
-use std::rc::Rc;
 
 use basic::{Type, Compression, Encoding};
-use errors::{Result, ParquetError};
-use file::metadata::{RowGroupMetaData, FileMetaData, ParquetMetaData};
-use byteorder::{LittleEndian, ByteOrder};
+use errors::{Result, ParquetError, ErrorContext};
+use file::footer::{self, DEFAULT_FOOTER_READ_SIZE};
+use file::metadata::{RowGroupMetaData, ParquetMetaData};
 use thrift::protocol::TCompactInputProtocol;
-use parquet_thrift::parquet::FileMetaData as TFileMetaData;
 use parquet_thrift::parquet::{PageType, PageHeader};
-use schema::types::{self, SchemaDescriptor};
+use record::reader::RowIter;
+use schema::types::{ColumnPath, Type as SchemaType};
+use column::batch::ColumnBatchIter;
 use column::page::{Page, PageReader};
 use column::reader::{ColumnReader, ColumnReaderImpl};
 use compression::{Codec, create_codec};
 use util::memory::ByteBufferPtr;
+use util::hash_util::crc32;
 
 // ----------------------------------------------------------------------
 // APIs for file & row group readers
@@ -49,6 +50,10 @@ pub trait FileReader {
   /// the same as this. Otherwise, the row group metadata stored in the row group reader
   /// may outlive the file reader.
   fn get_row_group<'a>(&'a self, i: usize) -> Result<Box<RowGroupReader<'a> + 'a>>;
+
+  /// Get an iterator over the rows of this file. If `projection` is set, only its
+  /// fields are read; it must be part of the schema of this file.
+  fn get_row_iter(&self, projection: Option<SchemaType>) -> Result<RowIter>;
 }
 
 /// Parquet row group reader API. With this, user can get metadata information about the
@@ -62,11 +67,25 @@ pub trait RowGroupReader<'a> {
   /// Get the total number of column chunks in this row group
   fn num_columns(&self) -> usize;
 
-  /// Get page reader for the `i`th column chunk
-  fn get_column_page_reader<'b>(&'b self, i: usize) -> Result<Box<PageReader + 'b>>;
+  /// Get page reader for the `i`th column chunk. The page reader only depends on the
+  /// metadata of the parent file reader, so it can outlive this row group reader.
+  fn get_column_page_reader(&self, i: usize) -> Result<Box<PageReader + 'a>>;
 
   /// Get value reader for the `i`th column chunk
-  fn get_column_reader(&self, i: usize) -> Result<ColumnReader>;
+  fn get_column_reader(&self, i: usize) -> Result<ColumnReader<'a>>;
+
+  /// Get an iterator over the rows of the `i`th column chunk in batches of at most
+  /// `batch_size` rows, with a slot for every null value, validity bitmaps and list
+  /// offsets, see `column::batch`.
+  fn get_column_batch_iter(
+    &self, i: usize, batch_size: usize
+  ) -> Result<ColumnBatchIter<'a>> {
+    ColumnBatchIter::new(self.get_column_reader(i)?, batch_size)
+  }
+
+  /// Get an iterator over the rows of this row group. If `projection` is set, only its
+  /// fields are read; it must be part of the schema of the file.
+  fn get_row_iter(&self, projection: Option<SchemaType>) -> Result<RowIter<'a>>;
 }
 
 
@@ -86,78 +105,116 @@ impl<'a, T: 'a + Read> Read for TMemoryBuffer<'a, T> {
   }
 }
 
+/// A `Read` wrapper that keeps track of the number of bytes consumed from `inner`.
+struct CountingReader<'a, T> where T: 'a + Read {
+  inner: &'a mut T,
+  bytes_read: usize
+}
+
+impl<'a, T: 'a + Read> CountingReader<'a, T> {
+  fn new(inner: &'a mut T) -> Self { Self { inner: inner, bytes_read: 0 } }
+
+  fn bytes_read(&self) -> usize {
+    self.bytes_read
+  }
+}
+
+impl<'a, T: 'a + Read> Read for CountingReader<'a, T> {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    let bytes_read = self.inner.read(buf)?;
+    self.bytes_read += bytes_read;
+    Ok(bytes_read)
+  }
+}
+
+/// Default maximum size in bytes of a serialized page header. Page headers can be large
+/// because of statistics, but anything beyond this is most likely a corrupt file.
+pub const DEFAULT_MAX_PAGE_HEADER_SIZE: usize = 16 * 1024 * 1024;
+
+/// Options to control the behavior of `SerializedFileReader`.
+/// All options are disabled by default.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReaderOptions {
+  verify_page_checksums: bool,
+  max_page_header_size: usize,
+  footer_read_size: usize
+}
+
+impl ReaderOptions {
+  pub fn new() -> Self {
+    Self {
+      verify_page_checksums: false,
+      max_page_header_size: DEFAULT_MAX_PAGE_HEADER_SIZE,
+      footer_read_size: DEFAULT_FOOTER_READ_SIZE
+    }
+  }
+
+  /// When enabled, the CRC of each page is computed on read and compared against
+  /// `PageHeader.crc`, and a `ParquetError::Checksum` is returned on mismatch.
+  /// Pages without a CRC in their header are not verified.
+  pub fn with_page_checksum_verification(mut self, verify: bool) -> Self {
+    self.verify_page_checksums = verify;
+    self
+  }
+
+  pub fn verify_page_checksums(&self) -> bool {
+    self.verify_page_checksums
+  }
+
+  /// Sets the maximum size in bytes of a page header. Reading a page whose header is
+  /// larger than this fails with an error instead of consuming the rest of the column
+  /// chunk. Defaults to `DEFAULT_MAX_PAGE_HEADER_SIZE`.
+  pub fn with_max_page_header_size(mut self, size: usize) -> Self {
+    self.max_page_header_size = size;
+    self
+  }
+
+  pub fn max_page_header_size(&self) -> usize {
+    self.max_page_header_size
+  }
+
+  /// Sets the number of bytes fetched from the end of the file with a single read when
+  /// opening it. The metadata is only read a second time if it is larger than this.
+  /// Defaults to `DEFAULT_FOOTER_READ_SIZE`.
+  pub fn with_footer_read_size(mut self, size: usize) -> Self {
+    self.footer_read_size = size;
+    self
+  }
+
+  pub fn footer_read_size(&self) -> usize {
+    self.footer_read_size
+  }
+}
+
+impl Default for ReaderOptions {
+  fn default() -> Self { ReaderOptions::new() }
+}
+
 // ----------------------------------------------------------------------
 // Serialized impl for file & row group readers
 
-const FOOTER_SIZE: usize = 8;
-const PARQUET_MAGIC: [u8; 4] = [b'P', b'A', b'R', b'1'];
-
 pub struct SerializedFileReader {
   buf: BufReader<File>,
-  metadata: ParquetMetaData
+  metadata: ParquetMetaData,
+  options: ReaderOptions
 }
 
 impl SerializedFileReader {
   pub fn new(file: File) -> Result<Self> {
+    Self::with_options(file, ReaderOptions::new())
+  }
+
+  /// Creates a file reader for `file` that reads according to `options`.
+  pub fn with_options(file: File, options: ReaderOptions) -> Result<Self> {
     let mut buf = BufReader::new(file);
-    let metadata = Self::parse_metadata(&mut buf)?;
-    Ok(Self { buf: buf, metadata: metadata })
-  }
-
-  //
-  // Layout of Parquet file
-  // +---------------------------+---+-----+
-  // |      Rest of file         | B |  A  |
-  // +---------------------------+---+-----+
-  // where A: parquet footer, B: parquet metadata.
-  //
-  fn parse_metadata(buf: &mut BufReader<File>) -> Result<ParquetMetaData> {
-    let file_metadata = buf.get_ref().metadata()?;
-    let file_size = file_metadata.len();
-    if file_size < (FOOTER_SIZE as u64) {
-      return Err(general_err!("Corrputed file, smaller than file footer"));
-    }
-    let mut footer_buffer: [u8; FOOTER_SIZE] = [0; FOOTER_SIZE];
-    buf.seek(SeekFrom::End(-(FOOTER_SIZE as i64)))?;
-    buf.read_exact(&mut footer_buffer)?;
-    if footer_buffer[4..] != PARQUET_MAGIC {
-      return Err(general_err!("Invalid parquet file. Corrupt footer."));
-    }
-    let metadata_len = LittleEndian::read_i32(&footer_buffer[0..4]) as i64;
-    if metadata_len < 0 {
-      return Err(general_err!(
-        "Invalid parquet file. Metadata length is less than zero ({})",
-        metadata_len));
-    }
-    let metadata_start: i64 = file_size as i64 - FOOTER_SIZE as i64 - metadata_len;
-    if metadata_start < 0 {
-      return Err(general_err!(
-        "Invalid parquet file. Metadata start is less than zero ({})",
-        metadata_start))
-    }
-    buf.seek(SeekFrom::Start(metadata_start as u64))?;
-    let metadata_buf = buf.take(metadata_len as u64).into_inner();
-    let transport = TMemoryBuffer::new(metadata_buf);
-
-    // TODO: row group filtering
-    let mut prot = TCompactInputProtocol::new(transport);
-    let mut t_file_metadata: TFileMetaData =
-      TFileMetaData::read_from_in_protocol(&mut prot)
-      .map_err(|e| ParquetError::General(format!("Could not parse metadata: {}", e)))?;
-    let schema = types::from_thrift(&mut t_file_metadata.schema)?;
-    let schema_descr = Rc::new(SchemaDescriptor::new(schema.clone()));
-    let mut row_groups = Vec::new();
-    for rg in t_file_metadata.row_groups {
-      row_groups.push(RowGroupMetaData::from_thrift(schema_descr.clone(), rg)?);
-    }
+    let metadata = Self::parse_metadata(&mut buf, &options)?;
+    Ok(Self { buf: buf, metadata: metadata, options: options })
+  }
 
-    let file_metadata = FileMetaData::new(
-      t_file_metadata.version,
-      t_file_metadata.num_rows,
-      t_file_metadata.created_by,
-      schema,
-      schema_descr);
-    Ok(ParquetMetaData::new(file_metadata, row_groups))
+  fn parse_metadata(buf: &mut BufReader<File>, options: &ReaderOptions)
+    -> Result<ParquetMetaData> {
+    let file_size = buf.get_ref().metadata()?.len();
+    footer::read_metadata(buf, file_size, options.footer_read_size())
   }
 }
 
@@ -173,7 +230,13 @@ impl FileReader for SerializedFileReader {
   fn get_row_group<'a>(&'a self, i: usize) -> Result<Box<RowGroupReader + 'a>> {
     let row_group_metadata = self.metadata.row_group(i);
     let f = self.buf.get_ref().try_clone()?;
-    Ok(Box::new(SerializedRowGroupReader::new(f, row_group_metadata)))
+    let row_group_reader =
+      SerializedRowGroupReader::new(f, row_group_metadata, self.options).with_index(i);
+    Ok(Box::new(row_group_reader))
+  }
+
+  fn get_row_iter(&self, projection: Option<SchemaType>) -> Result<RowIter> {
+    RowIter::from_file(projection, self)
   }
 }
 
@@ -182,13 +245,21 @@ impl FileReader for SerializedFileReader {
 /// Parquet file reader
 pub struct SerializedRowGroupReader<'a> {
   buf: BufReader<File>,
-  metadata: &'a RowGroupMetaData
+  metadata: &'a RowGroupMetaData,
+  options: ReaderOptions,
+  index: Option<usize>
 }
 
 impl<'a, 'm> SerializedRowGroupReader<'a> {
-  pub fn new(file: File, metadata: &'a RowGroupMetaData) -> Self {
+  pub fn new(file: File, metadata: &'a RowGroupMetaData, options: ReaderOptions) -> Self {
     let buf = BufReader::new(file);
-    Self { buf: buf, metadata: metadata }
+    Self { buf: buf, metadata: metadata, options: options, index: None }
+  }
+
+  /// Sets the index of this row group in the file. Only used for error reporting.
+  pub fn with_index(mut self, index: usize) -> Self {
+    self.index = Some(index);
+    self
   }
 }
 
@@ -202,22 +273,39 @@ impl<'a> RowGroupReader<'a> for SerializedRowGroupReader<'a> {
   }
 
   // TODO: fix PARQUET-816
-  fn get_column_page_reader<'b>(&'b self, i: usize) -> Result<Box<PageReader + 'b>> {
+  fn get_column_page_reader(&self, i: usize) -> Result<Box<PageReader + 'a>> {
     let col = self.metadata.column(i);
-    let mut col_start = col.data_page_offset();
-    if col.has_dictionary_page() {
-      col_start = col.dictionary_page_offset().unwrap();
+    let col_start = match col.dictionary_page_offset() {
+      Some(dict_offset) => dict_offset,
+      None => col.data_page_offset()
+    };
+    let col_length = col.compressed_size();
+    let mut ctx = ErrorContext::new().with_column(col.column_path().string());
+    if let Some(index) = self.index {
+      ctx = ctx.with_row_group(index);
+    }
+    if col_start < 0 || col_length < 0 {
+      return Err(corrupt_err!(ctx,
+        "Invalid column chunk: start offset {}, length {}", col_start, col_length));
     }
-    let col_length = col.compressed_size() as u64;
+    let ctx = ctx.with_offset(col_start as u64);
     let f = self.buf.get_ref().try_clone()?;
     let mut buf = BufReader::new(f);
-    let _ = buf.seek(SeekFrom::Start(col_start as u64));
-    let page_reader = SerializedPageReader::new(
-      buf.take(col_length).into_inner(), col.num_values(), col.compression())?;
+    buf.seek(SeekFrom::Start(col_start as u64)).map_err(|e| ParquetError::from(e)
+      .with_context(&ctx))?;
+    let mut page_reader =
+      SerializedPageReader::new(buf, col.num_values(), col.compression())
+      .map_err(|e| e.with_context(&ctx))?
+      .with_location(col.column_path().clone(), col_start as u64, col_length as u64)
+      .with_checksum_verification(self.options.verify_page_checksums())
+      .with_max_page_header_size(self.options.max_page_header_size());
+    if let Some(index) = self.index {
+      page_reader = page_reader.with_row_group(index);
+    }
     Ok(Box::new(page_reader))
   }
 
-  fn get_column_reader(&self, i: usize) -> Result<ColumnReader> {
+  fn get_column_reader(&self, i: usize) -> Result<ColumnReader<'a>> {
     let schema_descr = self.metadata.schema_descr();
     let col_descr = schema_descr.column(i);
     let col_page_reader = self.get_column_page_reader(i)?;
@@ -241,6 +329,10 @@ impl<'a> RowGroupReader<'a> for SerializedRowGroupReader<'a> {
     };
     Ok(col_reader)
   }
+
+  fn get_row_iter(&self, projection: Option<SchemaType>) -> Result<RowIter<'a>> {
+    RowIter::from_row_group(projection, self)
+  }
 }
 
 
@@ -259,6 +351,24 @@ pub struct SerializedPageReader {
 
   // The number of total values in this column chunk
   total_num_values: i64,
+
+  // The path of the column this page reader reads from. Only used for error reporting.
+  column_path: ColumnPath,
+
+  // The index of the row group of the column chunk. Only used for error reporting.
+  row_group: Option<usize>,
+
+  // The byte offset in the file of the next page to be read
+  offset: u64,
+
+  // The byte offset in the file where this column chunk ends
+  end_offset: u64,
+
+  // Whether to verify page data against the CRC in the page header
+  verify_checksum: bool,
+
+  // The maximum number of bytes a page header is allowed to take
+  max_page_header_size: usize
 }
 
 impl SerializedPageReader {
@@ -267,22 +377,102 @@ impl SerializedPageReader {
     let decompressor = create_codec(compression)?;
     let result =
       Self { buf: buf, total_num_values: total_num_values, seen_num_values: 0,
-             decompressor: decompressor };
+             decompressor: decompressor, column_path: ColumnPath::new(vec!()),
+             row_group: None, offset: 0, end_offset: u64::max_value(),
+             verify_checksum: false, max_page_header_size: DEFAULT_MAX_PAGE_HEADER_SIZE };
     Ok(result)
   }
 
+  /// Sets the path of the column, and the file offset and length of the column chunk
+  /// `buf` is positioned at. Errors refer to the column and the offset of the offending
+  /// page, and pages extending beyond `length` bytes are rejected.
+  pub fn with_location(
+    mut self, column_path: ColumnPath, start_offset: u64, length: u64
+  ) -> Self {
+    self.column_path = column_path;
+    self.offset = start_offset;
+    self.end_offset = start_offset.saturating_add(length);
+    self
+  }
+
+  /// Sets the index of the row group the column chunk belongs to. Only used for error
+  /// reporting.
+  pub fn with_row_group(mut self, row_group: usize) -> Self {
+    self.row_group = Some(row_group);
+    self
+  }
+
+  /// Enables or disables CRC verification of page data.
+  pub fn with_checksum_verification(mut self, verify: bool) -> Self {
+    self.verify_checksum = verify;
+    self
+  }
+
+  /// Sets the maximum size in bytes of a page header.
+  pub fn with_max_page_header_size(mut self, size: usize) -> Self {
+    self.max_page_header_size = size;
+    self
+  }
+
   fn read_page_header(&mut self) -> Result<PageHeader> {
-    let transport = TMemoryBuffer::new(&mut self.buf);
-    let mut prot = TCompactInputProtocol::new(transport);
-    let page_header = PageHeader::read_from_in_protocol(&mut prot)?;
+    let limit = self.max_page_header_size as u64;
+    let (page_header, bytes_read) = {
+      let mut limited = (&mut self.buf).take(limit);
+      let mut reader = CountingReader::new(&mut limited);
+      let page_header = {
+        let transport = TMemoryBuffer::new(&mut reader);
+        let mut prot = TCompactInputProtocol::new(transport);
+        PageHeader::read_from_in_protocol(&mut prot)
+      };
+      (page_header, reader.bytes_read() as u64)
+    };
+    let page_header = match page_header {
+      Ok(header) => header,
+      Err(_) if bytes_read >= limit => {
+        return Err(corrupt_err!(self.context(self.offset),
+          "Page header exceeds the maximum size of {} bytes", limit));
+      },
+      Err(e) => return Err(ParquetError::from(e).with_context(&self.context(self.offset)))
+    };
+    self.offset += bytes_read;
     Ok(page_header)
   }
+
+  // Returns the location of the page at `page_offset`, for error reporting.
+  fn context(&self, page_offset: u64) -> ErrorContext {
+    let mut context = ErrorContext::new().with_offset(page_offset);
+    let column = self.column_path.string();
+    if !column.is_empty() {
+      context = context.with_column(column);
+    }
+    if let Some(row_group) = self.row_group {
+      context = context.with_row_group(row_group);
+    }
+    context
+  }
+
+  // Checks `page_data`, the on-disk bytes of the page at `page_offset`, against the CRC
+  // in the page header, if there is one.
+  fn verify_checksum(
+    &self, page_header: &PageHeader, page_data: &[u8], page_offset: u64
+  ) -> Result<()> {
+    if let Some(expected) = page_header.crc {
+      let actual = crc32(page_data);
+      if actual != expected as u32 {
+        return Err(ParquetError::Checksum(
+          expected as u32, actual, self.context(page_offset)));
+      }
+    }
+    Ok(())
+  }
 }
 
 impl PageReader for SerializedPageReader {
   fn get_next_page(&mut self) -> Result<Option<Page>> {
     while self.seen_num_values < self.total_num_values {
+      let page_offset = self.offset;
       let page_header = self.read_page_header()?;
+      let ctx = self.context(page_offset);
 
       // When processing data page v2, depending on enabled compression for the page, we
       // should account for uncompressed data ('offset') of repetition and definition
@@ -290,31 +480,48 @@ impl PageReader for SerializedPageReader {
       //
       // We always use 0 offset for other pages other than v2, `true` flag means that
       // compression will be applied if decompressor is defined
-      let mut offset: usize = 0;
+      let mut offset: i64 = 0;
       let mut can_decompress = true;
 
       if let Some(ref header_v2) = page_header.data_page_header_v2 {
-        offset = (header_v2.definition_levels_byte_length +
-          header_v2.repetition_levels_byte_length) as usize;
+        offset = header_v2.definition_levels_byte_length as i64 +
+          header_v2.repetition_levels_byte_length as i64;
         // When is_compressed flag is missing the page is considered compressed
         can_decompress = header_v2.is_compressed.unwrap_or(true);
       }
 
-      let compressed_len = page_header.compressed_page_size as usize - offset;
-      let uncompressed_len = page_header.uncompressed_page_size as usize - offset;
+      let compressed_size = page_header.compressed_page_size as i64;
+      let uncompressed_size = page_header.uncompressed_page_size as i64;
+      if offset < 0 || compressed_size < offset || uncompressed_size < offset {
+        return Err(corrupt_err!(ctx,
+          "Invalid page sizes: compressed {}, uncompressed {}, levels {}",
+          compressed_size, uncompressed_size, offset));
+      }
+      if self.offset + compressed_size as u64 > self.end_offset {
+        return Err(corrupt_err!(ctx, "Page extends beyond the end of the column chunk"));
+      }
+      let offset = offset as usize;
+      let compressed_len = compressed_size as usize - offset;
+      let uncompressed_len = uncompressed_size as usize - offset;
       // We still need to read all bytes from buffered stream
       let mut buffer = vec![0; offset + compressed_len];
-      self.buf.read_exact(&mut buffer)?;
+      self.buf.read_exact(&mut buffer)
+        .map_err(|e| ParquetError::from(e).with_context(&ctx))?;
+      self.offset += buffer.len() as u64;
+
+      // The checksum covers the page as stored on disk, i.e., before decompression
+      if self.verify_checksum {
+        self.verify_checksum(&page_header, &buffer, page_offset)?;
+      }
 
-      // TODO: page header could be huge because of statistics. We should set a maximum
-      // page header size and abort if that is exceeded.
       if let Some(decompressor) = self.decompressor.as_mut() {
         if can_decompress {
           let mut decompressed_buffer = vec!();
           let decompressed_size =
-            decompressor.decompress(&buffer[offset..], &mut decompressed_buffer)?;
+            decompressor.decompress(&buffer[offset..], &mut decompressed_buffer)
+            .map_err(|e| e.with_context(&ctx))?;
           if decompressed_size != uncompressed_len {
-            return Err(general_err!("Actual decompressed size doesn't \
+            return Err(decode_err!(ctx, "Actual decompressed size doesn't \
               match the expected one ({} vs {})", decompressed_size, uncompressed_len));
           }
           if offset == 0 {
@@ -330,8 +537,8 @@ impl PageReader for SerializedPageReader {
       // TODO: process statistics
       let result = match page_header.type_ {
         PageType::DICTIONARY_PAGE => {
-          assert!(page_header.dictionary_page_header.is_some());
-          let dict_header = page_header.dictionary_page_header.as_ref().unwrap();
+          let dict_header = page_header.dictionary_page_header.as_ref().ok_or(
+            corrupt_err!(ctx.clone(), "Missing dictionary page header"))?;
           let is_sorted = dict_header.is_sorted.unwrap_or(false);
           Page::DictionaryPage {
             buf: ByteBufferPtr::new(buffer), num_values: dict_header.num_values as u32,
@@ -339,8 +546,12 @@ impl PageReader for SerializedPageReader {
           }
         },
         PageType::DATA_PAGE => {
-          assert!(page_header.data_page_header.is_some());
-          let header = page_header.data_page_header.as_ref().unwrap();
+          let header = page_header.data_page_header.as_ref().ok_or(
+            corrupt_err!(ctx.clone(), "Missing data page header"))?;
+          if header.num_values < 0 {
+            return Err(corrupt_err!(ctx,
+              "Invalid number of values in page: {}", header.num_values));
+          }
           self.seen_num_values += header.num_values as i64;
           Page::DataPage {
             buf: ByteBufferPtr::new(buffer), num_values: header.num_values as u32,
@@ -350,9 +561,13 @@ impl PageReader for SerializedPageReader {
           }
         },
         PageType::DATA_PAGE_V2 => {
-          assert!(page_header.data_page_header_v2.is_some());
-          let header = page_header.data_page_header_v2.as_ref().unwrap();
+          let header = page_header.data_page_header_v2.as_ref().ok_or(
+            corrupt_err!(ctx.clone(), "Missing data page v2 header"))?;
           let is_compressed = header.is_compressed.unwrap_or(true);
+          if header.num_values < 0 {
+            return Err(corrupt_err!(ctx,
+              "Invalid number of values in page: {}", header.num_values));
+          }
           self.seen_num_values += header.num_values as i64;
           Page::DataPageV2 {
             buf: ByteBufferPtr::new(buffer), num_values: header.num_values as u32,
@@ -382,6 +597,15 @@ mod tests {
   use super::*;
   use std::fs;
   use std::env;
+  use std::io::Write;
+  use thrift::protocol::{TCompactOutputProtocol, TOutputProtocol};
+  use parquet_thrift::parquet::DataPageHeader;
+  use parquet_thrift::parquet::Encoding as TEncoding;
+  use rand::{Rng, SeedableRng, StdRng};
+  use data_type::DataType;
+  use util::test_common::get_test_file;
+  use byteorder::{LittleEndian, ByteOrder};
+  use file::footer::{FOOTER_SIZE, PARQUET_MAGIC};
 
   #[test]
   fn test_file_reader() {
@@ -507,12 +731,215 @@ mod tests {
     assert_eq!(page_count, 2);
   }
 
-  fn get_test_file<'a>(file_name: &str) -> fs::File {
-    let mut path_buf = env::current_dir().unwrap();
-    path_buf.push("data");
+  #[test]
+  fn test_page_reader_checksum() {
+    let data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+    let crc = crc32(&data) as i32;
+
+    // Correct checksum, with verification
+    let file = write_test_page("page_crc_ok.bin", &data, Some(crc));
+    let mut page_reader = SerializedPageReader::new(
+      BufReader::new(file), 1, Compression::UNCOMPRESSED).unwrap()
+      .with_location(ColumnPath::from("a"), 0, 1024)
+      .with_checksum_verification(true);
+    let page = page_reader.get_next_page().unwrap().unwrap();
+    assert_eq!(page.buffer().data(), &data[..]);
+
+    // No checksum in page header, with verification
+    let file = write_test_page("page_crc_none.bin", &data, None);
+    let mut page_reader = SerializedPageReader::new(
+      BufReader::new(file), 1, Compression::UNCOMPRESSED).unwrap()
+      .with_checksum_verification(true);
+    assert!(page_reader.get_next_page().unwrap().is_some());
+
+    // Wrong checksum, without verification
+    let file = write_test_page("page_crc_bad_noverify.bin", &data, Some(crc + 1));
+    let mut page_reader = SerializedPageReader::new(
+      BufReader::new(file), 1, Compression::UNCOMPRESSED).unwrap();
+    assert!(page_reader.get_next_page().unwrap().is_some());
+
+    // Wrong checksum, with verification
+    let file = write_test_page("page_crc_bad.bin", &data, Some(crc + 1));
+    let mut page_reader = SerializedPageReader::new(
+      BufReader::new(file), 1, Compression::UNCOMPRESSED).unwrap()
+      .with_location(ColumnPath::from("a"), 4, 1024)
+      .with_row_group(1)
+      .with_checksum_verification(true);
+    match page_reader.get_next_page() {
+      Err(ParquetError::Checksum(expected, actual, context)) => {
+        assert_eq!(context.column(), Some("a"));
+        assert_eq!(context.row_group(), Some(1));
+        assert_eq!(context.offset(), Some(4));
+        assert_eq!(expected, (crc + 1) as u32);
+        assert_eq!(actual, crc as u32);
+      },
+      _ => panic!("Expected checksum mismatch")
+    }
+  }
+
+  #[test]
+  fn test_file_reader_with_checksum_verification() {
+    // The test files do not carry page checksums, so verification should be a no-op
+    let test_file = get_test_file("alltypes_plain.parquet");
+    let options = ReaderOptions::new().with_page_checksum_verification(true);
+    let reader = SerializedFileReader::with_options(test_file, options).unwrap();
+    let row_group_reader = reader.get_row_group(0).unwrap();
+    for i in 0..row_group_reader.num_columns() {
+      let mut page_reader = row_group_reader.get_column_page_reader(i).unwrap();
+      while let Some(_) = page_reader.get_next_page().unwrap() { }
+    }
+  }
+
+  #[test]
+  fn test_page_reader_max_page_header_size() {
+    let data = vec![1, 2, 3, 4];
+    let file = write_test_page("page_header_limit.bin", &data, None);
+    let mut page_reader = SerializedPageReader::new(
+      BufReader::new(file), 1, Compression::UNCOMPRESSED).unwrap()
+      .with_max_page_header_size(4);
+    match page_reader.get_next_page() {
+      Err(e) => assert!(format!("{}", e).contains("exceeds the maximum size")),
+      Ok(_) => panic!("Expected page header size to be exceeded")
+    }
+
+    let file = write_test_page("page_header_no_limit.bin", &data, None);
+    let mut page_reader = SerializedPageReader::new(
+      BufReader::new(file), 1, Compression::UNCOMPRESSED).unwrap();
+    assert!(page_reader.get_next_page().unwrap().is_some());
+  }
+
+  #[test]
+  fn test_page_reader_page_beyond_column_chunk() {
+    let data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+    let file = write_test_page("page_beyond_chunk.bin", &data, None);
+    let mut page_reader = SerializedPageReader::new(
+      BufReader::new(file), 1, Compression::UNCOMPRESSED).unwrap()
+      .with_location(ColumnPath::from("a"), 0, 10)
+      .with_row_group(2);
+    match page_reader.get_next_page() {
+      Err(ParquetError::CorruptMetadata(_, context)) => {
+        assert_eq!(context.column(), Some("a"));
+        assert_eq!(context.row_group(), Some(2));
+        assert_eq!(context.offset(), Some(0));
+      },
+      _ => panic!("Expected corrupt metadata error")
+    }
+  }
+
+  #[test]
+  fn test_file_reader_malformed_dictionary() {
+    let test_file = get_test_file("nation.dict-malformed.parquet");
+    match read_all_columns(test_file) {
+      Err(ParquetError::CorruptMetadata(_, ref context)) |
+      Err(ParquetError::Decode(_, ref context)) => assert!(context.column().is_some()),
+      other => panic!("Expected a corrupt dictionary error, got {:?}", other)
+    }
+  }
+
+  #[test]
+  fn test_file_reader_corrupted_pages() {
+    // Reading should fail gracefully (or succeed), but never panic
+    test_read_corrupted_column_chunks("alltypes_plain.parquet", 42);
+    test_read_corrupted_column_chunks("nation.dict-malformed.parquet", 816);
+  }
+
+  // Reads 200 copies of test file `file_name` with random bytes of its column chunks
+  // changed, with random numbers seeded by `seed`.
+  fn test_read_corrupted_column_chunks(file_name: &str, seed: usize) {
+    let mut bytes = vec!();
+    get_test_file(file_name).read_to_end(&mut bytes).unwrap();
+
+    // Only corrupt column chunks, i.e. the bytes between the leading magic number and
+    // the file metadata
+    let footer_start = bytes.len() - FOOTER_SIZE;
+    let metadata_len =
+      LittleEndian::read_i32(&bytes[footer_start..footer_start + 4]) as usize;
+    let data_start = PARQUET_MAGIC.len();
+    let data_end = footer_start - metadata_len;
+
+    let mut path_buf = env::temp_dir();
+    path_buf.push(format!("corrupted_{}", file_name));
+    let mut rng = StdRng::from_seed(&[seed][..]);
+    for _ in 0..200 {
+      let mut corrupted = bytes.clone();
+      let num_mutations = rng.gen_range(1, 5);
+      for _ in 0..num_mutations {
+        let pos = rng.gen_range(data_start, data_end);
+        corrupted[pos] = rng.gen::<u8>();
+      }
+      File::create(path_buf.as_path()).unwrap().write_all(&corrupted).unwrap();
+      let _ = read_all_columns(File::open(path_buf.as_path()).unwrap());
+    }
+  }
+
+  // Reads all values of every column in `file`, stopping at the first error.
+  fn read_all_columns(file: File) -> Result<()> {
+    let reader = SerializedFileReader::new(file)?;
+    for i in 0..reader.num_row_groups() {
+      let row_group_reader = reader.get_row_group(i)?;
+      for j in 0..row_group_reader.num_columns() {
+        match row_group_reader.get_column_reader(j)? {
+          ColumnReader::BoolColumnReader(mut r) => read_column(&mut r)?,
+          ColumnReader::Int32ColumnReader(mut r) => read_column(&mut r)?,
+          ColumnReader::Int64ColumnReader(mut r) => read_column(&mut r)?,
+          ColumnReader::Int96ColumnReader(mut r) => read_column(&mut r)?,
+          ColumnReader::FloatColumnReader(mut r) => read_column(&mut r)?,
+          ColumnReader::DoubleColumnReader(mut r) => read_column(&mut r)?,
+          ColumnReader::ByteArrayColumnReader(mut r) => read_column(&mut r)?,
+          ColumnReader::FixedLenByteArrayColumnReader(mut r) => read_column(&mut r)?
+        }
+      }
+    }
+    Ok(())
+  }
+
+  fn read_column<T: DataType>(reader: &mut ColumnReaderImpl<T>) -> Result<()>
+      where T: 'static {
+    let batch_size = 16;
+    let mut def_levels = vec![0; batch_size];
+    let mut rep_levels = vec![0; batch_size];
+    // `read_batch` may decode up to `2 * batch_size` values
+    let mut values = vec![T::T::default(); 2 * batch_size];
+    loop {
+      let (values_read, levels_read) = reader.read_batch(
+        batch_size, Some(&mut def_levels), Some(&mut rep_levels), &mut values)?;
+      if values_read == 0 && levels_read == 0 {
+        return Ok(());
+      }
+    }
+  }
+
+  // Writes a single data page with `data` as content and `crc` in its header to a
+  // temporary file, and returns the file opened for reading.
+  fn write_test_page(file_name: &str, data: &[u8], crc: Option<i32>) -> fs::File {
+    let page_header = PageHeader {
+      type_: PageType::DATA_PAGE,
+      uncompressed_page_size: data.len() as i32,
+      compressed_page_size: data.len() as i32,
+      crc: crc,
+      data_page_header: Some(DataPageHeader {
+        num_values: 1,
+        encoding: TEncoding::PLAIN,
+        definition_level_encoding: TEncoding::RLE,
+        repetition_level_encoding: TEncoding::RLE,
+        statistics: None
+      }),
+      index_page_header: None,
+      dictionary_page_header: None,
+      data_page_header_v2: None
+    };
+    let mut bytes = vec!();
+    {
+      let mut prot = TCompactOutputProtocol::new(&mut bytes);
+      page_header.write_to_out_protocol(&mut prot).unwrap();
+      prot.flush().unwrap();
+    }
+    bytes.extend_from_slice(data);
+
+    let mut path_buf = env::temp_dir();
     path_buf.push(file_name);
-    let file = File::open(path_buf.as_path());
-    assert!(file.is_ok());
-    file.unwrap()
+    let mut file = File::create(path_buf.as_path()).unwrap();
+    file.write_all(&bytes).unwrap();
+    File::open(path_buf.as_path()).unwrap()
   }
 }
@@ -0,0 +1,596 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Writing of Parquet files.
+//!
+//! A file is written one row group at a time: the column writers of a `RowGroupWriter`
+//! obtained from `SerializedFileWriter::next_row_group` are filled with values, and the
+//! row group is then passed back to `write_row_group`. `close` writes the file
+//! metadata and the footer.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::rc::Rc;
+
+use byteorder::{LittleEndian, WriteBytesExt};
+use basic::{Compression, Encoding};
+use column::page::Page;
+use column::writer::{get_column_writer, ColumnChunkPages, ColumnWriter};
+use compression::{create_codec, Codec};
+use errors::{ErrorContext, ParquetError, Result};
+use file::footer::PARQUET_MAGIC;
+use parquet_thrift::parquet::{
+  ColumnChunk, ColumnMetaData, DataPageHeader, DataPageHeaderV2, DictionaryPageHeader,
+  Encoding as TEncoding, FileMetaData as TFileMetaData, PageEncodingStats, PageHeader,
+  PageType as TPageType, RowGroup
+};
+use schema::types::{self, ColumnDescriptor, SchemaDescPtr, SchemaDescriptor, TypePtr};
+use thrift::protocol::{TCompactOutputProtocol, TOutputProtocol};
+use util::hash_util::crc32;
+
+/// Default maximum size in bytes of the uncompressed values of a data page.
+pub const DEFAULT_DATA_PAGE_SIZE: usize = 1024 * 1024;
+
+/// Options to control the behavior of `SerializedFileWriter`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WriterOptions {
+  data_page_size: usize,
+  compression: Compression,
+  created_by: String,
+  write_page_checksums: bool,
+  dictionary_enabled: bool
+}
+
+impl WriterOptions {
+  pub fn new() -> Self {
+    Self {
+      data_page_size: DEFAULT_DATA_PAGE_SIZE,
+      compression: Compression::UNCOMPRESSED,
+      created_by: format!("parquet-rs version {}", env!("CARGO_PKG_VERSION")),
+      write_page_checksums: false,
+      dictionary_enabled: false
+    }
+  }
+
+  /// Sets the size in bytes of the values of a data page, before encoding and
+  /// compression, after which a new page is started. Defaults to
+  /// `DEFAULT_DATA_PAGE_SIZE`.
+  pub fn with_data_page_size(mut self, size: usize) -> Self {
+    self.data_page_size = size;
+    self
+  }
+
+  pub fn data_page_size(&self) -> usize {
+    self.data_page_size
+  }
+
+  /// Sets the codec all pages are compressed with. Defaults to no compression.
+  pub fn with_compression(mut self, compression: Compression) -> Self {
+    self.compression = compression;
+    self
+  }
+
+  pub fn compression(&self) -> Compression {
+    self.compression
+  }
+
+  /// Sets the application that writes the file, which is stored in the file metadata.
+  pub fn with_created_by(mut self, created_by: String) -> Self {
+    self.created_by = created_by;
+    self
+  }
+
+  pub fn created_by(&self) -> &str {
+    &self.created_by
+  }
+
+  /// When enabled, the CRC of each page, as written to the file, is stored in
+  /// `PageHeader.crc`, so that readers can verify it. Disabled by default.
+  pub fn with_page_checksums(mut self, write: bool) -> Self {
+    self.write_page_checksums = write;
+    self
+  }
+
+  pub fn write_page_checksums(&self) -> bool {
+    self.write_page_checksums
+  }
+
+  /// When enabled, the values of each column chunk, except those of BOOLEAN columns, are
+  /// dictionary encoded: the column chunk starts with a dictionary page of its distinct
+  /// values, and its data pages only hold indices into it. Data pages never fall back
+  /// to plain encoding, however large the dictionary. Disabled by default.
+  pub fn with_dictionary(mut self, enabled: bool) -> Self {
+    self.dictionary_enabled = enabled;
+    self
+  }
+
+  pub fn dictionary_enabled(&self) -> bool {
+    self.dictionary_enabled
+  }
+}
+
+impl Default for WriterOptions {
+  fn default() -> Self { WriterOptions::new() }
+}
+
+/// The column writers of a row group of a file being written.
+pub struct RowGroupWriter {
+  descr: SchemaDescPtr,
+  columns: Vec<ColumnWriter>
+}
+
+impl RowGroupWriter {
+  fn new(descr: SchemaDescPtr, options: &WriterOptions) -> Self {
+    let columns = (0..descr.num_columns())
+      .map(|i| get_column_writer(descr.column(i), options))
+      .collect();
+    Self { descr: descr, columns: columns }
+  }
+
+  pub fn schema_descr(&self) -> &SchemaDescriptor {
+    self.descr.as_ref()
+  }
+
+  /// Get the total number of columns in this row group
+  pub fn num_columns(&self) -> usize {
+    self.columns.len()
+  }
+
+  /// Get the writer of the `i`th column of this row group
+  pub fn column(&mut self, i: usize) -> &mut ColumnWriter {
+    &mut self.columns[i]
+  }
+}
+
+/// A writer of a Parquet file to a `File`.
+pub struct SerializedFileWriter {
+  buf: BufWriter<File>,
+  // The number of bytes written so far
+  offset: u64,
+  schema: TypePtr,
+  descr: SchemaDescPtr,
+  options: WriterOptions,
+  row_groups: Vec<RowGroup>,
+  num_rows: i64
+}
+
+impl SerializedFileWriter {
+  /// Creates a writer of a file with `schema`, whose root must be a group.
+  pub fn new(file: File, schema: TypePtr, options: WriterOptions) -> Result<Self> {
+    if !schema.is_group() {
+      return Err(schema_err!(ErrorContext::new(),
+        "The root of the schema of a file must be a group, found {}", schema.name()));
+    }
+    let descr = Rc::new(SchemaDescriptor::new(schema.clone()));
+    let mut buf = BufWriter::new(file);
+    buf.write_all(&PARQUET_MAGIC)?;
+    Ok(Self {
+      buf: buf,
+      offset: PARQUET_MAGIC.len() as u64,
+      schema: schema,
+      descr: descr,
+      options: options,
+      row_groups: vec!(),
+      num_rows: 0
+    })
+  }
+
+  pub fn schema_descr(&self) -> &SchemaDescPtr {
+    &self.descr
+  }
+
+  /// Returns the writers of the columns of a new row group. The row group is only part
+  /// of the file once passed to `write_row_group`.
+  pub fn next_row_group(&self) -> RowGroupWriter {
+    RowGroupWriter::new(self.descr.clone(), &self.options)
+  }
+
+  /// Writes the pages of all columns of `row_group`, which must all have the same
+  /// number of rows.
+  pub fn write_row_group(&mut self, row_group: RowGroupWriter) -> Result<()> {
+    if !Rc::ptr_eq(&row_group.descr, &self.descr) {
+      return Err(general_err!("The row group was not created by this writer"));
+    }
+    let row_group_index = self.row_groups.len();
+    let mut chunks = vec!();
+    for column in row_group.columns {
+      let descr = column.descr().clone();
+      chunks.push((descr, column.close()?));
+    }
+    let num_rows = chunks.first().map(|&(_, ref chunk)| chunk.num_rows).unwrap_or(0);
+    for &(ref descr, ref chunk) in &chunks {
+      if chunk.num_rows != num_rows {
+        return Err(schema_err!(
+          ErrorContext::new().with_column(descr.path().string())
+            .with_row_group(row_group_index),
+          "Expected {} rows, found {}", num_rows, chunk.num_rows));
+      }
+    }
+
+    let mut columns = vec!();
+    let mut total_byte_size = 0;
+    for (descr, chunk) in chunks {
+      let column = self.write_column_chunk(descr.as_ref(), chunk)?;
+      total_byte_size += column.meta_data.as_ref()
+        .map(|m| m.total_uncompressed_size).unwrap_or(0);
+      columns.push(column);
+    }
+    self.row_groups.push(RowGroup {
+      columns: columns,
+      total_byte_size: total_byte_size,
+      num_rows: num_rows,
+      sorting_columns: None
+    });
+    self.num_rows += num_rows;
+    Ok(())
+  }
+
+  /// Writes the file metadata and the footer, and flushes the file.
+  pub fn close(mut self) -> Result<()> {
+    let metadata = TFileMetaData {
+      version: 1,
+      schema: types::to_thrift(self.schema.as_ref())?,
+      num_rows: self.num_rows,
+      row_groups: self.row_groups,
+      key_value_metadata: None,
+      created_by: Some(self.options.created_by().to_owned())
+    };
+    let mut bytes = vec!();
+    {
+      let mut prot = TCompactOutputProtocol::new(&mut bytes);
+      metadata.write_to_out_protocol(&mut prot)
+        .and_then(|_| prot.flush())
+        .map_err(|e| general_err!("Could not serialize metadata: {}", e))?;
+    }
+    self.buf.write_all(&bytes)?;
+    self.buf.write_i32::<LittleEndian>(bytes.len() as i32)?;
+    self.buf.write_all(&PARQUET_MAGIC)?;
+    self.buf.flush()?;
+    Ok(())
+  }
+
+  fn write_column_chunk(
+    &mut self, descr: &ColumnDescriptor, chunk: ColumnChunkPages
+  ) -> Result<ColumnChunk> {
+    let mut compressor = create_codec(self.options.compression())?;
+    let mut data_page_offset = None;
+    let mut dictionary_page_offset = None;
+    let mut total_uncompressed_size = 0;
+    let mut total_compressed_size = 0;
+    let mut encoding_stats = vec!();
+    for page in chunk.pages {
+      count_page_encoding(&mut encoding_stats, &page);
+      let page_offset = self.offset as i64;
+      match page {
+        Page::DictionaryPage { .. } => dictionary_page_offset = Some(page_offset),
+        _ => {
+          if data_page_offset.is_none() {
+            data_page_offset = Some(page_offset);
+          }
+        }
+      }
+      let (header, data) = page_to_thrift(
+        page, &mut compressor, self.options.write_page_checksums())?;
+      let mut bytes = vec!();
+      {
+        let mut prot = TCompactOutputProtocol::new(&mut bytes);
+        header.write_to_out_protocol(&mut prot)
+          .and_then(|_| prot.flush())
+          .map_err(|e| general_err!("Could not serialize page header: {}", e))?;
+      }
+      self.buf.write_all(&bytes)?;
+      self.buf.write_all(&data)?;
+      self.offset += (bytes.len() + data.len()) as u64;
+      total_uncompressed_size +=
+        bytes.len() as i64 + header.uncompressed_page_size as i64;
+      total_compressed_size += (bytes.len() + data.len()) as i64;
+    }
+
+    // An empty column chunk still needs a valid offset
+    let data_page_offset = data_page_offset.unwrap_or(self.offset as i64);
+    let mut encodings = chunk.encodings;
+    if dictionary_page_offset.is_some() && !encodings.contains(&Encoding::PLAIN) {
+      encodings.push(Encoding::PLAIN);
+    }
+    let metadata = ColumnMetaData {
+      type_: descr.physical_type().into(),
+      encodings: encodings.iter().map(|&e| e.into()).collect(),
+      path_in_schema: Vec::from(descr.path().parts()),
+      codec: self.options.compression().into(),
+      num_values: chunk.num_values,
+      total_uncompressed_size: total_uncompressed_size,
+      total_compressed_size: total_compressed_size,
+      key_value_metadata: None,
+      data_page_offset: data_page_offset,
+      index_page_offset: None,
+      dictionary_page_offset: dictionary_page_offset,
+      statistics: None,
+      encoding_stats: Some(encoding_stats)
+    };
+    Ok(ColumnChunk {
+      file_path: None,
+      file_offset: dictionary_page_offset.unwrap_or(data_page_offset),
+      meta_data: Some(metadata)
+    })
+  }
+}
+
+/// Builds the header of `page`, and returns it with the page data as written to the
+/// file, i.e. compressed with `compressor` if any. If `write_checksum` is set, the
+/// header holds the CRC of the page data.
+fn page_to_thrift(
+  page: Page, compressor: &mut Option<Box<Codec>>, write_checksum: bool
+) -> Result<(PageHeader, Vec<u8>)> {
+  let uncompressed = page.buffer().data().to_vec();
+  let mut header = PageHeader {
+    type_: page.page_type().into(),
+    uncompressed_page_size: uncompressed.len() as i32,
+    compressed_page_size: 0,
+    crc: None,
+    data_page_header: None,
+    index_page_header: None,
+    dictionary_page_header: None,
+    data_page_header_v2: None
+  };
+  let data = match page {
+    Page::DataPage {
+      num_values, encoding, def_level_encoding, rep_level_encoding, ..
+    } => {
+      header.data_page_header = Some(DataPageHeader {
+        num_values: num_values as i32,
+        encoding: encoding.into(),
+        definition_level_encoding: def_level_encoding.into(),
+        repetition_level_encoding: rep_level_encoding.into(),
+        statistics: None
+      });
+      compress(compressor, uncompressed)?
+    },
+    Page::DataPageV2 {
+      num_values, encoding, num_nulls, num_rows, def_levels_byte_len,
+      rep_levels_byte_len, is_compressed, ..
+    } => {
+      header.data_page_header_v2 = Some(DataPageHeaderV2 {
+        num_values: num_values as i32,
+        num_nulls: num_nulls as i32,
+        num_rows: num_rows as i32,
+        encoding: encoding.into(),
+        definition_levels_byte_length: def_levels_byte_len as i32,
+        repetition_levels_byte_length: rep_levels_byte_len as i32,
+        is_compressed: Some(is_compressed && compressor.is_some()),
+        statistics: None
+      });
+      // Levels are never compressed in data pages v2
+      let levels_len = (def_levels_byte_len + rep_levels_byte_len) as usize;
+      if is_compressed {
+        let mut data = uncompressed[..levels_len].to_vec();
+        data.extend(compress(compressor, uncompressed[levels_len..].to_vec())?);
+        data
+      } else {
+        uncompressed
+      }
+    },
+    Page::DictionaryPage { num_values, encoding, is_sorted, .. } => {
+      header.dictionary_page_header = Some(DictionaryPageHeader {
+        num_values: num_values as i32,
+        encoding: encoding.into(),
+        is_sorted: Some(is_sorted)
+      });
+      compress(compressor, uncompressed)?
+    }
+  };
+  header.compressed_page_size = data.len() as i32;
+  if write_checksum {
+    header.crc = Some(crc32(&data) as i32);
+  }
+  Ok((header, data))
+}
+
+/// Counts `page` in the encoding stats of its column chunk, which have an entry per page
+/// type and encoding.
+fn count_page_encoding(encoding_stats: &mut Vec<PageEncodingStats>, page: &Page) {
+  let page_type = TPageType::from(page.page_type());
+  let encoding = TEncoding::from(page.encoding());
+  let index = encoding_stats.iter()
+    .position(|stats| stats.page_type == page_type && stats.encoding == encoding);
+  match index {
+    Some(i) => encoding_stats[i].count += 1,
+    None => encoding_stats.push(PageEncodingStats {
+      page_type: page_type,
+      encoding: encoding,
+      count: 1
+    })
+  }
+}
+
+fn compress(compressor: &mut Option<Box<Codec>>, data: Vec<u8>) -> Result<Vec<u8>> {
+  match *compressor {
+    Some(ref mut codec) => codec.compress(&data),
+    None => Ok(data)
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use basic::{Repetition, Type as PhysicalType};
+  use column::writer::get_typed_column_writer_mut;
+  use data_type::{ByteArray, ByteArrayType, Int32Type};
+  use std::io::Read;
+  use file::reader::{FileReader, ReaderOptions, SerializedFileReader};
+  use schema::parser::parse_message_type;
+  use util::test_common::get_temp_file_path;
+
+  fn write_file(file_name: &str, options: WriterOptions) -> File {
+    let schema = parse_message_type("
+      message schema {
+        REQUIRED INT32 a;
+        OPTIONAL BYTE_ARRAY b (UTF8);
+      }
+    ").unwrap();
+    let path = get_temp_file_path(file_name);
+    let file = File::create(&path).unwrap();
+    let mut writer = SerializedFileWriter::new(file, Rc::new(schema), options).unwrap();
+    for i in 0..2 {
+      let mut row_group = writer.next_row_group();
+      // The page size is checked after each batch, so rows are written in batches of 20
+      {
+        let col = get_typed_column_writer_mut::<Int32Type>(row_group.column(0)).unwrap();
+        let values: Vec<i32> = (0..100).map(|v| v + i * 100).collect();
+        for batch in values.chunks(20) {
+          col.write_batch(batch, None, None).unwrap();
+        }
+      }
+      {
+        let col =
+          get_typed_column_writer_mut::<ByteArrayType>(row_group.column(1)).unwrap();
+        let values: Vec<ByteArray> = (0..50).map(|v| ByteArray::from(format!(
+          "{}", v).into_bytes())).collect();
+        let def_levels: Vec<i16> = (0..100).map(|v| (v % 2) as i16).collect();
+        for (batch, levels) in values.chunks(10).zip(def_levels.chunks(20)) {
+          col.write_batch(batch, Some(levels), None).unwrap();
+        }
+      }
+      writer.write_row_group(row_group).unwrap();
+    }
+    writer.close().unwrap();
+    File::open(&path).unwrap()
+  }
+
+  #[test]
+  fn test_write_read_file() {
+    for &compression in &[Compression::UNCOMPRESSED, Compression::SNAPPY,
+                          Compression::GZIP] {
+      let options = WriterOptions::new()
+        .with_compression(compression)
+        .with_data_page_size(128);
+      let file = write_file(&format!("test_write_{}.parquet", compression), options);
+      let reader = SerializedFileReader::new(file).unwrap();
+      let metadata = reader.metadata();
+      assert_eq!(metadata.num_row_groups(), 2);
+      assert_eq!(metadata.file_metadata().num_rows(), 200);
+      let column = metadata.row_group(1).column(1);
+      assert_eq!(column.compression(), compression);
+      assert_eq!(column.column_type(), PhysicalType::BYTE_ARRAY);
+      assert_eq!(column.num_values(), 100);
+      let schema = metadata.file_metadata().schema();
+      assert_eq!(schema.get_fields()[1].get_basic_info().repetition(),
+                 Repetition::OPTIONAL);
+
+      let rows: Vec<String> =
+        reader.get_row_iter(None).unwrap().map(|r| r.unwrap().to_string()).collect();
+      assert_eq!(rows.len(), 200);
+      assert_eq!(rows[0], "{a: 0, b: null}");
+      assert_eq!(rows[1], "{a: 1, b: \"0\"}");
+      assert_eq!(rows[199], "{a: 199, b: \"49\"}");
+    }
+  }
+
+  #[test]
+  fn test_write_read_dictionary() {
+    let options = WriterOptions::new().with_data_page_size(128).with_dictionary(true);
+    let file = write_file("test_write_dictionary.parquet", options);
+    let reader = SerializedFileReader::new(file).unwrap();
+    for i in 0..2 {
+      let column = reader.metadata().row_group(1).column(i);
+      assert!(column.has_dictionary_page());
+      assert!(column.is_fully_dictionary_encoded());
+      let stats = column.encoding_stats().unwrap();
+      assert_eq!(stats[0].page_type, TPageType::DICTIONARY_PAGE);
+      assert_eq!(stats[0].encoding, TEncoding::PLAIN);
+      assert_eq!(stats[0].count, 1);
+      assert_eq!(stats[1].page_type, TPageType::DATA_PAGE);
+      assert_eq!(stats[1].encoding, TEncoding::PLAIN_DICTIONARY);
+      assert_eq!(stats.len(), 2);
+    }
+    let column = reader.metadata().row_group(0).column(0);
+    assert!(column.encoding_stats().unwrap()[1].count > 1);
+
+    let rows: Vec<String> =
+      reader.get_row_iter(None).unwrap().map(|r| r.unwrap().to_string()).collect();
+    assert_eq!(rows.len(), 200);
+    assert_eq!(rows[1], "{a: 1, b: \"0\"}");
+    assert_eq!(rows[199], "{a: 199, b: \"49\"}");
+  }
+
+  #[test]
+  fn test_write_read_page_checksums() {
+    let options = WriterOptions::new()
+      .with_compression(Compression::SNAPPY)
+      .with_data_page_size(128)
+      .with_page_checksums(true);
+    let path = get_temp_file_path("test_write_checksums.parquet");
+    write_file("test_write_checksums.parquet", options);
+    let reader_options = ReaderOptions::new().with_page_checksum_verification(true);
+
+    let reader =
+      SerializedFileReader::with_options(File::open(&path).unwrap(), reader_options)
+      .unwrap();
+    let row_group = reader.get_row_group(0).unwrap();
+    let mut page_reader = row_group.get_column_page_reader(0).unwrap();
+    let mut num_pages = 0;
+    while let Some(_) = page_reader.get_next_page().unwrap() {
+      num_pages += 1;
+    }
+    assert!(num_pages > 1);
+    assert_eq!(reader.get_row_iter(None).unwrap().filter(|r| r.is_ok()).count(), 200);
+
+    // Flip the last byte of the last page of the first column
+    let (start, len) = {
+      let column = reader.metadata().row_group(0).column(0);
+      (column.data_page_offset() as usize, column.compressed_size() as usize)
+    };
+    let mut bytes = vec!();
+    File::open(&path).unwrap().read_to_end(&mut bytes).unwrap();
+    bytes[start + len - 1] ^= 0xff;
+    File::create(&path).unwrap().write_all(&bytes).unwrap();
+
+    let reader =
+      SerializedFileReader::with_options(File::open(&path).unwrap(), reader_options)
+      .unwrap();
+    let row_group = reader.get_row_group(0).unwrap();
+    let mut page_reader = row_group.get_column_page_reader(0).unwrap();
+    let mut result = Ok(Some(()));
+    while let Ok(Some(_)) = result {
+      result = page_reader.get_next_page().map(|page| page.map(|_| ()));
+    }
+    match result {
+      Err(ParquetError::Checksum(..)) => {},
+      r => panic!("Expected a checksum error, got {:?}", r)
+    }
+  }
+
+  #[test]
+  fn test_write_row_group_different_num_rows() {
+    let schema = parse_message_type("
+      message schema {
+        REQUIRED INT32 a;
+        REQUIRED INT32 b;
+      }
+    ").unwrap();
+    let file = File::create(get_temp_file_path("test_write_num_rows.parquet")).unwrap();
+    let mut writer =
+      SerializedFileWriter::new(file, Rc::new(schema), WriterOptions::new()).unwrap();
+    let mut row_group = writer.next_row_group();
+    get_typed_column_writer_mut::<Int32Type>(row_group.column(0)).unwrap()
+      .write_batch(&[1, 2], None, None).unwrap();
+    get_typed_column_writer_mut::<Int32Type>(row_group.column(1)).unwrap()
+      .write_batch(&[1], None, None).unwrap();
+    assert!(writer.write_row_group(row_group).is_err());
+  }
+}
@@ -18,6 +18,7 @@
 #![feature(type_ascription)]
 #![feature(rustc_private)]
 #![feature(specialization)]
+#![feature(i128_type)]
 
 #![allow(dead_code)]
 #![allow(non_camel_case_types)]
@@ -33,6 +34,13 @@ extern crate snap;
 extern crate brotli;
 extern crate flate2;
 extern crate rand;
+#[macro_use]
+extern crate serde;
+#[macro_use]
+extern crate serde_json;
+#[cfg(test)]
+#[macro_use]
+extern crate serde_derive;
 
 // TODO: don't expose everything!
 #[macro_use]
@@ -49,3 +57,4 @@ pub mod compression;
 pub mod schema;
 pub mod file;
 pub mod encodings;
+pub mod record;
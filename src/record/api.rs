@@ -0,0 +1,477 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Row API: the values of a row, or of a nested group, as named fields.
+
+use std::fmt;
+
+use basic::{LogicalAnnotation, LogicalType, TimeUnit, Type as PhysicalType};
+use data_type::*;
+use errors::{ParquetError, Result};
+use schema::types::ColumnDescriptor;
+
+/// A row of a file, or the value of a group that is neither a list nor a map: the names
+/// and values of its fields, in the order of the schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Row {
+  fields: Vec<(String, Field)>
+}
+
+impl Row {
+  pub fn new(fields: Vec<(String, Field)>) -> Self {
+    Self { fields: fields }
+  }
+
+  /// Returns the number of fields of this row.
+  pub fn len(&self) -> usize {
+    self.fields.len()
+  }
+
+  pub fn fields(&self) -> &[(String, Field)] {
+    &self.fields
+  }
+
+  /// Returns the value of the field with `name`, if any.
+  pub fn get(&self, name: &str) -> Option<&Field> {
+    self.fields.iter().find(|&&(ref n, _)| n == name).map(|&(_, ref field)| field)
+  }
+
+  pub fn into_fields(self) -> Vec<(String, Field)> {
+    self.fields
+  }
+}
+
+impl fmt::Display for Row {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{{")?;
+    for (i, &(ref name, ref field)) in self.fields.iter().enumerate() {
+      if i > 0 {
+        write!(f, ", ")?;
+      }
+      write!(f, "{}: {}", name, field)?;
+    }
+    write!(f, "}}")
+  }
+}
+
+/// The value of a field of a row.
+///
+/// Primitive values are converted according to the logical type of their column, e.g.
+/// a UTF8 BYTE_ARRAY is a `Str` and a DATE is a `Date`. Values without a logical type
+/// keep their physical type, with BYTE_ARRAY and FIXED_LEN_BYTE_ARRAY as `Bytes`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Field {
+  /// A null value of an optional field.
+  Null,
+  Bool(bool),
+  /// A signed integer of at most 32 bits.
+  Int(i32),
+  /// A signed 64 bit integer, or an unsigned integer of at most 32 bits.
+  Long(i64),
+  /// An unsigned 64 bit integer.
+  ULong(u64),
+  Float(f32),
+  Double(f64),
+  /// A UTF8, ENUM or JSON value.
+  Str(String),
+  Bytes(ByteArray),
+  Decimal(Decimal),
+  Date(Date),
+  Time(Time),
+  Timestamp(Timestamp),
+  Interval(Interval),
+  /// A group that is neither a list nor a map.
+  Group(Row),
+  List(Vec<Field>),
+  /// The entries of a map. Values of maps without a value field are `Null`.
+  Map(Vec<(Field, Field)>)
+}
+
+impl Field {
+  /// Converts a value of a BOOLEAN column.
+  pub fn convert_bool(_descr: &ColumnDescriptor, value: bool) -> Result<Self> {
+    Ok(Field::Bool(value))
+  }
+
+  /// Converts a value of an INT32 column.
+  pub fn convert_int32(descr: &ColumnDescriptor, value: i32) -> Result<Self> {
+    let field = match descr.logical_annotation() {
+      Some(LogicalAnnotation::INTEGER { is_signed: false, .. }) => {
+        Field::Long(value as u32 as i64)
+      },
+      Some(LogicalAnnotation::DATE) => Field::Date(Date::from_days(value)),
+      Some(LogicalAnnotation::TIME { unit, .. }) => {
+        Field::Time(Time::new(value as i64, unit))
+      },
+      Some(LogicalAnnotation::DECIMAL { precision, scale }) => {
+        Field::Decimal(Decimal::from_i32(value, precision, scale))
+      },
+      _ => Field::Int(value)
+    };
+    Ok(field)
+  }
+
+  /// Converts a value of an INT64 column.
+  pub fn convert_int64(descr: &ColumnDescriptor, value: i64) -> Result<Self> {
+    let field = match descr.logical_annotation() {
+      Some(LogicalAnnotation::INTEGER { is_signed: false, .. }) => {
+        Field::ULong(value as u64)
+      },
+      Some(LogicalAnnotation::TIME { unit, .. }) => Field::Time(Time::new(value, unit)),
+      Some(LogicalAnnotation::TIMESTAMP { is_adjusted_to_utc, unit }) => {
+        Field::Timestamp(Timestamp::new(value, unit, is_adjusted_to_utc))
+      },
+      Some(LogicalAnnotation::DECIMAL { precision, scale }) => {
+        Field::Decimal(Decimal::from_i64(value, precision, scale))
+      },
+      _ => Field::Long(value)
+    };
+    Ok(field)
+  }
+
+  /// Converts a value of an INT96 column, which is always a legacy timestamp.
+  pub fn convert_int96(_descr: &ColumnDescriptor, value: Int96) -> Result<Self> {
+    Timestamp::from_int96(&value).map(Field::Timestamp)
+  }
+
+  /// Converts a value of a FLOAT column.
+  pub fn convert_float(_descr: &ColumnDescriptor, value: f32) -> Result<Self> {
+    Ok(Field::Float(value))
+  }
+
+  /// Converts a value of a DOUBLE column.
+  pub fn convert_double(_descr: &ColumnDescriptor, value: f64) -> Result<Self> {
+    Ok(Field::Double(value))
+  }
+
+  /// Converts a value of a BYTE_ARRAY or FIXED_LEN_BYTE_ARRAY column.
+  pub fn convert_byte_array(descr: &ColumnDescriptor, value: ByteArray) -> Result<Self> {
+    if descr.logical_type() == LogicalType::INTERVAL {
+      return Interval::from_bytes(value.data()).map(Field::Interval);
+    }
+    let field = match descr.logical_annotation() {
+      Some(LogicalAnnotation::STRING) | Some(LogicalAnnotation::ENUM) |
+      Some(LogicalAnnotation::JSON) => {
+        let s = String::from_utf8(value.data().to_vec()).map_err(|e| general_err!(
+          "Invalid UTF8 value in column {}: {}", descr.path().string(), e))?;
+        Field::Str(s)
+      },
+      Some(LogicalAnnotation::DECIMAL { precision, scale }) => {
+        Field::Decimal(Decimal::from_bytes(value.data(), precision, scale))
+      },
+      _ => Field::Bytes(value)
+    };
+    Ok(field)
+  }
+
+  /// Converts this value to a value of a BOOLEAN column.
+  pub fn to_bool(&self, descr: &ColumnDescriptor) -> Result<bool> {
+    match *self {
+      Field::Bool(v) => Ok(v),
+      _ => Err(self.write_err(descr))
+    }
+  }
+
+  /// Converts this value to a value of an INT32 column, according to the logical type
+  /// of the column.
+  pub fn to_int32(&self, descr: &ColumnDescriptor) -> Result<i32> {
+    let unsigned = match descr.logical_annotation() {
+      Some(LogicalAnnotation::INTEGER { is_signed, .. }) => !is_signed,
+      _ => false
+    };
+    let value = match *self {
+      Field::Int(v) if !unsigned || v >= 0 => Some(v),
+      Field::Long(v) if unsigned && v >= 0 && v <= u32::max_value() as i64 => {
+        Some(v as u32 as i32)
+      },
+      Field::Long(v) if !unsigned && v >= i32::min_value() as i64 &&
+        v <= i32::max_value() as i64 => Some(v as i32),
+      Field::Date(ref d) => Some(d.days()),
+      Field::Time(ref t) => {
+        convert_time_unit(t.value(), t.unit(), TimeUnit::MILLIS).map(|v| v as i32)
+      },
+      Field::Decimal(ref d) if d.scale() == descr.type_scale() => {
+        d.to_i128().ok()
+          .and_then(|v| if v as i32 as i128 == v { Some(v as i32) } else { None })
+      },
+      _ => None
+    };
+    value.ok_or_else(|| self.write_err(descr))
+  }
+
+  /// Converts this value to a value of an INT64 column, according to the logical type
+  /// of the column.
+  pub fn to_int64(&self, descr: &ColumnDescriptor) -> Result<i64> {
+    let unsigned = match descr.logical_annotation() {
+      Some(LogicalAnnotation::INTEGER { is_signed, .. }) => !is_signed,
+      _ => false
+    };
+    let value = match *self {
+      Field::Int(v) if !unsigned || v >= 0 => Some(v as i64),
+      Field::Long(v) if !unsigned || v >= 0 => Some(v),
+      // UINT_64 values are stored with the bits of the unsigned value
+      Field::ULong(v) if unsigned => Some(v as i64),
+      Field::ULong(v) if v <= i64::max_value() as u64 => Some(v as i64),
+      Field::Time(ref t) => match descr.logical_annotation() {
+        Some(LogicalAnnotation::TIME { unit, .. }) => {
+          convert_time_unit(t.value(), t.unit(), unit)
+        },
+        _ => None
+      },
+      Field::Timestamp(ref ts) => match descr.logical_annotation() {
+        Some(LogicalAnnotation::TIMESTAMP { unit: TimeUnit::MILLIS, .. }) => {
+          ts.to_millis().ok()
+        },
+        Some(LogicalAnnotation::TIMESTAMP { unit: TimeUnit::MICROS, .. }) => {
+          ts.to_micros().ok()
+        },
+        Some(LogicalAnnotation::TIMESTAMP { unit: TimeUnit::NANOS, .. }) => {
+          ts.to_nanos().ok()
+        },
+        _ => None
+      },
+      Field::Decimal(ref d) if d.scale() == descr.type_scale() => {
+        d.to_i128().ok()
+          .and_then(|v| if v as i64 as i128 == v { Some(v as i64) } else { None })
+      },
+      _ => None
+    };
+    value.ok_or_else(|| self.write_err(descr))
+  }
+
+  /// Converts this value to a value of an INT96 column.
+  pub fn to_int96(&self, descr: &ColumnDescriptor) -> Result<Int96> {
+    match *self {
+      Field::Timestamp(ref ts) => ts.to_int96(),
+      _ => Err(self.write_err(descr))
+    }
+  }
+
+  /// Converts this value to a value of a FLOAT column.
+  pub fn to_float(&self, descr: &ColumnDescriptor) -> Result<f32> {
+    match *self {
+      Field::Float(v) => Ok(v),
+      _ => Err(self.write_err(descr))
+    }
+  }
+
+  /// Converts this value to a value of a DOUBLE column.
+  pub fn to_double(&self, descr: &ColumnDescriptor) -> Result<f64> {
+    match *self {
+      Field::Float(v) => Ok(v as f64),
+      Field::Double(v) => Ok(v),
+      _ => Err(self.write_err(descr))
+    }
+  }
+
+  /// Converts this value to a value of a BYTE_ARRAY or FIXED_LEN_BYTE_ARRAY column.
+  /// Values of FIXED_LEN_BYTE_ARRAY columns must have the length of the column.
+  pub fn to_byte_array(&self, descr: &ColumnDescriptor) -> Result<ByteArray> {
+    let bytes = match *self {
+      Field::Str(ref s) => s.as_bytes().to_vec(),
+      Field::Bytes(ref b) => b.data().to_vec(),
+      Field::Interval(ref i) => i.to_bytes().to_vec(),
+      Field::Decimal(ref d) if d.scale() == descr.type_scale() => {
+        if descr.physical_type() == PhysicalType::FIXED_LEN_BYTE_ARRAY {
+          d.to_fixed_len_bytes(descr.type_length() as usize)?
+        } else {
+          d.data().to_vec()
+        }
+      },
+      // Bytes deserialized from a row, e.g. a `Vec<u8>`, are a list of integers
+      Field::List(ref elements) => {
+        let mut bytes = Vec::with_capacity(elements.len());
+        for element in elements {
+          match *element {
+            Field::Int(v) if v >= 0 && v <= 255 => bytes.push(v as u8),
+            _ => return Err(self.write_err(descr))
+          }
+        }
+        bytes
+      },
+      _ => return Err(self.write_err(descr))
+    };
+    if descr.physical_type() == PhysicalType::FIXED_LEN_BYTE_ARRAY &&
+      bytes.len() != descr.type_length() as usize {
+      return Err(general_err!(
+        "Cannot write {} bytes to column {} of length {}",
+        bytes.len(), descr.path().string(), descr.type_length()));
+    }
+    Ok(ByteArray::from(bytes))
+  }
+
+  fn write_err(&self, descr: &ColumnDescriptor) -> ParquetError {
+    general_err!(
+      "Cannot write {} to column {} of type {}",
+      self, descr.path().string(), descr.physical_type())
+  }
+}
+
+/// Converts a time of day `value` in `from` units to `to` units, if it fits.
+fn convert_time_unit(value: i64, from: TimeUnit, to: TimeUnit) -> Option<i64> {
+  fn nanos_per_unit(unit: TimeUnit) -> i64 {
+    match unit {
+      TimeUnit::MILLIS => 1_000_000,
+      TimeUnit::MICROS => 1_000,
+      TimeUnit::NANOS => 1
+    }
+  }
+  let (from, to) = (nanos_per_unit(from), nanos_per_unit(to));
+  if from >= to {
+    value.checked_mul(from / to)
+  } else if value % (to / from) == 0 {
+    Some(value / (to / from))
+  } else {
+    None
+  }
+}
+
+impl fmt::Display for Field {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      Field::Null => write!(f, "null"),
+      Field::Bool(v) => write!(f, "{}", v),
+      Field::Int(v) => write!(f, "{}", v),
+      Field::Long(v) => write!(f, "{}", v),
+      Field::ULong(v) => write!(f, "{}", v),
+      Field::Float(v) => write!(f, "{:?}", v),
+      Field::Double(v) => write!(f, "{:?}", v),
+      Field::Str(ref v) => write!(f, "{:?}", v),
+      Field::Bytes(ref v) => write!(f, "{:?}", v.data()),
+      Field::Decimal(ref v) => write!(f, "{}", v),
+      Field::Date(ref v) => write!(f, "{}", v),
+      Field::Time(ref v) => write!(f, "{}", v),
+      Field::Timestamp(ref v) => write!(f, "{}", v),
+      Field::Interval(ref v) => write!(f, "{}", v),
+      Field::Group(ref row) => write!(f, "{}", row),
+      Field::List(ref elements) => {
+        write!(f, "[")?;
+        for (i, element) in elements.iter().enumerate() {
+          if i > 0 {
+            write!(f, ", ")?;
+          }
+          write!(f, "{}", element)?;
+        }
+        write!(f, "]")
+      },
+      Field::Map(ref entries) => {
+        write!(f, "{{")?;
+        for (i, &(ref key, ref value)) in entries.iter().enumerate() {
+          if i > 0 {
+            write!(f, ", ")?;
+          }
+          write!(f, "{} -> {}", key, value)?;
+        }
+        write!(f, "}}")
+      }
+    }
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::rc::Rc;
+
+  use basic::{Type as PhysicalType, TimeUnit};
+  use schema::types::{ColumnPath, Type};
+
+  fn column(
+    physical_type: PhysicalType, logical_type: LogicalType, length: i32
+  ) -> ColumnDescriptor {
+    let tp = Type::primitive_type_builder("col", physical_type)
+      .with_logical_type(logical_type)
+      .with_length(length)
+      .with_precision(if logical_type == LogicalType::DECIMAL { 9 } else { 0 })
+      .with_scale(if logical_type == LogicalType::DECIMAL { 2 } else { 0 })
+      .build()
+      .unwrap();
+    let path = ColumnPath::new(vec!["col".to_owned()]);
+    ColumnDescriptor::new(Rc::new(tp), None, 0, 0, path)
+  }
+
+  #[test]
+  fn test_convert_by_logical_type() {
+    let descr = column(PhysicalType::INT32, LogicalType::NONE, 0);
+    assert_eq!(Field::convert_int32(&descr, -1).unwrap(), Field::Int(-1));
+    let descr = column(PhysicalType::INT32, LogicalType::UINT_32, 0);
+    assert_eq!(Field::convert_int32(&descr, -1).unwrap(), Field::Long(4294967295));
+    let descr = column(PhysicalType::INT32, LogicalType::DATE, 0);
+    assert_eq!(Field::convert_int32(&descr, 1).unwrap().to_string(), "1970-01-02");
+    let descr = column(PhysicalType::INT32, LogicalType::DECIMAL, 0);
+    assert_eq!(Field::convert_int32(&descr, -150).unwrap().to_string(), "-1.50");
+
+    let descr = column(PhysicalType::INT64, LogicalType::UINT_64, 0);
+    assert_eq!(Field::convert_int64(&descr, -1).unwrap(), Field::ULong(u64::max_value()));
+    let descr = column(PhysicalType::INT64, LogicalType::TIMESTAMP_MILLIS, 0);
+    assert_eq!(
+      Field::convert_int64(&descr, 1235865600000).unwrap(),
+      Field::Timestamp(Timestamp::new(1235865600000, TimeUnit::MILLIS, true)));
+
+    let descr = column(PhysicalType::BYTE_ARRAY, LogicalType::UTF8, 0);
+    assert_eq!(
+      Field::convert_byte_array(&descr, ByteArray::from("abc")).unwrap(),
+      Field::Str("abc".to_owned()));
+    assert!(Field::convert_byte_array(&descr, ByteArray::from(vec![0xFF])).is_err());
+    let descr = column(PhysicalType::BYTE_ARRAY, LogicalType::NONE, 0);
+    assert_eq!(
+      Field::convert_byte_array(&descr, ByteArray::from("abc")).unwrap(),
+      Field::Bytes(ByteArray::from("abc")));
+    let descr = column(PhysicalType::FIXED_LEN_BYTE_ARRAY, LogicalType::INTERVAL, 12);
+    assert_eq!(
+      Field::convert_byte_array(&descr, ByteArray::from(Interval::new(1, 2, 3))).unwrap(),
+      Field::Interval(Interval::new(1, 2, 3)));
+  }
+
+  #[test]
+  fn test_to_int64() {
+    let descr = column(PhysicalType::INT64, LogicalType::UINT_64, 0);
+    assert_eq!(Field::ULong(u64::max_value()).to_int64(&descr).unwrap(), -1);
+    assert_eq!(Field::Long(5).to_int64(&descr).unwrap(), 5);
+    assert!(Field::Long(-1).to_int64(&descr).is_err());
+    assert!(Field::Int(-1).to_int64(&descr).is_err());
+
+    let descr = column(PhysicalType::INT64, LogicalType::NONE, 0);
+    assert_eq!(Field::Int(-1).to_int64(&descr).unwrap(), -1);
+    assert_eq!(Field::ULong(5).to_int64(&descr).unwrap(), 5);
+    assert_eq!(
+      Field::ULong(i64::max_value() as u64).to_int64(&descr).unwrap(), i64::max_value());
+    assert!(Field::ULong(i64::max_value() as u64 + 1).to_int64(&descr).is_err());
+    let descr = column(PhysicalType::INT64, LogicalType::INT_64, 0);
+    assert!(Field::ULong(u64::max_value()).to_int64(&descr).is_err());
+  }
+
+  #[test]
+  fn test_display() {
+    let row = Row::new(vec![
+      ("a".to_owned(), Field::Str("abc".to_owned())),
+      ("b".to_owned(), Field::Null),
+      ("c".to_owned(), Field::List(vec![Field::Int(1), Field::Int(2)])),
+      ("d".to_owned(), Field::Map(vec![
+        (Field::Str("k".to_owned()), Field::Double(1.0))
+      ])),
+      ("e".to_owned(), Field::Group(Row::new(vec![("f".to_owned(), Field::Bool(true))])))
+    ]);
+    assert_eq!(
+      row.to_string(),
+      "{a: \"abc\", b: null, c: [1, 2], d: {\"k\" -> 1.0}, e: {f: true}}");
+    assert_eq!(row.get("b"), Some(&Field::Null));
+    assert_eq!(row.get("x"), None);
+  }
+}
@@ -0,0 +1,886 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Deserialization of rows into Rust types with serde.
+//!
+//! Rows and their fields implement `serde::Deserializer`: groups deserialize as structs
+//! or maps, lists as sequences, maps as maps, and null values as `None`. For example:
+//!
+//! ```ignore
+//! #[derive(Deserialize)]
+//! struct Record {
+//!   id: i32,
+//!   name: Option<String>,
+//!   tags: Vec<String>,
+//!   attributes: HashMap<String, i64>,
+//!   location: Option<Location>
+//! }
+//!
+//! let reader = SerializedFileReader::new(file)?;
+//! for record in reader.into_iter::<Record>() {
+//!   let record = record?;
+//! }
+//! ```
+//!
+//! `into_iter` only reads the fields of the file whose names are fields of the struct,
+//! and likewise for nested structs, including the struct elements of lists. Fields
+//! deserialized in other ways, e.g. as maps or enums, are read in full. Struct fields
+//! that are not in the file must be `Option`s, or have a default value.
+//!
+//! Decimals, dates, times, timestamps and intervals deserialize as strings, formatted
+//! with their `Display` implementation. Decimals can also be deserialized as `f32` or
+//! `f64`, and dates, times and timestamps as integers, i.e. the number of days since the
+//! Unix epoch, or the value in the unit of the column.
+
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::vec;
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+
+use errors::{ParquetError, Result};
+use file::reader::FileReader;
+use record::api::{Field, Row};
+use record::reader::RowIter;
+use schema::nested::{nested_type, value_type, ListType, NestedType};
+use schema::types::{Type, TypePtr};
+
+impl de::Error for ParquetError {
+  fn custom<T: Display>(msg: T) -> Self {
+    ParquetError::General(msg.to_string())
+  }
+}
+
+/// Deserializes an instance of `T` from `row`.
+pub fn from_row<T: DeserializeOwned>(row: Row) -> Result<T> {
+  T::deserialize(Field::Group(row))
+}
+
+/// Deserializes an instance of `T` from `field`.
+pub fn from_field<T: DeserializeOwned>(field: Field) -> Result<T> {
+  T::deserialize(field)
+}
+
+impl<'de> de::Deserializer<'de> for Field {
+  type Error = ParquetError;
+
+  fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    match self {
+      Field::Null => visitor.visit_none(),
+      Field::Bool(v) => visitor.visit_bool(v),
+      Field::Int(v) => visitor.visit_i32(v),
+      Field::Long(v) => visitor.visit_i64(v),
+      Field::ULong(v) => visitor.visit_u64(v),
+      Field::Float(v) => visitor.visit_f32(v),
+      Field::Double(v) => visitor.visit_f64(v),
+      Field::Str(v) => visitor.visit_string(v),
+      Field::Bytes(v) => visitor.visit_byte_buf(v.data().to_vec()),
+      Field::Decimal(v) => visitor.visit_string(v.to_string()),
+      Field::Date(v) => visitor.visit_string(v.to_string()),
+      Field::Time(v) => visitor.visit_string(v.to_string()),
+      Field::Timestamp(v) => visitor.visit_string(v.to_string()),
+      Field::Interval(v) => visitor.visit_string(v.to_string()),
+      Field::Group(row) => visitor.visit_map(RowAccess::new(row)),
+      Field::List(elements) => visitor.visit_seq(ListAccess::new(elements)),
+      Field::Map(entries) => {
+        visitor.visit_map(MapAccess { entries: entries.into_iter(), value: None })
+      }
+    }
+  }
+
+  fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    match self {
+      Field::Null => visitor.visit_none(),
+      field => visitor.visit_some(field)
+    }
+  }
+
+  fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    match self {
+      Field::Null => visitor.visit_unit(),
+      field => field.deserialize_any(visitor)
+    }
+  }
+
+  fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    match self {
+      Field::Date(v) => visitor.visit_i32(v.days()),
+      field => field.deserialize_i64(visitor)
+    }
+  }
+
+  fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    match self {
+      Field::Date(v) => visitor.visit_i64(v.days() as i64),
+      Field::Time(v) => visitor.visit_i64(v.value()),
+      Field::Timestamp(v) => visitor.visit_i64(v.value()),
+      field => field.deserialize_any(visitor)
+    }
+  }
+
+  fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    self.deserialize_f64(visitor)
+  }
+
+  fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    match self {
+      Field::Decimal(v) => visitor.visit_f64(v.to_f64()),
+      field => field.deserialize_any(visitor)
+    }
+  }
+
+  fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    match self {
+      Field::Bytes(v) => {
+        let bytes = v.data().iter().map(|&b| Field::Int(b as i32)).collect();
+        visitor.visit_seq(ListAccess::new(bytes))
+      },
+      field => field.deserialize_any(visitor)
+    }
+  }
+
+  fn deserialize_newtype_struct<V: Visitor<'de>>(
+    self, _name: &'static str, visitor: V
+  ) -> Result<V::Value> {
+    visitor.visit_newtype_struct(self)
+  }
+
+  fn deserialize_enum<V: Visitor<'de>>(
+    self, _name: &'static str, _variants: &'static [&'static str], visitor: V
+  ) -> Result<V::Value> {
+    match self {
+      Field::Str(v) => visitor.visit_enum(v.into_deserializer()),
+      field => Err(general_err!("Expected an ENUM or UTF8 value, found {}", field))
+    }
+  }
+
+  forward_to_deserialize_any! {
+    bool i8 i16 u8 u16 u32 u64 char str string bytes byte_buf unit_struct tuple
+    tuple_struct map struct identifier ignored_any
+  }
+}
+
+/// Access to the fields of a row, as a map from field names to values.
+struct RowAccess {
+  fields: vec::IntoIter<(String, Field)>,
+  value: Option<Field>
+}
+
+impl RowAccess {
+  fn new(row: Row) -> Self {
+    Self { fields: row.into_fields().into_iter(), value: None }
+  }
+}
+
+impl<'de> de::MapAccess<'de> for RowAccess {
+  type Error = ParquetError;
+
+  fn next_key_seed<K: DeserializeSeed<'de>>(
+    &mut self, seed: K
+  ) -> Result<Option<K::Value>> {
+    match self.fields.next() {
+      Some((name, value)) => {
+        self.value = Some(value);
+        seed.deserialize(name.into_deserializer()).map(Some)
+      },
+      None => Ok(None)
+    }
+  }
+
+  fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+    match self.value.take() {
+      Some(value) => seed.deserialize(value),
+      None => Err(general_err!("Value requested before key"))
+    }
+  }
+
+  fn size_hint(&self) -> Option<usize> {
+    Some(self.fields.len())
+  }
+}
+
+/// Access to the entries of a map.
+struct MapAccess {
+  entries: vec::IntoIter<(Field, Field)>,
+  value: Option<Field>
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess {
+  type Error = ParquetError;
+
+  fn next_key_seed<K: DeserializeSeed<'de>>(
+    &mut self, seed: K
+  ) -> Result<Option<K::Value>> {
+    match self.entries.next() {
+      Some((key, value)) => {
+        self.value = Some(value);
+        seed.deserialize(key).map(Some)
+      },
+      None => Ok(None)
+    }
+  }
+
+  fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+    match self.value.take() {
+      Some(value) => seed.deserialize(value),
+      None => Err(general_err!("Value requested before key"))
+    }
+  }
+
+  fn size_hint(&self) -> Option<usize> {
+    Some(self.entries.len())
+  }
+}
+
+/// Access to the elements of a list.
+struct ListAccess {
+  elements: vec::IntoIter<Field>
+}
+
+impl ListAccess {
+  fn new(elements: Vec<Field>) -> Self {
+    Self { elements: elements.into_iter() }
+  }
+}
+
+impl<'de> de::SeqAccess<'de> for ListAccess {
+  type Error = ParquetError;
+
+  fn next_element_seed<T: DeserializeSeed<'de>>(
+    &mut self, seed: T
+  ) -> Result<Option<T::Value>> {
+    match self.elements.next() {
+      Some(element) => seed.deserialize(element).map(Some),
+      None => Ok(None)
+    }
+  }
+
+  fn size_hint(&self) -> Option<usize> {
+    Some(self.elements.len())
+  }
+}
+
+/// Iterator that deserializes the rows of a file into instances of `T`.
+pub struct DeserializeIter<'a, T> {
+  rows: Option<RowIter<'a>>,
+  error: Option<ParquetError>,
+  _phantom: PhantomData<T>
+}
+
+impl<'a, T: DeserializeOwned> Iterator for DeserializeIter<'a, T> {
+  type Item = Result<T>;
+
+  fn next(&mut self) -> Option<Result<T>> {
+    if let Some(e) = self.error.take() {
+      return Some(Err(e));
+    }
+    match self.rows {
+      Some(ref mut rows) => rows.next().map(|row| row.and_then(from_row)),
+      None => None
+    }
+  }
+}
+
+/// Deserialization of the rows of a file reader into Rust types.
+pub trait IntoRowIter<'a> {
+  /// Returns an iterator that deserializes the rows of this file into instances of
+  /// `T`. If `T` is a struct, only the fields of the file with the names of its fields
+  /// are read, recursively for nested structs. Fails if none of them is in the file.
+  fn into_iter<T: DeserializeOwned>(self) -> DeserializeIter<'a, T>;
+}
+
+impl<'a, R: FileReader + ?Sized> IntoRowIter<'a> for &'a R {
+  fn into_iter<T: DeserializeOwned>(self) -> DeserializeIter<'a, T> {
+    let rows = projection::<T>(self.metadata().file_metadata().schema())
+      .and_then(|projection| self.get_row_iter(projection));
+    let (rows, error) = match rows {
+      Ok(rows) => (Some(rows), None),
+      Err(e) => (None, Some(e))
+    };
+    DeserializeIter { rows: rows, error: error, _phantom: PhantomData }
+  }
+}
+
+/// Returns the projection of `schema` on the fields that `T` deserializes from, or
+/// `None` if `T` is not a struct. Fails if none of the fields of `T` is in `schema`.
+fn projection<T: DeserializeOwned>(schema: &Type) -> Result<Option<Type>> {
+  let mut shape = Shape::Any;
+  let _ = T::deserialize(ShapeTracer {
+    tp: NestedType::Struct(schema),
+    shape: &mut shape
+  });
+  match shape {
+    Shape::Struct(ref fields) => {
+      let projected = project_fields(schema, fields);
+      if projected.is_empty() {
+        let names: Vec<_> = fields.iter().map(|&(name, _)| name).collect();
+        return Err(general_err!(
+          "None of the fields {} is in the schema of the file", names.join(", ")));
+      }
+      Ok(Some(with_fields(schema, projected)))
+    },
+    _ => Ok(None)
+  }
+}
+
+/// Returns the fields of `group` that are in `fields`, in the order of the schema, each
+/// projected on its shape.
+fn project_fields(group: &Type, fields: &[(&str, Shape)]) -> Vec<TypePtr> {
+  let mut projected = vec!();
+  for field in group.get_fields() {
+    if let Some(&(_, ref shape)) = fields.iter().find(|f| f.0 == field.name()) {
+      projected.push(project_field(field, shape));
+    }
+  }
+  projected
+}
+
+/// Returns the projection of `field` on `shape`. Structs and the struct elements of
+/// lists are projected on the fields of `shape`, any other field is read in full.
+fn project_field(field: &TypePtr, shape: &Shape) -> TypePtr {
+  let projected = match (nested_type(field), shape) {
+    (Ok(NestedType::List(list)), &Shape::Seq(ref element)) => project_list(list, element),
+    (Ok(NestedType::Struct(_)), _) => {
+      project_struct(field, shape).map(|fields| Rc::new(with_fields(field, fields)))
+    },
+    _ => None
+  };
+  projected.unwrap_or_else(|| Rc::clone(field))
+}
+
+/// Returns the projection of `list` on the shape of its elements, or `None` if the list
+/// is read in full.
+fn project_list(list: ListType, element: &Shape) -> Option<TypePtr> {
+  if !list.is_two_level() {
+    let element = project_field(&list.repeated.get_fields()[0], element);
+    let repeated = Rc::new(with_fields(list.repeated, vec![element]));
+    return Some(Rc::new(with_fields(list.field, vec![repeated])));
+  }
+  let fields = match project_struct(list.element, element) {
+    Some(fields) => fields,
+    None => return None
+  };
+  if list.field as *const Type == list.repeated as *const Type {
+    return Some(Rc::new(with_fields(list.repeated, fields)));
+  }
+  // A repeated group with a single field is only the element of a LIST if it is named
+  // `array` or `<list name>_tuple`, see `schema::nested`
+  let name = list.repeated.name();
+  let tuple_name = format!("{}_tuple", list.field.name());
+  if fields.len() == 1 && name != "array" && name != tuple_name {
+    return None;
+  }
+  let repeated = Rc::new(with_fields(list.repeated, fields));
+  Some(Rc::new(with_fields(list.field, vec![repeated])))
+}
+
+/// Returns the projected fields of `tp` if its values are structs and `shape` is a
+/// struct with some of their fields, and `None` otherwise.
+fn project_struct(tp: &Type, shape: &Shape) -> Option<Vec<TypePtr>> {
+  match (value_type(tp), shape) {
+    (Ok(NestedType::Struct(group)), &Shape::Struct(ref fields)) => {
+      let projected = project_fields(group, fields);
+      if projected.is_empty() { None } else { Some(projected) }
+    },
+    _ => None
+  }
+}
+
+/// Returns a copy of the group `tp` with the fields `fields`.
+fn with_fields(tp: &Type, mut fields: Vec<TypePtr>) -> Type {
+  let info = tp.get_basic_info();
+  let mut builder = Type::group_type_builder(info.name())
+    .with_logical_type(info.logical_type())
+    .with_logical_annotation(info.logical_annotation())
+    .with_fields(&mut fields);
+  if info.has_repetition() {
+    builder = builder.with_repetition(info.repetition());
+  }
+  if info.has_id() {
+    builder = builder.with_id(info.id());
+  }
+  builder.build().unwrap()
+}
+
+/// The fields of a schema that a type deserializes from.
+#[derive(Debug)]
+enum Shape {
+  /// The whole field.
+  Any,
+  /// The named fields of a group, each with its own shape.
+  Struct(Vec<(&'static str, Shape)>),
+  /// The elements of a list.
+  Seq(Box<Shape>)
+}
+
+/// Deserializer that records the shape of a type by deserializing it from dummy values,
+/// following the structs and sequences it requests into the groups and lists of `tp`.
+///
+/// The shape is recorded before the fields or elements are deserialized, each of them
+/// as `Shape::Any`, so that a failure, e.g. of a type that does not match the schema,
+/// leaves the rest of the type to be read in full.
+struct ShapeTracer<'s, 'f> {
+  tp: NestedType<'s>,
+  shape: &'f mut Shape
+}
+
+impl<'de, 's, 'f> de::Deserializer<'de> for ShapeTracer<'s, 'f> {
+  type Error = ParquetError;
+
+  fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    visitor.visit_unit()
+  }
+
+  fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    visitor.visit_bool(false)
+  }
+
+  fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    self.deserialize_i64(visitor)
+  }
+
+  fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    self.deserialize_i64(visitor)
+  }
+
+  fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    self.deserialize_i64(visitor)
+  }
+
+  fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    visitor.visit_i64(0)
+  }
+
+  fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    self.deserialize_u64(visitor)
+  }
+
+  fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    self.deserialize_u64(visitor)
+  }
+
+  fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    self.deserialize_u64(visitor)
+  }
+
+  fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    visitor.visit_u64(0)
+  }
+
+  fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    self.deserialize_f64(visitor)
+  }
+
+  fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    visitor.visit_f64(0.0)
+  }
+
+  fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    visitor.visit_char('\0')
+  }
+
+  fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    visitor.visit_str("")
+  }
+
+  fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    self.deserialize_str(visitor)
+  }
+
+  fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    visitor.visit_bytes(&[])
+  }
+
+  fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    self.deserialize_bytes(visitor)
+  }
+
+  fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    visitor.visit_unit()
+  }
+
+  fn deserialize_unit_struct<V: Visitor<'de>>(
+    self, _name: &'static str, visitor: V
+  ) -> Result<V::Value> {
+    visitor.visit_unit()
+  }
+
+  fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    visitor.visit_some(self)
+  }
+
+  fn deserialize_newtype_struct<V: Visitor<'de>>(
+    self, _name: &'static str, visitor: V
+  ) -> Result<V::Value> {
+    visitor.visit_newtype_struct(self)
+  }
+
+  fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    let list = match self.tp {
+      NestedType::List(list) => list,
+      _ => return visitor.visit_seq(SeqTracer { element: None })
+    };
+    let element = list.element_type()?;
+    let shape = self.shape;
+    *shape = Shape::Seq(Box::new(Shape::Any));
+    match *shape {
+      Shape::Seq(ref mut shape) => {
+        let tracer = ShapeTracer { tp: element, shape: shape };
+        visitor.visit_seq(SeqTracer { element: Some(tracer) })
+      },
+      _ => unreachable!()
+    }
+  }
+
+  fn deserialize_tuple<V: Visitor<'de>>(
+    self, _len: usize, visitor: V
+  ) -> Result<V::Value> {
+    visitor.visit_seq(SeqTracer { element: None })
+  }
+
+  fn deserialize_tuple_struct<V: Visitor<'de>>(
+    self, _name: &'static str, _len: usize, visitor: V
+  ) -> Result<V::Value> {
+    visitor.visit_seq(SeqTracer { element: None })
+  }
+
+  fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+    visitor.visit_map(StructTracer { fields: vec!().into_iter(), value: None })
+  }
+
+  fn deserialize_struct<V: Visitor<'de>>(
+    self, _name: &'static str, fields: &'static [&'static str], visitor: V
+  ) -> Result<V::Value> {
+    let group = match self.tp {
+      NestedType::Struct(group) => group,
+      _ => return Err(general_err!("Expected a group for a struct"))
+    };
+    let shape = self.shape;
+    *shape = Shape::Struct(fields.iter().map(|&name| (name, Shape::Any)).collect());
+    let mut tracers = vec!();
+    if let Shape::Struct(ref mut fields) = *shape {
+      for entry in fields.iter_mut() {
+        let name = entry.0;
+        let field = group.get_fields().iter().find(|field| field.name() == name);
+        if let Some(field) = field {
+          let tracer = ShapeTracer { tp: nested_type(field)?, shape: &mut entry.1 };
+          tracers.push((name, tracer));
+        }
+      }
+    }
+    visitor.visit_map(StructTracer { fields: tracers.into_iter(), value: None })
+  }
+
+  fn deserialize_enum<V: Visitor<'de>>(
+    self, _name: &'static str, _variants: &'static [&'static str], _visitor: V
+  ) -> Result<V::Value> {
+    Err(general_err!("Enums are not traced"))
+  }
+
+  forward_to_deserialize_any! {
+    identifier ignored_any
+  }
+}
+
+/// Access to at most one element traced by a `ShapeTracer`.
+struct SeqTracer<'s, 'f> {
+  element: Option<ShapeTracer<'s, 'f>>
+}
+
+impl<'de, 's, 'f> de::SeqAccess<'de> for SeqTracer<'s, 'f> {
+  type Error = ParquetError;
+
+  fn next_element_seed<T: DeserializeSeed<'de>>(
+    &mut self, seed: T
+  ) -> Result<Option<T::Value>> {
+    match self.element.take() {
+      Some(tracer) => seed.deserialize(tracer).map(Some),
+      None => Ok(None)
+    }
+  }
+}
+
+/// Access to the fields of a struct that are in the schema, traced by `ShapeTracer`s.
+struct StructTracer<'s, 'f> {
+  fields: vec::IntoIter<(&'static str, ShapeTracer<'s, 'f>)>,
+  value: Option<ShapeTracer<'s, 'f>>
+}
+
+impl<'de, 's, 'f> de::MapAccess<'de> for StructTracer<'s, 'f> {
+  type Error = ParquetError;
+
+  fn next_key_seed<K: DeserializeSeed<'de>>(
+    &mut self, seed: K
+  ) -> Result<Option<K::Value>> {
+    match self.fields.next() {
+      Some((name, tracer)) => {
+        self.value = Some(tracer);
+        seed.deserialize(name.into_deserializer()).map(Some)
+      },
+      None => Ok(None)
+    }
+  }
+
+  fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+    match self.value.take() {
+      Some(tracer) => seed.deserialize(tracer),
+      None => Err(general_err!("Value requested before key"))
+    }
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::collections::HashMap;
+
+  use data_type::ByteArray;
+  use file::reader::SerializedFileReader;
+  use schema::parser::parse_message_type;
+  use util::test_common::get_test_file;
+
+  #[derive(Debug, Deserialize, PartialEq)]
+  struct Record {
+    b: i64,
+    a: Option<String>,
+    e: Option<Vec<u8>>,
+    missing: Option<i32>
+  }
+
+  #[test]
+  fn test_into_iter() {
+    let file = get_test_file("test_datapage_v2.snappy.parquet");
+    let reader = SerializedFileReader::new(file).unwrap();
+    let records: Vec<Record> = reader.into_iter::<Record>().map(|r| r.unwrap()).collect();
+    assert_eq!(records.len(), 5);
+    assert_eq!(
+      records[0],
+      Record { b: 1, a: Some("abc".to_owned()), e: Some(vec![1, 2, 3]), missing: None });
+    assert_eq!(
+      records[1],
+      Record { b: 2, a: Some("abc".to_owned()), e: None, missing: None });
+    assert_eq!(
+      records[3],
+      Record { b: 4, a: None, e: Some(vec![1, 2, 3]), missing: None });
+  }
+
+  #[test]
+  fn test_into_iter_projection() {
+    #[derive(Debug, Deserialize)]
+    struct Id {
+      id: i32
+    }
+
+    let file = get_test_file("alltypes_plain.parquet");
+    let reader = SerializedFileReader::new(file).unwrap();
+    let schema = reader.metadata().file_metadata().schema();
+    let projection = projection::<Id>(schema).unwrap().unwrap();
+    assert_eq!(projection.get_fields().len(), 1);
+    assert_eq!(projection.get_fields()[0].name(), "id");
+
+    let ids: Vec<i32> = reader.into_iter::<Id>().map(|r| r.unwrap().id).collect();
+    assert_eq!(ids, vec![4, 5, 6, 7, 2, 3, 0, 1]);
+
+    // Not a struct, read all fields
+    assert!(projection::<HashMap<String, i32>>(schema).unwrap().is_none());
+    let rows: Vec<Result<HashMap<String, i32>>> = reader.into_iter().collect();
+    assert!(rows[0].is_err());
+  }
+
+  #[test]
+  fn test_into_iter_empty_projection() {
+    #[derive(Debug, Deserialize)]
+    struct Unknown {
+      unknown: Option<i32>
+    }
+
+    let file = get_test_file("alltypes_plain.parquet");
+    let reader = SerializedFileReader::new(file).unwrap();
+    let rows: Vec<Result<Unknown>> = reader.into_iter().collect();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(
+      rows[0].as_ref().unwrap_err().to_string(),
+      "Parquet error: None of the fields unknown is in the schema of the file");
+  }
+
+  #[test]
+  fn test_nested_projection() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Location {
+      lat: f64
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Point {
+      x: i32
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Tag {
+      key: String
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Pair {
+      a: i32
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Record {
+      location: Option<Location>,
+      points: Option<Vec<Option<Point>>>,
+      tags: Vec<Tag>,
+      legacy: Vec<Pair>,
+      pairs: Vec<Pair>,
+      attributes: HashMap<String, i32>
+    }
+
+    let schema = parse_message_type("
+      message schema {
+        REQUIRED INT32 id;
+        OPTIONAL group location {
+          REQUIRED DOUBLE lat;
+          REQUIRED DOUBLE lon;
+        }
+        OPTIONAL group points (LIST) {
+          REPEATED group list {
+            OPTIONAL group element {
+              REQUIRED INT32 x;
+              REQUIRED INT32 y;
+            }
+          }
+        }
+        REPEATED group tags {
+          REQUIRED BINARY key (UTF8);
+          REQUIRED INT32 weight;
+        }
+        OPTIONAL group legacy (LIST) {
+          REPEATED group array {
+            REQUIRED INT32 a;
+            REQUIRED INT32 b;
+          }
+        }
+        OPTIONAL group pairs (LIST) {
+          REPEATED group pair {
+            REQUIRED INT32 a;
+            REQUIRED INT32 b;
+          }
+        }
+        OPTIONAL group attributes (MAP) {
+          REPEATED group map {
+            REQUIRED BINARY key (UTF8);
+            OPTIONAL INT32 value;
+          }
+        }
+      }
+    ").unwrap();
+    // `pairs` would be a list of `a` values without `b`, so it is read in full
+    let expected = parse_message_type("
+      message schema {
+        OPTIONAL group location {
+          REQUIRED DOUBLE lat;
+        }
+        OPTIONAL group points (LIST) {
+          REPEATED group list {
+            OPTIONAL group element {
+              REQUIRED INT32 x;
+            }
+          }
+        }
+        REPEATED group tags {
+          REQUIRED BINARY key (UTF8);
+        }
+        OPTIONAL group legacy (LIST) {
+          REPEATED group array {
+            REQUIRED INT32 a;
+          }
+        }
+        OPTIONAL group pairs (LIST) {
+          REPEATED group pair {
+            REQUIRED INT32 a;
+            REQUIRED INT32 b;
+          }
+        }
+        OPTIONAL group attributes (MAP) {
+          REPEATED group map {
+            REQUIRED BINARY key (UTF8);
+            OPTIONAL INT32 value;
+          }
+        }
+      }
+    ").unwrap();
+    let projection = projection::<Record>(&schema).unwrap().unwrap();
+    assert_eq!(projection, expected);
+    assert!(schema.check_contains(&projection));
+  }
+
+  #[test]
+  fn test_deserialize_fields() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Inner {
+      x: f64,
+      y: Vec<Option<String>>
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Outer {
+      inner: Inner,
+      map: HashMap<String, Option<i64>>,
+      bytes: Vec<u8>,
+      date: String,
+      days: i32
+    }
+
+    let row = Row::new(vec![
+      ("inner".to_owned(), Field::Group(Row::new(vec![
+        ("x".to_owned(), Field::Float(1.5)),
+        ("y".to_owned(), Field::List(vec![Field::Str("a".to_owned()), Field::Null]))
+      ]))),
+      ("map".to_owned(), Field::Map(vec![
+        (Field::Str("k1".to_owned()), Field::Int(1)),
+        (Field::Str("k2".to_owned()), Field::Null)
+      ])),
+      ("bytes".to_owned(), Field::Bytes(ByteArray::from(vec![1, 2]))),
+      ("date".to_owned(), Field::Date(::data_type::Date::from_days(1))),
+      ("days".to_owned(), Field::Date(::data_type::Date::from_days(1)))
+    ]);
+    let outer: Outer = from_row(row).unwrap();
+    let mut map = HashMap::new();
+    map.insert("k1".to_owned(), Some(1));
+    map.insert("k2".to_owned(), None);
+    assert_eq!(outer, Outer {
+      inner: Inner { x: 1.5, y: vec![Some("a".to_owned()), None] },
+      map: map,
+      bytes: vec![1, 2],
+      date: "1970-01-02".to_owned(),
+      days: 1
+    });
+
+    let result: Result<i32> = from_field(Field::Str("a".to_owned()));
+    assert!(result.is_err());
+    let result: Result<i32> = from_field(Field::Null);
+    assert!(result.is_err());
+  }
+}
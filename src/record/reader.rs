@@ -0,0 +1,476 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Record reader: assembles the rows of a file from the triplets of its leaf columns.
+//!
+//! The schema, or a projection of it, is turned into a tree of readers following the
+//! structure given by `schema::nested`: optional fields, groups, lists and maps each
+//! have their own reader, and leaf columns are read with a `TripletIter`. Definition
+//! and repetition levels of the leaves decide whether an optional field is null, and
+//! where a list or map ends.
+
+use basic::Repetition;
+use errors::{ParquetError, Result};
+use file::reader::{FileReader, RowGroupReader};
+use record::api::{Field, Row};
+use record::triplet::{TripletIter, DEFAULT_BATCH_SIZE};
+use schema::nested::{value_type, NestedType};
+use schema::types::{ColumnPath, SchemaDescriptor, Type};
+
+/// Reader of the values of a field, built from the schema.
+enum Reader<'a> {
+  /// A leaf column.
+  PrimitiveReader(TripletIter<'a>),
+  /// An optional field that is defined if the definition level is greater than the one
+  /// given.
+  OptionReader(i16, Box<Reader<'a>>),
+  /// A group of named fields.
+  GroupReader(Vec<(String, Reader<'a>)>),
+  /// A list whose elements are read with the inner reader. The levels are the
+  /// definition and repetition levels of the list itself.
+  RepeatedReader(i16, i16, Box<Reader<'a>>),
+  /// A map whose keys and values are read with the inner readers. The levels are the
+  /// definition and repetition levels of the map itself.
+  KeyValueReader(i16, i16, Box<Reader<'a>>, Option<Box<Reader<'a>>>)
+}
+
+impl<'a> Reader<'a> {
+  /// Reads the value of the current triplets, and advances the leaves to the next ones.
+  fn read_field(&mut self) -> Result<Field> {
+    match *self {
+      Reader::PrimitiveReader(ref mut column) => {
+        let value = column.current_value()?;
+        column.read_next()?;
+        Ok(value)
+      },
+      Reader::OptionReader(def_level, ref mut reader) => {
+        if reader.current_def_level() > def_level {
+          reader.read_field()
+        } else {
+          reader.advance_columns()?;
+          Ok(Field::Null)
+        }
+      },
+      Reader::GroupReader(ref mut readers) => {
+        let mut fields = Vec::with_capacity(readers.len());
+        for &mut (ref name, ref mut reader) in readers.iter_mut() {
+          fields.push((name.clone(), reader.read_field()?));
+        }
+        Ok(Field::Group(Row::new(fields)))
+      },
+      Reader::RepeatedReader(def_level, rep_level, ref mut reader) => {
+        let mut elements = vec!();
+        loop {
+          if reader.current_def_level() > def_level {
+            elements.push(reader.read_field()?);
+          } else {
+            // An empty list only has a single triplet in each leaf
+            reader.advance_columns()?;
+            break;
+          }
+          if !reader.has_next() || reader.current_rep_level() <= rep_level {
+            break;
+          }
+        }
+        Ok(Field::List(elements))
+      },
+      Reader::KeyValueReader(def_level, rep_level, ref mut keys, ref mut values) => {
+        let mut entries = vec!();
+        loop {
+          if keys.current_def_level() > def_level {
+            let key = keys.read_field()?;
+            let value = match *values {
+              Some(ref mut values) => values.read_field()?,
+              None => Field::Null
+            };
+            entries.push((key, value));
+          } else {
+            keys.advance_columns()?;
+            if let Some(ref mut values) = *values {
+              values.advance_columns()?;
+            }
+            break;
+          }
+          if !keys.has_next() || keys.current_rep_level() <= rep_level {
+            break;
+          }
+        }
+        Ok(Field::Map(entries))
+      }
+    }
+  }
+
+  /// Whether the leaves have more triplets.
+  fn has_next(&self) -> bool {
+    match *self {
+      Reader::PrimitiveReader(ref column) => column.has_next(),
+      Reader::OptionReader(_, ref reader) => reader.has_next(),
+      Reader::GroupReader(ref readers) => {
+        readers.first().map(|&(_, ref reader)| reader.has_next()).unwrap_or(false)
+      },
+      Reader::RepeatedReader(_, _, ref reader) => reader.has_next(),
+      Reader::KeyValueReader(_, _, ref keys, _) => keys.has_next()
+    }
+  }
+
+  /// Returns the definition level of the current triplets. All leaves under a field
+  /// have the same definition level up to the level of the field.
+  fn current_def_level(&self) -> i16 {
+    match *self {
+      Reader::PrimitiveReader(ref column) => column.current_def_level(),
+      Reader::OptionReader(_, ref reader) => reader.current_def_level(),
+      Reader::GroupReader(ref readers) => readers[0].1.current_def_level(),
+      Reader::RepeatedReader(_, _, ref reader) => reader.current_def_level(),
+      Reader::KeyValueReader(_, _, ref keys, _) => keys.current_def_level()
+    }
+  }
+
+  /// Returns the repetition level of the current triplets.
+  fn current_rep_level(&self) -> i16 {
+    match *self {
+      Reader::PrimitiveReader(ref column) => column.current_rep_level(),
+      Reader::OptionReader(_, ref reader) => reader.current_rep_level(),
+      Reader::GroupReader(ref readers) => readers[0].1.current_rep_level(),
+      Reader::RepeatedReader(_, _, ref reader) => reader.current_rep_level(),
+      Reader::KeyValueReader(_, _, ref keys, _) => keys.current_rep_level()
+    }
+  }
+
+  /// Advances all leaves to their next triplet, skipping a null or empty value.
+  fn advance_columns(&mut self) -> Result<()> {
+    match *self {
+      Reader::PrimitiveReader(ref mut column) => column.read_next().map(|_| ()),
+      Reader::OptionReader(_, ref mut reader) => reader.advance_columns(),
+      Reader::GroupReader(ref mut readers) => {
+        for &mut (_, ref mut reader) in readers.iter_mut() {
+          reader.advance_columns()?;
+        }
+        Ok(())
+      },
+      Reader::RepeatedReader(_, _, ref mut reader) => reader.advance_columns(),
+      Reader::KeyValueReader(_, _, ref mut keys, ref mut values) => {
+        keys.advance_columns()?;
+        match *values {
+          Some(ref mut values) => values.advance_columns(),
+          None => Ok(())
+        }
+      }
+    }
+  }
+}
+
+/// Builds the reader tree of a schema for the columns of a row group.
+struct TreeBuilder<'r, 'a: 'r> {
+  row_group: &'r RowGroupReader<'a>,
+  schema_descr: &'a SchemaDescriptor
+}
+
+impl<'r, 'a> TreeBuilder<'r, 'a> {
+  fn new(row_group: &'r RowGroupReader<'a>) -> Self {
+    Self { row_group: row_group, schema_descr: row_group.metadata().schema_descr() }
+  }
+
+  /// Builds the reader of the rows of `schema`, which is part of the schema of the row
+  /// group. The leaves are positioned at their first triplet.
+  fn build(&self, schema: &Type) -> Result<Reader<'a>> {
+    let mut path = vec!();
+    let mut readers = vec!();
+    for field in schema.get_fields() {
+      path.push(field.name().to_owned());
+      readers.push((field.name().to_owned(), self.build_field(field, 0, 0, &mut path)?));
+      path.pop();
+    }
+    let mut reader = Reader::GroupReader(readers);
+    reader.advance_columns()?;
+    Ok(reader)
+  }
+
+  /// Builds the reader of `field`, taking its repetition into account. `def_level` and
+  /// `rep_level` are the levels of the parent of the field.
+  fn build_field(
+    &self, field: &Type, def_level: i16, rep_level: i16, path: &mut Vec<String>
+  ) -> Result<Reader<'a>> {
+    match field.get_basic_info().repetition() {
+      Repetition::REQUIRED => self.build_value(field, def_level, rep_level, path),
+      Repetition::OPTIONAL => {
+        let reader = self.build_value(field, def_level + 1, rep_level, path)?;
+        Ok(Reader::OptionReader(def_level, Box::new(reader)))
+      },
+      Repetition::REPEATED => {
+        let reader = self.build_value(field, def_level + 1, rep_level + 1, path)?;
+        Ok(Reader::RepeatedReader(def_level, rep_level, Box::new(reader)))
+      }
+    }
+  }
+
+  /// Builds the reader of the values of `tp`, whose levels are `def_level` and
+  /// `rep_level`.
+  fn build_value(
+    &self, tp: &Type, def_level: i16, rep_level: i16, path: &mut Vec<String>
+  ) -> Result<Reader<'a>> {
+    match value_type(tp)? {
+      NestedType::Primitive(_) => {
+        let column_path = ColumnPath::new(path.clone());
+        let i = self.schema_descr.column_index(&column_path).ok_or_else(|| general_err!(
+          "Column {} does not exist in the file", column_path.string()))?;
+        let column = self.row_group.get_column_reader(i)?;
+        let triplets =
+          TripletIter::new(self.schema_descr.column(i), column, DEFAULT_BATCH_SIZE);
+        Ok(Reader::PrimitiveReader(triplets))
+      },
+      NestedType::Struct(group) => {
+        let mut readers = vec!();
+        for field in group.get_fields() {
+          path.push(field.name().to_owned());
+          let reader = self.build_field(field, def_level, rep_level, path)?;
+          readers.push((field.name().to_owned(), reader));
+          path.pop();
+        }
+        Ok(Reader::GroupReader(readers))
+      },
+      NestedType::List(list) => {
+        path.push(list.repeated.name().to_owned());
+        let element = if list.is_two_level() {
+          self.build_value(list.repeated, def_level + 1, rep_level + 1, path)?
+        } else {
+          path.push(list.element.name().to_owned());
+          let element =
+            self.build_field(list.element, def_level + 1, rep_level + 1, path)?;
+          path.pop();
+          element
+        };
+        path.pop();
+        Ok(Reader::RepeatedReader(def_level, rep_level, Box::new(element)))
+      },
+      NestedType::Map(map) => {
+        path.push(map.repeated.name().to_owned());
+        path.push(map.key.name().to_owned());
+        let keys = self.build_field(map.key, def_level + 1, rep_level + 1, path)?;
+        path.pop();
+        let values = match map.value {
+          Some(value) => {
+            path.push(value.name().to_owned());
+            let values = self.build_field(value, def_level + 1, rep_level + 1, path)?;
+            path.pop();
+            Some(Box::new(values))
+          },
+          None => None
+        };
+        path.pop();
+        Ok(Reader::KeyValueReader(def_level, rep_level, Box::new(keys), values))
+      }
+    }
+  }
+}
+
+/// Iterator over the rows of a file or of a row group.
+///
+/// If a projection is given, only its fields are read. The projection must be part of
+/// the schema of the file, see `Type::check_contains`, and the fields of a row are in
+/// the order of the projection.
+pub struct RowIter<'a> {
+  file_reader: Option<&'a FileReader>,
+  projection: Option<Type>,
+  current_row_group: usize,
+  num_row_groups: usize,
+  tree: Option<Reader<'a>>
+}
+
+impl<'a> RowIter<'a> {
+  /// Creates an iterator over the rows of all row groups of `reader`.
+  pub fn from_file(projection: Option<Type>, reader: &'a FileReader) -> Result<Self> {
+    check_projection(&projection, reader.metadata().file_metadata().schema_descr())?;
+    Ok(Self {
+      file_reader: Some(reader),
+      projection: projection,
+      current_row_group: 0,
+      num_row_groups: reader.num_row_groups(),
+      tree: None
+    })
+  }
+
+  /// Creates an iterator over the rows of the row group `reader`.
+  pub fn from_row_group(
+    projection: Option<Type>, reader: &RowGroupReader<'a>
+  ) -> Result<Self> {
+    let tree = {
+      let schema_descr = reader.metadata().schema_descr();
+      check_projection(&projection, schema_descr)?;
+      let schema = projection.as_ref().unwrap_or(schema_descr.root_schema());
+      TreeBuilder::new(reader).build(schema)?
+    };
+    Ok(Self {
+      file_reader: None,
+      projection: projection,
+      current_row_group: 0,
+      num_row_groups: 0,
+      tree: Some(tree)
+    })
+  }
+}
+
+impl<'a> Iterator for RowIter<'a> {
+  type Item = Result<Row>;
+
+  fn next(&mut self) -> Option<Result<Row>> {
+    loop {
+      let result = match self.tree {
+        Some(ref mut tree) => if tree.has_next() { Some(read_row(tree)) } else { None },
+        None => None
+      };
+      match result {
+        Some(Ok(row)) => return Some(Ok(row)),
+        Some(Err(e)) => {
+          // The leaves may be out of sync, stop reading
+          self.tree = None;
+          self.current_row_group = self.num_row_groups;
+          return Some(Err(e));
+        },
+        None => {}
+      }
+
+      let file_reader = match self.file_reader {
+        Some(reader) if self.current_row_group < self.num_row_groups => reader,
+        _ => return None
+      };
+      let tree = {
+        let schema = self.projection.as_ref()
+          .unwrap_or(file_reader.metadata().file_metadata().schema());
+        file_reader.get_row_group(self.current_row_group)
+          .and_then(|row_group| TreeBuilder::new(&*row_group).build(schema))
+      };
+      self.current_row_group += 1;
+      match tree {
+        Ok(tree) => self.tree = Some(tree),
+        Err(e) => {
+          self.tree = None;
+          self.current_row_group = self.num_row_groups;
+          return Some(Err(e));
+        }
+      }
+    }
+  }
+}
+
+fn read_row(tree: &mut Reader) -> Result<Row> {
+  match tree.read_field()? {
+    Field::Group(row) => Ok(row),
+    field => Err(general_err!("Expected a row, found {}", field))
+  }
+}
+
+fn check_projection(
+  projection: &Option<Type>, schema_descr: &SchemaDescriptor
+) -> Result<()> {
+  if let Some(ref projection) = *projection {
+    if !schema_descr.root_schema().check_contains(projection) {
+      return Err(general_err!("Projection is not a part of the schema of the file"));
+    }
+  }
+  Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use data_type::Timestamp;
+  use file::reader::SerializedFileReader;
+  use schema::parser::parse_message_type;
+  use util::test_common::get_test_file;
+
+  fn read_rows(file_name: &str, projection: Option<&str>) -> Result<Vec<Row>> {
+    let reader = SerializedFileReader::new(get_test_file(file_name))?;
+    let projection = projection.map(|p| parse_message_type(p).unwrap());
+    let rows = reader.get_row_iter(projection)?;
+    rows.collect()
+  }
+
+  #[test]
+  fn test_read_rows() {
+    let rows = read_rows("alltypes_plain.parquet", None).unwrap();
+    assert_eq!(rows.len(), 8);
+    let ids: Vec<String> =
+      rows.iter().map(|r| r.get("id").unwrap().to_string()).collect();
+    assert_eq!(ids, vec!["4", "5", "6", "7", "2", "3", "0", "1"]);
+    assert_eq!(
+      rows[1].get("timestamp_col"),
+      Some(&Field::Timestamp(Timestamp::from_nanos(1235865660000 * 1_000_000))));
+
+    let mut first = rows[0].clone().into_fields();
+    first.pop();
+    assert_eq!(
+      Row::new(first).to_string(),
+      "{id: 4, bool_col: true, tinyint_col: 0, smallint_col: 0, int_col: 0, \
+       bigint_col: 0, float_col: 0.0, double_col: 0.0, \
+       date_string_col: [48, 51, 47, 48, 49, 47, 48, 57], string_col: [48]}");
+  }
+
+  #[test]
+  fn test_read_nested_rows() {
+    let rows = read_rows("test_datapage_v2.snappy.parquet", None).unwrap();
+    let rows: Vec<String> = rows.iter().map(|r| r.to_string()).collect();
+    assert_eq!(rows, vec![
+      "{a: \"abc\", b: 1, c: 2.0, d: true, e: [1, 2, 3]}",
+      "{a: \"abc\", b: 2, c: 3.0, d: true, e: null}",
+      "{a: \"abc\", b: 3, c: 4.0, d: true, e: null}",
+      "{a: null, b: 4, c: 5.0, d: false, e: [1, 2, 3]}",
+      "{a: \"abc\", b: 5, c: 2.0, d: true, e: [1, 2]}"
+    ]);
+  }
+
+  #[test]
+  fn test_read_projected_rows() {
+    let projection = "
+      message spark_schema {
+        OPTIONAL group e (LIST) {
+          REPEATED group list {
+            REQUIRED INT32 element;
+          }
+        }
+        REQUIRED INT32 b;
+      }
+    ";
+    let rows = read_rows("test_datapage_v2.snappy.parquet", Some(projection)).unwrap();
+    let rows: Vec<String> = rows.iter().map(|r| r.to_string()).collect();
+    assert_eq!(rows, vec![
+      "{e: [1, 2, 3], b: 1}",
+      "{e: null, b: 2}",
+      "{e: null, b: 3}",
+      "{e: [1, 2, 3], b: 4}",
+      "{e: [1, 2], b: 5}"
+    ]);
+
+    let projection = "message spark_schema { REQUIRED INT64 b; }";
+    let result = read_rows("test_datapage_v2.snappy.parquet", Some(projection));
+    assert!(result.is_err());
+  }
+
+  #[test]
+  fn test_read_row_group_rows() {
+    let file = get_test_file("alltypes_plain.parquet");
+    let reader = SerializedFileReader::new(file).unwrap();
+    let row_group = reader.get_row_group(0).unwrap();
+    let projection = parse_message_type("message schema { OPTIONAL INT32 id; }").unwrap();
+    let rows: Vec<Row> = row_group.get_row_iter(Some(projection)).unwrap()
+      .map(|r| r.unwrap())
+      .collect();
+    assert_eq!(rows.len(), 8);
+    assert_eq!(rows[0].to_string(), "{id: 4}");
+  }
+}
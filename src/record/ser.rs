@@ -0,0 +1,822 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Serialization of Rust types into rows with serde, and writing of rows to files.
+//!
+//! Structs serialize as groups, sequences and tuples as lists, maps as maps, `None` and
+//! `()` as null values, and unit enum variants as strings. Other enum variants are not
+//! supported. For example:
+//!
+//! ```ignore
+//! #[derive(Serialize)]
+//! struct Record {
+//!   id: i32,
+//!   name: Option<String>,
+//!   tags: Vec<String>,
+//!   attributes: HashMap<String, i64>
+//! }
+//!
+//! let records: Vec<Record> = ...;
+//! write_records(File::create(path)?, &records)?;
+//! ```
+//!
+//! `write_records` infers the schema of the file from the records with `infer_schema`.
+//! To write with a given schema, convert records with `to_row` and write them to a
+//! `SerializedFileWriter` with `write_rows`, or with a `RowWriter`.
+
+use std::fmt::Display;
+use std::fs::File;
+use std::rc::Rc;
+
+use serde::ser::{self, Impossible, Serialize};
+
+use basic::{LogicalAnnotation, LogicalType, Repetition, TimeUnit, Type as PhysicalType};
+use data_type::ByteArray;
+use errors::{ParquetError, Result};
+use file::writer::{SerializedFileWriter, WriterOptions};
+use record::api::{Field, Row};
+use record::writer::RowWriter;
+use schema::types::{Type, TypePtr};
+
+impl ser::Error for ParquetError {
+  fn custom<T: Display>(msg: T) -> Self {
+    ParquetError::General(msg.to_string())
+  }
+}
+
+/// Serializes `value`, which must serialize as a struct or a map with string keys, to
+/// a row.
+pub fn to_row<T: Serialize + ?Sized>(value: &T) -> Result<Row> {
+  match to_field(value)? {
+    Field::Group(row) => Ok(row),
+    Field::Map(entries) => {
+      let mut fields = Vec::with_capacity(entries.len());
+      for (key, value) in entries {
+        match key {
+          Field::Str(name) => fields.push((name, value)),
+          key => return Err(general_err!("Cannot use {} as the name of a field", key))
+        }
+      }
+      Ok(Row::new(fields))
+    },
+    field => Err(general_err!("Cannot convert {} to a row", field))
+  }
+}
+
+/// Serializes `value` to a field.
+pub fn to_field<T: Serialize + ?Sized>(value: &T) -> Result<Field> {
+  value.serialize(Serializer)
+}
+
+/// Writes `records` to `file` as a single row group, with a schema inferred from the
+/// records by `infer_schema`.
+pub fn write_records<T: Serialize>(file: File, records: &[T]) -> Result<()> {
+  let rows = records.iter().map(to_row).collect::<Result<Vec<_>>>()?;
+  let schema = infer_schema(&rows)?;
+  let options = WriterOptions::new();
+  let mut writer = SerializedFileWriter::new(file, Rc::new(schema), options)?;
+  write_rows(&mut writer, &rows)?;
+  writer.close()
+}
+
+/// Writes `rows` to `writer` as a single row group. Rows must match the schema of the
+/// file: fields that are not in the schema are an error, and missing fields are null.
+pub fn write_rows(writer: &mut SerializedFileWriter, rows: &[Row]) -> Result<()> {
+  let mut row_writer = RowWriter::new(writer.schema_descr().clone());
+  for row in rows {
+    row_writer.write(row)?;
+  }
+  let mut row_group = writer.next_row_group();
+  row_writer.write_to_row_group(&mut row_group)?;
+  writer.write_row_group(row_group)
+}
+
+// ----------------------------------------------------------------------
+// Serializer
+
+/// A serde `Serializer` of values to fields.
+pub struct Serializer;
+
+impl ser::Serializer for Serializer {
+  type Ok = Field;
+  type Error = ParquetError;
+
+  type SerializeSeq = ListSerializer;
+  type SerializeTuple = ListSerializer;
+  type SerializeTupleStruct = ListSerializer;
+  type SerializeTupleVariant = Impossible<Field, ParquetError>;
+  type SerializeMap = MapSerializer;
+  type SerializeStruct = GroupSerializer;
+  type SerializeStructVariant = Impossible<Field, ParquetError>;
+
+  fn serialize_bool(self, v: bool) -> Result<Field> {
+    Ok(Field::Bool(v))
+  }
+
+  fn serialize_i8(self, v: i8) -> Result<Field> {
+    Ok(Field::Int(v as i32))
+  }
+
+  fn serialize_i16(self, v: i16) -> Result<Field> {
+    Ok(Field::Int(v as i32))
+  }
+
+  fn serialize_i32(self, v: i32) -> Result<Field> {
+    Ok(Field::Int(v))
+  }
+
+  fn serialize_i64(self, v: i64) -> Result<Field> {
+    Ok(Field::Long(v))
+  }
+
+  fn serialize_u8(self, v: u8) -> Result<Field> {
+    Ok(Field::Int(v as i32))
+  }
+
+  fn serialize_u16(self, v: u16) -> Result<Field> {
+    Ok(Field::Int(v as i32))
+  }
+
+  fn serialize_u32(self, v: u32) -> Result<Field> {
+    Ok(Field::Long(v as i64))
+  }
+
+  fn serialize_u64(self, v: u64) -> Result<Field> {
+    Ok(Field::ULong(v))
+  }
+
+  fn serialize_f32(self, v: f32) -> Result<Field> {
+    Ok(Field::Float(v))
+  }
+
+  fn serialize_f64(self, v: f64) -> Result<Field> {
+    Ok(Field::Double(v))
+  }
+
+  fn serialize_char(self, v: char) -> Result<Field> {
+    Ok(Field::Str(v.to_string()))
+  }
+
+  fn serialize_str(self, v: &str) -> Result<Field> {
+    Ok(Field::Str(v.to_owned()))
+  }
+
+  fn serialize_bytes(self, v: &[u8]) -> Result<Field> {
+    Ok(Field::Bytes(ByteArray::from(v.to_vec())))
+  }
+
+  fn serialize_none(self) -> Result<Field> {
+    Ok(Field::Null)
+  }
+
+  fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Field> {
+    value.serialize(self)
+  }
+
+  fn serialize_unit(self) -> Result<Field> {
+    Ok(Field::Null)
+  }
+
+  fn serialize_unit_struct(self, _name: &'static str) -> Result<Field> {
+    Ok(Field::Null)
+  }
+
+  fn serialize_unit_variant(
+    self, _name: &'static str, _index: u32, variant: &'static str
+  ) -> Result<Field> {
+    Ok(Field::Str(variant.to_owned()))
+  }
+
+  fn serialize_newtype_struct<T: Serialize + ?Sized>(
+    self, _name: &'static str, value: &T
+  ) -> Result<Field> {
+    value.serialize(self)
+  }
+
+  fn serialize_newtype_variant<T: Serialize + ?Sized>(
+    self, name: &'static str, _index: u32, variant: &'static str, _value: &T
+  ) -> Result<Field> {
+    Err(unsupported_variant(name, variant))
+  }
+
+  fn serialize_seq(self, len: Option<usize>) -> Result<ListSerializer> {
+    Ok(ListSerializer { elements: Vec::with_capacity(len.unwrap_or(0)) })
+  }
+
+  fn serialize_tuple(self, len: usize) -> Result<ListSerializer> {
+    self.serialize_seq(Some(len))
+  }
+
+  fn serialize_tuple_struct(
+    self, _name: &'static str, len: usize
+  ) -> Result<ListSerializer> {
+    self.serialize_seq(Some(len))
+  }
+
+  fn serialize_tuple_variant(
+    self, name: &'static str, _index: u32, variant: &'static str, _len: usize
+  ) -> Result<Self::SerializeTupleVariant> {
+    Err(unsupported_variant(name, variant))
+  }
+
+  fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer> {
+    Ok(MapSerializer { entries: Vec::with_capacity(len.unwrap_or(0)), key: None })
+  }
+
+  fn serialize_struct(self, _name: &'static str, len: usize) -> Result<GroupSerializer> {
+    Ok(GroupSerializer { fields: Vec::with_capacity(len) })
+  }
+
+  fn serialize_struct_variant(
+    self, name: &'static str, _index: u32, variant: &'static str, _len: usize
+  ) -> Result<Self::SerializeStructVariant> {
+    Err(unsupported_variant(name, variant))
+  }
+}
+
+fn unsupported_variant(name: &str, variant: &str) -> ParquetError {
+  general_err!("Cannot serialize variant {} of enum {}: only unit variants are supported",
+    variant, name)
+}
+
+/// Serializer of sequences and tuples to lists.
+pub struct ListSerializer {
+  elements: Vec<Field>
+}
+
+impl ser::SerializeSeq for ListSerializer {
+  type Ok = Field;
+  type Error = ParquetError;
+
+  fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+    self.elements.push(to_field(value)?);
+    Ok(())
+  }
+
+  fn end(self) -> Result<Field> {
+    Ok(Field::List(self.elements))
+  }
+}
+
+impl ser::SerializeTuple for ListSerializer {
+  type Ok = Field;
+  type Error = ParquetError;
+
+  fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+    ser::SerializeSeq::serialize_element(self, value)
+  }
+
+  fn end(self) -> Result<Field> {
+    ser::SerializeSeq::end(self)
+  }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer {
+  type Ok = Field;
+  type Error = ParquetError;
+
+  fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+    ser::SerializeSeq::serialize_element(self, value)
+  }
+
+  fn end(self) -> Result<Field> {
+    ser::SerializeSeq::end(self)
+  }
+}
+
+/// Serializer of maps.
+pub struct MapSerializer {
+  entries: Vec<(Field, Field)>,
+  key: Option<Field>
+}
+
+impl ser::SerializeMap for MapSerializer {
+  type Ok = Field;
+  type Error = ParquetError;
+
+  fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+    self.key = Some(to_field(key)?);
+    Ok(())
+  }
+
+  fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+    let key = self.key.take()
+      .ok_or_else(|| general_err!("Map value serialized before its key"))?;
+    self.entries.push((key, to_field(value)?));
+    Ok(())
+  }
+
+  fn end(self) -> Result<Field> {
+    Ok(Field::Map(self.entries))
+  }
+}
+
+/// Serializer of structs to groups.
+pub struct GroupSerializer {
+  fields: Vec<(String, Field)>
+}
+
+impl ser::SerializeStruct for GroupSerializer {
+  type Ok = Field;
+  type Error = ParquetError;
+
+  fn serialize_field<T: Serialize + ?Sized>(
+    &mut self, key: &'static str, value: &T
+  ) -> Result<()> {
+    self.fields.push((key.to_owned(), to_field(value)?));
+    Ok(())
+  }
+
+  fn end(self) -> Result<Field> {
+    Ok(Field::Group(Row::new(self.fields)))
+  }
+}
+
+// ----------------------------------------------------------------------
+// Schema inference
+
+/// Infers a schema from sample rows.
+///
+/// Fields are in the order in which they first appear in the rows. A field is optional
+/// if it is null or missing in any row, and required otherwise. Lists are 3-level lists
+/// with optional elements, and maps have optional values. Fields that are always null
+/// or in empty lists and maps are INT32 columns annotated UNKNOWN.
+pub fn infer_schema(rows: &[Row]) -> Result<Type> {
+  if rows.is_empty() {
+    return Err(general_err!("Cannot infer a schema without sample rows"));
+  }
+  let mut root = Node::new();
+  for row in rows {
+    root.merge_row("schema", row)?;
+  }
+  match root.shape {
+    Shape::Group(children) => build_group("schema", children).build(),
+    _ => unreachable!()
+  }
+}
+
+/// The inferred type of the values of a field.
+enum Shape {
+  /// Only nulls were seen.
+  Null,
+  Leaf(Leaf),
+  List(Box<Node>),
+  Map(Box<Node>, Box<Node>),
+  Group(Vec<(String, Node)>)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Leaf {
+  Bool,
+  Int,
+  Long,
+  ULong,
+  Float,
+  Double,
+  Str,
+  Bytes,
+  Decimal { precision: i32, scale: i32 },
+  Date,
+  Time(TimeUnit),
+  Timestamp(TimeUnit, bool),
+  Interval
+}
+
+struct Node {
+  shape: Shape,
+  optional: bool,
+  // Number of non-null values seen
+  num_values: usize
+}
+
+impl Node {
+  fn new() -> Self {
+    Self { shape: Shape::Null, optional: false, num_values: 0 }
+  }
+
+  fn merge(&mut self, name: &str, value: &Field) -> Result<()> {
+    let leaf = match *value {
+      Field::Null => {
+        self.optional = true;
+        return Ok(());
+      },
+      Field::Group(ref row) => return self.merge_row(name, row),
+      Field::List(ref elements) => {
+        if let Shape::Null = self.shape {
+          self.shape = Shape::List(Box::new(Node::new()));
+        }
+        match self.shape {
+          Shape::List(ref mut element) => {
+            for e in elements {
+              element.merge("element", e)?;
+            }
+          },
+          _ => return Err(incompatible_values(name, value))
+        }
+        self.num_values += 1;
+        return Ok(());
+      },
+      Field::Map(ref entries) => {
+        if let Shape::Null = self.shape {
+          self.shape = Shape::Map(Box::new(Node::new()), Box::new(Node::new()));
+        }
+        match self.shape {
+          Shape::Map(ref mut key, ref mut map_value) => {
+            for &(ref k, ref v) in entries {
+              if *k == Field::Null {
+                return Err(general_err!("Keys of map {} cannot be null", name));
+              }
+              key.merge("key", k)?;
+              map_value.merge("value", v)?;
+            }
+          },
+          _ => return Err(incompatible_values(name, value))
+        }
+        self.num_values += 1;
+        return Ok(());
+      },
+      Field::Bool(_) => Leaf::Bool,
+      Field::Int(_) => Leaf::Int,
+      Field::Long(_) => Leaf::Long,
+      Field::ULong(_) => Leaf::ULong,
+      Field::Float(_) => Leaf::Float,
+      Field::Double(_) => Leaf::Double,
+      Field::Str(_) => Leaf::Str,
+      Field::Bytes(_) => Leaf::Bytes,
+      Field::Decimal(ref d) => {
+        Leaf::Decimal { precision: d.precision(), scale: d.scale() }
+      },
+      Field::Date(_) => Leaf::Date,
+      Field::Time(ref t) => Leaf::Time(t.unit()),
+      Field::Timestamp(ref ts) => Leaf::Timestamp(ts.unit(), ts.is_adjusted_to_utc()),
+      Field::Interval(_) => Leaf::Interval
+    };
+    let merged = match self.shape {
+      Shape::Null => Some(leaf),
+      Shape::Leaf(current) => merge_leaves(current, leaf),
+      _ => None
+    };
+    match merged {
+      Some(merged) => self.shape = Shape::Leaf(merged),
+      None => return Err(incompatible_values(name, value))
+    }
+    self.num_values += 1;
+    Ok(())
+  }
+
+  fn merge_row(&mut self, name: &str, row: &Row) -> Result<()> {
+    if let Shape::Null = self.shape {
+      self.shape = Shape::Group(vec!());
+    }
+    let num_values = self.num_values;
+    match self.shape {
+      Shape::Group(ref mut children) => {
+        for &mut (ref child_name, ref mut child) in children.iter_mut() {
+          if row.get(child_name).is_none() {
+            child.optional = true;
+          }
+        }
+        for &(ref field_name, ref value) in row.fields() {
+          let index = match children.iter().position(|&(ref n, _)| n == field_name) {
+            Some(index) => index,
+            None => {
+              // Fields missing from previous rows are optional
+              let mut child = Node::new();
+              child.optional = num_values > 0;
+              children.push((field_name.clone(), child));
+              children.len() - 1
+            }
+          };
+          children[index].1.merge(field_name, value)?;
+        }
+      },
+      _ => return Err(general_err!("Incompatible values for field {}: {}", name, row))
+    }
+    self.num_values += 1;
+    Ok(())
+  }
+
+  fn build(self, name: &str) -> Result<TypePtr> {
+    let repetition =
+      if self.optional { Repetition::OPTIONAL } else { Repetition::REQUIRED };
+    self.build_with_repetition(name, repetition)
+  }
+
+  fn build_with_repetition(self, name: &str, repetition: Repetition) -> Result<TypePtr> {
+    let tp = match self.shape {
+      Shape::Null => {
+        Type::primitive_type_builder(name, PhysicalType::INT32)
+          .with_repetition(repetition)
+          .with_logical_annotation(Some(LogicalAnnotation::UNKNOWN))
+          .build()?
+      },
+      Shape::Leaf(leaf) => build_leaf(name, leaf, repetition)?,
+      Shape::List(element) => {
+        let mut repeated =
+          vec![(*element).build_with_repetition("element", Repetition::OPTIONAL)?];
+        let mut fields = vec![Rc::new(
+          Type::group_type_builder("list")
+            .with_repetition(Repetition::REPEATED)
+            .with_fields(&mut repeated)
+            .build()?)];
+        Type::group_type_builder(name)
+          .with_repetition(repetition)
+          .with_logical_type(LogicalType::LIST)
+          .with_fields(&mut fields)
+          .build()?
+      },
+      Shape::Map(key, value) => {
+        let mut key_value = vec![
+          (*key).build_with_repetition("key", Repetition::REQUIRED)?,
+          (*value).build_with_repetition("value", Repetition::OPTIONAL)?
+        ];
+        let mut fields = vec![Rc::new(
+          Type::group_type_builder("key_value")
+            .with_repetition(Repetition::REPEATED)
+            .with_fields(&mut key_value)
+            .build()?)];
+        Type::group_type_builder(name)
+          .with_repetition(repetition)
+          .with_logical_type(LogicalType::MAP)
+          .with_fields(&mut fields)
+          .build()?
+      },
+      Shape::Group(children) => {
+        build_group(name, children).with_repetition(repetition).build()?
+      }
+    };
+    Ok(Rc::new(tp))
+  }
+}
+
+fn build_group(name: &str, children: Vec<(String, Node)>) -> GroupBuilder {
+  GroupBuilder { name: name.to_owned(), children: children, repetition: None }
+}
+
+/// Builds a group from inferred fields, which is only possible once all of them are
+/// known.
+struct GroupBuilder {
+  name: String,
+  children: Vec<(String, Node)>,
+  repetition: Option<Repetition>
+}
+
+impl GroupBuilder {
+  fn with_repetition(mut self, repetition: Repetition) -> Self {
+    self.repetition = Some(repetition);
+    self
+  }
+
+  fn build(self) -> Result<Type> {
+    if self.children.is_empty() {
+      return Err(general_err!("Cannot infer the fields of empty group {}", self.name));
+    }
+    let mut fields = vec!();
+    for (name, child) in self.children {
+      fields.push(child.build(&name)?);
+    }
+    let mut builder = Type::group_type_builder(&self.name).with_fields(&mut fields);
+    if let Some(repetition) = self.repetition {
+      builder = builder.with_repetition(repetition);
+    }
+    builder.build()
+  }
+}
+
+fn build_leaf(name: &str, leaf: Leaf, repetition: Repetition) -> Result<Type> {
+  let (physical_type, logical_type, annotation) = match leaf {
+    Leaf::Bool => (PhysicalType::BOOLEAN, LogicalType::NONE, None),
+    Leaf::Int => (PhysicalType::INT32, LogicalType::NONE, None),
+    Leaf::Long => (PhysicalType::INT64, LogicalType::NONE, None),
+    Leaf::ULong => (PhysicalType::INT64, LogicalType::UINT_64, None),
+    Leaf::Float => (PhysicalType::FLOAT, LogicalType::NONE, None),
+    Leaf::Double => (PhysicalType::DOUBLE, LogicalType::NONE, None),
+    Leaf::Str => (PhysicalType::BYTE_ARRAY, LogicalType::UTF8, None),
+    Leaf::Bytes => (PhysicalType::BYTE_ARRAY, LogicalType::NONE, None),
+    Leaf::Decimal { precision, scale } => {
+      (PhysicalType::BYTE_ARRAY, LogicalType::NONE,
+       Some(LogicalAnnotation::DECIMAL { precision: precision, scale: scale }))
+    },
+    Leaf::Date => (PhysicalType::INT32, LogicalType::DATE, None),
+    Leaf::Time(unit) => {
+      let physical_type =
+        if unit == TimeUnit::MILLIS { PhysicalType::INT32 } else { PhysicalType::INT64 };
+      (physical_type, LogicalType::NONE,
+       Some(LogicalAnnotation::TIME { is_adjusted_to_utc: true, unit: unit }))
+    },
+    Leaf::Timestamp(unit, utc) => {
+      (PhysicalType::INT64, LogicalType::NONE,
+       Some(LogicalAnnotation::TIMESTAMP { is_adjusted_to_utc: utc, unit: unit }))
+    },
+    Leaf::Interval => (PhysicalType::FIXED_LEN_BYTE_ARRAY, LogicalType::INTERVAL, None)
+  };
+  let mut builder = Type::primitive_type_builder(name, physical_type)
+    .with_repetition(repetition)
+    .with_logical_type(logical_type)
+    .with_logical_annotation(annotation);
+  if leaf == Leaf::Interval {
+    builder = builder.with_length(12);
+  }
+  builder.build()
+}
+
+/// Returns the type of values of both `a` and `b`, widening integers and floating
+/// point numbers, if any.
+fn merge_leaves(a: Leaf, b: Leaf) -> Option<Leaf> {
+  match (a, b) {
+    (a, b) if a == b => Some(a),
+    (Leaf::Int, Leaf::Long) | (Leaf::Long, Leaf::Int) => Some(Leaf::Long),
+    (Leaf::Float, Leaf::Double) | (Leaf::Double, Leaf::Float) => Some(Leaf::Double),
+    (Leaf::Decimal { precision: p1, scale: s1 },
+     Leaf::Decimal { precision: p2, scale: s2 }) if s1 == s2 => {
+      Some(Leaf::Decimal { precision: p1.max(p2), scale: s1 })
+    },
+    _ => None
+  }
+}
+
+fn incompatible_values(name: &str, value: &Field) -> ParquetError {
+  general_err!("Incompatible values for field {}, including {}", name, value)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::collections::BTreeMap;
+
+  use file::reader::{FileReader, SerializedFileReader};
+  use record::de::IntoRowIter;
+  use schema::parser::parse_message_type;
+  use util::test_common::get_temp_file_path;
+
+  #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+  struct Location {
+    lat: f64,
+    lon: f64
+  }
+
+  #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+  struct Record {
+    id: i32,
+    name: Option<String>,
+    tags: Vec<String>,
+    scores: BTreeMap<String, Option<i64>>,
+    location: Option<Location>,
+    count: u64,
+    flag: bool
+  }
+
+  fn records() -> Vec<Record> {
+    (0..100).map(|i| {
+      let mut scores = BTreeMap::new();
+      for j in 0..i % 3 {
+        scores.insert(format!("s{}", j), if j == 1 { None } else { Some(i as i64 * j) });
+      }
+      Record {
+        id: i,
+        name: if i % 4 == 0 { None } else { Some(format!("name {}", i)) },
+        tags: (0..i % 5).map(|j| format!("t{}", j)).collect(),
+        scores: scores,
+        location: if i % 2 == 0 {
+          Some(Location { lat: i as f64, lon: -i as f64 })
+        } else {
+          None
+        },
+        count: u64::max_value() - i as u64,
+        flag: i % 3 == 0
+      }
+    }).collect()
+  }
+
+  #[test]
+  fn test_to_row() {
+    let row = to_row(&records()[5]).unwrap();
+    assert_eq!(
+      row.to_string(),
+      "{id: 5, name: \"name 5\", tags: [], scores: {\"s0\" -> 0, \"s1\" -> null}, \
+       location: null, count: 18446744073709551610, flag: false}");
+    assert!(to_row(&5).is_err());
+    assert!(to_field(&Some(vec![(1, "a")])).is_ok());
+  }
+
+  #[test]
+  fn test_infer_schema() {
+    let rows: Vec<Row> = records().iter().map(|r| to_row(r).unwrap()).collect();
+    let schema = infer_schema(&rows).unwrap();
+    let expected = parse_message_type("
+      message schema {
+        REQUIRED INT32 id;
+        OPTIONAL BYTE_ARRAY name (UTF8);
+        REQUIRED GROUP tags (LIST) {
+          REPEATED GROUP list {
+            OPTIONAL BYTE_ARRAY element (UTF8);
+          }
+        }
+        REQUIRED GROUP scores (MAP) {
+          REPEATED GROUP key_value {
+            REQUIRED BYTE_ARRAY key (UTF8);
+            OPTIONAL INT64 value;
+          }
+        }
+        OPTIONAL GROUP location {
+          REQUIRED DOUBLE lat;
+          REQUIRED DOUBLE lon;
+        }
+        REQUIRED INT64 count (UINT_64);
+        REQUIRED BOOLEAN flag;
+      }
+    ").unwrap();
+    assert_eq!(schema, expected);
+
+    // Integers are widened, and missing fields are optional
+    let rows = vec![
+      Row::new(vec![("a".to_owned(), Field::Int(1)), ("b".to_owned(), Field::Null)]),
+      Row::new(vec![
+        ("a".to_owned(), Field::Long(1)), ("c".to_owned(), Field::Float(1.0))
+      ])
+    ];
+    let expected = parse_message_type("
+      message schema {
+        REQUIRED INT64 a;
+        OPTIONAL INT32 b;
+        OPTIONAL FLOAT c;
+      }
+    ").unwrap();
+    let schema = infer_schema(&rows).unwrap();
+    assert_eq!(schema.get_fields()[0], expected.get_fields()[0]);
+    assert_eq!(
+      schema.get_fields()[1].get_basic_info().logical_annotation(),
+      &Some(LogicalAnnotation::UNKNOWN));
+    assert_eq!(schema.get_fields()[2], expected.get_fields()[2]);
+
+    let rows = vec![
+      Row::new(vec![("a".to_owned(), Field::Int(1))]),
+      Row::new(vec![("a".to_owned(), Field::Str("1".to_owned()))])
+    ];
+    assert!(infer_schema(&rows).is_err());
+    assert!(infer_schema(&[]).is_err());
+  }
+
+  #[test]
+  fn test_write_read_records() {
+    let path = get_temp_file_path("test_write_records.parquet");
+    let records = records();
+    write_records(File::create(&path).unwrap(), &records).unwrap();
+
+    let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+    assert_eq!(reader.metadata().file_metadata().num_rows(), 100);
+    let read: Vec<Record> = reader.into_iter::<Record>().map(|r| r.unwrap()).collect();
+    assert_eq!(read, records);
+  }
+
+  #[test]
+  fn test_write_rows_with_schema() {
+    let schema = parse_message_type("
+      message schema {
+        REQUIRED INT32 a;
+        REPEATED INT64 b;
+      }
+    ").unwrap();
+    let path = get_temp_file_path("test_write_rows.parquet");
+    let mut writer = SerializedFileWriter::new(
+      File::create(&path).unwrap(), Rc::new(schema), WriterOptions::new()).unwrap();
+    let rows = vec![
+      Row::new(vec![("a".to_owned(), Field::Int(1))]),
+      Row::new(vec![
+        ("a".to_owned(), Field::Int(2)),
+        ("b".to_owned(), Field::List(vec![Field::Long(3), Field::Int(4)]))
+      ])
+    ];
+    write_rows(&mut writer, &rows).unwrap();
+
+    let invalid_rows = vec![
+      Row::new(vec![("b".to_owned(), Field::List(vec![]))]),
+      Row::new(vec![("a".to_owned(), Field::Int(1)), ("c".to_owned(), Field::Int(1))]),
+      Row::new(vec![("a".to_owned(), Field::Str("1".to_owned()))])
+    ];
+    for row in invalid_rows {
+      assert!(write_rows(&mut writer, &[row]).is_err());
+    }
+    writer.close().unwrap();
+
+    let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+    let rows: Vec<String> =
+      reader.get_row_iter(None).unwrap().map(|r| r.unwrap().to_string()).collect();
+    assert_eq!(rows, vec!["{a: 1, b: []}", "{a: 2, b: [3, 4]}"]);
+  }
+}
@@ -0,0 +1,320 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use column::reader::{ColumnReader, ColumnReaderImpl};
+use data_type::*;
+use errors::{ErrorContext, ParquetError, Result};
+use record::api::Field;
+use schema::types::{ColumnDescPtr, ColumnDescriptor};
+
+/// Default number of values buffered by a `TripletIter`.
+pub const DEFAULT_BATCH_SIZE: usize = 1024;
+
+/// Iterator over the (definition level, repetition level, value) triplets of a column,
+/// whose values are converted to `Field`s.
+pub enum TripletIter<'a> {
+  BoolTripletIter(TypedTripletIter<'a, BoolType>),
+  Int32TripletIter(TypedTripletIter<'a, Int32Type>),
+  Int64TripletIter(TypedTripletIter<'a, Int64Type>),
+  Int96TripletIter(TypedTripletIter<'a, Int96Type>),
+  FloatTripletIter(TypedTripletIter<'a, FloatType>),
+  DoubleTripletIter(TypedTripletIter<'a, DoubleType>),
+  ByteArrayTripletIter(TypedTripletIter<'a, ByteArrayType>),
+  FixedLenByteArrayTripletIter(TypedTripletIter<'a, FixedLenByteArrayType>)
+}
+
+impl<'a> TripletIter<'a> {
+  /// Creates a triplet iterator over the values of `reader`, which reads the column
+  /// `descr`, buffering at most `batch_size` values at a time. The iterator has to be
+  /// advanced once with `read_next` before its first triplet can be accessed.
+  pub fn new(descr: ColumnDescPtr, reader: ColumnReader<'a>, batch_size: usize) -> Self {
+    match reader {
+      ColumnReader::BoolColumnReader(r) => {
+        TripletIter::BoolTripletIter(TypedTripletIter::new(descr, r, batch_size))
+      },
+      ColumnReader::Int32ColumnReader(r) => {
+        TripletIter::Int32TripletIter(TypedTripletIter::new(descr, r, batch_size))
+      },
+      ColumnReader::Int64ColumnReader(r) => {
+        TripletIter::Int64TripletIter(TypedTripletIter::new(descr, r, batch_size))
+      },
+      ColumnReader::Int96ColumnReader(r) => {
+        TripletIter::Int96TripletIter(TypedTripletIter::new(descr, r, batch_size))
+      },
+      ColumnReader::FloatColumnReader(r) => {
+        TripletIter::FloatTripletIter(TypedTripletIter::new(descr, r, batch_size))
+      },
+      ColumnReader::DoubleColumnReader(r) => {
+        TripletIter::DoubleTripletIter(TypedTripletIter::new(descr, r, batch_size))
+      },
+      ColumnReader::ByteArrayColumnReader(r) => {
+        TripletIter::ByteArrayTripletIter(TypedTripletIter::new(descr, r, batch_size))
+      },
+      ColumnReader::FixedLenByteArrayColumnReader(r) => {
+        TripletIter::FixedLenByteArrayTripletIter(
+          TypedTripletIter::new(descr, r, batch_size))
+      }
+    }
+  }
+
+  /// Advances to the next triplet. Returns false if the column is exhausted.
+  pub fn read_next(&mut self) -> Result<bool> {
+    match *self {
+      TripletIter::BoolTripletIter(ref mut it) => it.read_next(),
+      TripletIter::Int32TripletIter(ref mut it) => it.read_next(),
+      TripletIter::Int64TripletIter(ref mut it) => it.read_next(),
+      TripletIter::Int96TripletIter(ref mut it) => it.read_next(),
+      TripletIter::FloatTripletIter(ref mut it) => it.read_next(),
+      TripletIter::DoubleTripletIter(ref mut it) => it.read_next(),
+      TripletIter::ByteArrayTripletIter(ref mut it) => it.read_next(),
+      TripletIter::FixedLenByteArrayTripletIter(ref mut it) => it.read_next()
+    }
+  }
+
+  /// Whether there is a current triplet.
+  pub fn has_next(&self) -> bool {
+    match *self {
+      TripletIter::BoolTripletIter(ref it) => it.has_next(),
+      TripletIter::Int32TripletIter(ref it) => it.has_next(),
+      TripletIter::Int64TripletIter(ref it) => it.has_next(),
+      TripletIter::Int96TripletIter(ref it) => it.has_next(),
+      TripletIter::FloatTripletIter(ref it) => it.has_next(),
+      TripletIter::DoubleTripletIter(ref it) => it.has_next(),
+      TripletIter::ByteArrayTripletIter(ref it) => it.has_next(),
+      TripletIter::FixedLenByteArrayTripletIter(ref it) => it.has_next()
+    }
+  }
+
+  pub fn current_def_level(&self) -> i16 {
+    match *self {
+      TripletIter::BoolTripletIter(ref it) => it.current_def_level(),
+      TripletIter::Int32TripletIter(ref it) => it.current_def_level(),
+      TripletIter::Int64TripletIter(ref it) => it.current_def_level(),
+      TripletIter::Int96TripletIter(ref it) => it.current_def_level(),
+      TripletIter::FloatTripletIter(ref it) => it.current_def_level(),
+      TripletIter::DoubleTripletIter(ref it) => it.current_def_level(),
+      TripletIter::ByteArrayTripletIter(ref it) => it.current_def_level(),
+      TripletIter::FixedLenByteArrayTripletIter(ref it) => it.current_def_level()
+    }
+  }
+
+  pub fn current_rep_level(&self) -> i16 {
+    match *self {
+      TripletIter::BoolTripletIter(ref it) => it.current_rep_level(),
+      TripletIter::Int32TripletIter(ref it) => it.current_rep_level(),
+      TripletIter::Int64TripletIter(ref it) => it.current_rep_level(),
+      TripletIter::Int96TripletIter(ref it) => it.current_rep_level(),
+      TripletIter::FloatTripletIter(ref it) => it.current_rep_level(),
+      TripletIter::DoubleTripletIter(ref it) => it.current_rep_level(),
+      TripletIter::ByteArrayTripletIter(ref it) => it.current_rep_level(),
+      TripletIter::FixedLenByteArrayTripletIter(ref it) => it.current_rep_level()
+    }
+  }
+
+  /// Returns the current value as a `Field`, or `Field::Null` if it is null.
+  pub fn current_value(&self) -> Result<Field> {
+    match *self {
+      TripletIter::BoolTripletIter(ref it) => it.current_value(Field::convert_bool),
+      TripletIter::Int32TripletIter(ref it) => it.current_value(Field::convert_int32),
+      TripletIter::Int64TripletIter(ref it) => it.current_value(Field::convert_int64),
+      TripletIter::Int96TripletIter(ref it) => it.current_value(Field::convert_int96),
+      TripletIter::FloatTripletIter(ref it) => it.current_value(Field::convert_float),
+      TripletIter::DoubleTripletIter(ref it) => it.current_value(Field::convert_double),
+      TripletIter::ByteArrayTripletIter(ref it) => {
+        it.current_value(Field::convert_byte_array)
+      },
+      TripletIter::FixedLenByteArrayTripletIter(ref it) => {
+        it.current_value(Field::convert_byte_array)
+      }
+    }
+  }
+}
+
+/// Triplet iterator over the values of a column of type `T`.
+pub struct TypedTripletIter<'a, T: DataType> {
+  descr: ColumnDescPtr,
+  reader: ColumnReaderImpl<'a, T>,
+  batch_size: usize,
+  max_def_level: i16,
+  max_rep_level: i16,
+  values: Vec<T::T>,
+  def_levels: Vec<i16>,
+  rep_levels: Vec<i16>,
+  // Index of the current triplet in the buffers
+  curr_triplet_index: usize,
+  // Number of triplets in the buffers
+  num_triplets: usize,
+  has_next: bool
+}
+
+impl<'a, T: DataType> TypedTripletIter<'a, T> where T: 'static {
+  fn new(
+    descr: ColumnDescPtr, reader: ColumnReaderImpl<'a, T>, batch_size: usize
+  ) -> Self {
+    assert!(batch_size > 0, "Batch size must be positive");
+    let max_def_level = descr.max_def_level();
+    let max_rep_level = descr.max_rep_level();
+    Self {
+      descr: descr,
+      reader: reader,
+      batch_size: batch_size,
+      max_def_level: max_def_level,
+      max_rep_level: max_rep_level,
+      values: vec![T::T::default(); batch_size],
+      def_levels: vec![0; if max_def_level > 0 { batch_size } else { 0 }],
+      rep_levels: vec![0; if max_rep_level > 0 { batch_size } else { 0 }],
+      curr_triplet_index: 0,
+      num_triplets: 0,
+      has_next: false
+    }
+  }
+
+  fn has_next(&self) -> bool {
+    self.has_next
+  }
+
+  fn current_def_level(&self) -> i16 {
+    if self.max_def_level > 0 {
+      self.def_levels[self.curr_triplet_index]
+    } else {
+      self.max_def_level
+    }
+  }
+
+  fn current_rep_level(&self) -> i16 {
+    if self.max_rep_level > 0 {
+      self.rep_levels[self.curr_triplet_index]
+    } else {
+      self.max_rep_level
+    }
+  }
+
+  fn current_value<F>(&self, convert: F) -> Result<Field>
+    where F: Fn(&ColumnDescriptor, T::T) -> Result<Field> {
+    if self.current_def_level() < self.max_def_level {
+      return Ok(Field::Null);
+    }
+    convert(self.descr.as_ref(), self.values[self.curr_triplet_index].clone())
+  }
+
+  fn read_next(&mut self) -> Result<bool> {
+    self.curr_triplet_index += 1;
+    if self.curr_triplet_index < self.num_triplets {
+      return Ok(true);
+    }
+
+    let (values_read, levels_read) = {
+      let def_levels =
+        if self.max_def_level > 0 { Some(&mut self.def_levels[..]) } else { None };
+      let rep_levels =
+        if self.max_rep_level > 0 { Some(&mut self.rep_levels[..]) } else { None };
+      self.reader.read_batch(self.batch_size, def_levels, rep_levels, &mut self.values)?
+    };
+
+    if values_read == 0 && levels_read == 0 {
+      self.has_next = false;
+      return Ok(false);
+    }
+
+    if self.max_def_level > 0 {
+      let max_def_level = self.max_def_level;
+      let num_defined = self.def_levels[..levels_read].iter()
+        .filter(|&&level| level == max_def_level)
+        .count();
+      if num_defined != values_read {
+        return Err(decode_err!(
+          ErrorContext::new().with_column(self.descr.path().string()),
+          "Expected {} values for the definition levels of the batch, found {}",
+          num_defined, values_read));
+      }
+    }
+
+    if levels_read > values_read {
+      // Values are read densely: move them to the positions of their levels, so that
+      // the value of each triplet has the same index as its levels.
+      let mut index = values_read;
+      for i in (0..levels_read).rev() {
+        if self.def_levels[i] == self.max_def_level {
+          index -= 1;
+          self.values.swap(i, index);
+        }
+      }
+    }
+
+    self.num_triplets = if levels_read > 0 { levels_read } else { values_read };
+    self.curr_triplet_index = 0;
+    self.has_next = true;
+    Ok(true)
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::rc::Rc;
+  use std::vec::IntoIter;
+
+  use basic::{Encoding, Repetition, Type as PhysicalType};
+  use column::page::{Page, PageReader};
+  use encodings::levels::LevelEncoder;
+  use schema::types::{ColumnPath, Type as SchemaType};
+  use util::memory::ByteBufferPtr;
+
+  struct TestPageReader {
+    pages: IntoIter<Page>
+  }
+
+  impl PageReader for TestPageReader {
+    fn get_next_page(&mut self) -> Result<Option<Page>> {
+      Ok(self.pages.next())
+    }
+  }
+
+  #[test]
+  fn test_read_next_truncated_page() {
+    let tp = SchemaType::primitive_type_builder("a", PhysicalType::INT32)
+      .with_repetition(Repetition::OPTIONAL)
+      .build()
+      .unwrap();
+    let descr = Rc::new(ColumnDescriptor::new(
+      Rc::new(tp), None, 1, 0, ColumnPath::new(vec!["a".to_owned()])));
+
+    // The definition levels declare 3 values, but the page only contains 1
+    let def_levels = [1, 0, 1, 1];
+    let size = LevelEncoder::max_buffer_size(Encoding::RLE, 1, def_levels.len());
+    let mut encoder = LevelEncoder::new(Encoding::RLE, 1, vec![0; size]);
+    encoder.put(&def_levels).unwrap();
+    let mut buf = encoder.consume().unwrap();
+    buf.extend_from_slice(7i32.as_bytes());
+    let page = Page::DataPage {
+      buf: ByteBufferPtr::new(buf),
+      num_values: def_levels.len() as u32,
+      encoding: Encoding::PLAIN,
+      def_level_encoding: Encoding::RLE,
+      rep_level_encoding: Encoding::RLE
+    };
+    let page_reader = TestPageReader { pages: vec![page].into_iter() };
+    let reader = ColumnReaderImpl::<Int32Type>::new(descr.clone(), Box::new(page_reader));
+
+    let mut iter = TypedTripletIter::new(descr, reader, def_levels.len());
+    match iter.read_next() {
+      Err(ParquetError::Decode(_, _)) => (),
+      other => panic!("Expected a decode error, got {:?}", other)
+    }
+  }
+}
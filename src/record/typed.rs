@@ -0,0 +1,689 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+
+//! Reading and writing of Rust types whose schema is known at compile time.
+//!
+//! Structs implement `RecordWriter` and `RecordReader` with the derive macros of the
+//! `parquet_derive` crate:
+//!
+//! ```ignore
+//! #[derive(ParquetRecordWriter, ParquetRecordReader)]
+//! struct Record {
+//!   id: i64,
+//!   name: Option<String>,
+//!   scores: Vec<Option<f64>>,
+//!   location: Option<Location>
+//! }
+//!
+//! #[derive(ParquetRecordWriter, ParquetRecordReader)]
+//! struct Location {
+//!   lat: f64,
+//!   lon: f64
+//! }
+//!
+//! let mut writer = SerializedFileWriter::new(file, Record::schema()?, options)?;
+//! let mut row_group = writer.next_row_group();
+//! Record::write_to_row_group(&records, &mut row_group)?;
+//! writer.write_row_group(row_group)?;
+//! writer.close()?;
+//!
+//! let records = Record::read_from_row_group(&*reader.get_row_group(0)?)?;
+//! ```
+//!
+//! Fields map to the schema as follows: integers, floating point numbers and `bool` to
+//! the corresponding physical types, with an INT_* or UINT_* annotation for integers of
+//! less than 32 bits and unsigned integers, `String` to UTF8 BYTE_ARRAY, `ByteArray` to
+//! BYTE_ARRAY, `Option<T>` to an optional field, `Vec<T>` to a 3-level list whose
+//! elements are optional if `T` is an `Option`, and structs to groups.
+//!
+//! Every leaf column of a record type has its own `LeafWriter` and `LeafReader`, whose
+//! data type is fixed by the type of the field, and a struct holds those of its fields
+//! in a tuple. Records are therefore shredded and assembled without matching on the
+//! physical type of the columns, and the levels and values of each column are written
+//! with `ColumnWriterImpl::write_batch` every `WRITE_BATCH_SIZE` records, and read with
+//! `ColumnReaderImpl::read_batch` `READ_BATCH_SIZE` levels at a time. The levels of
+//! `Option` and `Vec` fields are computed as for the values of `column::levels`.
+
+use std::rc::Rc;
+use std::slice;
+
+use basic::{LogicalAnnotation, LogicalType, Repetition, Type as PhysicalType};
+use column::levels::{
+  assemble_list, assemble_option, column_shape, shred_list, shred_option, LevelReader,
+  LevelSink, LevelSource, Levels
+};
+use column::reader::{get_typed_column_reader, ColumnReaderImpl};
+use column::writer::get_typed_column_writer_mut;
+use data_type::*;
+use errors::{ParquetError, Result};
+use file::reader::RowGroupReader;
+use file::writer::RowGroupWriter;
+use schema::merge::type_str;
+use schema::types::{ColumnDescPtr, ColumnDescriptor, SchemaDescriptor, Type, TypePtr};
+
+/// Number of records shredded before their levels and values are written.
+const WRITE_BATCH_SIZE: usize = 1024;
+
+/// Number of levels read at once from a column chunk.
+const READ_BATCH_SIZE: usize = 1024;
+
+/// A type that can be written as the rows of a file.
+pub trait RecordWriter: WriteField + Sized {
+  /// Returns the schema of a file with records of this type.
+  fn schema() -> Result<TypePtr> {
+    root_schema(Self::field_type("schema", Repetition::REQUIRED)?)
+  }
+
+  /// Writes `records` to the columns of `row_group`, whose schema must have the fields
+  /// of `schema()`.
+  fn write_to_row_group(records: &[Self], row_group: &mut RowGroupWriter) -> Result<()> {
+    let schema = Self::schema()?;
+    if row_group.schema_descr().root_schema().get_fields() != schema.get_fields() {
+      return Err(general_err!(
+        "The schema of the row group is not the schema of the records"));
+    }
+    let mut leaves = <Self::Leaves as WriteLeaves>::new();
+    for batch in records.chunks(WRITE_BATCH_SIZE) {
+      for record in batch {
+        record.shred(0, 0, 0, &mut leaves);
+      }
+      leaves.write(row_group, 0)?;
+    }
+    Ok(())
+  }
+}
+
+/// A type that can be read from the rows of a file.
+pub trait RecordReader: for<'a> ReadField<'a> {
+  /// Reads all records of `row_group`. Columns are looked up by path, so the schema
+  /// of the file can contain other fields, and must have the repetition, physical type
+  /// and logical type of the fields of the records.
+  fn read_from_row_group<'a>(row_group: &RowGroupReader<'a>) -> Result<Vec<Self>> {
+    let schema = <Self as ReadField<'a>>::field_type("schema", Repetition::REQUIRED)?;
+    let schema = SchemaDescriptor::new(root_schema(schema)?);
+    let mut leaves = <<Self as ReadField<'a>>::Leaves as ReadLeaves<'a>>::new(
+      row_group, &mut schema.columns().iter())?;
+    let num_rows = row_group.metadata().num_rows() as usize;
+    let mut records = Vec::with_capacity(num_rows);
+    for _ in 0..num_rows {
+      records.push(<Self as ReadField<'a>>::assemble(0, 0, &mut leaves)?);
+    }
+    Ok(records)
+  }
+}
+
+fn root_schema(group: TypePtr) -> Result<TypePtr> {
+  let mut fields = group.get_fields().to_vec();
+  let schema = Type::group_type_builder("schema").with_fields(&mut fields).build()?;
+  Ok(Rc::new(schema))
+}
+
+/// A type of the values of a field that can be written to leaf columns.
+///
+/// The levels of a field are computed from `def`, the definition level of the parent
+/// of the field, `rep`, the repetition level of the first value of each leaf column,
+/// and `depth`, the number of repeated ancestors of the field.
+pub trait WriteField {
+  /// The buffers of the leaf columns of a field with values of this type: a
+  /// `LeafWriter` for a primitive value, and a tuple of the buffers of its fields,
+  /// nested as `(first, (second, ()))`, for a struct.
+  type Leaves: WriteLeaves;
+
+  /// Returns the type of a field `name` with values of this type. `repetition` is
+  /// REQUIRED, unless the values are wrapped in an `Option`.
+  fn field_type(name: &str, repetition: Repetition) -> Result<TypePtr>;
+
+  /// Appends the levels and values of this value to the buffers of its leaf columns.
+  fn shred(&self, def: i16, rep: i16, depth: i16, leaves: &mut Self::Leaves);
+}
+
+/// A type of the values of a field that can be read from leaf columns, whose readers
+/// live for `'a`.
+pub trait ReadField<'a>: Sized {
+  /// The readers of the leaf columns of a field with values of this type, see
+  /// `WriteField::Leaves`.
+  type Leaves: ReadLeaves<'a>;
+
+  /// Returns the type of a field `name` with values of this type, see
+  /// `WriteField::field_type`.
+  fn field_type(name: &str, repetition: Repetition) -> Result<TypePtr>;
+
+  /// Reads the next value from the readers of its leaf columns.
+  fn assemble(def: i16, depth: i16, leaves: &mut Self::Leaves) -> Result<Self>;
+}
+
+/// The buffers of the leaf columns of a field, see `WriteField::Leaves`.
+pub trait WriteLeaves: LevelSink {
+  /// Returns empty buffers.
+  fn new() -> Self;
+
+  /// Writes the buffered levels and values to the columns of `row_group`, the first
+  /// one to column `leaf`, and clears the buffers. Returns the index of the column
+  /// after the last one written.
+  fn write(&mut self, row_group: &mut RowGroupWriter, leaf: usize) -> Result<usize>;
+}
+
+/// The readers of the leaf columns of a field, see `ReadField::Leaves`.
+pub trait ReadLeaves<'a>: LevelSource + Sized {
+  /// Creates the readers of the columns of `row_group` with the paths of the next
+  /// leaves in `columns`, which are the leaf columns of the records.
+  fn new(
+    row_group: &RowGroupReader<'a>, columns: &mut slice::Iter<ColumnDescPtr>
+  ) -> Result<Self>;
+}
+
+// ----------------------------------------------------------------------
+// Leaf columns
+
+/// The buffered levels and values of a leaf column with data type `D`.
+pub struct LeafWriter<D: DataType> {
+  levels: Levels<D::T>
+}
+
+impl<D: DataType> LeafWriter<D> {
+  /// Appends a non-null value with its levels.
+  pub fn push(&mut self, def: i16, rep: i16, value: D::T) {
+    self.levels.push(def, rep, Some(value));
+  }
+}
+
+impl<D: DataType> LevelSink for LeafWriter<D> {
+  fn push_null(&mut self, def_level: i16, rep_level: i16) {
+    self.levels.push_null(def_level, rep_level);
+  }
+}
+
+impl<D: DataType> WriteLeaves for LeafWriter<D> where D: 'static {
+  fn new() -> Self {
+    Self { levels: Levels::new() }
+  }
+
+  fn write(&mut self, row_group: &mut RowGroupWriter, leaf: usize) -> Result<usize> {
+    {
+      let writer = get_typed_column_writer_mut::<D>(row_group.column(leaf))?;
+      let has_def_levels = writer.descr().max_def_level() > 0;
+      let has_rep_levels = writer.descr().max_rep_level() > 0;
+      let levels = &self.levels;
+      let def = if has_def_levels { Some(&levels.def_levels[..]) } else { None };
+      let rep = if has_rep_levels { Some(&levels.rep_levels[..]) } else { None };
+      writer.write_batch(&levels.values, def, rep)?;
+    }
+    self.levels.def_levels.clear();
+    self.levels.rep_levels.clear();
+    self.levels.values.clear();
+    Ok(leaf + 1)
+  }
+}
+
+/// Reader of the levels and values of a leaf column with data type `D`, which reads
+/// `READ_BATCH_SIZE` levels at a time into a `LevelReader`.
+pub struct LeafReader<'a, D: DataType> {
+  reader: ColumnReaderImpl<'a, D>,
+  descr: ColumnDescPtr,
+  levels: LevelReader<D::T>
+}
+
+impl<'a, D: DataType> LeafReader<'a, D> where D: 'static {
+  /// Reads the value of the next triplet, which must not be null.
+  pub fn next_value(&mut self) -> Result<D::T> {
+    self.fill()?;
+    self.levels.next_value()
+  }
+
+  /// Reads the next batch of levels and values if all buffered ones were read. The
+  /// buffers of the levels and values are reused for every batch.
+  fn fill(&mut self) -> Result<()> {
+    if self.levels.has_next() {
+      return Ok(());
+    }
+    let has_def_levels = self.descr.max_def_level() > 0;
+    let has_rep_levels = self.descr.max_rep_level() > 0;
+    let reader = &mut self.reader;
+    self.levels.refill(|levels| {
+      if has_def_levels {
+        levels.def_levels.resize(READ_BATCH_SIZE, 0);
+      }
+      if has_rep_levels {
+        levels.rep_levels.resize(READ_BATCH_SIZE, 0);
+      }
+      levels.values.resize(READ_BATCH_SIZE, <D::T>::default());
+      let def = if has_def_levels { Some(&mut levels.def_levels[..]) } else { None };
+      let rep = if has_rep_levels { Some(&mut levels.rep_levels[..]) } else { None };
+      reader.read_batch(READ_BATCH_SIZE, def, rep, &mut levels.values)
+    })
+  }
+}
+
+impl<'a, D: DataType> ReadLeaves<'a> for LeafReader<'a, D> where D: 'static {
+  fn new(
+    row_group: &RowGroupReader<'a>, columns: &mut slice::Iter<ColumnDescPtr>
+  ) -> Result<Self> {
+    let descr = columns.next().ok_or_else(|| general_err!(
+      "The records have more leaves than columns"))?;
+    let file_schema = row_group.metadata().schema_descr();
+    let index = file_schema.column_index(descr.path()).ok_or_else(|| general_err!(
+      "Column {} is not part of the file", descr.path().string()))?;
+    let file_descr = file_schema.column(index);
+    if column_shape(&file_descr)? != column_shape(descr)? {
+      return Err(general_err!(
+        "The repetition of column {} differs from the schema of the records",
+        descr.path().string()));
+    }
+    if file_descr.physical_type() != D::get_physical_type() ||
+        value_annotation(&file_descr) != value_annotation(descr) {
+      return Err(general_err!("Column {} has type {}, but the records have type {}",
+        descr.path().string(), type_str(file_descr.self_type()),
+        type_str(descr.self_type())));
+    }
+    let reader = get_typed_column_reader::<D>(row_group.get_column_reader(index)?);
+    let levels = LevelReader::new(descr, Levels::new())?;
+    Ok(Self { reader: reader, descr: descr.clone(), levels: levels })
+  }
+}
+
+// Returns the annotation of column `descr`, without the INTEGER annotation of signed
+// integers as wide as the physical type, whose values are the same as without it.
+fn value_annotation(descr: &ColumnDescriptor) -> Option<LogicalAnnotation> {
+  let physical_width = match descr.physical_type() {
+    PhysicalType::INT32 => 32,
+    PhysicalType::INT64 => 64,
+    _ => 0
+  };
+  match descr.logical_annotation() {
+    Some(LogicalAnnotation::INTEGER { bit_width, is_signed: true })
+      if bit_width == physical_width => None,
+    annotation => annotation
+  }
+}
+
+impl<'a, D: DataType> LevelSource for LeafReader<'a, D> where D: 'static {
+  fn peek_def_level(&mut self) -> Result<i16> {
+    self.fill()?;
+    self.levels.peek_def_level()
+  }
+
+  fn peek_rep_level(&mut self) -> Result<Option<i16>> {
+    self.fill()?;
+    Ok(self.levels.peek_rep_level())
+  }
+
+  fn skip_null(&mut self) -> Result<()> {
+    self.fill()?;
+    self.levels.skip_null()
+  }
+}
+
+impl LevelSink for () {
+  fn push_null(&mut self, _def_level: i16, _rep_level: i16) {}
+}
+
+impl<A: LevelSink, B: LevelSink> LevelSink for (A, B) {
+  fn push_null(&mut self, def_level: i16, rep_level: i16) {
+    self.0.push_null(def_level, rep_level);
+    self.1.push_null(def_level, rep_level);
+  }
+}
+
+impl WriteLeaves for () {
+  fn new() -> Self {
+    ()
+  }
+
+  fn write(&mut self, _row_group: &mut RowGroupWriter, leaf: usize) -> Result<usize> {
+    Ok(leaf)
+  }
+}
+
+impl<A: WriteLeaves, B: WriteLeaves> WriteLeaves for (A, B) {
+  fn new() -> Self {
+    (A::new(), B::new())
+  }
+
+  fn write(&mut self, row_group: &mut RowGroupWriter, leaf: usize) -> Result<usize> {
+    let leaf = self.0.write(row_group, leaf)?;
+    self.1.write(row_group, leaf)
+  }
+}
+
+impl LevelSource for () {
+  fn peek_def_level(&mut self) -> Result<i16> {
+    Err(general_err!("A group without leaf columns has no levels"))
+  }
+
+  fn peek_rep_level(&mut self) -> Result<Option<i16>> {
+    Err(general_err!("A group without leaf columns has no levels"))
+  }
+
+  fn skip_null(&mut self) -> Result<()> {
+    Ok(())
+  }
+}
+
+impl<A: LevelSource, B: LevelSource> LevelSource for (A, B) {
+  fn peek_def_level(&mut self) -> Result<i16> {
+    self.0.peek_def_level()
+  }
+
+  fn peek_rep_level(&mut self) -> Result<Option<i16>> {
+    self.0.peek_rep_level()
+  }
+
+  fn skip_null(&mut self) -> Result<()> {
+    self.0.skip_null()?;
+    self.1.skip_null()
+  }
+}
+
+impl<'a> ReadLeaves<'a> for () {
+  fn new(
+    _row_group: &RowGroupReader<'a>, _columns: &mut slice::Iter<ColumnDescPtr>
+  ) -> Result<Self> {
+    Ok(())
+  }
+}
+
+impl<'a, A: ReadLeaves<'a>, B: ReadLeaves<'a>> ReadLeaves<'a> for (A, B) {
+  fn new(
+    row_group: &RowGroupReader<'a>, columns: &mut slice::Iter<ColumnDescPtr>
+  ) -> Result<Self> {
+    let first = A::new(row_group, columns)?;
+    Ok((first, B::new(row_group, columns)?))
+  }
+}
+
+// ----------------------------------------------------------------------
+// Field implementations
+
+fn primitive_type(
+  name: &str, repetition: Repetition, physical_type: PhysicalType,
+  logical_type: LogicalType
+) -> Result<TypePtr> {
+  let tp = Type::primitive_type_builder(name, physical_type)
+    .with_repetition(repetition)
+    .with_logical_type(logical_type)
+    .build()?;
+  Ok(Rc::new(tp))
+}
+
+macro_rules! primitive_field {
+  ($ty:ty, $data_ty:ty, $logical:ident,
+   |$w:ident| $to_value:expr, |$r:ident| $from_value:expr) => {
+    impl WriteField for $ty {
+      type Leaves = LeafWriter<$data_ty>;
+
+      fn field_type(name: &str, repetition: Repetition) -> Result<TypePtr> {
+        primitive_type(
+          name, repetition, <$data_ty>::get_physical_type(), LogicalType::$logical)
+      }
+
+      fn shred(&self, def: i16, rep: i16, _depth: i16, leaves: &mut Self::Leaves) {
+        let $w = self;
+        leaves.push(def, rep, $to_value);
+      }
+    }
+
+    impl<'a> ReadField<'a> for $ty {
+      type Leaves = LeafReader<'a, $data_ty>;
+
+      fn field_type(name: &str, repetition: Repetition) -> Result<TypePtr> {
+        <$ty as WriteField>::field_type(name, repetition)
+      }
+
+      fn assemble(_def: i16, _depth: i16, leaves: &mut Self::Leaves) -> Result<Self> {
+        let $r = leaves.next_value()?;
+        Ok($from_value)
+      }
+    }
+  }
+}
+
+primitive_field!(bool, BoolType, NONE, |v| *v, |v| v);
+primitive_field!(i8, Int32Type, INT_8, |v| *v as i32, |v| v as i8);
+primitive_field!(i16, Int32Type, INT_16, |v| *v as i32, |v| v as i16);
+primitive_field!(i32, Int32Type, NONE, |v| *v, |v| v);
+primitive_field!(i64, Int64Type, NONE, |v| *v, |v| v);
+primitive_field!(u8, Int32Type, UINT_8, |v| *v as i32, |v| v as u8);
+primitive_field!(u16, Int32Type, UINT_16, |v| *v as i32, |v| v as u16);
+primitive_field!(u32, Int32Type, UINT_32, |v| *v as i32, |v| v as u32);
+primitive_field!(u64, Int64Type, UINT_64, |v| *v as i64, |v| v as u64);
+primitive_field!(f32, FloatType, NONE, |v| *v, |v| v);
+primitive_field!(f64, DoubleType, NONE, |v| *v, |v| v);
+primitive_field!(ByteArray, ByteArrayType, NONE, |v| v.clone(), |v| v);
+primitive_field!(
+  String, ByteArrayType, UTF8,
+  |v| ByteArray::from(v.as_str()),
+  |v| String::from_utf8(v.data().to_vec())
+    .map_err(|e| general_err!("Invalid UTF8 value: {}", e))?);
+
+impl<T: WriteField> WriteField for Option<T> {
+  type Leaves = T::Leaves;
+
+  fn field_type(name: &str, _repetition: Repetition) -> Result<TypePtr> {
+    T::field_type(name, Repetition::OPTIONAL)
+  }
+
+  fn shred(&self, def: i16, rep: i16, depth: i16, leaves: &mut Self::Leaves) {
+    shred_option(self.as_ref(), def, rep, depth, leaves, T::shred)
+  }
+}
+
+impl<'a, T: ReadField<'a>> ReadField<'a> for Option<T> {
+  type Leaves = T::Leaves;
+
+  fn field_type(name: &str, _repetition: Repetition) -> Result<TypePtr> {
+    T::field_type(name, Repetition::OPTIONAL)
+  }
+
+  fn assemble(def: i16, depth: i16, leaves: &mut Self::Leaves) -> Result<Self> {
+    assemble_option(def, depth, leaves, T::assemble)
+  }
+}
+
+fn list_type(name: &str, repetition: Repetition, element: TypePtr) -> Result<TypePtr> {
+  let list = Type::group_type_builder("list")
+    .with_repetition(Repetition::REPEATED)
+    .with_fields(&mut vec![element])
+    .build()?;
+  let tp = Type::group_type_builder(name)
+    .with_repetition(repetition)
+    .with_logical_type(LogicalType::LIST)
+    .with_fields(&mut vec![Rc::new(list)])
+    .build()?;
+  Ok(Rc::new(tp))
+}
+
+impl<T: WriteField> WriteField for Vec<T> {
+  type Leaves = T::Leaves;
+
+  fn field_type(name: &str, repetition: Repetition) -> Result<TypePtr> {
+    list_type(name, repetition, T::field_type("element", Repetition::REQUIRED)?)
+  }
+
+  fn shred(&self, def: i16, rep: i16, depth: i16, leaves: &mut Self::Leaves) {
+    shred_list(self, def, rep, depth, leaves, T::shred)
+  }
+}
+
+impl<'a, T: ReadField<'a>> ReadField<'a> for Vec<T> {
+  type Leaves = T::Leaves;
+
+  fn field_type(name: &str, repetition: Repetition) -> Result<TypePtr> {
+    list_type(name, repetition, T::field_type("element", Repetition::REQUIRED)?)
+  }
+
+  fn assemble(def: i16, depth: i16, leaves: &mut Self::Leaves) -> Result<Self> {
+    assemble_list(def, depth, leaves, T::assemble)
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::fs::File;
+
+  use file::reader::{FileReader, SerializedFileReader};
+  use file::writer::{SerializedFileWriter, WriterOptions};
+  use schema::parser::parse_message_type;
+  use util::test_common::get_temp_file_path;
+
+  #[derive(Debug, PartialEq)]
+  struct Record {
+    id: i64,
+    values: Vec<Option<Vec<Option<i32>>>>
+  }
+
+  // The implementations generated by `#[derive(ParquetRecordWriter,
+  // ParquetRecordReader)]`
+  impl WriteField for Record {
+    type Leaves = (LeafWriter<Int64Type>, (LeafWriter<Int32Type>, ()));
+
+    fn field_type(name: &str, repetition: Repetition) -> Result<TypePtr> {
+      let mut fields = vec![
+        <i64 as WriteField>::field_type("id", Repetition::REQUIRED)?,
+        <Vec<Option<Vec<Option<i32>>>> as WriteField>::field_type(
+          "values", Repetition::REQUIRED)?
+      ];
+      let tp = Type::group_type_builder(name)
+        .with_repetition(repetition)
+        .with_fields(&mut fields)
+        .build()?;
+      Ok(Rc::new(tp))
+    }
+
+    fn shred(&self, def: i16, rep: i16, depth: i16, leaves: &mut Self::Leaves) {
+      WriteField::shred(&self.id, def, rep, depth, &mut leaves.0);
+      WriteField::shred(&self.values, def, rep, depth, &mut (leaves.1).0);
+    }
+  }
+
+  impl RecordWriter for Record {}
+
+  impl<'a> ReadField<'a> for Record {
+    type Leaves = (LeafReader<'a, Int64Type>, (LeafReader<'a, Int32Type>, ()));
+
+    fn field_type(name: &str, repetition: Repetition) -> Result<TypePtr> {
+      <Record as WriteField>::field_type(name, repetition)
+    }
+
+    fn assemble(def: i16, depth: i16, leaves: &mut Self::Leaves) -> Result<Self> {
+      Ok(Record {
+        id: ReadField::assemble(def, depth, &mut leaves.0)?,
+        values: ReadField::assemble(def, depth, &mut (leaves.1).0)?
+      })
+    }
+  }
+
+  impl RecordReader for Record {}
+
+  #[test]
+  fn test_field_types() {
+    let tp = <Vec<Option<Vec<u32>>> as WriteField>::field_type(
+      "a", Repetition::REQUIRED).unwrap();
+    let expected = parse_message_type("
+      message schema {
+        REQUIRED GROUP a (LIST) {
+          REPEATED GROUP list {
+            OPTIONAL GROUP element (LIST) {
+              REPEATED GROUP list {
+                REQUIRED INT32 element (UINT_32);
+              }
+            }
+          }
+        }
+      }
+    ").unwrap();
+    assert_eq!(tp, expected.get_fields()[0]);
+    assert_eq!(
+      <Option<String> as ReadField>::field_type("b", Repetition::REQUIRED).unwrap(),
+      <String as ReadField>::field_type("b", Repetition::OPTIONAL).unwrap());
+  }
+
+  #[test]
+  fn test_shred_nested_lists() {
+    let values: Vec<Option<Vec<Option<i32>>>> = vec![
+      Some(vec![Some(1), None, Some(2)]),
+      None,
+      Some(vec![]),
+      Some(vec![None])
+    ];
+    let mut leaves = <LeafWriter<Int32Type> as WriteLeaves>::new();
+    values.shred(0, 0, 0, &mut leaves);
+    vec![Some(vec![Some(3i32)])].shred(0, 0, 0, &mut leaves);
+    Vec::<Option<Vec<Option<i32>>>>::new().shred(0, 0, 0, &mut leaves);
+    assert_eq!(leaves.levels.def_levels, vec![4, 3, 4, 1, 2, 3, 4, 0]);
+    assert_eq!(leaves.levels.rep_levels, vec![0, 2, 2, 1, 1, 1, 0, 0]);
+    assert_eq!(leaves.levels.values, vec![1, 2, 3]);
+  }
+
+  #[test]
+  fn test_write_read_records() {
+    // More records and levels than a write or read batch
+    let records: Vec<Record> = (0..3000).map(|i| Record {
+      id: i,
+      values: match i % 4 {
+        0 => vec![],
+        1 => vec![None],
+        2 => vec![Some(vec![])],
+        _ => vec![Some(vec![Some(i as i32), None]), Some(vec![Some(-i as i32)])]
+      }
+    }).collect();
+
+    let path = get_temp_file_path("test_typed_write_read_records.parquet");
+    let mut writer = SerializedFileWriter::new(
+      File::create(&path).unwrap(), Record::schema().unwrap(), WriterOptions::new())
+      .unwrap();
+    let mut row_group = writer.next_row_group();
+    Record::write_to_row_group(&records, &mut row_group).unwrap();
+    writer.write_row_group(row_group).unwrap();
+    writer.close().unwrap();
+
+    let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+    let row_group = reader.get_row_group(0).unwrap();
+    let read = Record::read_from_row_group(&*row_group).unwrap();
+    assert_eq!(read, records);
+  }
+
+  #[test]
+  fn test_leaf_reader_logical_type() {
+    let records = vec![Record { id: 1, values: vec![] }];
+    let path = get_temp_file_path("test_typed_leaf_reader_logical_type.parquet");
+    let mut writer = SerializedFileWriter::new(
+      File::create(&path).unwrap(), Record::schema().unwrap(), WriterOptions::new())
+      .unwrap();
+    let mut row_group = writer.next_row_group();
+    Record::write_to_row_group(&records, &mut row_group).unwrap();
+    writer.write_row_group(row_group).unwrap();
+    writer.close().unwrap();
+
+    let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+    let row_group = reader.get_row_group(0).unwrap();
+    let leaf_reader = |message_type: &str| {
+      let schema = SchemaDescriptor::new(Rc::new(parse_message_type(message_type)?));
+      let mut columns = schema.columns().iter();
+      <LeafReader<Int64Type> as ReadLeaves>::new(&*row_group, &mut columns)
+    };
+    // A signed INT_64 annotation does not change the values of an INT64 column
+    assert!(leaf_reader("message schema { REQUIRED INT64 id; }").is_ok());
+    assert!(leaf_reader("message schema { REQUIRED INT64 id (INT_64); }").is_ok());
+    assert!(leaf_reader("message schema { REQUIRED INT64 id (TIMESTAMP_MILLIS); }")
+      .is_err());
+    assert!(leaf_reader("message schema { REQUIRED INT64 id (UINT_64); }").is_err());
+  }
+}
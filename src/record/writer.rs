@@ -0,0 +1,554 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Record writer: shreds rows into the levels and values of the leaf columns of a
+//! schema.
+//!
+//! This is the counterpart of the record reader. Rows are walked together with the
+//! schema, following the structure given by `schema::nested`: an optional field that is
+//! defined, and each element of a list or entry of a map, increment the definition
+//! level, and elements after the first of a list or map are written at the repetition
+//! level of that list or map. Nulls, empty lists and empty maps are written as a level
+//! without a value to every leaf column below them.
+//!
+//! ```ignore
+//! let mut row_writer = RowWriter::new(file_writer.schema_descr().clone());
+//! for row in rows {
+//!   row_writer.write(&row)?;
+//! }
+//! let mut row_group = file_writer.next_row_group();
+//! row_writer.write_to_row_group(&mut row_group)?;
+//! file_writer.write_row_group(row_group)?;
+//! ```
+
+use basic::Repetition;
+use column::writer::{ColumnWriter, ColumnWriterImpl};
+use data_type::*;
+use errors::{ParquetError, Result};
+use file::writer::RowGroupWriter;
+use record::api::{Field, Row};
+use schema::nested::{value_type, NestedType};
+use schema::types::{
+  ColumnDescPtr, ColumnDescriptor, SchemaDescPtr, SchemaDescriptor, Type
+};
+
+/// Writer of rows into the leaf columns of a schema. Levels and values are buffered
+/// until they are written to a row group with `write_to_row_group`.
+pub struct RowWriter {
+  descr: SchemaDescPtr,
+  leaves: Vec<LeafValues>,
+  num_rows: usize
+}
+
+impl RowWriter {
+  /// Creates a writer of rows with schema `descr`.
+  pub fn new(descr: SchemaDescPtr) -> Self {
+    let leaves = new_leaves(&descr);
+    Self { descr: descr, leaves: leaves, num_rows: 0 }
+  }
+
+  pub fn schema_descr(&self) -> &SchemaDescPtr {
+    &self.descr
+  }
+
+  /// Returns the number of rows buffered by this writer.
+  pub fn num_rows(&self) -> usize {
+    self.num_rows
+  }
+
+  /// Shreds `row` into the leaf columns. Fields that are not in the schema are an
+  /// error, and missing fields are null. If the row is invalid, nothing is buffered.
+  pub fn write(&mut self, row: &Row) -> Result<()> {
+    let result = shred_group(self.descr.root_schema(), row, 0, 0, 0, 0, &mut self.leaves);
+    match result {
+      Ok(()) => self.num_rows += 1,
+      Err(_) => for leaf in &mut self.leaves {
+        leaf.truncate()
+      }
+    }
+    for leaf in &mut self.leaves {
+      leaf.mark();
+    }
+    result
+  }
+
+  /// Returns the definition levels buffered for the `i`th leaf column.
+  pub fn def_levels(&self, i: usize) -> &[i16] {
+    &self.leaves[i].def_levels
+  }
+
+  /// Returns the repetition levels buffered for the `i`th leaf column.
+  pub fn rep_levels(&self, i: usize) -> &[i16] {
+    &self.leaves[i].rep_levels
+  }
+
+  /// Returns the non-null values buffered for the `i`th leaf column.
+  pub fn values(&self, i: usize) -> &[Field] {
+    &self.leaves[i].values
+  }
+
+  /// Writes the buffered rows to the column writers of `row_group`, whose schema must
+  /// have the fields of the schema of this writer, and clears the buffers.
+  pub fn write_to_row_group(&mut self, row_group: &mut RowGroupWriter) -> Result<()> {
+    let fields = self.descr.root_schema().get_fields();
+    if row_group.schema_descr().root_schema().get_fields() != fields {
+      return Err(general_err!(
+        "The schema of the row group is not the schema of the rows"));
+    }
+    for (i, leaf) in self.leaves.iter().enumerate() {
+      write_leaf(row_group.column(i), leaf)?;
+    }
+    self.leaves = new_leaves(&self.descr);
+    self.num_rows = 0;
+    Ok(())
+  }
+}
+
+fn new_leaves(descr: &SchemaDescriptor) -> Vec<LeafValues> {
+  (0..descr.num_columns()).map(|i| LeafValues::new(descr.column(i))).collect()
+}
+
+/// The levels and non-null values of a leaf column, shredded from rows.
+struct LeafValues {
+  descr: ColumnDescPtr,
+  def_levels: Vec<i16>,
+  rep_levels: Vec<i16>,
+  values: Vec<Field>,
+  // Number of levels and values of the rows written successfully
+  num_levels: usize,
+  num_values: usize
+}
+
+impl LeafValues {
+  fn new(descr: ColumnDescPtr) -> Self {
+    Self {
+      descr: descr,
+      def_levels: vec!(),
+      rep_levels: vec!(),
+      values: vec!(),
+      num_levels: 0,
+      num_values: 0
+    }
+  }
+
+  /// Appends a value, or a null if `value` is `None`, with its levels. The levels are
+  /// checked against the maximum levels of the column: a value must be at the maximum
+  /// definition level, and a null below it.
+  fn push(
+    &mut self, def_level: i16, rep_level: i16, value: Option<&Field>
+  ) -> Result<()> {
+    let max_def_level = self.descr.max_def_level();
+    let max_rep_level = self.descr.max_rep_level();
+    if def_level > max_def_level || rep_level > max_rep_level {
+      return Err(general_err!(
+        "Levels ({}, {}) of column {} exceed the maximum levels ({}, {})",
+        def_level, rep_level, self.descr.path().string(), max_def_level, max_rep_level));
+    }
+    if value.is_some() != (def_level == max_def_level) {
+      return Err(general_err!(
+        "Invalid definition level {} of a {} in column {} with maximum level {}",
+        def_level, if value.is_some() { "value" } else { "null" },
+        self.descr.path().string(), max_def_level));
+    }
+    self.def_levels.push(def_level);
+    self.rep_levels.push(rep_level);
+    if let Some(value) = value {
+      self.values.push(value.clone());
+    }
+    Ok(())
+  }
+
+  /// Marks the levels and values appended so far as those of complete rows.
+  fn mark(&mut self) {
+    self.num_levels = self.def_levels.len();
+    self.num_values = self.values.len();
+  }
+
+  /// Removes the levels and values appended since the last call to `mark`.
+  fn truncate(&mut self) {
+    self.def_levels.truncate(self.num_levels);
+    self.rep_levels.truncate(self.num_levels);
+    self.values.truncate(self.num_values);
+  }
+}
+
+// The functions below shred a value into the leaf columns of its field, whose first
+// leaf column is `leaf`. `def` is the definition level of the parent of the field, and
+// `rep` the repetition level of the first value written to each leaf column. `depth` is
+// the number of repeated ancestors of the field.
+
+fn shred_field(
+  field: &Type, value: &Field, def: i16, rep: i16, depth: i16, leaf: usize,
+  leaves: &mut [LeafValues]
+) -> Result<()> {
+  match field.get_basic_info().repetition() {
+    Repetition::REQUIRED => {
+      if *value == Field::Null {
+        return Err(general_err!("Required field {} is null", field.name()));
+      }
+      shred_value(field, value, def, rep, depth, leaf, leaves)
+    },
+    Repetition::OPTIONAL => {
+      if *value == Field::Null {
+        return write_nulls(field, def, rep, leaf, leaves);
+      }
+      shred_value(field, value, def + 1, rep, depth, leaf, leaves)
+    },
+    Repetition::REPEATED => {
+      // A repeated field is a list of required elements
+      let elements: &[Field] = match *value {
+        Field::List(ref elements) => elements,
+        Field::Null => &[],
+        _ => return Err(invalid_value(field, value))
+      };
+      if elements.is_empty() {
+        write_nulls(field, def, rep, leaf, leaves)?;
+      }
+      for (i, element) in elements.iter().enumerate() {
+        let rep = if i == 0 { rep } else { depth + 1 };
+        if *element == Field::Null {
+          return Err(general_err!("Repeated field {} contains null", field.name()));
+        }
+        shred_value(field, element, def + 1, rep, depth + 1, leaf, leaves)?;
+      }
+      Ok(())
+    }
+  }
+}
+
+fn shred_value(
+  tp: &Type, value: &Field, def: i16, rep: i16, depth: i16, leaf: usize,
+  leaves: &mut [LeafValues]
+) -> Result<()> {
+  match value_type(tp)? {
+    NestedType::Primitive(_) => leaves[leaf].push(def, rep, Some(value))?,
+    NestedType::Struct(group) => match *value {
+      Field::Group(ref row) => shred_group(group, row, def, rep, depth, leaf, leaves)?,
+      _ => return Err(invalid_value(tp, value))
+    },
+    NestedType::List(list) => {
+      let elements = match *value {
+        Field::List(ref elements) => elements,
+        _ => return Err(invalid_value(tp, value))
+      };
+      if elements.is_empty() {
+        write_nulls(list.repeated, def, rep, leaf, leaves)?;
+      }
+      for (i, element) in elements.iter().enumerate() {
+        let rep = if i == 0 { rep } else { depth + 1 };
+        if list.is_two_level() {
+          if *element == Field::Null {
+            return Err(general_err!("List {} cannot contain null", tp.name()));
+          }
+          shred_value(list.repeated, element, def + 1, rep, depth + 1, leaf, leaves)?;
+        } else {
+          shred_field(list.element, element, def + 1, rep, depth + 1, leaf, leaves)?;
+        }
+      }
+    },
+    NestedType::Map(map) => {
+      let entries = match *value {
+        Field::Map(ref entries) => entries,
+        _ => return Err(invalid_value(tp, value))
+      };
+      if entries.is_empty() {
+        write_nulls(map.repeated, def, rep, leaf, leaves)?;
+      }
+      let value_leaf = leaf + num_leaves(map.key);
+      for (i, &(ref k, ref v)) in entries.iter().enumerate() {
+        let rep = if i == 0 { rep } else { depth + 1 };
+        shred_field(map.key, k, def + 1, rep, depth + 1, leaf, leaves)?;
+        if let Some(value_field) = map.value {
+          shred_field(value_field, v, def + 1, rep, depth + 1, value_leaf, leaves)?;
+        }
+      }
+    }
+  }
+  Ok(())
+}
+
+fn shred_group(
+  group: &Type, row: &Row, def: i16, rep: i16, depth: i16, leaf: usize,
+  leaves: &mut [LeafValues]
+) -> Result<()> {
+  let fields = group.get_fields();
+  for &(ref name, _) in row.fields() {
+    if !fields.iter().any(|f| f.name() == name) {
+      return Err(general_err!("Field {} is not part of group {}", name, group.name()));
+    }
+  }
+  let mut leaf = leaf;
+  for field in fields {
+    let value = row.get(field.name()).unwrap_or(&Field::Null);
+    shred_field(field, value, def, rep, depth, leaf, leaves)?;
+    leaf += num_leaves(field);
+  }
+  Ok(())
+}
+
+/// Writes a null, or an empty list or map, at definition level `def` to all leaf
+/// columns of `tp`.
+fn write_nulls(
+  tp: &Type, def: i16, rep: i16, leaf: usize, leaves: &mut [LeafValues]
+) -> Result<()> {
+  for i in leaf..leaf + num_leaves(tp) {
+    leaves[i].push(def, rep, None)?;
+  }
+  Ok(())
+}
+
+fn num_leaves(tp: &Type) -> usize {
+  if tp.is_primitive() {
+    1
+  } else {
+    tp.get_fields().iter().map(|f| num_leaves(f)).sum()
+  }
+}
+
+fn invalid_value(tp: &Type, value: &Field) -> ParquetError {
+  general_err!("Invalid value for field {}: {}", tp.name(), value)
+}
+
+/// Writes the shredded values of a leaf column to its column writer.
+fn write_leaf(writer: &mut ColumnWriter, leaf: &LeafValues) -> Result<()> {
+  match *writer {
+    ColumnWriter::BoolColumnWriter(ref mut w) => write_typed(w, leaf, Field::to_bool),
+    ColumnWriter::Int32ColumnWriter(ref mut w) => write_typed(w, leaf, Field::to_int32),
+    ColumnWriter::Int64ColumnWriter(ref mut w) => write_typed(w, leaf, Field::to_int64),
+    ColumnWriter::Int96ColumnWriter(ref mut w) => write_typed(w, leaf, Field::to_int96),
+    ColumnWriter::FloatColumnWriter(ref mut w) => write_typed(w, leaf, Field::to_float),
+    ColumnWriter::DoubleColumnWriter(ref mut w) => write_typed(w, leaf, Field::to_double),
+    ColumnWriter::ByteArrayColumnWriter(ref mut w) => {
+      write_typed(w, leaf, Field::to_byte_array)
+    },
+    ColumnWriter::FixedLenByteArrayColumnWriter(ref mut w) => {
+      write_typed(w, leaf, Field::to_byte_array)
+    }
+  }
+}
+
+fn write_typed<T: DataType, F>(
+  writer: &mut ColumnWriterImpl<T>, leaf: &LeafValues, convert: F
+) -> Result<()> where T: 'static, F: Fn(&Field, &ColumnDescriptor) -> Result<T::T> {
+  let descr = writer.descr().clone();
+  let values = leaf.values.iter()
+    .map(|value| convert(value, descr.as_ref()))
+    .collect::<Result<Vec<_>>>()?;
+  let def_levels =
+    if descr.max_def_level() > 0 { Some(&leaf.def_levels[..]) } else { None };
+  let rep_levels =
+    if descr.max_rep_level() > 0 { Some(&leaf.rep_levels[..]) } else { None };
+  writer.write_batch(&values, def_levels, rep_levels)?;
+  Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::error::Error;
+  use std::fs::File;
+  use std::rc::Rc;
+
+  use file::reader::{FileReader, SerializedFileReader};
+  use file::writer::{SerializedFileWriter, WriterOptions};
+  use schema::parser::parse_message_type;
+  use util::test_common::get_temp_file_path;
+
+  fn row(fields: Vec<(&str, Field)>) -> Row {
+    Row::new(fields.into_iter().map(|(k, v)| (k.to_owned(), v)).collect())
+  }
+
+  fn row_writer(schema: &str) -> RowWriter {
+    let schema = parse_message_type(schema).unwrap();
+    RowWriter::new(Rc::new(SchemaDescriptor::new(Rc::new(schema))))
+  }
+
+  #[test]
+  fn test_shred_nested_lists() {
+    let mut writer = row_writer("
+      message schema {
+        OPTIONAL GROUP a (LIST) {
+          REPEATED GROUP list {
+            OPTIONAL GROUP element (LIST) {
+              REPEATED GROUP list {
+                OPTIONAL INT32 element;
+              }
+            }
+          }
+        }
+      }
+    ");
+    let rows = vec![
+      row(vec![("a", Field::List(vec![
+        Field::List(vec![Field::Int(1), Field::Null, Field::Int(2)]),
+        Field::Null,
+        Field::List(vec![]),
+        Field::List(vec![Field::Null])
+      ]))]),
+      row(vec![("a", Field::List(vec![Field::List(vec![Field::Int(3)])]))]),
+      row(vec![("a", Field::List(vec![]))]),
+      row(vec![])
+    ];
+    for row in &rows {
+      writer.write(row).unwrap();
+    }
+    assert_eq!(writer.num_rows(), 4);
+    assert_eq!(writer.def_levels(0), &[5, 4, 5, 2, 3, 4, 5, 1, 0]);
+    assert_eq!(writer.rep_levels(0), &[0, 2, 2, 1, 1, 1, 0, 0, 0]);
+    assert_eq!(writer.values(0), &[Field::Int(1), Field::Int(2), Field::Int(3)]);
+  }
+
+  #[test]
+  fn test_shred_maps_and_groups() {
+    let mut writer = row_writer("
+      message schema {
+        REQUIRED INT64 id;
+        OPTIONAL GROUP attrs (MAP) {
+          REPEATED GROUP key_value {
+            REQUIRED BYTE_ARRAY key (UTF8);
+            OPTIONAL GROUP value {
+              REQUIRED INT32 x;
+              REPEATED INT32 y;
+            }
+          }
+        }
+      }
+    ");
+    let value = |x, y: Vec<i32>| Field::Group(row(vec![
+      ("x", Field::Int(x)),
+      ("y", Field::List(y.into_iter().map(Field::Int).collect()))
+    ]));
+    writer.write(&row(vec![
+      ("id", Field::Long(1)),
+      ("attrs", Field::Map(vec![
+        (Field::Str("a".to_owned()), value(1, vec![2, 3])),
+        (Field::Str("b".to_owned()), Field::Null),
+        (Field::Str("c".to_owned()), value(4, vec![]))
+      ]))
+    ])).unwrap();
+    writer.write(&row(vec![("id", Field::Long(2))])).unwrap();
+
+    assert_eq!(writer.def_levels(0), &[0, 0]);
+    assert_eq!(writer.values(0), &[Field::Long(1), Field::Long(2)]);
+    assert_eq!(writer.def_levels(1), &[2, 2, 2, 0]);
+    assert_eq!(writer.rep_levels(1), &[0, 1, 1, 0]);
+    assert_eq!(writer.def_levels(2), &[3, 2, 3, 0]);
+    assert_eq!(writer.rep_levels(2), &[0, 1, 1, 0]);
+    assert_eq!(writer.values(2), &[Field::Int(1), Field::Int(4)]);
+    assert_eq!(writer.def_levels(3), &[4, 4, 2, 3, 0]);
+    assert_eq!(writer.rep_levels(3), &[0, 2, 1, 1, 0]);
+    assert_eq!(writer.values(3), &[Field::Int(2), Field::Int(3)]);
+  }
+
+  #[test]
+  fn test_invalid_row_is_not_buffered() {
+    let mut writer = row_writer("
+      message schema {
+        REQUIRED INT32 a;
+        REQUIRED GROUP b {
+          OPTIONAL INT32 c;
+          REQUIRED INT32 d;
+        }
+      }
+    ");
+    writer.write(&row(vec![
+      ("a", Field::Int(1)),
+      ("b", Field::Group(row(vec![("d", Field::Int(2))])))
+    ])).unwrap();
+    let result = writer.write(&row(vec![
+      ("a", Field::Int(3)),
+      ("b", Field::Group(row(vec![("c", Field::Int(4))])))
+    ]));
+    assert!(result.is_err());
+    let result = writer.write(&row(vec![("a", Field::Int(3)), ("e", Field::Int(4))]));
+    assert_eq!(result.unwrap_err().description(), "Field e is not part of group schema");
+    assert_eq!(writer.num_rows(), 1);
+    assert_eq!(writer.values(0), &[Field::Int(1)]);
+    assert_eq!(writer.def_levels(1), &[0]);
+    assert_eq!(writer.values(1), &[] as &[Field]);
+    assert_eq!(writer.values(2), &[Field::Int(2)]);
+  }
+
+  #[test]
+  fn test_write_to_row_group() {
+    let schema = Rc::new(parse_message_type("
+      message schema {
+        REQUIRED INT32 a;
+        OPTIONAL GROUP b (LIST) {
+          REPEATED GROUP list {
+            OPTIONAL BYTE_ARRAY element (UTF8);
+          }
+        }
+      }
+    ").unwrap());
+    let path = get_temp_file_path("test_row_writer.parquet");
+    let mut file_writer = SerializedFileWriter::new(
+      File::create(&path).unwrap(), schema, WriterOptions::new()).unwrap();
+    let mut writer = RowWriter::new(file_writer.schema_descr().clone());
+    let rows = vec![
+      row(vec![
+        ("a", Field::Int(1)),
+        ("b", Field::List(vec![Field::Str("x".to_owned()), Field::Null]))
+      ]),
+      row(vec![("a", Field::Int(2)), ("b", Field::Null)]),
+      row(vec![("a", Field::Int(3)), ("b", Field::List(vec![]))])
+    ];
+    for _ in 0..2 {
+      for row in &rows {
+        writer.write(row).unwrap();
+      }
+      let mut row_group = file_writer.next_row_group();
+      writer.write_to_row_group(&mut row_group).unwrap();
+      assert_eq!(writer.num_rows(), 0);
+      file_writer.write_row_group(row_group).unwrap();
+    }
+    file_writer.close().unwrap();
+
+    let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+    assert_eq!(reader.num_row_groups(), 2);
+    let read: Vec<Row> = reader.get_row_iter(None).unwrap().map(|r| r.unwrap()).collect();
+    let expected = vec![
+      "{a: 1, b: [\"x\", null]}",
+      "{a: 2, b: null}",
+      "{a: 3, b: []}"
+    ];
+    let read: Vec<String> = read.iter().map(|r| r.to_string()).collect();
+    assert_eq!(read[..3], expected[..]);
+    assert_eq!(read[3..], expected[..]);
+  }
+
+  #[test]
+  fn test_write_to_row_group_schema() {
+    let schema = parse_message_type("message schema { REQUIRED INT32 a; }").unwrap();
+    let path = get_temp_file_path("test_row_writer_schema.parquet");
+    let file_writer = SerializedFileWriter::new(
+      File::create(&path).unwrap(), Rc::new(schema), WriterOptions::new()).unwrap();
+    let mut row_group = file_writer.next_row_group();
+
+    // The name of the message does not matter
+    let mut writer = row_writer("message spark_schema { REQUIRED INT32 a; }");
+    writer.write(&row(vec![("a", Field::Int(1))])).unwrap();
+    writer.write_to_row_group(&mut row_group).unwrap();
+
+    let mut writer = row_writer("message schema { OPTIONAL INT32 a; }");
+    let result = writer.write_to_row_group(&mut row_group);
+    assert_eq!(result.unwrap_err().to_string(),
+      "Parquet error: The schema of the row group is not the schema of the rows");
+  }
+}
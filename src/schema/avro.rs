@@ -0,0 +1,780 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Conversion between Avro schemas (in JSON) and Parquet schemas, following the
+//! conventions of parquet-avro:
+//! - An Avro record is a group; the top-level record is the message type.
+//! - A union of `null` and a single type is an OPTIONAL field. Other unions are groups
+//!   with an OPTIONAL field `member<i>` for every non-null branch.
+//! - An array is a LIST group in the 3-level structure:
+//!   `<repetition> group <name> (LIST) { repeated group list { <element> } }`.
+//! - A map is a MAP group: `<repetition> group <name> (MAP) { repeated group key_value
+//!   { required binary key (UTF8); <value> } }`. Map keys are always strings.
+//! - Strings are UTF8 and enums are ENUM binaries, `fixed` is FIXED_LEN_BYTE_ARRAY, and
+//!   the Avro logical types `decimal`, `date`, `time-*`, `timestamp-*`,
+//!   `local-timestamp-*` and `duration` map to the equivalent Parquet logical types.
+//!
+//! When converting back to Avro, lists and maps are recognized with the
+//! backward-compatibility rules of `schema::nested`, REPEATED fields outside of a LIST
+//! are arrays, and OPTIONAL fields default to `null`. INT96 has no Avro equivalent.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use serde_json::{self, Map, Value};
+
+use basic::{LogicalAnnotation, LogicalType, Repetition, TimeUnit, Type as PhysicalType};
+use errors::{ErrorContext, ParquetError, Result};
+use schema::nested::{value_type, ListType, MapType, NestedType};
+use schema::types::{PrimitiveTypeBuilder, Type, TypePtr};
+
+/// Converts an Avro record schema in JSON into a Parquet message type.
+pub fn from_avro_json(json: &str) -> Result<Type> {
+  let schema: Value = serde_json::from_str(json)
+    .map_err(|e| schema_err!(ErrorContext::new(), "Invalid Avro schema: {}", e))?;
+  AvroConverter::new().convert_schema(&schema)
+}
+
+/// Converts the Parquet message type `schema` into an Avro record schema in JSON.
+pub fn to_avro_json(schema: &Type) -> Result<String> {
+  if !schema.is_group() {
+    return Err(schema_err!(ErrorContext::new(),
+      "Expected a message type, found {}", schema.name()));
+  }
+  let record = record_schema(schema.name(), "", schema.get_fields())?;
+  serde_json::to_string_pretty(&record)
+    .map_err(|e| general_err!("Could not write Avro schema: {}", e))
+}
+
+// ----------------------------------------------------------------------
+// Avro to Parquet
+
+struct AvroConverter {
+  // Named types (records, enums and fixed) defined so far, by full and simple name
+  named_types: HashMap<String, Value>,
+  // Full names of the records being converted, to detect recursive schemas
+  records: Vec<String>
+}
+
+impl AvroConverter {
+  fn new() -> Self {
+    Self { named_types: HashMap::new(), records: vec!() }
+  }
+
+  fn convert_schema(&mut self, schema: &Value) -> Result<Type> {
+    let object = match schema.as_object() {
+      Some(object) if object.get("type") == Some(&json!("record")) => object,
+      _ => return Err(schema_err!(ErrorContext::new(),
+        "Expected an Avro record schema, found {}", schema))
+    };
+    let name = get_str(object, "name")?;
+    let namespace = object.get("namespace").and_then(|v| v.as_str()).unwrap_or("");
+    let full_name = self.register(name, namespace, schema)?;
+    self.records.push(full_name);
+    let mut fields = self.convert_fields(object, namespace)?;
+    self.records.pop();
+    Type::group_type_builder(name).with_fields(&mut fields).build()
+  }
+
+  fn convert_fields(
+    &mut self, record: &Map<String, Value>, namespace: &str
+  ) -> Result<Vec<TypePtr>> {
+    let fields = match record.get("fields").and_then(|v| v.as_array()) {
+      Some(fields) => fields,
+      None => return Err(schema_err!(ErrorContext::new(),
+        "Avro record without fields: {:?}", record))
+    };
+    let mut result = Vec::with_capacity(fields.len());
+    for field in fields {
+      let object = match field.as_object() {
+        Some(object) => object,
+        None => return Err(schema_err!(ErrorContext::new(),
+          "Invalid Avro record field: {}", field))
+      };
+      let name = get_str(object, "name")?;
+      let schema = match object.get("type") {
+        Some(schema) => schema,
+        None => return Err(schema_err!(ErrorContext::new(),
+          "Avro field {} has no type", name))
+      };
+      let tp = self.convert_type(name, schema, Repetition::REQUIRED, namespace)?;
+      result.push(Rc::new(tp));
+    }
+    Ok(result)
+  }
+
+  fn convert_type(
+    &mut self, name: &str, schema: &Value, repetition: Repetition, namespace: &str
+  ) -> Result<Type> {
+    match *schema {
+      Value::String(ref type_name) => {
+        self.convert_named(name, type_name, repetition, namespace)
+      },
+      Value::Array(ref branches) => {
+        self.convert_union(name, branches, repetition, namespace)
+      },
+      Value::Object(ref object) => {
+        self.convert_complex(name, schema, object, repetition, namespace)
+      },
+      _ => Err(schema_err!(ErrorContext::new(),
+        "Invalid Avro schema for field {}: {}", name, schema))
+    }
+  }
+
+  // Converts a primitive type, or a reference to a named type
+  fn convert_named(
+    &mut self, name: &str, type_name: &str, repetition: Repetition, namespace: &str
+  ) -> Result<Type> {
+    let physical_type = match type_name {
+      "boolean" => PhysicalType::BOOLEAN,
+      "int" => PhysicalType::INT32,
+      "long" => PhysicalType::INT64,
+      "float" => PhysicalType::FLOAT,
+      "double" => PhysicalType::DOUBLE,
+      "bytes" | "string" => PhysicalType::BYTE_ARRAY,
+      "null" => {
+        return Err(schema_err!(ErrorContext::new(),
+          "Avro null type of field {} is only supported in unions", name));
+      },
+      _ => {
+        let schema = self.named_types.get(&full_name(type_name, namespace))
+          .or_else(|| self.named_types.get(type_name))
+          .cloned();
+        return match schema {
+          Some(schema) => self.convert_type(name, &schema, repetition, namespace),
+          None => Err(schema_err!(ErrorContext::new(),
+            "Unknown Avro type of field {}: {}", name, type_name))
+        };
+      }
+    };
+    let logical_type =
+      if type_name == "string" { LogicalType::UTF8 } else { LogicalType::NONE };
+    Type::primitive_type_builder(name, physical_type)
+      .with_repetition(repetition)
+      .with_logical_type(logical_type)
+      .build()
+  }
+
+  fn convert_union(
+    &mut self, name: &str, branches: &[Value], repetition: Repetition, namespace: &str
+  ) -> Result<Type> {
+    let null = json!("null");
+    let types: Vec<&Value> = branches.iter().filter(|b| **b != null).collect();
+    let repetition =
+      if types.len() < branches.len() { Repetition::OPTIONAL } else { repetition };
+    match types.len() {
+      0 => Err(schema_err!(ErrorContext::new(),
+        "Avro union of field {} has no non-null type", name)),
+      1 => self.convert_type(name, types[0], repetition, namespace),
+      _ => {
+        let mut fields = Vec::with_capacity(types.len());
+        for (i, tp) in types.iter().enumerate() {
+          let member = format!("member{}", i);
+          fields.push(Rc::new(
+            self.convert_type(&member, tp, Repetition::OPTIONAL, namespace)?));
+        }
+        Type::group_type_builder(name)
+          .with_repetition(repetition)
+          .with_fields(&mut fields)
+          .build()
+      }
+    }
+  }
+
+  fn convert_complex(
+    &mut self,
+    name: &str,
+    schema: &Value,
+    object: &Map<String, Value>,
+    repetition: Repetition,
+    namespace: &str
+  ) -> Result<Type> {
+    let type_name = match object.get("type") {
+      Some(&Value::String(ref type_name)) => type_name.as_str(),
+      Some(tp) => return self.convert_type(name, tp, repetition, namespace),
+      None => return Err(schema_err!(ErrorContext::new(),
+        "Avro schema of field {} has no type", name))
+    };
+    let logical_type = object.get("logicalType").and_then(|v| v.as_str());
+    match type_name {
+      "record" => {
+        let record_name = get_str(object, "name")?;
+        let namespace =
+          object.get("namespace").and_then(|v| v.as_str()).unwrap_or(namespace);
+        let full_name = self.register(record_name, namespace, schema)?;
+        if self.records.contains(&full_name) {
+          return Err(unsupported_err!(ErrorContext::new(),
+            "Recursive Avro record {} is not supported", full_name));
+        }
+        self.records.push(full_name);
+        let mut fields = self.convert_fields(object, namespace)?;
+        self.records.pop();
+        Type::group_type_builder(name)
+          .with_repetition(repetition)
+          .with_fields(&mut fields)
+          .build()
+      },
+      "enum" => {
+        self.register(get_str(object, "name")?, namespace, schema)?;
+        Type::primitive_type_builder(name, PhysicalType::BYTE_ARRAY)
+          .with_repetition(repetition)
+          .with_logical_type(LogicalType::ENUM)
+          .build()
+      },
+      "fixed" => {
+        self.register(get_str(object, "name")?, namespace, schema)?;
+        let size = match object.get("size").and_then(|v| v.as_i64()) {
+          Some(size) => size as i32,
+          None => return Err(schema_err!(ErrorContext::new(),
+            "Avro fixed type of field {} has no size", name))
+        };
+        let builder =
+          Type::primitive_type_builder(name, PhysicalType::FIXED_LEN_BYTE_ARRAY)
+            .with_repetition(repetition)
+            .with_length(size);
+        match logical_type {
+          Some("decimal") => decimal(builder, object)?.build(),
+          Some("duration") => builder.with_logical_type(LogicalType::INTERVAL).build(),
+          _ => builder.build()
+        }
+      },
+      "array" => {
+        let items = match object.get("items") {
+          Some(items) => items,
+          None => return Err(schema_err!(ErrorContext::new(),
+            "Avro array of field {} has no items", name))
+        };
+        let element =
+          self.convert_type("element", items, Repetition::REQUIRED, namespace)?;
+        let list = Type::group_type_builder("list")
+          .with_repetition(Repetition::REPEATED)
+          .with_fields(&mut vec!(Rc::new(element)))
+          .build()?;
+        Type::group_type_builder(name)
+          .with_repetition(repetition)
+          .with_logical_type(LogicalType::LIST)
+          .with_fields(&mut vec!(Rc::new(list)))
+          .build()
+      },
+      "map" => {
+        let values = match object.get("values") {
+          Some(values) => values,
+          None => return Err(schema_err!(ErrorContext::new(),
+            "Avro map of field {} has no values", name))
+        };
+        let key = Type::primitive_type_builder("key", PhysicalType::BYTE_ARRAY)
+          .with_repetition(Repetition::REQUIRED)
+          .with_logical_type(LogicalType::UTF8)
+          .build()?;
+        let value = self.convert_type("value", values, Repetition::REQUIRED, namespace)?;
+        let key_value = Type::group_type_builder("key_value")
+          .with_repetition(Repetition::REPEATED)
+          .with_fields(&mut vec!(Rc::new(key), Rc::new(value)))
+          .build()?;
+        Type::group_type_builder(name)
+          .with_repetition(repetition)
+          .with_logical_type(LogicalType::MAP)
+          .with_fields(&mut vec!(Rc::new(key_value)))
+          .build()
+      },
+      _ => {
+        let tp = self.convert_named(name, type_name, repetition, namespace)?;
+        let (physical_type, logical_type) = match (type_name, logical_type) {
+          ("int", Some("date")) => (PhysicalType::INT32, LogicalType::DATE),
+          ("int", Some("time-millis")) => (PhysicalType::INT32, LogicalType::TIME_MILLIS),
+          ("long", Some("time-micros")) => {
+            (PhysicalType::INT64, LogicalType::TIME_MICROS)
+          },
+          ("long", Some("timestamp-millis")) => {
+            (PhysicalType::INT64, LogicalType::TIMESTAMP_MILLIS)
+          },
+          ("long", Some("timestamp-micros")) => {
+            (PhysicalType::INT64, LogicalType::TIMESTAMP_MICROS)
+          },
+          ("long", Some("timestamp-nanos")) => {
+            return timestamp(name, repetition, true, TimeUnit::NANOS);
+          },
+          ("long", Some("local-timestamp-millis")) => {
+            return timestamp(name, repetition, false, TimeUnit::MILLIS);
+          },
+          ("long", Some("local-timestamp-micros")) => {
+            return timestamp(name, repetition, false, TimeUnit::MICROS);
+          },
+          ("long", Some("local-timestamp-nanos")) => {
+            return timestamp(name, repetition, false, TimeUnit::NANOS);
+          },
+          ("bytes", Some("decimal")) => {
+            let builder = Type::primitive_type_builder(name, PhysicalType::BYTE_ARRAY)
+              .with_repetition(repetition);
+            return decimal(builder, object)?.build();
+          },
+          // Unknown logical types are ignored, as required by the Avro specification
+          _ => return Ok(tp)
+        };
+        Type::primitive_type_builder(name, physical_type)
+          .with_repetition(repetition)
+          .with_logical_type(logical_type)
+          .build()
+      }
+    }
+  }
+
+  // Registers the named type `schema`, and returns its full name.
+  fn register(&mut self, name: &str, namespace: &str, schema: &Value) -> Result<String> {
+    let full_name = full_name(name, namespace);
+    self.named_types.insert(full_name.clone(), schema.clone());
+    self.named_types.insert(name.to_owned(), schema.clone());
+    Ok(full_name)
+  }
+}
+
+fn timestamp(
+  name: &str, repetition: Repetition, is_adjusted_to_utc: bool, unit: TimeUnit
+) -> Result<Type> {
+  let annotation =
+    LogicalAnnotation::TIMESTAMP { is_adjusted_to_utc: is_adjusted_to_utc, unit: unit };
+  Type::primitive_type_builder(name, PhysicalType::INT64)
+    .with_repetition(repetition)
+    .with_logical_annotation(Some(annotation))
+    .build()
+}
+
+fn decimal<'a>(
+  builder: PrimitiveTypeBuilder<'a>, object: &Map<String, Value>
+) -> Result<PrimitiveTypeBuilder<'a>> {
+  let precision = match object.get("precision").and_then(|v| v.as_i64()) {
+    Some(precision) => precision as i32,
+    None => return Err(schema_err!(ErrorContext::new(),
+      "Avro decimal without precision: {:?}", object))
+  };
+  let scale = object.get("scale").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+  Ok(builder
+    .with_logical_type(LogicalType::DECIMAL)
+    .with_precision(precision)
+    .with_scale(scale))
+}
+
+fn get_str<'a>(object: &'a Map<String, Value>, key: &str) -> Result<&'a str> {
+  match object.get(key).and_then(|v| v.as_str()) {
+    Some(value) => Ok(value),
+    None => Err(schema_err!(ErrorContext::new(),
+      "Expected string attribute {} in Avro schema {:?}", key, object))
+  }
+}
+
+fn full_name(name: &str, namespace: &str) -> String {
+  if namespace.is_empty() || name.contains('.') {
+    name.to_owned()
+  } else {
+    format!("{}.{}", namespace, name)
+  }
+}
+
+// ----------------------------------------------------------------------
+// Parquet to Avro
+
+fn record_schema(name: &str, namespace: &str, fields: &[TypePtr]) -> Result<Value> {
+  let nested_namespace = full_name(name, namespace);
+  let mut avro_fields = Vec::with_capacity(fields.len());
+  for field in fields {
+    let mut avro_field = Map::new();
+    avro_field.insert("name".to_owned(), json!(field.name()));
+    avro_field.insert("type".to_owned(), field_schema(field, &nested_namespace)?);
+    if field.get_basic_info().repetition() == Repetition::OPTIONAL {
+      avro_field.insert("default".to_owned(), Value::Null);
+    }
+    avro_fields.push(Value::Object(avro_field));
+  }
+
+  let mut record = Map::new();
+  record.insert("type".to_owned(), json!("record"));
+  record.insert("name".to_owned(), json!(name));
+  if !namespace.is_empty() {
+    record.insert("namespace".to_owned(), json!(namespace));
+  }
+  record.insert("fields".to_owned(), Value::Array(avro_fields));
+  Ok(Value::Object(record))
+}
+
+// Returns the Avro schema of a field, including its repetition.
+fn field_schema(field: &Type, namespace: &str) -> Result<Value> {
+  let schema = type_schema(field, namespace)?;
+  Ok(match field.get_basic_info().repetition() {
+    Repetition::OPTIONAL => json!(["null", schema]),
+    Repetition::REPEATED => json!({ "type": "array", "items": schema }),
+    Repetition::REQUIRED => schema
+  })
+}
+
+// Returns the Avro schema of a field, ignoring its repetition.
+fn type_schema(tp: &Type, namespace: &str) -> Result<Value> {
+  match *tp {
+    Type::PrimitiveType {
+      ref basic_info, physical_type, type_length, scale, precision
+    } => {
+      let logical_type = basic_info.logical_type();
+      let schema = match physical_type {
+        PhysicalType::BOOLEAN => json!("boolean"),
+        PhysicalType::INT32 => match logical_type {
+          LogicalType::DATE => json!({ "type": "int", "logicalType": "date" }),
+          LogicalType::TIME_MILLIS => {
+            json!({ "type": "int", "logicalType": "time-millis" })
+          },
+          _ => json!("int")
+        },
+        PhysicalType::INT64 => match basic_info.logical_annotation() {
+          Some(LogicalAnnotation::TIME { unit: TimeUnit::MICROS, .. }) => {
+            json!({ "type": "long", "logicalType": "time-micros" })
+          },
+          Some(LogicalAnnotation::TIMESTAMP { is_adjusted_to_utc, unit }) => {
+            let unit = match unit {
+              TimeUnit::MILLIS => "millis",
+              TimeUnit::MICROS => "micros",
+              TimeUnit::NANOS => "nanos"
+            };
+            let prefix = if is_adjusted_to_utc { "" } else { "local-" };
+            let logical_type = format!("{}timestamp-{}", prefix, unit);
+            json!({ "type": "long", "logicalType": logical_type })
+          },
+          _ => json!("long")
+        },
+        PhysicalType::INT96 => {
+          return Err(unsupported_err!(ErrorContext::new(),
+            "INT96 field {} cannot be converted to Avro", tp.name()));
+        },
+        PhysicalType::FLOAT => json!("float"),
+        PhysicalType::DOUBLE => json!("double"),
+        PhysicalType::BYTE_ARRAY => match logical_type {
+          LogicalType::UTF8 | LogicalType::ENUM | LogicalType::JSON => json!("string"),
+          LogicalType::DECIMAL => json!({
+            "type": "bytes", "logicalType": "decimal",
+            "precision": precision, "scale": scale
+          }),
+          _ => json!("bytes")
+        },
+        PhysicalType::FIXED_LEN_BYTE_ARRAY => {
+          let mut fixed = Map::new();
+          fixed.insert("type".to_owned(), json!("fixed"));
+          fixed.insert("name".to_owned(), json!(tp.name()));
+          if !namespace.is_empty() {
+            fixed.insert("namespace".to_owned(), json!(namespace));
+          }
+          fixed.insert("size".to_owned(), json!(type_length));
+          match logical_type {
+            LogicalType::DECIMAL => {
+              fixed.insert("logicalType".to_owned(), json!("decimal"));
+              fixed.insert("precision".to_owned(), json!(precision));
+              fixed.insert("scale".to_owned(), json!(scale));
+            },
+            LogicalType::INTERVAL => {
+              fixed.insert("logicalType".to_owned(), json!("duration"));
+            },
+            _ => ()
+          }
+          Value::Object(fixed)
+        }
+      };
+      Ok(schema)
+    },
+    Type::GroupType { ref fields, .. } => {
+      match value_type(tp)? {
+        NestedType::List(ref list) => list_schema(list, namespace),
+        NestedType::Map(ref map) => map_schema(map, namespace),
+        _ => record_schema(tp.name(), namespace, fields)
+      }
+    }
+  }
+}
+
+fn list_schema(list: &ListType, namespace: &str) -> Result<Value> {
+  let nested_namespace = full_name(list.field.name(), namespace);
+  let items = if list.is_two_level() {
+    type_schema(list.element, &nested_namespace)?
+  } else {
+    field_schema(list.element, &nested_namespace)?
+  };
+  Ok(json!({ "type": "array", "items": items }))
+}
+
+fn map_schema(map: &MapType, namespace: &str) -> Result<Value> {
+  let key_info = map.key.get_basic_info();
+  let string_keys = match *map.key {
+    Type::PrimitiveType { physical_type: PhysicalType::BYTE_ARRAY, .. } => {
+      key_info.logical_type() == LogicalType::UTF8 ||
+        key_info.logical_annotation() == Some(LogicalAnnotation::STRING)
+    },
+    _ => false
+  };
+  let value = match map.value {
+    Some(value) if string_keys => value,
+    _ => {
+      return Err(schema_err!(ErrorContext::new(),
+        "MAP group {} must have UTF8 keys and values to convert to Avro",
+        map.field.name()));
+    }
+  };
+  let values = field_schema(value, &full_name(map.field.name(), namespace))?;
+  Ok(json!({ "type": "map", "values": values }))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::error::Error;
+
+  use schema::parser::parse_message_type;
+
+  #[test]
+  fn test_from_avro_json() {
+    let avro = r#"{
+      "type": "record",
+      "name": "User",
+      "namespace": "com.example",
+      "fields": [
+        {"name": "id", "type": "long"},
+        {"name": "name", "type": "string"},
+        {"name": "email", "type": ["null", "string"], "default": null},
+        {"name": "active", "type": "boolean"},
+        {"name": "score", "type": ["double", "null"]},
+        {"name": "rank", "type": "float"},
+        {"name": "avatar", "type": "bytes"},
+        {"name": "kind", "type": {"type": "enum", "name": "Kind", "symbols": ["A", "B"]}},
+        {"name": "kind2", "type": "Kind"},
+        {"name": "hash", "type": {"type": "fixed", "name": "MD5", "size": 16}},
+        {"name": "balance", "type": {
+          "type": "fixed", "name": "Balance", "size": 8, "logicalType": "decimal",
+          "precision": 18, "scale": 2}},
+        {"name": "price", "type": {
+          "type": "bytes", "logicalType": "decimal", "precision": 10, "scale": 3}},
+        {"name": "birthday", "type": {"type": "int", "logicalType": "date"}},
+        {"name": "created", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+        {"name": "local", "type": {
+          "type": "long", "logicalType": "local-timestamp-micros"}},
+        {"name": "uuid", "type": {"type": "string", "logicalType": "uuid"}},
+        {"name": "tags", "type": {"type": "array", "items": "string"}},
+        {"name": "scores", "type": ["null", {"type": "array", "items": ["null", "int"]}]},
+        {"name": "props", "type": {"type": "map", "values": "long"}},
+        {"name": "address", "type": ["null", {
+          "type": "record", "name": "Address", "fields": [
+            {"name": "street", "type": "string"},
+            {"name": "zip", "type": ["null", "int"]}
+          ]}]},
+        {"name": "previous", "type": {"type": "array", "items": "Address"}},
+        {"name": "value", "type": ["null", "int", "string"]}
+      ]
+    }"#;
+    let expected = parse_message_type("
+      message User {
+        REQUIRED INT64 id;
+        REQUIRED BYTE_ARRAY name (UTF8);
+        OPTIONAL BYTE_ARRAY email (UTF8);
+        REQUIRED BOOLEAN active;
+        OPTIONAL DOUBLE score;
+        REQUIRED FLOAT rank;
+        REQUIRED BYTE_ARRAY avatar;
+        REQUIRED BYTE_ARRAY kind (ENUM);
+        REQUIRED BYTE_ARRAY kind2 (ENUM);
+        REQUIRED FIXED_LEN_BYTE_ARRAY (16) hash;
+        REQUIRED FIXED_LEN_BYTE_ARRAY (8) balance (DECIMAL(18,2));
+        REQUIRED BYTE_ARRAY price (DECIMAL(10,3));
+        REQUIRED INT32 birthday (DATE);
+        REQUIRED INT64 created (TIMESTAMP_MILLIS);
+        REQUIRED INT64 local (TIMESTAMP(MICROS,false));
+        REQUIRED BYTE_ARRAY uuid (UTF8);
+        REQUIRED GROUP tags (LIST) {
+          REPEATED GROUP list {
+            REQUIRED BYTE_ARRAY element (UTF8);
+          }
+        }
+        OPTIONAL GROUP scores (LIST) {
+          REPEATED GROUP list {
+            OPTIONAL INT32 element;
+          }
+        }
+        REQUIRED GROUP props (MAP) {
+          REPEATED GROUP key_value {
+            REQUIRED BYTE_ARRAY key (UTF8);
+            REQUIRED INT64 value;
+          }
+        }
+        OPTIONAL GROUP address {
+          REQUIRED BYTE_ARRAY street (UTF8);
+          OPTIONAL INT32 zip;
+        }
+        REQUIRED GROUP previous (LIST) {
+          REPEATED GROUP list {
+            REQUIRED GROUP element {
+              REQUIRED BYTE_ARRAY street (UTF8);
+              OPTIONAL INT32 zip;
+            }
+          }
+        }
+        OPTIONAL GROUP value {
+          OPTIONAL INT32 member0;
+          OPTIONAL BYTE_ARRAY member1 (UTF8);
+        }
+      }
+    ").unwrap();
+    assert_eq!(from_avro_json(avro).unwrap(), expected);
+  }
+
+  #[test]
+  fn test_from_avro_json_invalid() {
+    let invalid = [
+      "{",
+      r#""string""#,
+      r#"{"type": "record", "name": "r"}"#,
+      r#"{"type": "record", "name": "r", "fields": [{"name": "a", "type": "null"}]}"#,
+      r#"{"type": "record", "name": "r", "fields": [{"name": "a", "type": "B"}]}"#
+    ];
+    for json in invalid.iter() {
+      match from_avro_json(json) {
+        Err(ParquetError::SchemaMismatch(_, _)) => (),
+        other => panic!("Expected a schema error for {}, got {:?}", json, other)
+      }
+    }
+
+    let recursive = r#"{
+      "type": "record", "name": "r", "fields": [
+        {"name": "node", "type": {"type": "record", "name": "Node", "fields": [
+          {"name": "next", "type": ["null", "Node"]}
+        ]}}
+      ]
+    }"#;
+    match from_avro_json(recursive) {
+      Err(ref e @ ParquetError::Unsupported(_, _)) => assert_eq!(
+        e.description(), "Recursive Avro record Node is not supported"),
+      other => panic!("Expected error for recursive record, got {:?}", other)
+    }
+  }
+
+  #[test]
+  fn test_to_avro_json() {
+    let schema = parse_message_type("
+      message spark_schema {
+        REQUIRED INT32 a (INT_16);
+        OPTIONAL BYTE_ARRAY b (UTF8);
+        REPEATED INT64 c (TIMESTAMP_MICROS);
+        OPTIONAL FIXED_LEN_BYTE_ARRAY (12) d (INTERVAL);
+        OPTIONAL GROUP e (LIST) {
+          REPEATED GROUP list {
+            OPTIONAL BYTE_ARRAY element (DECIMAL(9,2));
+          }
+        }
+        OPTIONAL GROUP f (LIST) {
+          REPEATED INT32 array;
+        }
+        REQUIRED GROUP g (LIST) {
+          REPEATED GROUP array {
+            REQUIRED DOUBLE x;
+          }
+        }
+        OPTIONAL GROUP h (MAP) {
+          REPEATED GROUP key_value {
+            REQUIRED BYTE_ARRAY key (UTF8);
+            OPTIONAL GROUP value {
+              REQUIRED BOOLEAN y;
+            }
+          }
+        }
+      }
+    ").unwrap();
+    let expected: Value = serde_json::from_str(r#"{
+      "type": "record",
+      "name": "spark_schema",
+      "fields": [
+        {"name": "a", "type": "int"},
+        {"name": "b", "type": ["null", "string"], "default": null},
+        {"name": "c", "type": {
+          "type": "array",
+          "items": {"type": "long", "logicalType": "timestamp-micros"}}},
+        {"name": "d", "type": ["null", {
+          "type": "fixed", "name": "d", "namespace": "spark_schema", "size": 12,
+          "logicalType": "duration"}], "default": null},
+        {"name": "e", "type": ["null", {
+          "type": "array",
+          "items": ["null", {
+            "type": "bytes", "logicalType": "decimal", "precision": 9, "scale": 2}]}],
+          "default": null},
+        {"name": "f", "type": ["null", {"type": "array", "items": "int"}],
+          "default": null},
+        {"name": "g", "type": {"type": "array", "items": {
+          "type": "record", "name": "array", "namespace": "spark_schema.g",
+          "fields": [{"name": "x", "type": "double"}]}}},
+        {"name": "h", "type": ["null", {"type": "map", "values": ["null", {
+          "type": "record", "name": "value", "namespace": "spark_schema.h",
+          "fields": [{"name": "y", "type": "boolean"}]}]}],
+          "default": null}
+      ]
+    }"#).unwrap();
+    let actual: Value = serde_json::from_str(&to_avro_json(&schema).unwrap()).unwrap();
+    assert_eq!(actual, expected);
+  }
+
+  #[test]
+  fn test_to_avro_json_invalid() {
+    let schema = parse_message_type("message m { REQUIRED INT96 a; }").unwrap();
+    match to_avro_json(&schema) {
+      Err(ParquetError::Unsupported(_, _)) => (),
+      other => panic!("Expected INT96 to be unsupported, got {:?}", other)
+    }
+
+    // Avro map keys are strings, so INT32 and unannotated BYTE_ARRAY keys are invalid
+    for key_type in ["INT32", "BYTE_ARRAY"].iter() {
+      let schema = parse_message_type(&format!("
+        message m {{
+          REQUIRED GROUP a (MAP) {{
+            REPEATED GROUP key_value {{
+              REQUIRED {} key;
+              REQUIRED INT32 value;
+            }}
+          }}
+        }}
+      ", key_type)).unwrap();
+      match to_avro_json(&schema) {
+        Err(ParquetError::SchemaMismatch(_, _)) => (),
+        other => panic!("Expected a schema error for {} keys, got {:?}", key_type, other)
+      }
+    }
+  }
+
+  #[test]
+  fn test_avro_round_trip() {
+    let schema = parse_message_type("
+      message m {
+        REQUIRED INT64 a;
+        OPTIONAL GROUP b (LIST) {
+          REPEATED GROUP list {
+            OPTIONAL BYTE_ARRAY element (UTF8);
+          }
+        }
+        OPTIONAL GROUP c (MAP) {
+          REPEATED GROUP key_value {
+            REQUIRED BYTE_ARRAY key (UTF8);
+            REQUIRED FIXED_LEN_BYTE_ARRAY (4) value (DECIMAL(9,2));
+          }
+        }
+        REQUIRED GROUP d {
+          OPTIONAL INT32 e (DATE);
+          REQUIRED INT64 f (TIMESTAMP(NANOS,false));
+        }
+      }
+    ").unwrap();
+    let avro = to_avro_json(&schema).unwrap();
+    assert_eq!(from_avro_json(&avro).unwrap(), schema);
+  }
+}
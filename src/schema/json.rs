@@ -0,0 +1,563 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! JSON representation of schemas and file metadata, as an alternative to the text
+//! format of `schema::printer` for tools that ingest Parquet metadata.
+//!
+//! A schema is a JSON object per type, e.g.
+//! ```text
+//! {
+//!   "name": "schema",
+//!   "fields": [
+//!     {"name": "a", "repetition": "REQUIRED", "type": "INT32", "id": 1},
+//!     {"name": "b", "repetition": "OPTIONAL", "type": "FIXED_LEN_BYTE_ARRAY",
+//!      "length": 8, "precision": 18, "scale": 2, "logical_type": "DECIMAL",
+//!      "annotation": {"type": "DECIMAL", "precision": 18, "scale": 2}},
+//!     {"name": "c", "repetition": "OPTIONAL", "logical_type": "LIST", ...,
+//!      "fields": [...]}
+//!   ]
+//! }
+//! ```
+//! Groups have `fields`, primitive types have a physical `type`. The root (message) type
+//! has no repetition. `logical_type` is the converted type, and `annotation` the
+//! logical type annotation; both are omitted if not set.
+//!
+//! Metadata is written as JSON, including the schema, row groups and column chunks with
+//! their offsets, encodings, sizes and statistics. Min and max statistics are decoded
+//! according to the physical type of the column; binary values are UTF-8 strings for
+//! string columns and hex strings otherwise.
+//!
+//! Writers compute min and max statistics with a signed comparison, i.e. of signed
+//! integers and of signed bytes for binary values. This is the order of BOOLEAN, INT32,
+//! INT64, FLOAT and DOUBLE columns, whose statistics are written as `min` and `max`.
+//! For other columns, e.g. UINT_32 or UTF8 columns, the signed min and max can differ
+//! from the actual ones, and are written as `signed_min` and `signed_max`, with integers
+//! decoded as signed.
+
+use std::io;
+use std::rc::Rc;
+use std::str;
+
+use byteorder::{ByteOrder, LittleEndian};
+use serde_json::{self, Map, Value};
+
+use basic::{LogicalAnnotation, LogicalType, Repetition, TimeUnit, Type as PhysicalType};
+use errors::{ParquetError, Result};
+use file::metadata::{
+  ColumnChunkMetaData, FileMetaData, ParquetMetaData, RowGroupMetaData, Statistics
+};
+use schema::types::{Type, TypePtr};
+
+/// Prints `metadata`, including row groups and column chunks, as pretty JSON.
+#[allow(unused_must_use)]
+pub fn print_parquet_metadata_json(out: &mut io::Write, metadata: &ParquetMetaData) {
+  writeln!(out, "{}", to_pretty_string(&metadata_to_json(metadata)));
+}
+
+/// Prints `file_metadata` as pretty JSON.
+#[allow(unused_must_use)]
+pub fn print_file_metadata_json(out: &mut io::Write, file_metadata: &FileMetaData) {
+  writeln!(out, "{}", to_pretty_string(&file_metadata_to_json(file_metadata)));
+}
+
+/// Returns the JSON representation of `metadata`, i.e. the file metadata with an
+/// additional `row_groups` attribute.
+pub fn metadata_to_json(metadata: &ParquetMetaData) -> Value {
+  let mut result = file_metadata_to_json(metadata.file_metadata());
+  let row_groups = metadata.row_groups().iter().map(row_group_to_json).collect();
+  if let Value::Object(ref mut object) = result {
+    object.insert("row_groups".to_owned(), Value::Array(row_groups));
+  }
+  result
+}
+
+/// Returns the JSON representation of `file_metadata`, including the schema.
+pub fn file_metadata_to_json(file_metadata: &FileMetaData) -> Value {
+  let mut result = Map::new();
+  result.insert("version".to_owned(), json!(file_metadata.version()));
+  result.insert("num_rows".to_owned(), json!(file_metadata.num_rows()));
+  if let Some(ref created_by) = *file_metadata.created_by() {
+    result.insert("created_by".to_owned(), json!(created_by));
+  }
+  if let Some(ref key_values) = *file_metadata.key_value_metadata() {
+    let key_values = key_values.iter()
+      .map(|kv| json!({ "key": kv.key, "value": kv.value }))
+      .collect();
+    result.insert("key_value_metadata".to_owned(), Value::Array(key_values));
+  }
+  result.insert("schema".to_owned(), schema_to_json(file_metadata.schema()));
+  Value::Object(result)
+}
+
+fn row_group_to_json(row_group: &RowGroupMetaData) -> Value {
+  let columns: Vec<Value> =
+    row_group.columns().iter().map(column_chunk_to_json).collect();
+  json!({
+    "num_rows": row_group.num_rows(),
+    "total_byte_size": row_group.total_byte_size(),
+    "columns": columns
+  })
+}
+
+fn column_chunk_to_json(column: &ColumnChunkMetaData) -> Value {
+  let mut result = Map::new();
+  result.insert("path".to_owned(), json!(column.column_path().string()));
+  result.insert("type".to_owned(), json!(column.column_type().to_string()));
+  let encodings: Vec<String> = column.encodings().iter().map(|e| e.to_string()).collect();
+  result.insert("encodings".to_owned(), json!(encodings));
+  result.insert("compression".to_owned(), json!(column.compression().to_string()));
+  if let Some(file_path) = column.file_path() {
+    result.insert("file_path".to_owned(), json!(file_path));
+  }
+  result.insert("file_offset".to_owned(), json!(column.file_offset()));
+  result.insert("num_values".to_owned(), json!(column.num_values()));
+  result.insert("compressed_size".to_owned(), json!(column.compressed_size()));
+  result.insert("uncompressed_size".to_owned(), json!(column.uncompressed_size()));
+  result.insert("data_page_offset".to_owned(), json!(column.data_page_offset()));
+  if let Some(offset) = column.index_page_offset() {
+    result.insert("index_page_offset".to_owned(), json!(offset));
+  }
+  if let Some(offset) = column.dictionary_page_offset() {
+    result.insert("dictionary_page_offset".to_owned(), json!(offset));
+  }
+  if let Some(statistics) = column.statistics() {
+    result.insert("statistics".to_owned(), statistics_to_json(column, statistics));
+  }
+  Value::Object(result)
+}
+
+fn statistics_to_json(column: &ColumnChunkMetaData, statistics: &Statistics) -> Value {
+  let (min_key, max_key) =
+    if has_signed_order(column) { ("min", "max") } else { ("signed_min", "signed_max") };
+  let mut result = Map::new();
+  if let Some(ref min) = statistics.min {
+    result.insert(min_key.to_owned(), statistics_value_to_json(column, min));
+  }
+  if let Some(ref max) = statistics.max {
+    result.insert(max_key.to_owned(), statistics_value_to_json(column, max));
+  }
+  if let Some(null_count) = statistics.null_count {
+    result.insert("null_count".to_owned(), json!(null_count));
+  }
+  if let Some(distinct_count) = statistics.distinct_count {
+    result.insert("distinct_count".to_owned(), json!(distinct_count));
+  }
+  Value::Object(result)
+}
+
+// Whether values of `column` are ordered as the min and max statistics are computed,
+// see the module documentation.
+fn has_signed_order(column: &ColumnChunkMetaData) -> bool {
+  match column.column_type() {
+    PhysicalType::BOOLEAN | PhysicalType::FLOAT | PhysicalType::DOUBLE => true,
+    PhysicalType::INT32 | PhysicalType::INT64 => {
+      match column.column_descr().logical_type() {
+        LogicalType::UINT_8 | LogicalType::UINT_16 | LogicalType::UINT_32 |
+        LogicalType::UINT_64 => false,
+        _ => true
+      }
+    },
+    PhysicalType::INT96 | PhysicalType::BYTE_ARRAY |
+    PhysicalType::FIXED_LEN_BYTE_ARRAY => false
+  }
+}
+
+// Decodes a PLAIN encoded min or max value. Integers are signed, as they are compared.
+fn statistics_value_to_json(column: &ColumnChunkMetaData, bytes: &[u8]) -> Value {
+  let logical_type = column.column_descr().logical_type();
+  match (column.column_type(), bytes.len()) {
+    (PhysicalType::BOOLEAN, 1) => json!(bytes[0] != 0),
+    (PhysicalType::INT32, 4) => json!(LittleEndian::read_i32(bytes)),
+    (PhysicalType::INT64, 8) => json!(LittleEndian::read_i64(bytes)),
+    (PhysicalType::FLOAT, 4) => json!(LittleEndian::read_f32(bytes)),
+    (PhysicalType::DOUBLE, 8) => json!(LittleEndian::read_f64(bytes)),
+    _ => {
+      let is_string = match logical_type {
+        LogicalType::UTF8 | LogicalType::ENUM | LogicalType::JSON => true,
+        _ => false
+      };
+      match str::from_utf8(bytes) {
+        Ok(value) if is_string => json!(value),
+        _ => json!(bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+      }
+    }
+  }
+}
+
+/// Returns the JSON representation of the schema type `tp`.
+pub fn schema_to_json(tp: &Type) -> Value {
+  let basic_info = tp.get_basic_info();
+  let mut result = Map::new();
+  result.insert("name".to_owned(), json!(tp.name()));
+  if basic_info.has_repetition() {
+    result.insert("repetition".to_owned(), json!(basic_info.repetition().to_string()));
+  }
+  if let Type::PrimitiveType { physical_type, type_length, precision, scale, .. } = *tp {
+    result.insert("type".to_owned(), json!(physical_type.to_string()));
+    if physical_type == PhysicalType::FIXED_LEN_BYTE_ARRAY {
+      result.insert("length".to_owned(), json!(type_length));
+    }
+    if basic_info.logical_type() == LogicalType::DECIMAL {
+      result.insert("precision".to_owned(), json!(precision));
+      result.insert("scale".to_owned(), json!(scale));
+    }
+  }
+  if basic_info.logical_type() != LogicalType::NONE {
+    result.insert(
+      "logical_type".to_owned(), json!(basic_info.logical_type().to_string()));
+  }
+  if let Some(annotation) = basic_info.logical_annotation() {
+    result.insert("annotation".to_owned(), annotation_to_json(annotation));
+  }
+  if basic_info.has_id() {
+    result.insert("id".to_owned(), json!(basic_info.id()));
+  }
+  if tp.is_group() {
+    let fields = tp.get_fields().iter().map(|f| schema_to_json(f)).collect();
+    result.insert("fields".to_owned(), Value::Array(fields));
+  }
+  Value::Object(result)
+}
+
+fn annotation_to_json(annotation: LogicalAnnotation) -> Value {
+  match annotation {
+    LogicalAnnotation::DECIMAL { scale, precision } => {
+      json!({ "type": "DECIMAL", "precision": precision, "scale": scale })
+    },
+    LogicalAnnotation::TIME { is_adjusted_to_utc, unit } => json!({
+      "type": "TIME", "unit": unit.to_string(), "is_adjusted_to_utc": is_adjusted_to_utc
+    }),
+    LogicalAnnotation::TIMESTAMP { is_adjusted_to_utc, unit } => json!({
+      "type": "TIMESTAMP", "unit": unit.to_string(),
+      "is_adjusted_to_utc": is_adjusted_to_utc
+    }),
+    LogicalAnnotation::INTEGER { bit_width, is_signed } => {
+      json!({ "type": "INTEGER", "bit_width": bit_width, "is_signed": is_signed })
+    },
+    LogicalAnnotation::STRING => json!({ "type": "STRING" }),
+    LogicalAnnotation::MAP => json!({ "type": "MAP" }),
+    LogicalAnnotation::LIST => json!({ "type": "LIST" }),
+    LogicalAnnotation::ENUM => json!({ "type": "ENUM" }),
+    LogicalAnnotation::DATE => json!({ "type": "DATE" }),
+    LogicalAnnotation::UNKNOWN => json!({ "type": "UNKNOWN" }),
+    LogicalAnnotation::JSON => json!({ "type": "JSON" }),
+    LogicalAnnotation::BSON => json!({ "type": "BSON" }),
+    LogicalAnnotation::UUID => json!({ "type": "UUID" })
+  }
+}
+
+/// Parses the JSON representation of a message type, as returned by
+/// `schema_to_json`.
+pub fn parse_schema_json(json: &str) -> Result<Type> {
+  let value: Value = serde_json::from_str(json)
+    .map_err(|e| general_err!("Invalid schema JSON: {}", e))?;
+  let tp = schema_from_json(&value)?;
+  if tp.get_basic_info().has_repetition() || !tp.is_group() {
+    return Err(general_err!("Expected a message type, found {}", value));
+  }
+  Ok(tp)
+}
+
+/// Converts the JSON representation of a type, as returned by `schema_to_json`, back
+/// into a `Type`.
+pub fn schema_from_json(value: &Value) -> Result<Type> {
+  let object = match value.as_object() {
+    Some(object) => object,
+    None => return Err(general_err!("Expected a JSON object for a type, found {}", value))
+  };
+  let name = get_str(object, "name")?.unwrap_or("");
+  if name.is_empty() {
+    return Err(general_err!("Type without name: {}", value));
+  }
+  let repetition = match get_str(object, "repetition")? {
+    Some(repetition) => Some(repetition.parse::<Repetition>()?),
+    None => None
+  };
+  let logical_type = match get_str(object, "logical_type")? {
+    Some(logical_type) => logical_type.parse::<LogicalType>()?,
+    None => LogicalType::NONE
+  };
+  let annotation = match object.get("annotation") {
+    Some(annotation) => Some(annotation_from_json(annotation)?),
+    None => None
+  };
+  let id = get_i64(object, "id")?;
+
+  match object.get("fields") {
+    Some(fields) => {
+      let fields = match fields.as_array() {
+        Some(fields) => fields,
+        None => return Err(general_err!("Invalid fields of {}: {}", name, fields))
+      };
+      let mut fields = fields.iter()
+        .map(|f| schema_from_json(f).map(Rc::new))
+        .collect::<Result<Vec<TypePtr>>>()?;
+      let mut builder = Type::group_type_builder(name)
+        .with_logical_type(logical_type)
+        .with_logical_annotation(annotation)
+        .with_fields(&mut fields);
+      if let Some(repetition) = repetition {
+        builder = builder.with_repetition(repetition);
+      }
+      if let Some(id) = id {
+        builder = builder.with_id(id as i32);
+      }
+      builder.build()
+    },
+    None => {
+      let physical_type = match get_str(object, "type")? {
+        Some(physical_type) => physical_type.parse::<PhysicalType>()?,
+        None => return Err(general_err!("Primitive type {} has no physical type", name))
+      };
+      let repetition = match repetition {
+        Some(repetition) => repetition,
+        None => return Err(general_err!("Primitive type {} has no repetition", name))
+      };
+      let mut builder = Type::primitive_type_builder(name, physical_type)
+        .with_repetition(repetition)
+        .with_logical_type(logical_type)
+        .with_logical_annotation(annotation)
+        .with_length(get_i64(object, "length")?.unwrap_or(0) as i32)
+        .with_precision(get_i64(object, "precision")?.unwrap_or(0) as i32)
+        .with_scale(get_i64(object, "scale")?.unwrap_or(0) as i32);
+      if let Some(id) = id {
+        builder = builder.with_id(id as i32);
+      }
+      builder.build()
+    }
+  }
+}
+
+fn annotation_from_json(value: &Value) -> Result<LogicalAnnotation> {
+  let object = match value.as_object() {
+    Some(object) => object,
+    None => return Err(general_err!("Invalid logical type annotation: {}", value))
+  };
+  let unit = || -> Result<TimeUnit> {
+    match get_str(object, "unit")? {
+      Some(unit) => unit.parse::<TimeUnit>(),
+      None => Err(general_err!("Missing unit in annotation: {}", value))
+    }
+  };
+  let is_adjusted_to_utc = || get_bool(object, "is_adjusted_to_utc", value);
+  let annotation = match get_str(object, "type")?.unwrap_or("") {
+    "STRING" => LogicalAnnotation::STRING,
+    "MAP" => LogicalAnnotation::MAP,
+    "LIST" => LogicalAnnotation::LIST,
+    "ENUM" => LogicalAnnotation::ENUM,
+    "DATE" => LogicalAnnotation::DATE,
+    "UNKNOWN" => LogicalAnnotation::UNKNOWN,
+    "JSON" => LogicalAnnotation::JSON,
+    "BSON" => LogicalAnnotation::BSON,
+    "UUID" => LogicalAnnotation::UUID,
+    "DECIMAL" => LogicalAnnotation::DECIMAL {
+      scale: get_i64(object, "scale")?.unwrap_or(0) as i32,
+      precision: get_i64(object, "precision")?.unwrap_or(-1) as i32
+    },
+    "TIME" => LogicalAnnotation::TIME {
+      is_adjusted_to_utc: is_adjusted_to_utc()?, unit: unit()?
+    },
+    "TIMESTAMP" => LogicalAnnotation::TIMESTAMP {
+      is_adjusted_to_utc: is_adjusted_to_utc()?, unit: unit()?
+    },
+    "INTEGER" => LogicalAnnotation::INTEGER {
+      bit_width: get_i64(object, "bit_width")?.unwrap_or(0) as i8,
+      is_signed: get_bool(object, "is_signed", value)?
+    },
+    _ => return Err(general_err!("Unknown logical type annotation: {}", value))
+  };
+  Ok(annotation)
+}
+
+fn get_str<'a>(object: &'a Map<String, Value>, key: &str) -> Result<Option<&'a str>> {
+  match object.get(key) {
+    Some(value) => match value.as_str() {
+      Some(s) => Ok(Some(s)),
+      None => Err(general_err!("Expected a string for {}, found {}", key, value))
+    },
+    None => Ok(None)
+  }
+}
+
+fn get_i64(object: &Map<String, Value>, key: &str) -> Result<Option<i64>> {
+  match object.get(key) {
+    Some(value) => match value.as_i64() {
+      Some(v) => Ok(Some(v)),
+      None => Err(general_err!("Expected an integer for {}, found {}", key, value))
+    },
+    None => Ok(None)
+  }
+}
+
+fn get_bool(object: &Map<String, Value>, key: &str, parent: &Value) -> Result<bool> {
+  match object.get(key).and_then(|v| v.as_bool()) {
+    Some(v) => Ok(v),
+    None => Err(general_err!("Expected a boolean for {} in {}", key, parent))
+  }
+}
+
+fn to_pretty_string(value: &Value) -> String {
+  serde_json::to_string_pretty(value).expect("JSON values can always be serialized")
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use file::reader::{FileReader, SerializedFileReader};
+  use schema::parser::parse_message_type;
+  use schema::types::SchemaDescriptor;
+  use util::test_common::get_test_file;
+
+  #[test]
+  fn test_schema_json_round_trip() {
+    let schema = parse_message_type("
+      message schema {
+        REQUIRED INT32 a (INT_16) = 1;
+        OPTIONAL BYTE_ARRAY b (UTF8);
+        OPTIONAL FIXED_LEN_BYTE_ARRAY (8) c (DECIMAL(18,2));
+        REQUIRED INT64 d (TIMESTAMP(NANOS,false));
+        OPTIONAL GROUP e (LIST) = 2 {
+          REPEATED GROUP list {
+            OPTIONAL INT32 element (TIME_MILLIS);
+          }
+        }
+        REQUIRED FIXED_LEN_BYTE_ARRAY (16) f (UUID);
+        REQUIRED INT96 g;
+      }
+    ").unwrap();
+    let json = schema_to_json(&schema);
+    assert_eq!(json["name"], json!("schema"));
+    assert!(json.get("repetition").is_none());
+    assert_eq!(json["fields"][0], json!({
+      "name": "a", "repetition": "REQUIRED", "type": "INT32", "logical_type": "INT_16",
+      "annotation": {"type": "INTEGER", "bit_width": 16, "is_signed": true}, "id": 1
+    }));
+    assert_eq!(json["fields"][2], json!({
+      "name": "c", "repetition": "OPTIONAL", "type": "FIXED_LEN_BYTE_ARRAY",
+      "length": 8, "precision": 18, "scale": 2, "logical_type": "DECIMAL",
+      "annotation": {"type": "DECIMAL", "precision": 18, "scale": 2}
+    }));
+    assert_eq!(json["fields"][3]["annotation"], json!({
+      "type": "TIMESTAMP", "unit": "NANOS", "is_adjusted_to_utc": false
+    }));
+    assert_eq!(json["fields"][4]["fields"][0]["repetition"], json!("REPEATED"));
+
+    let parsed = parse_schema_json(&to_pretty_string(&json)).unwrap();
+    assert_eq!(parsed, schema);
+  }
+
+  #[test]
+  fn test_parse_schema_json_invalid() {
+    assert!(parse_schema_json("[]").is_err());
+    assert!(parse_schema_json(r#"{"name": "m"}"#).is_err());
+    assert!(parse_schema_json(
+      r#"{"name": "m", "repetition": "REQUIRED", "fields": []}"#).is_err());
+    assert!(parse_schema_json(
+      r#"{"name": "m", "fields": [{"name": "a", "type": "INT32"}]}"#).is_err());
+    assert!(parse_schema_json(r#"{"name": "m", "fields": [
+      {"name": "a", "repetition": "REQUIRED", "type": "INT33"}]}"#).is_err());
+    assert!(parse_schema_json(r#"{"name": "m", "fields": [
+      {"name": "a", "repetition": "REQUIRED", "type": "INT32",
+       "annotation": {"type": "TIME", "unit": "MILLIS"}}]}"#).is_err());
+  }
+
+  #[test]
+  fn test_metadata_to_json() {
+    let reader = SerializedFileReader::new(
+      get_test_file("test_datapage_v2.snappy.parquet")).unwrap();
+    let json = metadata_to_json(reader.metadata());
+
+    assert_eq!(json["version"], json!(1));
+    assert_eq!(json["num_rows"], json!(5));
+    let schema = reader.metadata().file_metadata().schema();
+    assert_eq!(json["schema"], schema_to_json(schema));
+    assert_eq!(json["key_value_metadata"][0]["key"],
+               json!("org.apache.spark.sql.parquet.row.metadata"));
+
+    let row_groups = json["row_groups"].as_array().unwrap();
+    assert_eq!(row_groups.len(), 1);
+    assert_eq!(row_groups[0]["num_rows"], json!(5));
+    let columns = row_groups[0]["columns"].as_array().unwrap();
+    assert_eq!(columns.len(), 5);
+    assert_eq!(columns[0], json!({
+      "path": "a", "type": "BYTE_ARRAY", "encodings": ["PLAIN", "RLE_DICTIONARY"],
+      "compression": "SNAPPY", "file_offset": 4, "num_values": 5,
+      "compressed_size": 63, "uncompressed_size": 59, "data_page_offset": 4,
+      "statistics": {"signed_min": "abc", "signed_max": "abc", "null_count": 1}
+    }));
+    assert_eq!(columns[1]["statistics"], json!({"min": 1, "max": 5, "null_count": 0}));
+    assert_eq!(columns[2]["statistics"],
+               json!({"min": 2.0, "max": 5.0, "null_count": 0}));
+    assert_eq!(columns[3]["statistics"],
+               json!({"min": false, "max": true, "null_count": 0}));
+    assert_eq!(columns[4]["path"], json!("e.list.element"));
+  }
+
+  #[test]
+  fn test_statistics_to_json_signed_order() {
+    let reader = SerializedFileReader::new(
+      get_test_file("test_datapage_v2.snappy.parquet")).unwrap();
+    let column = reader.metadata().row_group(0).column(1);
+    let statistics = Statistics {
+      max: Some(vec![0, 0, 0, 0xb2]), min: Some(vec![5, 0, 0, 0]),
+      null_count: None, distinct_count: None
+    };
+    let with_type = |message_type: &str| {
+      let schema = parse_message_type(message_type).unwrap();
+      let descr = SchemaDescriptor::new(Rc::new(schema));
+      ColumnChunkMetaData::from_thrift(descr.column(0), column.to_thrift()).unwrap()
+    };
+
+    let signed = with_type("message m { REQUIRED INT32 b; }");
+    assert_eq!(statistics_to_json(&signed, &statistics),
+               json!({"min": 5, "max": -1308622848}));
+    // The max value of a UINT_32 column is not the max of its signed values
+    let unsigned = with_type("message m { REQUIRED INT32 b (UINT_32); }");
+    assert_eq!(statistics_to_json(&unsigned, &statistics),
+               json!({"signed_min": 5, "signed_max": -1308622848}));
+  }
+
+  #[test]
+  fn test_annotation_to_json() {
+    let annotations = vec![
+      (LogicalAnnotation::STRING, "STRING"), (LogicalAnnotation::MAP, "MAP"),
+      (LogicalAnnotation::LIST, "LIST"), (LogicalAnnotation::ENUM, "ENUM"),
+      (LogicalAnnotation::DATE, "DATE"), (LogicalAnnotation::UNKNOWN, "UNKNOWN"),
+      (LogicalAnnotation::JSON, "JSON"), (LogicalAnnotation::BSON, "BSON"),
+      (LogicalAnnotation::UUID, "UUID")
+    ];
+    for (annotation, name) in annotations {
+      let json = annotation_to_json(annotation);
+      assert_eq!(json, json!({ "type": name }));
+      assert_eq!(annotation_from_json(&json).unwrap(), annotation);
+    }
+  }
+
+  #[test]
+  fn test_print_file_metadata_json() {
+    let reader = SerializedFileReader::new(
+      get_test_file("alltypes_plain.parquet")).unwrap();
+    let mut out = vec!();
+    print_file_metadata_json(&mut out, reader.metadata().file_metadata());
+    let json: Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json["num_rows"], json!(8));
+    assert_eq!(json["schema"]["fields"].as_array().unwrap().len(), 11);
+    assert!(json.get("row_groups").is_none());
+  }
+}
@@ -0,0 +1,584 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Compatibility checking and merging of schemas, e.g. to read files whose schema
+//! evolved over time with a single schema.
+//!
+//! Fields are matched by name at every level of nesting. `Type::check_contains` is
+//! built on the same comparison.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+
+use basic::{LogicalType, Repetition, Type as PhysicalType};
+use errors::{ErrorContext, ParquetError, Result};
+use schema::types::{ColumnPath, Type, TypePtr};
+
+/// A difference between an old and a new version of a schema. Paths do not include the
+/// name of the root (message) type.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaChange {
+  /// The field only exists in the new schema, with this repetition.
+  FieldAdded(ColumnPath, Repetition),
+  /// The field only exists in the old schema.
+  FieldRemoved(ColumnPath),
+  /// The old and new repetition of the field.
+  RepetitionChanged(ColumnPath, Repetition, Repetition),
+  /// The field is a group in one schema, and a primitive type in the other.
+  KindChanged(ColumnPath),
+  /// The old and new type of a primitive field whose physical type, length or logical
+  /// type changed.
+  TypeChanged(ColumnPath, TypePtr, TypePtr),
+  /// The old and new converted type of a group.
+  LogicalTypeChanged(ColumnPath, LogicalType, LogicalType)
+}
+
+impl SchemaChange {
+  pub fn path(&self) -> &ColumnPath {
+    match *self {
+      SchemaChange::FieldAdded(ref path, _) |
+      SchemaChange::FieldRemoved(ref path) |
+      SchemaChange::RepetitionChanged(ref path, _, _) |
+      SchemaChange::KindChanged(ref path) |
+      SchemaChange::TypeChanged(ref path, _, _) |
+      SchemaChange::LogicalTypeChanged(ref path, _, _) => path
+    }
+  }
+
+  /// Whether data written with the old schema cannot be read with the new schema.
+  ///
+  /// Removed fields, added fields that are not REQUIRED, REQUIRED fields that become
+  /// OPTIONAL and types that can be promoted safely (see `column::projection`) are
+  /// compatible. All other changes are breaking; in particular data written with the
+  /// old schema can contain nulls in a field that becomes REQUIRED.
+  pub fn is_breaking(&self) -> bool {
+    match *self {
+      SchemaChange::FieldAdded(_, repetition) => repetition == Repetition::REQUIRED,
+      SchemaChange::FieldRemoved(_) => false,
+      SchemaChange::RepetitionChanged(_, old, new) => {
+        old != Repetition::REQUIRED || new != Repetition::OPTIONAL
+      },
+      SchemaChange::TypeChanged(_, ref old, ref new) => !is_promotable(old, new),
+      _ => true
+    }
+  }
+}
+
+impl fmt::Display for SchemaChange {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let path = self.path().string();
+    match *self {
+      SchemaChange::FieldAdded(_, _) => write!(f, "Field {} added", path),
+      SchemaChange::FieldRemoved(_) => write!(f, "Field {} removed", path),
+      SchemaChange::RepetitionChanged(_, old, new) => {
+        write!(f, "Field {} changed repetition from {} to {}", path, old, new)
+      },
+      SchemaChange::KindChanged(_) => {
+        write!(f, "Field {} changed between group and primitive type", path)
+      },
+      SchemaChange::TypeChanged(_, ref old, ref new) => {
+        write!(f, "Field {} changed type from {} to {}",
+               path, type_str(old), type_str(new))
+      },
+      SchemaChange::LogicalTypeChanged(_, old, new) => {
+        write!(f, "Field {} changed logical type from {} to {}", path, old, new)
+      }
+    }
+  }
+}
+
+/// Returns the differences between schema `old` and schema `new`, in the order of the
+/// fields of `old`, followed by the fields added in `new`.
+pub fn check_compatibility(old: &Type, new: &Type) -> Vec<SchemaChange> {
+  let mut changes = vec!();
+  compare_types(old, new, &mut vec!(), &mut changes);
+  changes
+}
+
+fn compare_types(
+  old: &Type, new: &Type, path: &mut Vec<String>, changes: &mut Vec<SchemaChange>
+) {
+  let old_info = old.get_basic_info();
+  let new_info = new.get_basic_info();
+  if old_info.has_repetition() && new_info.has_repetition() &&
+      old_info.repetition() != new_info.repetition() {
+    changes.push(SchemaChange::RepetitionChanged(
+      column_path(path), old_info.repetition(), new_info.repetition()));
+  }
+
+  match (old, new) {
+    (&Type::PrimitiveType { physical_type: old_type, type_length: old_length, .. },
+     &Type::PrimitiveType { physical_type: new_type, type_length: new_length, .. }) => {
+      if old_type != new_type || old_length != new_length ||
+          old_info.logical_type() != new_info.logical_type() ||
+          old_info.logical_annotation() != new_info.logical_annotation() {
+        changes.push(SchemaChange::TypeChanged(
+          column_path(path), Rc::new(old.clone()), Rc::new(new.clone())));
+      }
+    },
+    (&Type::GroupType { .. }, &Type::GroupType { .. }) => {
+      if old_info.logical_type() != new_info.logical_type() {
+        changes.push(SchemaChange::LogicalTypeChanged(
+          column_path(path), old_info.logical_type(), new_info.logical_type()));
+      }
+      let new_fields = field_map(new);
+      for field in old.get_fields() {
+        path.push(field.name().to_owned());
+        match new_fields.get(field.name()) {
+          Some(new_field) => compare_types(field, new_field, path, changes),
+          None => changes.push(SchemaChange::FieldRemoved(column_path(path)))
+        }
+        path.pop();
+      }
+      let old_names: HashSet<&str> = old.get_fields().iter().map(|f| f.name()).collect();
+      for field in new.get_fields() {
+        if !old_names.contains(field.name()) {
+          path.push(field.name().to_owned());
+          changes.push(SchemaChange::FieldAdded(
+            column_path(path), field.get_basic_info().repetition()));
+          path.pop();
+        }
+      }
+    },
+    _ => changes.push(SchemaChange::KindChanged(column_path(path)))
+  }
+}
+
+/// Merges schemas `a` and `b` into a schema that can read data written with either.
+///
+/// The merged schema contains the fields of `a`, followed by the fields that only exist
+/// in `b`. Fields that are missing from either schema or are REQUIRED in only one of
+/// them become OPTIONAL. A primitive field whose type in `a` can be promoted to its
+/// type in `b` takes the type of `b`. Otherwise names, ids and annotations of types are
+/// taken from `a`.
+///
+/// Returns an error listing the breaking changes if data written with `a` or `b` cannot
+/// be read with the merged schema, see `SchemaChange::is_breaking`.
+pub fn merge(a: &Type, b: &Type) -> Result<Type> {
+  let merged = merge_types(a, b)?;
+  let breaking: Vec<String> = check_compatibility(a, &merged).iter()
+    .chain(check_compatibility(b, &merged).iter())
+    .filter(|change| change.is_breaking())
+    .map(|change| change.to_string())
+    .collect();
+  if !breaking.is_empty() {
+    return Err(schema_err!(ErrorContext::new(),
+      "Cannot merge incompatible schemas: {}", breaking.join("; ")));
+  }
+  Ok(merged)
+}
+
+/// Whether values of primitive type `from` can be read as primitive type `to`, i.e.
+/// the types are the same or `from` can be promoted safely to `to`, see
+/// `column::projection`. Repetitions are not compared. Always false for group types.
+pub fn is_promotable(from: &Type, to: &Type) -> bool {
+  let (from_type, from_length, from_precision, from_scale) = match primitive(from) {
+    Some(parts) => parts,
+    None => return false
+  };
+  let (to_type, to_length, to_precision, to_scale) = match primitive(to) {
+    Some(parts) => parts,
+    None => return false
+  };
+  let (from_info, to_info) = (from.get_basic_info(), to.get_basic_info());
+  let (from_logical, to_logical) = (from_info.logical_type(), to_info.logical_type());
+
+  if from_logical == LogicalType::DECIMAL || to_logical == LogicalType::DECIMAL {
+    if from_logical != to_logical || from_scale != to_scale ||
+        from_precision > to_precision {
+      return false;
+    }
+    return match (from_type, to_type) {
+      (PhysicalType::INT32, PhysicalType::INT64) => true,
+      (PhysicalType::FIXED_LEN_BYTE_ARRAY, PhysicalType::FIXED_LEN_BYTE_ARRAY) => {
+        from_length <= to_length
+      },
+      _ => from_type == to_type
+    };
+  }
+
+  match (from_type, to_type) {
+    (PhysicalType::INT32, PhysicalType::INT64) => {
+      match (from_logical, to_logical) {
+        (LogicalType::NONE, LogicalType::NONE) |
+        (LogicalType::INT_8, LogicalType::INT_64) |
+        (LogicalType::INT_16, LogicalType::INT_64) |
+        (LogicalType::INT_32, LogicalType::INT_64) |
+        (LogicalType::UINT_8, LogicalType::UINT_64) |
+        (LogicalType::UINT_16, LogicalType::UINT_64) |
+        (LogicalType::UINT_32, LogicalType::UINT_64) => true,
+        _ => false
+      }
+    },
+    (PhysicalType::FLOAT, PhysicalType::DOUBLE) => {
+      from_logical == LogicalType::NONE && to_logical == LogicalType::NONE
+    },
+    _ => {
+      from_type == to_type && from_logical == to_logical &&
+        from_info.logical_annotation() == to_info.logical_annotation() &&
+        (from_type != PhysicalType::FIXED_LEN_BYTE_ARRAY || from_length == to_length)
+    }
+  }
+}
+
+// Returns the physical type, length, precision and scale of a primitive type.
+fn primitive(tp: &Type) -> Option<(PhysicalType, i32, i32, i32)> {
+  match *tp {
+    Type::PrimitiveType { physical_type, type_length, precision, scale, .. } => {
+      Some((physical_type, type_length, precision, scale))
+    },
+    Type::GroupType { .. } => None
+  }
+}
+
+/// Describes a primitive type by its physical type, length and logical type, e.g.
+/// `FIXED_LEN_BYTE_ARRAY(8) DECIMAL(18,2)`.
+pub fn type_str(tp: &Type) -> String {
+  let (physical_type, type_length, _, _) = match primitive(tp) {
+    Some(parts) => parts,
+    None => return "group".to_owned()
+  };
+  let info = tp.get_basic_info();
+  let mut result = physical_type.to_string();
+  if physical_type == PhysicalType::FIXED_LEN_BYTE_ARRAY {
+    result.push_str(&format!("({})", type_length));
+  }
+  match info.logical_annotation() {
+    Some(annotation) => result.push_str(&format!(" {}", annotation)),
+    None if info.logical_type() != LogicalType::NONE => {
+      result.push_str(&format!(" {}", info.logical_type()))
+    },
+    None => ()
+  }
+  result
+}
+
+// Fields of `a` and `b` that cannot be merged keep the type of `a`, and are reported
+// by `merge`.
+fn merge_types(a: &Type, b: &Type) -> Result<Type> {
+  let a_info = a.get_basic_info();
+  let b_info = b.get_basic_info();
+  let repetition = if !a_info.has_repetition() || !b_info.has_repetition() {
+    None
+  } else if a_info.repetition() == b_info.repetition() {
+    Some(a_info.repetition())
+  } else {
+    Some(Repetition::OPTIONAL)
+  };
+
+  if a.is_group() != b.is_group() {
+    let fields = if a.is_group() { a.get_fields().to_vec() } else { vec!() };
+    return rebuild(a, repetition, fields);
+  }
+  if !a.is_group() {
+    let promoted = is_promotable(a, b) && !is_promotable(b, a);
+    return rebuild(if promoted { b } else { a }, repetition, vec!());
+  }
+  let mut fields = vec!();
+  let b_fields = field_map(b);
+  for field in a.get_fields() {
+    match b_fields.get(field.name()) {
+      Some(b_field) => fields.push(Rc::new(merge_types(field, b_field)?)),
+      None => fields.push(relax(field)?)
+    }
+  }
+  let a_names: HashSet<&str> = a.get_fields().iter().map(|f| f.name()).collect();
+  for field in b.get_fields() {
+    if !a_names.contains(field.name()) {
+      fields.push(relax(field)?);
+    }
+  }
+  rebuild(a, repetition, fields)
+}
+
+// Makes a field that is missing from one of the merged schemas OPTIONAL.
+fn relax(field: &TypePtr) -> Result<TypePtr> {
+  let info = field.get_basic_info();
+  if info.has_repetition() && info.repetition() == Repetition::REQUIRED {
+    let fields = if field.is_group() { field.get_fields().to_vec() } else { vec!() };
+    Ok(Rc::new(rebuild(field, Some(Repetition::OPTIONAL), fields)?))
+  } else {
+    Ok(field.clone())
+  }
+}
+
+// Copies `tp` with a new repetition and, for groups, new fields.
+fn rebuild(
+  tp: &Type, repetition: Option<Repetition>, mut fields: Vec<TypePtr>
+) -> Result<Type> {
+  let info = tp.get_basic_info();
+  match *tp {
+    Type::PrimitiveType { physical_type, type_length, scale, precision, .. } => {
+      let mut builder = Type::primitive_type_builder(info.name(), physical_type)
+        .with_logical_type(info.logical_type())
+        .with_logical_annotation(info.logical_annotation())
+        .with_length(type_length)
+        .with_precision(precision)
+        .with_scale(scale);
+      if let Some(repetition) = repetition {
+        builder = builder.with_repetition(repetition);
+      }
+      if info.has_id() {
+        builder = builder.with_id(info.id());
+      }
+      builder.build()
+    },
+    Type::GroupType { .. } => {
+      let mut builder = Type::group_type_builder(info.name())
+        .with_logical_type(info.logical_type())
+        .with_logical_annotation(info.logical_annotation())
+        .with_fields(&mut fields);
+      if let Some(repetition) = repetition {
+        builder = builder.with_repetition(repetition);
+      }
+      if info.has_id() {
+        builder = builder.with_id(info.id());
+      }
+      builder.build()
+    }
+  }
+}
+
+fn field_map(tp: &Type) -> HashMap<&str, &TypePtr> {
+  tp.get_fields().iter().map(|field| (field.name(), field)).collect()
+}
+
+fn column_path(path: &[String]) -> ColumnPath {
+  ColumnPath::new(path.to_vec())
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use schema::parser::parse_message_type;
+
+  #[test]
+  fn test_check_compatibility() {
+    let old = parse_message_type("
+      message schema {
+        REQUIRED INT32 a;
+        OPTIONAL BYTE_ARRAY b (UTF8);
+        OPTIONAL GROUP c {
+          REQUIRED INT64 d;
+          OPTIONAL INT32 e;
+        }
+        REQUIRED FIXED_LEN_BYTE_ARRAY (4) f;
+      }
+    ").unwrap();
+    let new = parse_message_type("
+      message schema {
+        OPTIONAL INT32 a;
+        OPTIONAL BYTE_ARRAY b;
+        OPTIONAL GROUP c {
+          REPEATED INT64 d;
+          OPTIONAL INT32 g;
+        }
+        REQUIRED FIXED_LEN_BYTE_ARRAY (8) f;
+        OPTIONAL INT32 h;
+      }
+    ").unwrap();
+
+    let changes = check_compatibility(&old, &new);
+    let paths: Vec<String> = changes.iter().map(|c| c.path().string()).collect();
+    assert_eq!(paths, vec!["a", "b", "c.d", "c.e", "c.g", "f", "h"]);
+    let breaking: Vec<bool> = changes.iter().map(|c| c.is_breaking()).collect();
+    assert_eq!(breaking, vec![false, true, true, false, false, true, false]);
+
+    assert_eq!(changes[0], SchemaChange::RepetitionChanged(
+      ColumnPath::from("a"), Repetition::REQUIRED, Repetition::OPTIONAL));
+    assert_eq!(changes[1].to_string(),
+               "Field b changed type from BYTE_ARRAY STRING to BYTE_ARRAY");
+    assert_eq!(changes[3].to_string(), "Field c.e removed");
+    assert_eq!(changes[5].to_string(), "Field f changed type from \
+      FIXED_LEN_BYTE_ARRAY(4) to FIXED_LEN_BYTE_ARRAY(8)");
+
+    assert!(check_compatibility(&old, &old).is_empty());
+  }
+
+  #[test]
+  fn test_check_compatibility_repetition() {
+    let old = parse_message_type("
+      message schema {
+        REQUIRED INT32 a;
+        OPTIONAL INT32 b;
+      }
+    ").unwrap();
+    let new = parse_message_type("
+      message schema {
+        OPTIONAL INT32 a;
+        REQUIRED INT32 b;
+        OPTIONAL INT32 c;
+        REQUIRED INT32 d;
+      }
+    ").unwrap();
+
+    let changes = check_compatibility(&old, &new);
+    assert_eq!(changes, vec![
+      SchemaChange::RepetitionChanged(
+        ColumnPath::from("a"), Repetition::REQUIRED, Repetition::OPTIONAL),
+      SchemaChange::RepetitionChanged(
+        ColumnPath::from("b"), Repetition::OPTIONAL, Repetition::REQUIRED),
+      SchemaChange::FieldAdded(ColumnPath::from("c"), Repetition::OPTIONAL),
+      SchemaChange::FieldAdded(ColumnPath::from("d"), Repetition::REQUIRED)
+    ]);
+    // Data written with the old schema can contain nulls in b, and has no values for d
+    let breaking: Vec<bool> = changes.iter().map(|c| c.is_breaking()).collect();
+    assert_eq!(breaking, vec![false, true, false, true]);
+  }
+
+  #[test]
+  fn test_check_compatibility_types() {
+    let old = parse_message_type("
+      message schema {
+        REQUIRED INT32 a;
+        REQUIRED INT32 b;
+        REQUIRED INT32 c (DECIMAL(5,2));
+        REQUIRED FLOAT d;
+        REQUIRED INT32 e (DECIMAL(5,2));
+        REQUIRED INT64 f;
+        REQUIRED INT32 g (INT_32);
+      }
+    ").unwrap();
+    let new = parse_message_type("
+      message schema {
+        REQUIRED INT64 a;
+        REQUIRED GROUP b {
+          REQUIRED INT32 b;
+        }
+        REQUIRED INT32 c (DECIMAL(6,2));
+        REQUIRED DOUBLE d;
+        REQUIRED INT32 e (DECIMAL(4,2));
+        REQUIRED INT32 f;
+        REQUIRED INT64 g (INT_64);
+      }
+    ").unwrap();
+
+    let changes = check_compatibility(&old, &new);
+    let paths: Vec<String> = changes.iter().map(|c| c.path().string()).collect();
+    assert_eq!(paths, vec!["a", "b", "c", "d", "e", "f", "g"]);
+    assert_eq!(changes[1], SchemaChange::KindChanged(ColumnPath::from("b")));
+    // Promotions are safe, narrowing is not
+    let breaking: Vec<bool> = changes.iter().map(|c| c.is_breaking()).collect();
+    assert_eq!(breaking, vec![false, true, false, false, true, true, false]);
+
+    assert_eq!(changes[0].to_string(), "Field a changed type from INT32 to INT64");
+    assert_eq!(changes[2].to_string(),
+               "Field c changed type from INT32 DECIMAL(5,2) to INT32 DECIMAL(6,2)");
+  }
+
+  #[test]
+  fn test_merge() {
+    let a = parse_message_type("
+      message schema {
+        REQUIRED INT32 a;
+        REQUIRED BYTE_ARRAY b (UTF8);
+        OPTIONAL GROUP c {
+          REQUIRED INT64 d;
+        }
+      }
+    ").unwrap();
+    let b = parse_message_type("
+      message schema {
+        OPTIONAL GROUP c {
+          REQUIRED INT64 d;
+          REQUIRED INT32 e;
+        }
+        REQUIRED INT32 a;
+        REQUIRED GROUP f {
+          REQUIRED INT32 g;
+        }
+      }
+    ").unwrap();
+    let expected = parse_message_type("
+      message schema {
+        REQUIRED INT32 a;
+        OPTIONAL BYTE_ARRAY b (UTF8);
+        OPTIONAL GROUP c {
+          REQUIRED INT64 d;
+          OPTIONAL INT32 e;
+        }
+        OPTIONAL GROUP f {
+          REQUIRED INT32 g;
+        }
+      }
+    ").unwrap();
+
+    assert_eq!(merge(&a, &b).unwrap(), expected);
+    assert_eq!(merge(&a, &a).unwrap(), a);
+    // Both schemas can be read with the merged schema
+    let merged = merge(&a, &b).unwrap();
+    assert!(check_compatibility(&a, &merged).iter().all(|c| !c.is_breaking()));
+    assert!(check_compatibility(&b, &merged).iter().all(|c| !c.is_breaking()));
+  }
+
+  #[test]
+  fn test_merge_promotion() {
+    let a = parse_message_type("
+      message schema {
+        REQUIRED INT32 a;
+        REQUIRED DOUBLE b;
+        REQUIRED INT32 c (DECIMAL(5,2)) = 1;
+      }
+    ").unwrap();
+    let b = parse_message_type("
+      message schema {
+        REQUIRED INT64 a;
+        REQUIRED FLOAT b;
+        REQUIRED INT64 c (DECIMAL(12,2)) = 2;
+      }
+    ").unwrap();
+    let expected = parse_message_type("
+      message schema {
+        REQUIRED INT64 a;
+        REQUIRED DOUBLE b;
+        REQUIRED INT64 c (DECIMAL(12,2)) = 2;
+      }
+    ").unwrap();
+
+    assert_eq!(merge(&a, &b).unwrap(), expected);
+    assert_eq!(merge(&b, &a).unwrap(), expected);
+  }
+
+  #[test]
+  fn test_merge_incompatible() {
+    let a = parse_message_type("
+      message schema {
+        REQUIRED INT32 a;
+        REPEATED INT32 b;
+        REQUIRED INT32 c;
+      }
+    ").unwrap();
+    let b = parse_message_type("
+      message schema {
+        REQUIRED BOOLEAN a;
+        OPTIONAL INT32 b;
+        REQUIRED INT64 c;
+      }
+    ").unwrap();
+
+    match merge(&a, &b) {
+      Err(ParquetError::SchemaMismatch(message, _)) => {
+        assert_eq!(message, "Cannot merge incompatible schemas: \
+          Field b changed repetition from REPEATED to OPTIONAL; \
+          Field a changed type from BOOLEAN to INT32");
+      },
+      _ => panic!("Expected schema mismatch error")
+    }
+  }
+}
@@ -18,3 +18,9 @@
 pub mod types;
 pub mod printer;
 pub mod parser;
+pub mod merge;
+pub mod avro;
+pub mod json;
+pub mod nested;
+
+pub use self::merge::{check_compatibility, merge};
@@ -0,0 +1,466 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Interpretation of nested fields as lists, maps and structs, applying the
+//! backward-compatibility rules of the Parquet format for files written by older Avro,
+//! Thrift and Hive writers.
+//!
+//! A list is a group annotated with LIST that contains a single repeated field. The
+//! element type is determined by the following rules:
+//! 1. If the repeated field is not a group, it is the element, and elements are
+//!    required, e.g. `optional group a (LIST) { repeated int32 element; }`.
+//! 2. If the repeated field is a group with multiple fields, it is the element, and
+//!    elements are required.
+//! 3. If the repeated field is a group with one field and is named `array` or
+//!    `<list name>_tuple`, it is the element, and elements are required.
+//! 4. Otherwise, the single field of the repeated group is the element, e.g.
+//!    `repeated group list { optional int32 element; }` or
+//!    `repeated group bag { optional int32 array_element; }`.
+//!
+//! A map is a group annotated with MAP, or with MAP_KEY_VALUE by older writers, that
+//! contains a single repeated group with a required `key` field, followed by a `value`
+//! field unless the map only has keys. The value can be required or optional. The
+//! names of the repeated group and of its fields, and its annotation, are ignored.
+//!
+//! A repeated field that is not part of a list or map is a list of required elements,
+//! i.e. the repeated field itself.
+
+use basic::{LogicalType, Repetition};
+use errors::{ErrorContext, ParquetError, Result};
+use schema::types::{ColumnPath, Type};
+
+/// Interpretation of a field of a schema.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NestedType<'a> {
+  /// A primitive value.
+  Primitive(&'a Type),
+  /// A group of fields that is neither a list nor a map.
+  Struct(&'a Type),
+  /// A list, see `ListType`.
+  List(ListType<'a>),
+  /// A map, see `MapType`.
+  Map(MapType<'a>)
+}
+
+/// A list, either annotated or a repeated field.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ListType<'a> {
+  /// The field holding the list, i.e. the LIST group, or the repeated field if the
+  /// list is not annotated.
+  pub field: &'a Type,
+  /// The repeated field, whose repetitions are the elements of the list.
+  pub repeated: &'a Type,
+  /// The elements of the list. This is the repeated field for 2-level lists.
+  pub element: &'a Type
+}
+
+impl<'a> ListType<'a> {
+  /// Whether the elements of the list are the repeated field itself.
+  pub fn is_two_level(&self) -> bool {
+    self.element as *const Type == self.repeated as *const Type
+  }
+
+  /// Whether elements of the list can be null.
+  pub fn is_element_optional(&self) -> bool {
+    !self.is_two_level() &&
+      self.element.get_basic_info().repetition() == Repetition::OPTIONAL
+  }
+
+  /// Returns the interpretation of the elements of the list.
+  pub fn element_type(&self) -> Result<NestedType<'a>> {
+    if self.is_two_level() { value_type(self.element) } else { nested_type(self.element) }
+  }
+}
+
+/// A map of keys to values, or a set of keys if it has no value field.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MapType<'a> {
+  /// The MAP or MAP_KEY_VALUE group holding the map.
+  pub field: &'a Type,
+  /// The repeated group, whose repetitions are the entries of the map.
+  pub repeated: &'a Type,
+  /// The key field, which is required.
+  pub key: &'a Type,
+  /// The value field of any repetition, or `None` if the map only has keys.
+  pub value: Option<&'a Type>
+}
+
+impl<'a> MapType<'a> {
+  /// Returns the interpretation of the keys of the map.
+  pub fn key_type(&self) -> Result<NestedType<'a>> {
+    nested_type(self.key)
+  }
+
+  /// Returns the interpretation of the values of the map, if any.
+  pub fn value_type(&self) -> Result<Option<NestedType<'a>>> {
+    match self.value {
+      Some(value) => nested_type(value).map(Some),
+      None => Ok(None)
+    }
+  }
+}
+
+/// Returns the interpretation of `field`, a field of a group, taking its repetition
+/// into account: a repeated field is a list.
+pub fn nested_type(field: &Type) -> Result<NestedType> {
+  if field.get_basic_info().has_repetition() &&
+      field.get_basic_info().repetition() == Repetition::REPEATED {
+    let list = ListType { field: field, repeated: field, element: field };
+    return Ok(NestedType::List(list));
+  }
+  value_type(field)
+}
+
+/// Returns the interpretation of the values of `tp`, ignoring its repetition.
+pub fn value_type(tp: &Type) -> Result<NestedType> {
+  if tp.is_primitive() {
+    return Ok(NestedType::Primitive(tp));
+  }
+  match tp.get_basic_info().logical_type() {
+    LogicalType::LIST => list_type(tp).map(NestedType::List),
+    LogicalType::MAP | LogicalType::MAP_KEY_VALUE => map_type(tp).map(NestedType::Map),
+    _ => Ok(NestedType::Struct(tp))
+  }
+}
+
+fn list_type(list: &Type) -> Result<ListType> {
+  let fields = list.get_fields();
+  if fields.len() != 1 || !is_repeated(&fields[0]) {
+    return Err(schema_err!(ErrorContext::new(),
+      "LIST group {} must contain a single repeated field", list.name()));
+  }
+  let repeated = fields[0].as_ref();
+  let element = if repeated.is_group() && repeated.get_fields().len() == 1 &&
+      repeated.name() != "array" && repeated.name() != format!("{}_tuple", list.name()) {
+    repeated.get_fields()[0].as_ref()
+  } else {
+    repeated
+  };
+  Ok(ListType { field: list, repeated: repeated, element: element })
+}
+
+fn map_type(map: &Type) -> Result<MapType> {
+  let fields = map.get_fields();
+  if fields.len() != 1 || !fields[0].is_group() || !is_repeated(&fields[0]) {
+    return Err(schema_err!(ErrorContext::new(),
+      "{} group {} must contain a single repeated group",
+      map.get_basic_info().logical_type(), map.name()));
+  }
+  let repeated = fields[0].as_ref();
+  let key_value = repeated.get_fields();
+  if key_value.is_empty() || key_value.len() > 2 ||
+      key_value[0].get_basic_info().repetition() != Repetition::REQUIRED {
+    return Err(schema_err!(ErrorContext::new(),
+      "Repeated group {} of map {} must contain a required key and at most one value",
+      repeated.name(), map.name()));
+  }
+  Ok(MapType {
+    field: map,
+    repeated: repeated,
+    key: key_value[0].as_ref(),
+    value: key_value.get(1).map(|v| v.as_ref())
+  })
+}
+
+fn is_repeated(tp: &Type) -> bool {
+  tp.get_basic_info().repetition() == Repetition::REPEATED
+}
+
+/// Returns the paths of the leaf columns of `schema` that do not depend on the list and
+/// map structure used by the writer of a file, in the order of the leaf columns.
+///
+/// Lists contribute `element` to the path, and maps `key` and `value`, e.g. the leaf
+/// `a.list.element.b` of a 3-level list and the leaf `a.array.b` of a 2-level list both
+/// have the path `a.element.b`, and the leaf `m.map.key` has the path `m.key`.
+pub fn canonical_paths(schema: &Type) -> Result<Vec<ColumnPath>> {
+  let mut result = vec!();
+  let mut path = vec!();
+  for field in schema.get_fields() {
+    path.push(field.name().to_owned());
+    add_canonical_paths(nested_type(field)?, &mut path, &mut result)?;
+    path.pop();
+  }
+  Ok(result)
+}
+
+fn add_canonical_paths(
+  nested: NestedType, path: &mut Vec<String>, result: &mut Vec<ColumnPath>
+) -> Result<()> {
+  match nested {
+    NestedType::Primitive(_) => result.push(ColumnPath::new(path.clone())),
+    NestedType::Struct(tp) => {
+      for field in tp.get_fields() {
+        path.push(field.name().to_owned());
+        add_canonical_paths(nested_type(field)?, path, result)?;
+        path.pop();
+      }
+    },
+    NestedType::List(list) => {
+      path.push("element".to_owned());
+      add_canonical_paths(list.element_type()?, path, result)?;
+      path.pop();
+    },
+    NestedType::Map(map) => {
+      path.push("key".to_owned());
+      add_canonical_paths(map.key_type()?, path, result)?;
+      path.pop();
+      if let Some(value) = map.value_type()? {
+        path.push("value".to_owned());
+        add_canonical_paths(value, path, result)?;
+        path.pop();
+      }
+    }
+  }
+  Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use schema::parser::parse_message_type;
+
+  fn field(message_type: &str) -> Type {
+    let mut schema = parse_message_type(message_type).unwrap();
+    match schema {
+      Type::GroupType { ref mut fields, .. } => {
+        ::std::rc::Rc::try_unwrap(fields.remove(0)).unwrap()
+      },
+      _ => panic!("Expected a message type")
+    }
+  }
+
+  fn check_list(
+    message_type: &str, element_name: &str, two_level: bool, element_optional: bool
+  ) {
+    let tp = field(message_type);
+    match nested_type(&tp).unwrap() {
+      NestedType::List(list) => {
+        assert_eq!(list.field.name(), tp.name());
+        assert_eq!(list.element.name(), element_name, "{}", message_type);
+        assert_eq!(list.is_two_level(), two_level, "{}", message_type);
+        assert_eq!(list.is_element_optional(), element_optional, "{}", message_type);
+      },
+      other => panic!("Expected a list for {}, found {:?}", message_type, other)
+    }
+  }
+
+  #[test]
+  fn test_list_rules() {
+    // 3-level lists
+    check_list("message m {
+      required group my_list (LIST) {
+        repeated group list { optional binary element (UTF8); }
+      }
+    }", "element", false, true);
+    check_list("message m {
+      optional group my_list (LIST) {
+        repeated group list { required binary element (UTF8); }
+      }
+    }", "element", false, false);
+    // Hive
+    check_list("message m {
+      optional group my_list (LIST) {
+        repeated group bag { optional int32 array_element; }
+      }
+    }", "array_element", false, true);
+
+    // Rule 1: repeated primitive
+    check_list("message m {
+      optional group my_list (LIST) { repeated int32 element; }
+    }", "element", true, false);
+    // Rule 2: repeated group with multiple fields
+    check_list("message m {
+      optional group my_list (LIST) {
+        repeated group element { required binary str (UTF8); required int32 num; }
+      }
+    }", "element", true, false);
+    // Rule 3: repeated group with one field named `array` or `<name>_tuple`
+    check_list("message m {
+      optional group my_list (LIST) {
+        repeated group array { required binary str (UTF8); }
+      }
+    }", "array", true, false);
+    check_list("message m {
+      optional group my_list (LIST) {
+        repeated group my_list_tuple { required binary str (UTF8); }
+      }
+    }", "my_list_tuple", true, false);
+
+    // Repeated fields without annotation
+    check_list("message m { repeated int32 numbers; }", "numbers", true, false);
+    check_list("message m {
+      repeated group pairs { required int32 a; required int32 b; }
+    }", "pairs", true, false);
+  }
+
+  #[test]
+  fn test_list_element_types() {
+    let tp = field("message m {
+      optional group a (LIST) {
+        repeated group array { required int32 x; }
+      }
+    }");
+    let list = match nested_type(&tp).unwrap() {
+      NestedType::List(list) => list,
+      _ => panic!("Expected a list")
+    };
+    // The repeated group is a struct element, regardless of its repetition
+    assert_eq!(list.element_type().unwrap(), NestedType::Struct(list.repeated));
+
+    // List of lists
+    let tp = field("message m {
+      optional group a (LIST) {
+        repeated group list {
+          optional group element (LIST) { repeated int32 array; }
+        }
+      }
+    }");
+    let list = match nested_type(&tp).unwrap() {
+      NestedType::List(list) => list,
+      _ => panic!("Expected a list")
+    };
+    match list.element_type().unwrap() {
+      NestedType::List(inner) => {
+        assert_eq!(inner.field.name(), "element");
+        assert_eq!(inner.element_type().unwrap(), NestedType::Primitive(inner.element));
+      },
+      _ => panic!("Expected a nested list")
+    }
+  }
+
+  #[test]
+  fn test_map_rules() {
+    let maps = vec!(
+      "message m {
+        required group my_map (MAP) {
+          repeated group key_value {
+            required binary key (UTF8);
+            optional int32 value;
+          }
+        }
+      }",
+      "message m {
+        optional group my_map (MAP_KEY_VALUE) {
+          repeated group map {
+            required binary key (UTF8);
+            optional int32 value;
+          }
+        }
+      }",
+      "message m {
+        optional group my_map (MAP) {
+          repeated group map (MAP_KEY_VALUE) {
+            required binary key (UTF8);
+            optional int32 value;
+          }
+        }
+      }"
+    );
+    for message_type in maps {
+      let tp = field(message_type);
+      match nested_type(&tp).unwrap() {
+        NestedType::Map(map) => {
+          assert_eq!(map.key.name(), "key");
+          assert_eq!(map.value.map(|v| v.name()), Some("value"));
+          assert_eq!(map.key_type().unwrap(), NestedType::Primitive(map.key));
+        },
+        other => panic!("Expected a map for {}, found {:?}", message_type, other)
+      }
+    }
+
+    // A map with required values
+    let tp = field("message m {
+      optional group my_map (MAP) {
+        repeated group map { required int32 key; required int32 value; }
+      }
+    }");
+    match nested_type(&tp).unwrap() {
+      NestedType::Map(map) => {
+        let value = map.value.expect("Expected a value field");
+        assert_eq!(value.get_basic_info().repetition(), Repetition::REQUIRED);
+        assert_eq!(map.value_type().unwrap(), Some(NestedType::Primitive(value)));
+      },
+      _ => panic!("Expected a map")
+    }
+
+    // A map without values
+    let tp = field("message m {
+      optional group my_set (MAP) { repeated group map { required int32 key; } }
+    }");
+    match nested_type(&tp).unwrap() {
+      NestedType::Map(map) => assert!(map.value_type().unwrap().is_none()),
+      _ => panic!("Expected a map")
+    }
+  }
+
+  #[test]
+  fn test_invalid_nested_types() {
+    let invalid = vec!(
+      "message m { optional group a (LIST) { optional int32 element; } }",
+      "message m {
+        optional group a (LIST) { repeated int32 a; repeated int32 b; }
+      }",
+      "message m { optional group a (MAP) { repeated int32 key; } }",
+      "message m {
+        optional group a (MAP) { repeated group map { optional int32 key; } }
+      }",
+      "message m {
+        optional group a (MAP) {
+          repeated group map { required int32 key; optional int32 a; optional int32 b; }
+        }
+      }"
+    );
+    for message_type in invalid {
+      match nested_type(&field(message_type)) {
+        Err(ParquetError::SchemaMismatch(_, _)) => (),
+        other => panic!("Expected a schema error for {}, got {:?}", message_type, other)
+      }
+    }
+  }
+
+  #[test]
+  fn test_canonical_paths() {
+    let schema = parse_message_type("
+      message m {
+        required int32 a;
+        optional group b (LIST) {
+          repeated group list {
+            optional group element { required int32 c; required int32 d; }
+          }
+        }
+        optional group e (LIST) {
+          repeated group array { required int32 c; }
+        }
+        repeated group f { required int32 g; }
+        optional group h (MAP_KEY_VALUE) {
+          repeated group map {
+            required binary key (UTF8);
+            optional group value (LIST) { repeated int32 i; }
+          }
+        }
+      }
+    ").unwrap();
+    let paths: Vec<String> =
+      canonical_paths(&schema).unwrap().iter().map(|p| p.string()).collect();
+    assert_eq!(paths, vec!(
+      "a", "b.element.c", "b.element.d", "e.element.c", "f.element.g", "h.key",
+      "h.value.element"
+    ));
+  }
+}
@@ -17,7 +17,7 @@
 
 use std::rc::Rc;
 
-use basic::{Repetition, Type as PhysicalType, LogicalType};
+use basic::{Repetition, Type as PhysicalType, LogicalType, LogicalAnnotation, TimeUnit};
 use errors::{ParquetError, Result};
 use schema::types::{Type, TypePtr};
 
@@ -114,6 +114,15 @@ fn parse_i32(
       map_err(|_| general_err!(parse_fail_msg)))
 }
 
+// Utility function to parse bool or return general error
+fn parse_bool(
+  value: Option<&str>, not_found_msg: &str, parse_fail_msg: &str
+) -> Result<bool> {
+  value.ok_or(general_err!(not_found_msg)).
+    and_then(|v| v.to_lowercase().parse::<bool>().
+      map_err(|_| general_err!(parse_fail_msg)))
+}
+
 impl<'a> Parser<'a> {
   // Entry function to parse message type, uses internal tokenizer
   fn parse_message_type(&mut self) -> Result<Type> {
@@ -221,11 +230,16 @@ impl<'a> Parser<'a> {
     // Parse name of the primitive type
     let name = self.tokenizer.next().ok_or(general_err!("Expected name, found None"))?;
 
-    // Parse logical type
-    let (logical_type, precision, scale) = if let Some("(") = self.tokenizer.next() {
-      let tpe = self.tokenizer.next().
-        ok_or(general_err!("Expected logical type, found None")).
-        and_then(|v| v.to_uppercase().parse::<LogicalType>())?;
+    // Parse logical type, either a converted type or an annotation that has no
+    // equivalent converted type
+    let (logical_type, logical_annotation, precision, scale) =
+        if let Some("(") = self.tokenizer.next() {
+      let type_name = self.tokenizer.next().
+        ok_or(general_err!("Expected logical type, found None"))?.to_uppercase();
+      let (tpe, logical_annotation) = match type_name.parse::<LogicalType>() {
+        Ok(tpe) => (tpe, None),
+        Err(_) => (LogicalType::NONE, Some(self.parse_logical_annotation(&type_name)?))
+      };
 
       // Parse precision and scale for decimals
       let mut precision: i32 = 0;
@@ -255,10 +269,10 @@ impl<'a> Parser<'a> {
       }
 
       assert_token(self.tokenizer.next(), ")")?;
-      (tpe, precision, scale)
+      (tpe, logical_annotation, precision, scale)
     } else {
       self.tokenizer.backtrack();
-      (LogicalType::NONE, 0, 0)
+      (LogicalType::NONE, None, 0, 0)
     };
 
     // Parse optional id
@@ -273,6 +287,7 @@ impl<'a> Parser<'a> {
     let mut builder = Type::primitive_type_builder(name, physical_type)
       .with_repetition(repetition)
       .with_logical_type(logical_type)
+      .with_logical_annotation(logical_annotation)
       .with_length(length)
       .with_precision(precision)
       .with_scale(scale);
@@ -281,6 +296,52 @@ impl<'a> Parser<'a> {
     }
     Ok(builder.build()?)
   }
+
+  // Parses a logical type annotation without an equivalent converted type, i.e.
+  // STRING, UUID, UNKNOWN, TIME(unit,isAdjustedToUTC), TIMESTAMP(unit,isAdjustedToUTC)
+  // and INTEGER(bitWidth,isSigned). `name` is the already consumed name of the type.
+  fn parse_logical_annotation(&mut self, name: &str) -> Result<LogicalAnnotation> {
+    match name {
+      "STRING" => Ok(LogicalAnnotation::STRING),
+      "UUID" => Ok(LogicalAnnotation::UUID),
+      "UNKNOWN" => Ok(LogicalAnnotation::UNKNOWN),
+      "TIME" | "TIMESTAMP" => {
+        assert_token(self.tokenizer.next(), "(")?;
+        let unit = self.tokenizer.next().
+          ok_or(general_err!("Expected time unit, found None")).
+          and_then(|v| v.to_uppercase().parse::<TimeUnit>())?;
+        assert_token(self.tokenizer.next(), ",")?;
+        let is_adjusted_to_utc = parse_bool(self.tokenizer.next(),
+          "Expected isAdjustedToUTC, found None",
+          "Failed to parse isAdjustedToUTC")?;
+        assert_token(self.tokenizer.next(), ")")?;
+        if name == "TIME" {
+          Ok(LogicalAnnotation::TIME {
+            is_adjusted_to_utc: is_adjusted_to_utc, unit: unit })
+        } else {
+          Ok(LogicalAnnotation::TIMESTAMP {
+            is_adjusted_to_utc: is_adjusted_to_utc, unit: unit })
+        }
+      },
+      "INTEGER" => {
+        assert_token(self.tokenizer.next(), "(")?;
+        let bit_width = parse_i32(self.tokenizer.next(),
+          "Expected bit width, found None",
+          "Failed to parse bit width for INTEGER type")?;
+        if bit_width <= 0 || bit_width > 64 {
+          return Err(general_err!("Invalid INTEGER bit width: {}", bit_width));
+        }
+        assert_token(self.tokenizer.next(), ",")?;
+        let is_signed = parse_bool(self.tokenizer.next(),
+          "Expected isSigned, found None",
+          "Failed to parse isSigned for INTEGER type")?;
+        assert_token(self.tokenizer.next(), ")")?;
+        Ok(LogicalAnnotation::INTEGER {
+          bit_width: bit_width as i8, is_signed: is_signed })
+      },
+      other => Err(general_err!("Invalid logical type {}", other))
+    }
+  }
 }
 
 #[cfg(test)]
@@ -603,4 +664,48 @@ mod tests {
       .build().unwrap();
     assert_eq!(message, expected);
   }
+
+  #[test]
+  fn test_parse_logical_annotation() {
+    let schema = "
+    message root {
+      required int64 f1 (TIMESTAMP(NANOS, false));
+      required int32 f2 (time(millis, true));
+      required int32 f3 (INTEGER(16, false));
+      optional binary f4 (STRING);
+      required fixed_len_byte_array(16) f5 (UUID);
+    }
+    ";
+    let message = parse_message_type(schema).unwrap();
+    let fields = message.get_fields();
+    let annotations: Vec<_> = fields.iter()
+      .map(|f| f.get_basic_info().logical_annotation().unwrap())
+      .collect();
+    assert_eq!(annotations, vec![
+      LogicalAnnotation::TIMESTAMP { is_adjusted_to_utc: false, unit: TimeUnit::NANOS },
+      LogicalAnnotation::TIME { is_adjusted_to_utc: true, unit: TimeUnit::MILLIS },
+      LogicalAnnotation::INTEGER { bit_width: 16, is_signed: false },
+      LogicalAnnotation::STRING,
+      LogicalAnnotation::UUID
+    ]);
+    let logical_types: Vec<_> = fields.iter()
+      .map(|f| f.get_basic_info().logical_type())
+      .collect();
+    assert_eq!(logical_types, vec![LogicalType::NONE, LogicalType::TIME_MILLIS,
+      LogicalType::UINT_16, LogicalType::UTF8, LogicalType::NONE]);
+
+    // Invalid annotations
+    let schemas = vec![
+      "message root { required int64 f1 (TIMESTAMP); }",
+      "message root { required int64 f1 (TIMESTAMP(SECONDS, true)); }",
+      "message root { required int64 f1 (TIMESTAMP(MILLIS, yes)); }",
+      "message root { required int32 f1 (INTEGER(12, true)); }",
+      "message root { required int32 f1 (INTEGER(1000, true)); }",
+      "message root { required int32 f1 (UUID); }",
+      "message root { required int32 f1 (FOO); }"
+    ];
+    for schema in schemas {
+      assert!(parse_message_type(schema).is_err(), "Expected error for {}", schema);
+    }
+  }
 }
@@ -18,7 +18,7 @@
 use std::fmt;
 use std::io;
 
-use basic::{LogicalType, Type as PhysicalType};
+use basic::{LogicalType, LogicalAnnotation, Type as PhysicalType};
 use schema::types::Type;
 use file::metadata::{ParquetMetaData, FileMetaData, RowGroupMetaData, ColumnChunkMetaData};
 
@@ -115,6 +115,23 @@ fn print_dashes(out: &mut io::Write, num: i32) {
   writeln!(out, "");
 }
 
+fn logical_type_str(logical_type: LogicalType, precision: i32, scale: i32) -> String {
+  match logical_type {
+    LogicalType::NONE => format!(""),
+    decimal @ LogicalType::DECIMAL => {
+      // For decimal type we should print precision and scale if they are > 0, e.g.
+      // DECIMAL(9, 2) - DECIMAL(9) - DECIMAL
+      let precision_scale = match (precision, scale) {
+        (p, s) if p > 0 && s > 0 => format!(" ({}, {})", p, s),
+        (p, 0) if p > 0 => format!(" ({})", p),
+        _ => format!("")
+      };
+      format!(" ({}{})", decimal, precision_scale)
+    },
+    other_logical_type => format!(" ({})", other_logical_type),
+  }
+}
+
 const INDENT_WIDTH: i32 = 2;
 
 pub struct Printer<'a> {
@@ -150,20 +167,16 @@ impl<'a> Printer<'a> {
           },
           _ => format!("{}", physical_type),
         };
-        // Also print logical type if it is available
-        let logical_type_str = match basic_info.logical_type() {
-          LogicalType::NONE => format!(""),
-          decimal @ LogicalType::DECIMAL => {
-            // For decimal type we should print precision and scale if they are > 0, e.g.
-            // DECIMAL(9, 2) - DECIMAL(9) - DECIMAL
-            let precision_scale = match (precision, scale) {
-              (p, s) if p > 0 && s > 0 => format!(" ({}, {})", p, s),
-              (p, 0) if p > 0 => format!(" ({})", p),
-              _ => format!("")
-            };
-            format!(" ({}{})", decimal, precision_scale)
+        // Also print logical type if it is available. Annotations are only printed if
+        // they carry more information than the converted type.
+        let logical_type = basic_info.logical_type();
+        let derived_annotation =
+          LogicalAnnotation::from_converted_type(logical_type, precision, scale);
+        let logical_type_str = match basic_info.logical_annotation() {
+          Some(annotation) if Some(annotation) != derived_annotation => {
+            format!(" ({})", annotation)
           },
-          other_logical_type => format!(" ({})", other_logical_type),
+          _ => logical_type_str(logical_type, precision, scale)
         };
         write!(
           self.output, "{} {} {}{};",
@@ -200,7 +213,7 @@ mod tests {
   use std::rc::Rc;
   use schema::types::Type;
   use schema::parser::parse_message_type;
-  use basic::{Type as PhysicalType, Repetition};
+  use basic::{Type as PhysicalType, Repetition, TimeUnit};
 
   fn assert_print_parse_message(message: Type) {
     let mut s = String::new();
@@ -388,4 +401,46 @@ mod tests {
 
     assert_print_parse_message(message);
   }
+
+  #[test]
+  fn test_print_and_parse_logical_annotation() {
+    let annotations = vec![
+      (PhysicalType::INT64, LogicalAnnotation::TIMESTAMP {
+        is_adjusted_to_utc: true, unit: TimeUnit::NANOS }),
+      (PhysicalType::INT64, LogicalAnnotation::TIMESTAMP {
+        is_adjusted_to_utc: false, unit: TimeUnit::MICROS }),
+      (PhysicalType::INT32, LogicalAnnotation::TIME {
+        is_adjusted_to_utc: false, unit: TimeUnit::MILLIS }),
+      (PhysicalType::INT32,
+       LogicalAnnotation::INTEGER { bit_width: 8, is_signed: false }),
+      (PhysicalType::BYTE_ARRAY, LogicalAnnotation::STRING),
+      (PhysicalType::BYTE_ARRAY, LogicalAnnotation::UNKNOWN),
+      (PhysicalType::FIXED_LEN_BYTE_ARRAY, LogicalAnnotation::UUID)
+    ];
+    let mut fields = vec!();
+    for (i, (physical_type, annotation)) in annotations.into_iter().enumerate() {
+      let length = match physical_type {
+        PhysicalType::FIXED_LEN_BYTE_ARRAY => 16,
+        _ => 0
+      };
+      let field = Type::primitive_type_builder(&format!("f{}", i), physical_type)
+        .with_repetition(Repetition::REQUIRED)
+        .with_logical_annotation(Some(annotation))
+        .with_length(length)
+        .build().unwrap();
+      fields.push(Rc::new(field));
+    }
+    let message = Type::group_type_builder("schema")
+      .with_fields(&mut fields)
+      .build().unwrap();
+
+    let mut s = String::new();
+    {
+      let mut p = Printer::new(&mut s);
+      p.print(&message.get_fields()[0]);
+    }
+    assert_eq!(s, "REQUIRED INT64 f0 (TIMESTAMP(NANOS,true));");
+
+    assert_print_parse_message(message);
+  }
 }
@@ -19,10 +19,11 @@ use std::fmt;
 use std::rc::Rc;
 use std::convert::From;
 use std::collections::HashMap;
-use basic::{Type as PhysicalType, Repetition, LogicalType};
+use basic::{Type as PhysicalType, Repetition, LogicalType, LogicalAnnotation, TimeUnit};
 use errors::Result;
-use errors::ParquetError;
+use errors::{ParquetError, ErrorContext};
 use parquet_thrift::parquet::SchemaElement;
+use schema::merge::{check_compatibility, SchemaChange};
 
 // ----------------------------------------------------------------------
 // Parquet Type definitions
@@ -34,7 +35,7 @@ pub type ColumnDescPtr = Rc<ColumnDescriptor>;
 /// Representation of a Parquet type.
 /// Note that the top-level schema type is represented using `GroupType` whose
 /// repetition is `None`.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Type {
   PrimitiveType {
     basic_info: BasicTypeInfo, physical_type: PhysicalType,
@@ -85,46 +86,33 @@ impl Type {
     }
   }
 
-  /// Check if `sub_type` schema is part of current schema, e.g. projected columns
+  /// Check if `sub_type` schema is part of current schema, e.g. projected columns.
+  ///
+  /// Fields are matched by name, and must have the same repetition and physical type.
+  /// Logical types and lengths are not compared.
   pub fn check_contains(&self, sub_type: &Type) -> bool {
-    // Names match, and repetitions match or not set for both
-    let basic_match = self.get_basic_info().name() == sub_type.get_basic_info().name() &&
-      (self.is_schema() && sub_type.is_schema() || !self.is_schema() &&
-       !sub_type.is_schema() &&
-       self.get_basic_info().repetition() == sub_type.get_basic_info().repetition());
-
-    match *self {
-      Type::PrimitiveType { .. } if basic_match && sub_type.is_primitive() => {
-        self.get_physical_type() == sub_type.get_physical_type()
-      },
-      Type::GroupType { .. } if basic_match && sub_type.is_group() => {
-        // build hashmap of name -> TypePtr
-        let mut field_map = HashMap::new();
-        for field in self.get_fields() {
-          field_map.insert(field.name(), field);
-        }
-
-        for field in sub_type.get_fields() {
-          if !field_map.get(field.name())
-            .map(|tpe| tpe.check_contains(field))
-            .unwrap_or(false) {
-            return false;
-          }
-        }
-        true
-      },
-      _ => false,
+    if self.name() != sub_type.name() || self.is_schema() != sub_type.is_schema() {
+      return false;
     }
+    check_compatibility(self, sub_type).iter().all(|change| match *change {
+      SchemaChange::FieldRemoved(_) | SchemaChange::LogicalTypeChanged(..) => true,
+      SchemaChange::TypeChanged(_, ref tp, ref sub_tp) => {
+        tp.get_physical_type() == sub_tp.get_physical_type()
+      },
+      _ => false
+    })
   }
 
-  fn is_primitive(&self) -> bool {
+  /// Whether this is a primitive type.
+  pub fn is_primitive(&self) -> bool {
     match *self {
       Type::PrimitiveType{ .. } => true,
       _ => false
     }
   }
 
-  fn is_group(&self) -> bool {
+  /// Whether this is a group type, including the top-level schema type.
+  pub fn is_group(&self) -> bool {
     match *self {
       Type::GroupType{ .. } => true,
       _ => false
@@ -148,6 +136,7 @@ pub struct PrimitiveTypeBuilder<'a> {
   repetition: Repetition,
   physical_type: PhysicalType,
   logical_type: LogicalType,
+  logical_annotation: Option<LogicalAnnotation>,
   length: i32,
   precision: i32,
   scale: i32,
@@ -161,6 +150,7 @@ impl<'a> PrimitiveTypeBuilder<'a> {
       repetition: Repetition::OPTIONAL,
       physical_type: physical_type,
       logical_type: LogicalType::NONE,
+      logical_annotation: None,
       length: 0,
       precision: 0,
       scale: 0,
@@ -178,6 +168,16 @@ impl<'a> PrimitiveTypeBuilder<'a> {
     self
   }
 
+  /// Sets the logical type annotation. If no converted type is set, it is derived
+  /// from the annotation; if no annotation is set, it is derived from the converted
+  /// type.
+  pub fn with_logical_annotation(
+    mut self, annotation: Option<LogicalAnnotation>
+  ) -> Self {
+    self.logical_annotation = annotation;
+    self
+  }
+
   pub fn with_length(mut self, length: i32) -> Self {
     self.length = length;
     self
@@ -200,11 +200,29 @@ impl<'a> PrimitiveTypeBuilder<'a> {
 
   // Creates a new `PrimitiveType` instance from the gathered attributes.
   // This also checks various illegal conditions and returns `Err` if that that happen.
-  pub fn build(self) -> Result<Type> {
+  pub fn build(mut self) -> Result<Type> {
+    // Precision and scale can be given by the annotation only
+    if let Some(LogicalAnnotation::DECIMAL { scale, precision }) =
+        self.logical_annotation {
+      if self.precision <= 0 {
+        self.precision = precision;
+        self.scale = scale;
+      } else if self.precision != precision || self.scale != scale {
+        return Err(general_err!(
+          "DECIMAL({}, {}) does not match precision {} and scale {}",
+          precision, scale, self.precision, self.scale))
+      }
+    }
+    let (logical_type, logical_annotation) = resolve_logical_types(
+      self.logical_type, self.logical_annotation, self.precision, self.scale)?;
+    self.logical_type = logical_type;
+    self.logical_annotation = logical_annotation;
+
     let basic_info = BasicTypeInfo {
       name: String::from(self.name),
       repetition: Some(self.repetition),
       logical_type: self.logical_type,
+      logical_annotation: self.logical_annotation,
       id: self.id
     };
 
@@ -266,6 +284,26 @@ impl<'a> PrimitiveTypeBuilder<'a> {
           "{} cannot be applied to a primitive type", self.logical_type))
       }
     };
+    // Annotations without an equivalent converted type are checked separately
+    match self.logical_annotation {
+      Some(LogicalAnnotation::UUID) => {
+        if self.physical_type != PhysicalType::FIXED_LEN_BYTE_ARRAY || self.length != 16 {
+          return Err(general_err!("UUID can only annotate FIXED(16)"))
+        }
+      },
+      Some(annotation @ LogicalAnnotation::TIME { unit: TimeUnit::NANOS, .. }) |
+      Some(annotation @ LogicalAnnotation::TIMESTAMP { unit: TimeUnit::NANOS, .. }) => {
+        if self.physical_type != PhysicalType::INT64 {
+          return Err(general_err!("{} can only annotate INT64", annotation))
+        }
+      },
+      Some(LogicalAnnotation::INTEGER { bit_width, .. }) => {
+        if self.logical_type == LogicalType::NONE {
+          return Err(general_err!("Invalid INTEGER bit width: {}", bit_width))
+        }
+      },
+      _ => ()
+    }
     if self.physical_type == PhysicalType::FIXED_LEN_BYTE_ARRAY && self.length < 0 {
       return Err(general_err!("Invalid FIXED_LEN_BYTE_ARRAY length: {}", self.length))
     }
@@ -286,6 +324,7 @@ pub struct GroupTypeBuilder<'a> {
   name: &'a str,
   repetition: Option<Repetition>,
   logical_type: LogicalType,
+  logical_annotation: Option<LogicalAnnotation>,
   fields: Vec<TypePtr>,
   id: Option<i32>
 }
@@ -296,6 +335,7 @@ impl<'a> GroupTypeBuilder<'a> {
       name: name,
       repetition: None,
       logical_type: LogicalType::NONE,
+      logical_annotation: None,
       fields: Vec::new(),
       id: None
     }
@@ -311,6 +351,15 @@ impl<'a> GroupTypeBuilder<'a> {
     self
   }
 
+  /// Sets the logical type annotation, see
+  /// `PrimitiveTypeBuilder::with_logical_annotation`.
+  pub fn with_logical_annotation(
+    mut self, annotation: Option<LogicalAnnotation>
+  ) -> Self {
+    self.logical_annotation = annotation;
+    self
+  }
+
   pub fn with_fields(mut self, fields: &mut Vec<TypePtr>) -> Self {
     self.fields.append(fields);
     self
@@ -323,22 +372,62 @@ impl<'a> GroupTypeBuilder<'a> {
 
   // Create a new `GroupType` instance from the gathered attributes.
   pub fn build(self) -> Result<Type> {
+    match self.logical_annotation {
+      None | Some(LogicalAnnotation::MAP) | Some(LogicalAnnotation::LIST) => (),
+      Some(annotation) => {
+        return Err(general_err!("{} cannot be applied to a group type", annotation))
+      }
+    }
+    let (logical_type, logical_annotation) =
+      resolve_logical_types(self.logical_type, self.logical_annotation, 0, 0)?;
     let basic_info = BasicTypeInfo {
       name: String::from(self.name), repetition: self.repetition,
-      logical_type: self.logical_type, id: self.id };
+      logical_type: logical_type, logical_annotation: logical_annotation,
+      id: self.id };
     Ok(Type::GroupType {
       basic_info: basic_info, fields: self.fields
     })
   }
 }
 
+/// Derives the converted type from the logical annotation, or the annotation from the
+/// converted type, whichever is missing. Returns an error if both are set but the
+/// converted type does not correspond to the annotation.
+fn resolve_logical_types(
+  logical_type: LogicalType,
+  logical_annotation: Option<LogicalAnnotation>,
+  precision: i32,
+  scale: i32
+) -> Result<(LogicalType, Option<LogicalAnnotation>)> {
+  match logical_annotation {
+    Some(annotation) => {
+      let converted_type = annotation.to_converted_type();
+      if logical_type == LogicalType::NONE {
+        Ok((converted_type, logical_annotation))
+      } else if logical_type == converted_type {
+        Ok((logical_type, logical_annotation))
+      } else {
+        Err(general_err!(
+          "Logical type {} is incompatible with converted type {}",
+          annotation, logical_type))
+      }
+    },
+    None => {
+      let annotation =
+        LogicalAnnotation::from_converted_type(logical_type, precision, scale);
+      Ok((logical_type, annotation))
+    }
+  }
+}
+
 /// Basic type info. This contains information such as the name of the type,
 /// the repetition level, the logical type and the kind of the type (group, primitive).
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct BasicTypeInfo {
   name: String,
   repetition: Option<Repetition>,
   logical_type: LogicalType,
+  logical_annotation: Option<LogicalAnnotation>,
   id: Option<i32>,
 }
 
@@ -360,6 +449,12 @@ impl BasicTypeInfo {
     self.logical_type
   }
 
+  /// Logical type annotation, which is derived from the converted type returned by
+  /// `logical_type()` if the schema does not carry one.
+  pub fn logical_annotation(&self) -> Option<LogicalAnnotation> {
+    self.logical_annotation
+  }
+
   pub fn has_id(&self) -> bool {
     self.id.is_some()
   }
@@ -375,7 +470,7 @@ impl BasicTypeInfo {
 // Parquet descriptor definitions
 
 /// Represents a path in a nested schema
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct ColumnPath {
   parts: Vec<String>
 }
@@ -388,6 +483,11 @@ impl ColumnPath {
   pub fn string(&self) -> String {
     self.parts.join(".")
   }
+
+  /// Returns the individual components of this path, from the root to the leaf.
+  pub fn parts(&self) -> &[String] {
+    &self.parts
+  }
 }
 
 impl fmt::Display for ColumnPath {
@@ -480,10 +580,19 @@ impl ColumnDescriptor {
     self.primitive_type.name()
   }
 
+  /// Returns the primitive type of this column.
+  pub fn self_type(&self) -> &Type {
+    self.primitive_type.as_ref()
+  }
+
   pub fn logical_type(&self) -> LogicalType {
     self.primitive_type.get_basic_info().logical_type()
   }
 
+  pub fn logical_annotation(&self) -> Option<LogicalAnnotation> {
+    self.primitive_type.get_basic_info().logical_annotation()
+  }
+
   pub fn physical_type(&self) -> PhysicalType {
     match self.primitive_type.as_ref() {
       &Type::PrimitiveType{ physical_type, .. } => physical_type,
@@ -531,7 +640,13 @@ pub struct SchemaDescriptor {
   // -- -- b     |
   // -- -- -- c  |
   // -- -- -- -- d
-  leaf_to_base: HashMap<usize, TypePtr>
+  leaf_to_base: HashMap<usize, TypePtr>,
+
+  // Mapping from the path of a leaf column to its index.
+  path_to_leaf: HashMap<ColumnPath, usize>,
+
+  // Mapping from a field id to the indexes of the leaf columns with this id.
+  id_to_leaf: HashMap<i32, Vec<usize>>
 }
 
 impl SchemaDescriptor {
@@ -545,7 +660,23 @@ impl SchemaDescriptor {
         f.clone(), tp.clone(), f.clone(), 0, 0, &mut leaves,
         &mut leaf_to_base, &mut path);
     }
-    Self { schema: tp, leaves: leaves, leaf_to_base: leaf_to_base }
+
+    let mut path_to_leaf = HashMap::new();
+    let mut id_to_leaf = HashMap::new();
+    for (i, leaf) in leaves.iter().enumerate() {
+      path_to_leaf.entry(leaf.path().clone()).or_insert(i);
+      let info = leaf.self_type().get_basic_info();
+      if info.has_id() {
+        id_to_leaf.entry(info.id()).or_insert(vec!()).push(i);
+      }
+    }
+    Self {
+      schema: tp,
+      leaves: leaves,
+      leaf_to_base: leaf_to_base,
+      path_to_leaf: path_to_leaf,
+      id_to_leaf: id_to_leaf
+    }
   }
 
   pub fn column(&self, i: usize) -> ColumnDescPtr {
@@ -562,6 +693,36 @@ impl SchemaDescriptor {
     self.leaves.len()
   }
 
+  /// Returns the index of the leaf column with the given `path`, or `None` if there
+  /// is no such column.
+  pub fn column_index(&self, path: &ColumnPath) -> Option<usize> {
+    self.path_to_leaf.get(path).cloned()
+  }
+
+  /// Returns the index of the leaf column with the given dotted path, e.g. `a.b.c`, or
+  /// `None` if there is no such column. Use `column_index` if a field name contains a
+  /// dot.
+  pub fn column_index_by_dotted_path(&self, path: &str) -> Option<usize> {
+    let parts: Vec<String> = path.split('.').map(String::from).collect();
+    self.column_index(&ColumnPath::new(parts))
+  }
+
+  /// Returns the index of the leaf column with the given field id, or `None` if there
+  /// is no such column. Field ids are meant to be unique, so an id that several leaves
+  /// have is ambiguous and an error is returned.
+  pub fn column_index_by_id(&self, id: i32) -> Result<Option<usize>> {
+    match self.id_to_leaf.get(&id) {
+      None => Ok(None),
+      Some(leaves) if leaves.len() == 1 => Ok(Some(leaves[0])),
+      Some(leaves) => {
+        let paths: Vec<String> =
+          leaves.iter().map(|&i| self.leaves[i].path().string()).collect();
+        Err(schema_err!(ErrorContext::new(),
+          "Field id {} is not unique, it is used by columns {}", id, paths.join(", ")))
+      }
+    }
+  }
+
   pub fn get_column_root(&self, i: usize) -> &Type {
     assert!(
       i < self.leaves.len(),
@@ -635,9 +796,13 @@ pub fn from_thrift(elements: &mut [SchemaElement]) -> Result<TypePtr> {
     schema_nodes.push(t.1);
   }
   if schema_nodes.len() != 1 {
-    return Err(general_err!(
+    return Err(corrupt_err!(ErrorContext::new(),
       "Expected exactly one root node, but found {}", schema_nodes.len()))
   }
+  if !schema_nodes[0].is_group() {
+    return Err(corrupt_err!(
+      ErrorContext::new(), "Expected root node to be a group type"));
+  }
 
   Ok(schema_nodes.remove(0))
 }
@@ -650,20 +815,27 @@ fn from_thrift_helper(
   elements: &mut [SchemaElement],
   index: usize
 ) -> Result<(usize, TypePtr)> {
-  if index > elements.len() {
-    return Err(general_err!(
+  if index >= elements.len() {
+    return Err(corrupt_err!(ErrorContext::new(),
       "Index out of bound, index = {}, len = {}", index, elements.len()))
   }
   let logical_type = LogicalType::from(elements[index].converted_type);
+  let logical_annotation =
+    elements[index].logical_type.clone().map(|tp| LogicalAnnotation::from(tp));
   let field_id = elements[index].field_id;
   match elements[index].num_children {
     None => {
       // primitive type
       if elements[index].repetition_type.is_none() {
-        return Err(general_err!("Repetition level must be defined for a primitive type"));
+        return Err(corrupt_err!(ErrorContext::new(),
+          "Repetition level must be defined for a primitive type"));
       }
       let repetition = Repetition::from(elements[index].repetition_type.unwrap());
-      let physical_type = PhysicalType::from(elements[index].type_.unwrap());
+      let physical_type = match elements[index].type_ {
+        Some(tp) => PhysicalType::from(tp),
+        None => return Err(corrupt_err!(ErrorContext::new(),
+          "Physical type must be defined for primitive type {}", elements[index].name))
+      };
       let length = elements[index].type_length.unwrap_or(-1);
       let scale = elements[index].scale.unwrap_or(-1);
       let precision = elements[index].precision.unwrap_or(-1);
@@ -671,6 +843,7 @@ fn from_thrift_helper(
       let mut builder = Type::primitive_type_builder(name, physical_type)
         .with_repetition(repetition)
         .with_logical_type(logical_type)
+        .with_logical_annotation(logical_annotation)
         .with_length(length)
         .with_precision(precision)
         .with_scale(scale);
@@ -685,12 +858,18 @@ fn from_thrift_helper(
       let mut next_index = index + 1;
       for _ in 0..n {
         let child_result = from_thrift_helper(elements, next_index as usize)?;
+        if !child_result.1.get_basic_info().has_repetition() {
+          return Err(corrupt_err!(ErrorContext::new(),
+            "Repetition level must be defined for non-root type {}",
+            child_result.1.name()));
+        }
         next_index = child_result.0;
         fields.push(child_result.1);
       }
 
       let mut builder = Type::group_type_builder(&elements[index].name)
         .with_logical_type(logical_type)
+        .with_logical_annotation(logical_annotation)
         .with_fields(&mut fields);
       if let Some(rep) = repetition {
         builder = builder.with_repetition(rep);
@@ -698,7 +877,70 @@ fn from_thrift_helper(
       if let Some(id) = field_id {
         builder = builder.with_id(id);
       }
-      Ok((next_index, Rc::new(builder.build().unwrap())))
+      Ok((next_index, Rc::new(builder.build()?)))
+    }
+  }
+}
+
+/// Conversion to Thrift equivalents
+
+/// Flattens `schema` into a list of `SchemaElement`s in depth-first order.
+/// This is the inverse of `from_thrift`.
+pub fn to_thrift(schema: &Type) -> Result<Vec<SchemaElement>> {
+  if !schema.is_group() {
+    return Err(general_err!("Root schema must be Group type"));
+  }
+  let mut elements: Vec<SchemaElement> = Vec::new();
+  to_thrift_helper(schema, &mut elements);
+  Ok(elements)
+}
+
+/// Constructs the `SchemaElement` for `schema` and appends it to `elements`,
+/// followed by the elements of its fields, if it is a group type.
+fn to_thrift_helper(schema: &Type, elements: &mut Vec<SchemaElement>) {
+  let basic_info = schema.get_basic_info();
+  let repetition = if basic_info.has_repetition() {
+    Some(basic_info.repetition().into())
+  } else {
+    None
+  };
+  let field_id = if basic_info.has_id() { Some(basic_info.id()) } else { None };
+
+  match *schema {
+    Type::PrimitiveType { physical_type, type_length, scale, precision, .. } => {
+      let is_fixed = physical_type == PhysicalType::FIXED_LEN_BYTE_ARRAY;
+      let is_decimal = basic_info.logical_type() == LogicalType::DECIMAL;
+      let element = SchemaElement {
+        type_: Some(physical_type.into()),
+        type_length: if is_fixed || type_length > 0 { Some(type_length) } else { None },
+        repetition_type: repetition,
+        name: basic_info.name().to_owned(),
+        num_children: None,
+        converted_type: basic_info.logical_type().into(),
+        scale: if is_decimal || scale > 0 { Some(scale) } else { None },
+        precision: if is_decimal || precision > 0 { Some(precision) } else { None },
+        field_id: field_id,
+        logical_type: basic_info.logical_annotation().map(|tp| tp.into())
+      };
+      elements.push(element);
+    },
+    Type::GroupType { ref fields, .. } => {
+      let element = SchemaElement {
+        type_: None,
+        type_length: None,
+        repetition_type: repetition,
+        name: basic_info.name().to_owned(),
+        num_children: Some(fields.len() as i32),
+        converted_type: basic_info.logical_type().into(),
+        scale: None,
+        precision: None,
+        field_id: field_id,
+        logical_type: basic_info.logical_annotation().map(|tp| tp.into())
+      };
+      elements.push(element);
+      for field in fields {
+        to_thrift_helper(field, elements);
+      }
     }
   }
 }
@@ -843,6 +1085,96 @@ mod tests {
     }
   }
 
+  #[test]
+  fn test_primitive_type_logical_annotation() {
+    // Converted type is derived from the annotation
+    let tp = Type::primitive_type_builder("foo", PhysicalType::BYTE_ARRAY)
+      .with_logical_annotation(Some(LogicalAnnotation::STRING))
+      .build().unwrap();
+    assert_eq!(tp.get_basic_info().logical_type(), LogicalType::UTF8);
+    assert_eq!(tp.get_basic_info().logical_annotation(), Some(LogicalAnnotation::STRING));
+
+    // Annotation is derived from the converted type
+    let tp = Type::primitive_type_builder("foo", PhysicalType::INT32)
+      .with_logical_type(LogicalType::UINT_16)
+      .build().unwrap();
+    assert_eq!(tp.get_basic_info().logical_annotation(),
+               Some(LogicalAnnotation::INTEGER { bit_width: 16, is_signed: false }));
+
+    // Annotations without converted type
+    let annotation = LogicalAnnotation::TIMESTAMP {
+      is_adjusted_to_utc: false, unit: TimeUnit::NANOS };
+    let tp = Type::primitive_type_builder("foo", PhysicalType::INT64)
+      .with_logical_annotation(Some(annotation))
+      .build().unwrap();
+    assert_eq!(tp.get_basic_info().logical_type(), LogicalType::NONE);
+    assert_eq!(tp.get_basic_info().logical_annotation(), Some(annotation));
+
+    let tp = Type::primitive_type_builder("foo", PhysicalType::FIXED_LEN_BYTE_ARRAY)
+      .with_length(16)
+      .with_logical_annotation(Some(LogicalAnnotation::UUID))
+      .build().unwrap();
+    assert_eq!(tp.get_basic_info().logical_type(), LogicalType::NONE);
+
+    // Precision and scale of decimals
+    let decimal = LogicalAnnotation::DECIMAL { scale: 2, precision: 9 };
+    let tp = Type::primitive_type_builder("foo", PhysicalType::INT64)
+      .with_logical_annotation(Some(decimal))
+      .build().unwrap();
+    assert_eq!(tp.get_basic_info().logical_type(), LogicalType::DECIMAL);
+    match tp {
+      Type::PrimitiveType { precision, scale, .. } => {
+        assert_eq!(precision, 9);
+        assert_eq!(scale, 2);
+      },
+      _ => panic!("Expected primitive type")
+    }
+
+    // Test illegal inputs
+    let result = Type::primitive_type_builder("foo", PhysicalType::INT64)
+      .with_logical_annotation(
+        Some(LogicalAnnotation::INTEGER { bit_width: 8, is_signed: false }))
+      .build();
+    assert_eq!(result.unwrap_err().description(), "UINT_8 can only annotate INT32");
+
+    let result = Type::primitive_type_builder("foo", PhysicalType::INT32)
+      .with_logical_annotation(
+        Some(LogicalAnnotation::INTEGER { bit_width: 7, is_signed: false }))
+      .build();
+    assert_eq!(result.unwrap_err().description(), "Invalid INTEGER bit width: 7");
+
+    let result = Type::primitive_type_builder("foo", PhysicalType::BYTE_ARRAY)
+      .with_logical_annotation(Some(LogicalAnnotation::UUID))
+      .build();
+    assert_eq!(result.unwrap_err().description(), "UUID can only annotate FIXED(16)");
+
+    let result = Type::primitive_type_builder("foo", PhysicalType::INT32)
+      .with_logical_annotation(Some(annotation))
+      .build();
+    assert_eq!(result.unwrap_err().description(),
+               "TIMESTAMP(NANOS,false) can only annotate INT64");
+
+    let result = Type::primitive_type_builder("foo", PhysicalType::BYTE_ARRAY)
+      .with_logical_type(LogicalType::UTF8)
+      .with_logical_annotation(Some(LogicalAnnotation::JSON))
+      .build();
+    assert_eq!(result.unwrap_err().description(),
+               "Logical type JSON is incompatible with converted type UTF8");
+
+    let result = Type::primitive_type_builder("foo", PhysicalType::INT32)
+      .with_precision(5)
+      .with_scale(1)
+      .with_logical_annotation(Some(decimal))
+      .build();
+    assert!(result.is_err());
+
+    let result = Type::group_type_builder("foo")
+      .with_logical_annotation(Some(LogicalAnnotation::STRING))
+      .build();
+    assert_eq!(result.unwrap_err().description(),
+               "STRING cannot be applied to a group type");
+  }
+
   #[test]
   fn test_group_type() {
     let f1 = Type::primitive_type_builder("f1", PhysicalType::INT32)
@@ -1001,6 +1333,58 @@ mod tests {
     Ok(())
   }
 
+  #[test]
+  fn test_schema_descriptor_column_index() {
+    let message_type = "
+    message schema {
+      REQUIRED INT32 a = 1;
+      OPTIONAL group b = 2 {
+        OPTIONAL INT32 c = 3;
+        OPTIONAL INT64 d;
+      }
+    }
+    ";
+    let schema = parse_message_type(message_type).expect("should parse schema");
+    let descr = SchemaDescriptor::new(Rc::new(schema));
+
+    let path = ColumnPath::new(vec!("b".to_owned(), "d".to_owned()));
+    assert_eq!(descr.column_index(&ColumnPath::from("a")), Some(0));
+    assert_eq!(descr.column_index(&path), Some(2));
+    assert_eq!(descr.column_index(&ColumnPath::from("b")), None);
+    assert_eq!(descr.column_index_by_dotted_path("b.c"), Some(1));
+    assert_eq!(descr.column_index_by_dotted_path("b.d"), Some(2));
+    assert_eq!(descr.column_index_by_dotted_path("b.e"), None);
+
+    assert_eq!(descr.column_index_by_id(1).unwrap(), Some(0));
+    assert_eq!(descr.column_index_by_id(3).unwrap(), Some(1));
+    // Ids of groups do not identify a leaf column
+    assert_eq!(descr.column_index_by_id(2).unwrap(), None);
+    assert_eq!(descr.column_index_by_id(4).unwrap(), None);
+  }
+
+  #[test]
+  fn test_schema_descriptor_duplicate_id() {
+    let message_type = "
+    message schema {
+      REQUIRED INT32 a = 1;
+      OPTIONAL group b {
+        OPTIONAL INT32 c = 1;
+        OPTIONAL INT64 d = 2;
+      }
+    }
+    ";
+    let schema = parse_message_type(message_type).expect("should parse schema");
+    let descr = SchemaDescriptor::new(Rc::new(schema));
+
+    assert_eq!(descr.column_index_by_id(2).unwrap(), Some(2));
+    match descr.column_index_by_id(1) {
+      Err(ParquetError::SchemaMismatch(message, _)) => {
+        assert_eq!(message, "Field id 1 is not unique, it is used by columns a, b.c");
+      },
+      _ => panic!("Expected an error for an ambiguous field id")
+    }
+  }
+
   #[test]
   fn test_schema_build_tree_def_rep_levels() {
     let message_type = "
@@ -1189,4 +1573,96 @@ mod tests {
     assert!(f1.check_contains(&f2)); // should match
     assert!(!f2.check_contains(&f1)); // should fail
   }
+
+  #[test]
+  fn test_schema_type_thrift_conversion() {
+    let message_type = "
+    message conversions {
+      REQUIRED INT64 id;
+      OPTIONAL group int_array_Array (LIST) = 3 {
+        REPEATED group list {
+          OPTIONAL group element (LIST) {
+            REPEATED group list {
+              OPTIONAL INT32 element = 5;
+            }
+          }
+        }
+      }
+      OPTIONAL FIXED_LEN_BYTE_ARRAY (16) uuid;
+      REQUIRED INT64 amount (DECIMAL(18, 2));
+      OPTIONAL BYTE_ARRAY name (UTF8);
+      REPEATED INT96 ts;
+      OPTIONAL INT64 ts_nanos (TIMESTAMP(NANOS,false));
+    }
+    ";
+    let expected_schema = parse_message_type(message_type).unwrap();
+    let thrift_schema = to_thrift(&expected_schema).unwrap();
+    assert_eq!(thrift_schema.len(), 12);
+    assert_eq!(thrift_schema[0].num_children, Some(7));
+    assert_eq!(thrift_schema[2].field_id, Some(3));
+    assert_eq!(thrift_schema[7].type_length, Some(16));
+    assert_eq!(thrift_schema[8].precision, Some(18));
+    assert_eq!(thrift_schema[8].scale, Some(2));
+    assert_eq!(thrift_schema[10].type_length, None);
+    assert_eq!(thrift_schema[11].converted_type, None);
+    assert_eq!(
+      thrift_schema[11].logical_type.clone().map(|tp| LogicalAnnotation::from(tp)),
+      Some(LogicalAnnotation::TIMESTAMP {
+        is_adjusted_to_utc: false, unit: TimeUnit::NANOS }));
+
+    let result_schema = from_thrift(&mut thrift_schema.clone()).unwrap();
+    assert_eq!(to_thrift(&result_schema).unwrap(), thrift_schema);
+    assert!(result_schema.check_contains(&expected_schema));
+    assert!(expected_schema.check_contains(&result_schema));
+  }
+
+  #[test]
+  fn test_schema_type_thrift_conversion_non_group() {
+    let tp = Type::primitive_type_builder("a", PhysicalType::INT32).build().unwrap();
+    assert!(to_thrift(&tp).is_err());
+  }
+
+  #[test]
+  fn test_schema_type_thrift_conversion_malformed() {
+    use parquet_thrift::parquet::{Type as TType, FieldRepetitionType};
+
+    fn element(
+      name: &str, type_: Option<TType>, repetition: Option<FieldRepetitionType>,
+      num_children: Option<i32>
+    ) -> SchemaElement {
+      SchemaElement {
+        type_: type_, type_length: None, repetition_type: repetition,
+        name: name.to_owned(), num_children: num_children, converted_type: None,
+        scale: None, precision: None, field_id: None, logical_type: None
+      }
+    }
+
+    // Root claims more children than there are elements
+    let mut elements = vec![
+      element("schema", None, None, Some(2)),
+      element("a", Some(TType::INT32), Some(FieldRepetitionType::REQUIRED), None)
+    ];
+    assert!(from_thrift(&mut elements).is_err());
+
+    // Root is not a group
+    let mut elements = vec![
+      element("a", Some(TType::INT32), Some(FieldRepetitionType::REQUIRED), None)
+    ];
+    assert!(from_thrift(&mut elements).is_err());
+
+    // Primitive type without a physical type
+    let mut elements = vec![
+      element("schema", None, None, Some(1)),
+      element("a", None, Some(FieldRepetitionType::REQUIRED), None)
+    ];
+    assert!(from_thrift(&mut elements).is_err());
+
+    // Nested group without repetition
+    let mut elements = vec![
+      element("schema", None, None, Some(1)),
+      element("b", None, None, Some(1)),
+      element("a", Some(TType::INT32), Some(FieldRepetitionType::REQUIRED), None)
+    ];
+    assert!(from_thrift(&mut elements).is_err());
+  }
 }
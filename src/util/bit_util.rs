@@ -116,6 +116,11 @@ pub fn unset_array_bit(bits: &mut [u8], i: usize) {
   bits[i / 8] &= !(1 << (i % 8));
 }
 
+#[inline]
+pub fn get_array_bit(bits: &[u8], i: usize) -> bool {
+  bits[i / 8] & (1 << (i % 8)) != 0
+}
+
 /// Returns the minimum number of bits needed to represent the value 'x'
 #[inline]
 pub fn num_required_bits(x: u64) -> usize {
@@ -471,12 +476,14 @@ impl BitReader {
     let mut v: i64 = 0;
     while let Some(byte) = self.get_aligned::<u8>(1) {
       v |= ((byte & 0x7F) as i64) << shift;
-      shift += 7;
-      assert!(shift <= MAX_VLQ_BYTE_LEN * 7,
-        "Num of bytes exceed MAX_VLQ_BYTE_LEN ({})", MAX_VLQ_BYTE_LEN);
       if byte & 0x80 == 0 {
         return Some(v);
       }
+      shift += 7;
+      if shift >= MAX_VLQ_BYTE_LEN * 7 {
+        // Malformed input: the VLQ int is longer than MAX_VLQ_BYTE_LEN bytes
+        return None;
+      }
     }
     None
   }
@@ -599,6 +606,9 @@ mod tests {
     assert_eq!(buffer, vec![16, 12, 0]);
     unset_array_bit(&mut buffer[..], 10);
     assert_eq!(buffer, vec![16, 8, 0]);
+    assert!(get_array_bit(&buffer[..], 4));
+    assert!(get_array_bit(&buffer[..], 11));
+    assert!(!get_array_bit(&buffer[..], 10));
   }
 
   #[test]
@@ -15,6 +15,9 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::env;
+use std::fs;
+use std::path;
 use rand::{thread_rng, Rng, Rand};
 use rand::distributions::range::SampleRange;
 
@@ -91,3 +94,24 @@ pub fn random_numbers_range<T>(
     result.push(rng.gen_range(low, high));
   }
 }
+
+/// Returns file handle for a test parquet file from 'data' directory
+pub fn get_test_file(file_name: &str) -> fs::File {
+  let mut path_buf = env::current_dir().unwrap();
+  path_buf.push("data");
+  path_buf.push(file_name);
+  let file = fs::File::open(path_buf.as_path());
+  assert!(file.is_ok());
+  file.unwrap()
+}
+
+/// Returns a path in the temporary directory for a file written by a test. Any file
+/// already at this path is removed.
+pub fn get_temp_file_path(file_name: &str) -> path::PathBuf {
+  let mut path_buf = env::temp_dir();
+  path_buf.push("parquet-rs-test");
+  fs::create_dir_all(path_buf.as_path()).unwrap();
+  path_buf.push(file_name);
+  let _ = fs::remove_file(path_buf.as_path());
+  path_buf
+}